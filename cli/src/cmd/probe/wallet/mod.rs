@@ -3,16 +3,21 @@
 pub mod vanity;
 
 use crate::{
-    cmd::{probe::wallet::vanity::VanityArgs, Cmd},
+    cmd::{
+        probe::wallet::vanity::VanityArgs,
+        spark::script::offline::{SignedBatch, UnsignedBatch},
+        Cmd,
+    },
     opts::Wallet,
 };
-use clap::Parser;
+use clap::{Parser, ValueHint};
 use corebc::{
     core::rand::thread_rng,
     signers::{LocalWallet, Signer},
     types::{transaction::cip712::TypedData, Address, Network, Signature},
 };
 use eyre::Context;
+use std::path::PathBuf;
 
 /// CLI arguments for `probe wallet`.
 #[derive(Debug, Parser)]
@@ -88,6 +93,27 @@ pub enum WalletSubcommands {
         wallet: Wallet,
     },
 
+    /// Sign a batch of transactions exported by `spark script --export-unsigned`.
+    ///
+    /// Only the transactions sent by the wallet are signed. If the output file already exists,
+    /// they are added to it, so a batch with several senders is signed by running this once per
+    /// sender.
+    ///
+    /// This doesn't need network access, so it can run on an offline machine.
+    #[clap(name = "sign-batch", visible_alias = "sb")]
+    SignBatch {
+        /// The unsigned batch file.
+        #[clap(value_hint = ValueHint::FilePath)]
+        batch: PathBuf,
+
+        /// Where to write the signed batch.
+        #[clap(long, short, value_hint = ValueHint::FilePath, value_name = "PATH")]
+        out: PathBuf,
+
+        #[clap(flatten)]
+        wallet: Wallet,
+    },
+
     /// Verify the signature of a message.
     #[clap(visible_alias = "v")]
     Verify {
@@ -173,6 +199,20 @@ impl WalletSubcommands {
                 };
                 println!("0x{sig}");
             }
+            WalletSubcommands::SignBatch { batch, out, wallet } => {
+                let batch = UnsignedBatch::load(&batch)?;
+                let wallet = wallet.signer(u64::from(batch.network)).await?;
+                let mut signed =
+                    if out.exists() { SignedBatch::load(&out)? } else { SignedBatch::new(&batch) };
+                let count = batch.sign(&wallet, &mut signed).await?;
+                signed.save(&out)?;
+                println!("Signed {count} transactions to: {}", out.display());
+
+                let unsigned = signed.transactions.iter().filter(|tx| tx.is_none()).count();
+                if unsigned > 0 {
+                    println!("{unsigned} transactions of other senders still need to be signed");
+                }
+            }
             WalletSubcommands::Verify { message, signature, address, network } => {
                 match signature.verify(Self::hex_str_to_bytes(&message)?, &network, address) {
                     Ok(_) => {
@@ -257,4 +297,22 @@ mod tests {
             _ => panic!("expected WalletSubcommands::Sign"),
         }
     }
+
+    #[test]
+    fn can_parse_wallet_sign_batch() {
+        let args = WalletSubcommands::parse_from([
+            "foxar-cli",
+            "sign-batch",
+            "unsigned.json",
+            "--out",
+            "signed.json",
+        ]);
+        match args {
+            WalletSubcommands::SignBatch { batch, out, .. } => {
+                assert_eq!(batch, PathBuf::from("unsigned.json"));
+                assert_eq!(out, PathBuf::from("signed.json"));
+            }
+            _ => panic!("expected WalletSubcommands::SignBatch"),
+        }
+    }
 }
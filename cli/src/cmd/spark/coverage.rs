@@ -22,8 +22,9 @@ use foxar_evm::utils::evm_spec;
 use semver::Version;
 use spark::{
     coverage::{
-        analysis::SourceAnalyzer, anchors::find_anchors, ContractId, CoverageReport,
-        CoverageReporter, DebugReporter, ItemAnchor, LcovReporter, SummaryReporter,
+        analysis::SourceAnalyzer, anchors::find_anchors, CoberturaReporter, ContractId,
+        CoverageReport, CoverageReporter, DebugReporter, HtmlReporter, ItemAnchor, LcovReporter,
+        SummaryReporter,
     },
     executor::{inspector::CheatsConfig, opts::EvmOpts},
     result::SuiteResult,
@@ -323,6 +324,13 @@ impl CoverageArgs {
                 CoverageReportKind::Lcov => {
                     LcovReporter::new(&mut fs::create_file(root.join("lcov.info"))?).report(&report)
                 }
+                CoverageReportKind::Html => {
+                    HtmlReporter::new(&root, root.join("coverage")).report(&report)
+                }
+                CoverageReportKind::Cobertura => {
+                    CoberturaReporter::new(&mut fs::create_file(root.join("cobertura.xml"))?, &root)
+                        .report(&report)
+                }
                 CoverageReportKind::Debug => DebugReporter.report(&report),
             }?;
         }
@@ -330,12 +338,13 @@ impl CoverageArgs {
     }
 }
 
-// TODO: HTML
 #[derive(Debug, Clone, ValueEnum)]
 pub enum CoverageReportKind {
     Summary,
     Lcov,
     Debug,
+    Html,
+    Cobertura,
 }
 
 /// Helper function that will link references in unlinked bytecode to the 0 address.
@@ -1,5 +1,8 @@
 //! Create command
-use super::verify;
+use super::{
+    deployments::{Deployment, DeploymentsRegistry},
+    verify,
+};
 use crate::{
     cmd::{
         read_constructor_args_file, remove_contract, retry::RetryArgs, spark::build::CoreBuildArgs,
@@ -11,7 +14,9 @@ use crate::{
 use clap::{Parser, ValueHint};
 use corebc::{
     abi::{Abi, Constructor, Token},
-    prelude::{artifacts::BytecodeObject, ContractFactory, Middleware, MiddlewareBuilder},
+    prelude::{
+        artifacts::BytecodeObject, ArtifactId, ContractFactory, Middleware, MiddlewareBuilder,
+    },
     types::Network,
     ylem::{info::ContractInfo, utils::canonicalized},
 };
@@ -19,7 +24,10 @@ use eyre::Context;
 use foxar_common::{abi::parse_tokens, compile};
 use rustc_hex::ToHex;
 use serde_json::json;
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 /// CLI arguments for `spark create`.
 #[derive(Debug, Clone, Parser)]
@@ -89,6 +97,12 @@ impl CreateArgs {
             *path = canonicalized(project.root().join(&path)).to_string_lossy().to_string();
         }
 
+        let artifact = output.artifact_ids().map(|(id, _)| id).find(|id| {
+            id.name == self.contract.name &&
+                self.contract.path.as_ref().map_or(true, |path| id.source == Path::new(path))
+        });
+        let registry = DeploymentsRegistry::new(project.root());
+
         let (abi, bin, _) = remove_contract(&mut output, &self.contract)?;
 
         let bin = match bin.object {
@@ -127,12 +141,12 @@ impl CreateArgs {
             // Deploy with unlocked account
             let sender = self.eth.wallet.from.expect("required");
             let provider = provider.with_sender(sender);
-            self.deploy(abi, bin, params, provider, chain_id).await
+            self.deploy(abi, bin, params, provider, chain_id, artifact, registry).await
         } else {
             // Deploy with signer
             let signer = self.eth.wallet.signer(chain_id).await?;
             let provider = provider.with_signer(signer);
-            self.deploy(abi, bin, params, provider, chain_id).await
+            self.deploy(abi, bin, params, provider, chain_id, artifact, registry).await
         }
     }
 
@@ -171,7 +185,8 @@ impl CreateArgs {
         Ok(())
     }
 
-    /// Deploys the contract
+    /// Deploys the contract and records the deployment in the project's deployments registry
+    #[allow(clippy::too_many_arguments)]
     async fn deploy<M: Middleware + 'static>(
         self,
         abi: Abi,
@@ -179,6 +194,8 @@ impl CreateArgs {
         args: Vec<Token>,
         provider: M,
         chain: u64,
+        artifact: Option<ArtifactId>,
+        registry: DeploymentsRegistry,
     ) -> eyre::Result<()> {
         let deployer_address =
             provider.default_sender().expect("no sender address set for provider");
@@ -242,6 +259,8 @@ impl CreateArgs {
             self.verify_preflight_check(constructor_args.clone(), chain).await?;
         }
 
+        let init_code = deployer.tx.data().cloned().unwrap_or_default();
+
         // Deploy the actual contract
         let (deployed_contract, receipt) = deployer.send_with_receipt().await?;
 
@@ -259,6 +278,22 @@ impl CreateArgs {
             println!("Transaction hash: {:?}", receipt.transaction_hash);
         };
 
+        if let Some(artifact) = artifact {
+            let deployment = Deployment::new(
+                registry.project_root(),
+                &artifact,
+                &bin,
+                &init_code,
+                address,
+                receipt.transaction_hash,
+                receipt.block_number,
+            );
+            let path = registry.add(Network::from(chain), &deployment)?;
+            if !self.json {
+                println!("Deployment saved to: {}", path.display());
+            }
+        }
+
         if !self.verify {
             return Ok(());
         }
@@ -0,0 +1,435 @@
+//! deployments command
+
+use crate::{
+    cmd::{spark::build::CoreBuildArgs, Cmd},
+    opts::RpcOpts,
+    utils,
+};
+use clap::{Parser, Subcommand, ValueHint};
+use corebc::{
+    prelude::{
+        artifacts::{ConfigurableContractArtifact, DeployedBytecode},
+        ArtifactId, Middleware,
+    },
+    types::{Address, Network, TxHash, H256, U64},
+    utils::sha3,
+};
+use eyre::Result;
+use foxar_common::{
+    compile,
+    contracts::{get_contract_name, runtime_code_matches},
+    fs,
+};
+use foxar_config::{
+    figment::{self, value::Dict, Metadata, Profile},
+    impl_figment_convert_probe, load_config_with_root, Config,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// The directory of the deployments registry, relative to the project root
+pub const DEPLOYMENTS_DIR: &str = "deployments";
+
+/// A contract deployment of the deployments registry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deployment {
+    /// The contract identifier in the form `<path>:<contractname>`
+    pub contract: String,
+    pub address: Address,
+    pub transaction_hash: TxHash,
+    pub block_number: Option<U64>,
+    /// The hex encoded constructor arguments
+    pub constructor_args: String,
+    /// The ylem version the contract was compiled with
+    pub compiler_version: String,
+    /// The hash of the creation bytecode of the artifact
+    pub bytecode_hash: H256,
+}
+
+impl Deployment {
+    /// Creates the deployment of the given artifact
+    ///
+    /// `init_code` is the creation bytecode of the artifact followed by the constructor
+    /// arguments.
+    pub fn new(
+        root: &Path,
+        artifact: &ArtifactId,
+        bytecode: &[u8],
+        init_code: &[u8],
+        address: Address,
+        transaction_hash: TxHash,
+        block_number: Option<U64>,
+    ) -> Self {
+        Self {
+            contract: contract_identifier(root, artifact),
+            address,
+            transaction_hash,
+            block_number,
+            constructor_args: hex::encode(init_code.get(bytecode.len()..).unwrap_or_default()),
+            compiler_version: artifact.version.to_string(),
+            bytecode_hash: sha3(bytecode).into(),
+        }
+    }
+
+    /// Returns the name of the deployed contract
+    pub fn name(&self) -> &str {
+        get_contract_name(&self.contract)
+    }
+}
+
+/// Returns the `<path>:<contractname>` identifier of the artifact, with the path relative to the
+/// project root
+fn contract_identifier(root: &Path, artifact: &ArtifactId) -> String {
+    let source = artifact.source.strip_prefix(root).unwrap_or(&artifact.source);
+    format!("{}:{}", source.display(), artifact.name)
+}
+
+/// The deployments of a project, stored as `deployments/<network>/<Contract>.json`
+///
+/// Only the most recent deployment of a contract is kept per network.
+#[derive(Debug, Clone)]
+pub struct DeploymentsRegistry {
+    project_root: PathBuf,
+}
+
+impl DeploymentsRegistry {
+    /// Returns the registry of the project with the given root
+    pub fn new(project_root: impl Into<PathBuf>) -> Self {
+        Self { project_root: project_root.into() }
+    }
+
+    /// Returns the root of the project
+    pub fn project_root(&self) -> &Path {
+        &self.project_root
+    }
+
+    /// Returns the directory of the registry
+    pub fn dir(&self) -> PathBuf {
+        self.project_root.join(DEPLOYMENTS_DIR)
+    }
+
+    /// Returns the path of the deployment of the named contract on the given network
+    pub fn path(&self, network: &str, name: &str) -> PathBuf {
+        self.dir().join(network).join(format!("{name}.json"))
+    }
+
+    /// Records the deployment, replacing any earlier deployment of the contract on the network
+    ///
+    /// Returns the path of the deployment file.
+    pub fn add(&self, network: Network, deployment: &Deployment) -> Result<PathBuf> {
+        let path = self.path(&network.to_string(), deployment.name());
+        fs::create_dir_all(path.parent().expect("has parent"))?;
+        fs::write(&path, serde_json::to_string_pretty(deployment)?)?;
+        Ok(path)
+    }
+
+    /// Returns all deployments by network and contract name, optionally only of one network
+    pub fn deployments(
+        &self,
+        network: Option<&str>,
+    ) -> Result<BTreeMap<String, BTreeMap<String, Deployment>>> {
+        let dir = self.dir();
+        let mut deployments = BTreeMap::new();
+        if !dir.is_dir() {
+            return Ok(deployments)
+        }
+
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if network.map_or(false, |network| network != name) {
+                continue
+            }
+
+            let mut contracts = BTreeMap::new();
+            for path in fs::json_files(entry.path()) {
+                let deployment: Deployment = fs::read_json_file(&path)?;
+                contracts.insert(deployment.name().to_string(), deployment);
+            }
+            if !contracts.is_empty() {
+                deployments.insert(name, contracts);
+            }
+        }
+        Ok(deployments)
+    }
+}
+
+/// CLI arguments for `spark deployments`.
+#[derive(Debug, Parser)]
+pub struct DeploymentsArgs {
+    #[clap(subcommand)]
+    pub sub: DeploymentsSubcommands,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DeploymentsSubcommands {
+    /// List the deployments of the project.
+    #[clap(visible_alias = "ls")]
+    List(ListArgs),
+
+    /// Compare the code of the deployments with the code on chain and the project's artifacts.
+    Diff(DiffArgs),
+
+    /// Export all deployments of the project as a single JSON file.
+    Export(ExportArgs),
+}
+
+/// CLI arguments for `spark deployments list`.
+#[derive(Debug, Parser)]
+pub struct ListArgs {
+    /// Only list the deployments on this network.
+    #[clap(long, short)]
+    network: Option<String>,
+
+    /// Print the deployments as JSON.
+    #[clap(long)]
+    json: bool,
+
+    /// The project's root path.
+    ///
+    /// By default root of the Git repository, if in one,
+    /// or the current working directory.
+    #[clap(long, value_hint = ValueHint::DirPath, value_name = "PATH")]
+    root: Option<PathBuf>,
+}
+
+impl Cmd for ListArgs {
+    type Output = ();
+
+    fn run(self) -> Result<Self::Output> {
+        let config = load_config_with_root(self.root);
+        let deployments =
+            DeploymentsRegistry::new(config.__root.0).deployments(self.network.as_deref())?;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&deployments)?);
+            return Ok(())
+        }
+
+        if deployments.is_empty() {
+            println!("No deployments found.");
+        }
+        for (network, contracts) in deployments {
+            println!("{network}:");
+            for (name, deployment) in contracts {
+                let block = deployment
+                    .block_number
+                    .map(|number| number.to_string())
+                    .unwrap_or_else(|| "-".to_string());
+                println!(
+                    "  {name}: {:?} (block {block}, tx {:?})",
+                    deployment.address, deployment.transaction_hash
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// CLI arguments for `spark deployments export`.
+#[derive(Debug, Parser)]
+pub struct ExportArgs {
+    /// The file to write the deployments to.
+    ///
+    /// By default the deployments are printed to stdout.
+    #[clap(long, short, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    out: Option<PathBuf>,
+
+    /// Only export the deployments on this network.
+    #[clap(long, short)]
+    network: Option<String>,
+
+    /// The project's root path.
+    ///
+    /// By default root of the Git repository, if in one,
+    /// or the current working directory.
+    #[clap(long, value_hint = ValueHint::DirPath, value_name = "PATH")]
+    root: Option<PathBuf>,
+}
+
+impl Cmd for ExportArgs {
+    type Output = ();
+
+    fn run(self) -> Result<Self::Output> {
+        let config = load_config_with_root(self.root);
+        let deployments =
+            DeploymentsRegistry::new(config.__root.0).deployments(self.network.as_deref())?;
+        let json = serde_json::to_string_pretty(&deployments)?;
+
+        match self.out {
+            Some(out) => {
+                fs::write(&out, json)?;
+                println!("Deployments exported to: {}", out.display());
+            }
+            None => println!("{json}"),
+        }
+        Ok(())
+    }
+}
+
+/// CLI arguments for `spark deployments diff`.
+#[derive(Debug, Clone, Parser)]
+pub struct DiffArgs {
+    #[clap(flatten)]
+    rpc: RpcOpts,
+
+    #[clap(flatten)]
+    build: CoreBuildArgs,
+}
+
+impl_figment_convert_probe!(DiffArgs);
+
+impl figment::Provider for DiffArgs {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("DiffArgs")
+    }
+
+    fn data(&self) -> Result<figment::value::Map<Profile, Dict>, figment::Error> {
+        let mut map = self.build.data()?;
+        let dict = map.get_mut(&Config::selected_profile()).unwrap();
+        dict.extend(self.rpc.dict());
+        Ok(map)
+    }
+}
+
+impl DiffArgs {
+    /// Compares the deployments on the network of the RPC endpoint with the code on chain
+    ///
+    /// Fails if the code of a deployment doesn't match its artifact.
+    pub async fn run(self) -> Result<()> {
+        let config = Config::from(&self);
+        let provider = utils::get_provider(&config)?;
+        let network = Network::from(provider.get_networkid().await?.as_u64());
+
+        let project = self.build.project()?;
+        let deployments = DeploymentsRegistry::new(project.root())
+            .deployments(Some(network.to_string().as_str()))?;
+        let Some(contracts) = deployments.into_values().next() else {
+            println!("No deployments found on network `{network}`.");
+            return Ok(())
+        };
+
+        let output = compile::compile(&project, false, false)?;
+        let artifacts: BTreeMap<String, &ConfigurableContractArtifact> = output
+            .artifact_ids()
+            .map(|(id, artifact)| (contract_identifier(project.root(), &id), artifact))
+            .collect();
+
+        let mut mismatches = 0;
+        for (name, deployment) in contracts {
+            let code = provider.get_code(deployment.address, None).await?;
+            let artifact = artifacts.get(&deployment.contract);
+            let status = if code.is_empty() {
+                mismatches += 1;
+                "no code on chain".to_string()
+            } else if let Some(artifact) = artifact {
+                let deployed = artifact.deployed_bytecode.as_ref();
+                let bytecode = artifact.bytecode.as_ref().and_then(|code| code.object.as_bytes());
+                if deployed.map_or(false, |deployed| code_matches(deployed, &code)) {
+                    "matches".to_string()
+                } else {
+                    mismatches += 1;
+                    if bytecode
+                        .map_or(false, |code| H256::from(sha3(code)) != deployment.bytecode_hash)
+                    {
+                        "differs, the artifact changed since the deployment".to_string()
+                    } else {
+                        "differs".to_string()
+                    }
+                }
+            } else {
+                mismatches += 1;
+                format!("artifact `{}` not found", deployment.contract)
+            };
+            println!("{name} ({:?}): {status}", deployment.address);
+        }
+
+        if mismatches > 0 {
+            eyre::bail!(
+                "{mismatches} deployments don't match their artifacts on network `{network}`"
+            )
+        }
+        Ok(())
+    }
+}
+
+/// Returns `true` if the code on chain is exactly the runtime code of the artifact
+///
+/// The immutables are zeroed out in both, since their values are filled in by the constructor.
+fn code_matches(deployed: &DeployedBytecode, code: &[u8]) -> bool {
+    deployed
+        .bytecode
+        .as_ref()
+        .and_then(|bytecode| bytecode.object.as_bytes())
+        .map_or(false, |runtime_code| {
+            runtime_code_matches(runtime_code, code, &deployed.immutable_references)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use corebc::prelude::artifacts::Offsets;
+    use foxar_cli_test_utils::tempfile::tempdir;
+
+    #[test]
+    fn can_add_and_read_deployments() {
+        let dir = tempdir().unwrap();
+        let registry = DeploymentsRegistry::new(dir.path());
+        let deployment = Deployment {
+            contract: "src/Counter.sol:Counter".to_string(),
+            address: Address::random(),
+            transaction_hash: TxHash::random(),
+            block_number: Some(1u64.into()),
+            constructor_args: String::new(),
+            compiler_version: "1.1.0".to_string(),
+            bytecode_hash: H256::random(),
+        };
+
+        let path = registry.add(Network::Mainnet, &deployment).unwrap();
+        assert_eq!(path, registry.path(&Network::Mainnet.to_string(), "Counter"));
+
+        let deployments = registry.deployments(None).unwrap();
+        assert_eq!(deployments[&Network::Mainnet.to_string()]["Counter"], deployment);
+        assert!(registry.deployments(Some("unknown")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn ignores_only_immutables() {
+        let immutable_references: BTreeMap<String, Vec<Offsets>> =
+            serde_json::from_str(r#"{"7": [{"start": 40, "length": 5}]}"#).unwrap();
+        let deployed = [0x60u8; 100];
+        let mut code = deployed;
+        // an immutable that was filled in by the constructor
+        code[40..45].copy_from_slice(&[1, 2, 3, 4, 5]);
+        assert!(runtime_code_matches(&deployed, &code, &immutable_references));
+
+        // a single differing byte outside of the immutables
+        code[50] = 0;
+        assert!(!runtime_code_matches(&deployed, &code, &immutable_references));
+    }
+
+    #[test]
+    fn rejects_code_of_different_length() {
+        let deployed = [0x60u8; 100];
+        assert!(!runtime_code_matches(&deployed, &deployed[..99], &BTreeMap::new()));
+        assert!(!runtime_code_matches(&deployed, &[0x60u8; 101], &BTreeMap::new()));
+    }
+
+    #[test]
+    fn can_parse_deployments() {
+        let args: DeploymentsArgs =
+            DeploymentsArgs::parse_from(["foxar-cli", "export", "--out", "deployments.json"]);
+        assert!(matches!(
+            args.sub,
+            DeploymentsSubcommands::Export(ExportArgs { out: Some(_), .. })
+        ));
+    }
+}
@@ -46,6 +46,7 @@ pub mod config;
 pub mod coverage;
 pub mod create;
 pub mod debug;
+pub mod deployments;
 pub mod doc;
 pub mod flatten;
 pub mod fmt;
@@ -1,19 +1,29 @@
-use super::{multi::MultiChainSequence, providers::ProvidersManager, sequence::ScriptSequence, *};
+use super::{
+    multi::MultiChainSequence,
+    providers::ProvidersManager,
+    sequence::{ReplacementKind, ScriptSequence, TransactionReplacement},
+    *,
+};
 use crate::{
-    cmd::spark::script::{receipts::clear_pendings, verify::VerifyBundle},
+    cmd::spark::script::{receipts::clear_pendings_with_timeout, verify::VerifyBundle},
     init_progress,
     opts::WalletSigner,
     update_progress,
 };
 use corebc::{
-    prelude::{Provider, Signer, TxHash},
+    prelude::{Provider, Signer, TransactionRequest, TxHash},
     providers::JsonRpcClient,
     utils::format_units,
 };
 use eyre::{bail, Result};
 use foxar_common::{try_get_http_provider, RetryProvider};
 use futures::StreamExt;
-use std::{cmp::min, ops::Mul, sync::Arc};
+use std::{
+    cmp::min,
+    collections::{BTreeMap, BTreeSet},
+    ops::Mul,
+    sync::Arc,
+};
 use tracing::trace;
 
 impl ScriptArgs {
@@ -137,8 +147,13 @@ impl ScriptArgs {
                         update_progress!(pb, (index + already_broadcasted));
                         index += 1;
 
-                        clear_pendings(provider.clone(), deployment_sequence, Some(vec![tx_hash]))
-                            .await?;
+                        self.wait_for_receipts(
+                            provider.clone(),
+                            deployment_sequence,
+                            Some(vec![tx_hash]),
+                            &send_kind,
+                        )
+                        .await?;
                     } else {
                         pending_transactions.push(tx_hash);
                     }
@@ -160,7 +175,13 @@ impl ScriptArgs {
 
                     if !sequential_broadcast {
                         shell::println("##\nWaiting for receipts.")?;
-                        clear_pendings(provider.clone(), deployment_sequence, None).await?;
+                        self.wait_for_receipts(
+                            provider.clone(),
+                            deployment_sequence,
+                            None,
+                            &send_kind,
+                        )
+                        .await?;
                     }
                 }
 
@@ -169,6 +190,206 @@ impl ScriptArgs {
             }
         }
 
+        self.show_onchain_summary(deployment_sequence)
+    }
+
+    /// Waits for the receipts of the given transactions, or all pending transactions.
+    ///
+    /// With `--bump-timeout`, transactions that aren't mined in time are re-sent with the same
+    /// nonce and a bumped energy price and dropped transactions are re-sent, at most
+    /// `--max-bumps` times. With `--fill-nonce-gaps`, nonces that block the pending transactions
+    /// are filled with zero-value self-transfers, after the dropped transactions were re-sent.
+    /// Every replacement is recorded in the sequence.
+    async fn wait_for_receipts(
+        &self,
+        provider: Arc<RetryProvider>,
+        deployment_sequence: &mut ScriptSequence,
+        tx_hashes: Option<Vec<TxHash>>,
+        send_kind: &SendTransactionsKind,
+    ) -> Result<()> {
+        let enabled = self.stuck.bump_timeout.is_some() || self.stuck.fill_nonce_gaps;
+        let mut to_query = tx_hashes;
+        let mut bumps = 0;
+
+        loop {
+            let can_replace = enabled && bumps < self.stuck.max_bumps;
+            let timeout = if can_replace { self.stuck.timeout() } else { None };
+            let unresolved = clear_pendings_with_timeout(
+                provider.clone(),
+                deployment_sequence,
+                to_query.take(),
+                timeout,
+                can_replace,
+            )
+            .await?;
+            if unresolved.is_empty() {
+                return Ok(())
+            }
+            bumps += 1;
+
+            let mut replacements = vec![];
+            for tx_hash in unresolved.stuck {
+                replacements.push(
+                    self.replace_transaction(
+                        &provider,
+                        deployment_sequence,
+                        tx_hash,
+                        ReplacementKind::Bump,
+                        send_kind,
+                    )
+                    .await?,
+                );
+            }
+            for tx_hash in unresolved.dropped {
+                replacements.push(
+                    self.replace_transaction(
+                        &provider,
+                        deployment_sequence,
+                        tx_hash,
+                        ReplacementKind::Resend,
+                        send_kind,
+                    )
+                    .await?,
+                );
+            }
+
+            // the dropped transactions are pending again, so their nonces aren't filled
+            if self.stuck.fill_nonce_gaps {
+                self.fill_nonce_gaps(&provider, deployment_sequence, send_kind).await?;
+            }
+
+            // Checkpoint save
+            deployment_sequence.save()?;
+            to_query = Some(replacements);
+        }
+    }
+
+    /// Re-sends the transaction with the given hash with the same nonce and a bumped energy price
+    ///
+    /// Returns the hash of the replacement.
+    async fn replace_transaction(
+        &self,
+        provider: &Arc<RetryProvider>,
+        deployment_sequence: &mut ScriptSequence,
+        tx_hash: TxHash,
+        kind: ReplacementKind,
+        send_kind: &SendTransactionsKind,
+    ) -> Result<TxHash> {
+        let index = deployment_sequence
+            .transactions
+            .iter()
+            .position(|tx| tx.hash == Some(tx_hash))
+            .wrap_err_with(|| format!("Transaction {tx_hash:?} is not part of the deployment"))?;
+
+        let mut tx = deployment_sequence.transactions[index].typed_tx().clone();
+        let from = *tx.from().expect("No sender for onchain transaction!");
+        let nonce = *tx.nonce().expect("no nonce");
+
+        // the sent transaction may have used a different price than the simulated one
+        let previous_price = match provider.get_transaction(tx_hash).await? {
+            Some(sent) => sent.energy_price,
+            None => tx.energy_price().unwrap_or_default(),
+        };
+        let energy_price = self.stuck.bump(previous_price).max(provider.get_energy_price().await?);
+        tx.set_network_id(u64::from(deployment_sequence.network));
+        tx.set_energy_price(energy_price);
+
+        let replacement = self.send_replacement(provider, tx, &from, send_kind).await?;
+        shell::println(format!(
+            "Replaced transaction {tx_hash:?} (nonce {nonce}) with {replacement:?} at energy price {energy_price}"
+        ))?;
+
+        deployment_sequence.add_replacement(
+            Some(index),
+            TransactionReplacement {
+                kind,
+                from,
+                nonce,
+                replaced: Some(tx_hash),
+                hash: replacement,
+                energy_price,
+            },
+        );
+        Ok(replacement)
+    }
+
+    /// Fills the unused nonces below the pending transactions of every sender with zero-value
+    /// self-transfers, so the pending transactions can be mined.
+    async fn fill_nonce_gaps(
+        &self,
+        provider: &Arc<RetryProvider>,
+        deployment_sequence: &mut ScriptSequence,
+        send_kind: &SendTransactionsKind,
+    ) -> Result<()> {
+        let mut pending_nonces: BTreeMap<Address, BTreeSet<U256>> = BTreeMap::new();
+        for tx in deployment_sequence.transactions.iter() {
+            if tx.hash.map_or(false, |hash| deployment_sequence.pending.contains(&hash)) {
+                let tx = tx.typed_tx();
+                if let (Some(from), Some(nonce)) = (tx.from(), tx.nonce()) {
+                    pending_nonces.entry(*from).or_default().insert(*nonce);
+                }
+            }
+        }
+
+        for (from, nonces) in pending_nonces {
+            let Some(highest) = nonces.iter().next_back().copied() else { continue };
+            let mut nonce = provider.get_transaction_count(from, None).await?;
+            while nonce < highest {
+                if !nonces.contains(&nonce) {
+                    let energy_price = match self.with_energy_price {
+                        Some(energy_price) => energy_price,
+                        None => provider.get_energy_price().await?,
+                    };
+                    let energy_price = self.stuck.bump(energy_price);
+                    let tx: TypedTransaction = TransactionRequest::new()
+                        .from(from)
+                        .to(from)
+                        .value(0u64)
+                        .nonce(nonce)
+                        .energy(21_000u64)
+                        .energy_price(energy_price)
+                        .network_id(u64::from(deployment_sequence.network))
+                        .into();
+
+                    let hash = self.send_replacement(provider, tx, &from, send_kind).await?;
+                    shell::println(format!("Filled nonce gap {nonce} of {from:?} with {hash:?}"))?;
+
+                    deployment_sequence.add_replacement(
+                        None,
+                        TransactionReplacement {
+                            kind: ReplacementKind::GapFill,
+                            from,
+                            nonce,
+                            replaced: None,
+                            hash,
+                            energy_price,
+                        },
+                    );
+                }
+                nonce += U256::one();
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends a transaction that replaces or precedes a transaction of the deployment
+    async fn send_replacement(
+        &self,
+        provider: &Arc<RetryProvider>,
+        tx: TypedTransaction,
+        from: &Address,
+        send_kind: &SendTransactionsKind,
+    ) -> Result<TxHash> {
+        match send_kind.for_sender(from)? {
+            SendTransactionKind::Unlocked(_) => {
+                Ok(provider.send_transaction(tx, None).await?.tx_hash())
+            }
+            SendTransactionKind::Raw(signer) => self.broadcast(provider.clone(), signer, tx).await,
+        }
+    }
+
+    /// Prints the total energy used and amount paid by the broadcasted transactions
+    pub(crate) fn show_onchain_summary(&self, deployment_sequence: &ScriptSequence) -> Result<()> {
         shell::println("\n\n==========================")?;
         shell::println("\nONCHAIN EXECUTION COMPLETE & SUCCESSFUL.")?;
 
@@ -238,6 +459,10 @@ impl ScriptArgs {
 
     /// Executes the created transactions, and if no error has occurred, broadcasts
     /// them.
+    ///
+    /// CREATE2 deployments of contracts that already exist on chain are skipped, see
+    /// [ScriptArgs::skip_existing_deployments]. The simulated transactions are reviewed before
+    /// they are broadcasted, see [ScriptArgs::review_transactions].
     pub async fn handle_broadcastable_transactions(
         &self,
         mut result: ScriptResult,
@@ -245,12 +470,27 @@ impl ScriptArgs {
         decoder: &mut CallTraceDecoder,
         mut script_config: ScriptConfig,
         verify: VerifyBundle,
+        highlevel_known_contracts: ArtifactContracts<ContractBytecodeSome>,
     ) -> Result<()> {
         if let Some(txs) = result.transactions.take() {
             script_config.collect_rpcs(&txs);
             script_config.check_multi_chain_constraints(&libraries)?;
 
             if !script_config.missing_rpc {
+                trace!(target: "script", "skipping existing deployments");
+                let total = txs.len();
+                let txs = self
+                    .skip_existing_deployments(
+                        txs,
+                        &verify.known_contracts,
+                        &highlevel_known_contracts,
+                    )
+                    .await?;
+                if total > 0 && txs.is_empty() {
+                    shell::println("\nAll deployments already exist, nothing to broadcast.")?;
+                    return Ok(())
+                }
+
                 trace!(target: "script", "creating deployments");
 
                 let mut deployments = self
@@ -262,6 +502,19 @@ impl ScriptArgs {
                         &verify.known_contracts,
                     )
                     .await?;
+                self.flag_noop_calls(&deployments).await?;
+
+                if let Some(path) = &self.export_unsigned {
+                    if script_config.has_multiple_rpcs() {
+                        bail!("Exporting unsigned transactions of multi chain deployments is not supported");
+                    }
+                    let deployment_sequence = deployments.first().expect("to be set.");
+                    return self.export_unsigned_batch(deployment_sequence, path).await;
+                }
+
+                if self.broadcast && !self.skip_simulation {
+                    self.review_transactions(&deployments, decoder).await?;
+                }
 
                 if script_config.has_multiple_rpcs() {
                     trace!(target: "script", "broadcasting multi chain deployment");
@@ -321,6 +574,7 @@ impl ScriptArgs {
         deployment_sequence.add_libraries(libraries);
 
         self.send_transactions(deployment_sequence, &rpc, &result.script_wallets).await?;
+        deployment_sequence.record_deployments(&verify)?;
 
         if self.verify {
             return deployment_sequence.verify_contracts(&script_config.config, verify).await;
@@ -612,3 +866,109 @@ impl SendTransactionsKind {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foxar_cli_test_utils::tempfile::tempdir;
+    use shuttle::{spawn, NodeConfig};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn replaces_stuck_and_dropped_transactions_and_fills_nonce_gaps() {
+        let (api, handle) = spawn(NodeConfig::test()).await;
+        api.shuttle_set_auto_mine(false).await.unwrap();
+
+        let provider = Arc::new(try_get_http_provider(handle.http_endpoint()).unwrap());
+        let network = Network::from(api.eth_chain_id().unwrap().unwrap().as_u64());
+        let from = handle.dev_accounts().next().unwrap();
+        let energy_price = provider.get_energy_price().await.unwrap();
+        let transfer = |nonce: u64| -> TypedTransaction {
+            TransactionRequest::new()
+                .from(from)
+                .to(Address::random())
+                .value(1u64)
+                .nonce(nonce)
+                .energy(21_000u64)
+                .energy_price(energy_price)
+                .network_id(u64::from(network))
+                .into()
+        };
+
+        // the sequence is saved on every receipt and when it's dropped, so the directories must
+        // outlive it
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("sensitive")).unwrap();
+
+        // nonce 0 is left open, nonces 1 and 3 wait for it and nonce 2 is unknown to the node,
+        // like a dropped transaction
+        let mut sequence = ScriptSequence {
+            network,
+            path: dir.path().join("run-latest.json"),
+            sensitive_path: dir.path().join("sensitive").join("run-latest.json"),
+            ..Default::default()
+        };
+        for nonce in 1..=3u64 {
+            let tx = transfer(nonce);
+            let hash = if nonce == 2 {
+                TxHash::random()
+            } else {
+                provider.send_transaction(tx.clone(), None).await.unwrap().tx_hash()
+            };
+            let mut tx = TransactionWithMetadata::from_typed_transaction(tx);
+            tx.hash = Some(hash);
+            sequence.transactions.push_back(tx);
+            sequence.pending.push(hash);
+        }
+        let dropped = sequence.transactions[1].hash.unwrap();
+
+        let miner = tokio::spawn({
+            let api = api.clone();
+            async move {
+                loop {
+                    api.mine_one().await;
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                }
+            }
+        });
+
+        let args = ScriptArgs::parse_from([
+            "foxar-cli",
+            "Contract.sol",
+            "--bump-timeout",
+            "2",
+            "--fill-nonce-gaps",
+        ]);
+        let send_kind = SendTransactionsKind::Unlocked(HashSet::from([from]));
+        args.wait_for_receipts(provider.clone(), &mut sequence, None, &send_kind).await.unwrap();
+        miner.abort();
+
+        assert!(sequence.pending.is_empty());
+        assert_eq!(sequence.receipts.len(), 3);
+        assert_eq!(provider.get_transaction_count(from, None).await.unwrap(), 4u64.into());
+
+        // `--resume` reads the replacements back from the saved sequence
+        let mut saved: ScriptSequence =
+            corebc::ylem::utils::read_json_file(&sequence.path).unwrap();
+        // the read sequence has no paths, so it must not be saved when it's dropped
+        let saved_transactions = std::mem::take(&mut saved.transactions);
+        let mut replacements: Vec<_> = saved
+            .replacements
+            .iter()
+            .map(|replacement| (replacement.nonce.as_u64(), replacement.kind))
+            .collect();
+        replacements.sort_unstable_by_key(|(nonce, _)| *nonce);
+        assert_eq!(
+            replacements,
+            vec![
+                (0, ReplacementKind::GapFill),
+                (1, ReplacementKind::Bump),
+                (2, ReplacementKind::Resend),
+                (3, ReplacementKind::Bump),
+            ]
+        );
+
+        let resent = saved_transactions[1].hash.unwrap();
+        assert_eq!(saved.replaced_hashes(resent), vec![dropped]);
+    }
+}
@@ -1,4 +1,4 @@
-use super::{sequence::ScriptSequence, *};
+use super::{offline::SignedBatch, sequence::ScriptSequence, *};
 use crate::cmd::{
     spark::script::{multi::MultiChainSequence, verify::VerifyBundle},
     LoadConfig,
@@ -122,6 +122,7 @@ impl ScriptArgs {
             &mut decoder,
             script_config,
             verify,
+            highlevel_known_contracts,
         )
         .await
     }
@@ -265,8 +266,14 @@ impl ScriptArgs {
         receipts::wait_for_pending(provider, &mut deployment_sequence).await?;
 
         if self.resume {
-            self.send_transactions(&mut deployment_sequence, fork_url, &result.script_wallets)
-                .await?;
+            if let Some(path) = &self.signed_batch {
+                let batch = SignedBatch::load(path)?;
+                self.send_signed_batch(&mut deployment_sequence, fork_url, batch).await?;
+            } else {
+                self.send_transactions(&mut deployment_sequence, fork_url, &result.script_wallets)
+                    .await?;
+            }
+            deployment_sequence.record_deployments(&verify)?;
         }
 
         if self.verify {
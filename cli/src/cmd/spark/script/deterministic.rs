@@ -0,0 +1,191 @@
+//! Detection of deterministic deployments that already exist on chain
+//!
+//! CREATE2 deployments of contracts that already exist are skipped by the broadcast cheatcodes
+//! while the script is executed. Deployments that still end up in the broadcast set, because their
+//! contract was deployed after the script was executed locally, are removed from it before the
+//! transactions are simulated.
+
+use super::{providers::ProvidersManager, sequence::ScriptSequence, ScriptArgs};
+use corebc::{
+    prelude::artifacts::ContractBytecodeSome,
+    providers::Middleware,
+    types::{Address, NameOrAddress, Network, U256},
+    utils::get_create2_address,
+    ylem::contracts::ArtifactContracts,
+};
+use eyre::Result;
+use foxar_common::{contracts::runtime_code_matches, shell, ContractsByArtifact};
+use foxar_evm::executor::inspector::cheatcodes::util::{
+    default_create2_address, BroadcastableTransactions,
+};
+use std::collections::{HashMap, HashSet};
+use yansi::Paint;
+
+/// The length of the salt the CREATE2 deployer expects in front of the init code
+const SALT_LEN: usize = 32;
+
+impl ScriptArgs {
+    /// Removes the CREATE2 deployments whose address already holds the contract's runtime code
+    /// from the transactions and reports them.
+    ///
+    /// The nonces of the following transactions of the same sender are shifted, so no nonce gaps
+    /// are left.
+    pub(crate) async fn skip_existing_deployments(
+        &self,
+        txs: BroadcastableTransactions,
+        creation_contracts: &ContractsByArtifact,
+        highlevel_known_contracts: &ArtifactContracts<ContractBytecodeSome>,
+    ) -> Result<BroadcastableTransactions> {
+        let mut manager = ProvidersManager::default();
+        let mut remaining = BroadcastableTransactions::new();
+        // skipped deployments per rpc and sender
+        let mut skipped: HashMap<(String, Address), U256> = HashMap::new();
+
+        for mut btx in txs {
+            let Some(rpc) = btx.rpc.clone().or_else(|| self.evm_opts.fork_url.clone()) else {
+                remaining.push_back(btx);
+                continue
+            };
+            let provider_info = manager.get_or_init_provider(&rpc).await?;
+            let (provider, network) = (provider_info.provider.clone(), provider_info.network);
+
+            let tx = &mut btx.transaction;
+            let from = *tx.from().expect("No sender for onchain transaction!");
+            let shift = skipped.get(&(rpc.clone(), from)).copied().unwrap_or_default();
+            let create2_deployer = default_create2_address(Some(network));
+
+            match tx.to().cloned() {
+                Some(NameOrAddress::Address(to)) if to == create2_deployer => {
+                    let data = tx.data().map(|data| data.to_vec()).unwrap_or_default();
+                    if data.len() > SALT_LEN {
+                        let (salt, init_code) = data.split_at(SALT_LEN);
+                        let address = get_create2_address(to, salt, init_code, network);
+                        let code = provider.get_code(address, None).await?;
+                        if !code.is_empty() {
+                            let name = existing_contract_name(
+                                address,
+                                init_code,
+                                &code,
+                                creation_contracts,
+                                highlevel_known_contracts,
+                            )?;
+                            shell::println(format!(
+                                "{} {name} is already deployed at {address:?} on {network}",
+                                Paint::yellow("Skipping deployment:"),
+                            ))?;
+                            *skipped.entry((rpc, from)).or_default() += U256::one();
+                            continue
+                        }
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    // the address of a CREATE deployment depends on the sender's nonce
+                    if let Some(nonce) = tx.nonce().copied().filter(|_| !shift.is_zero()) {
+                        eyre::bail!("Skipping existing deployments would change the address of the contract created by {from:?} with nonce {nonce}")
+                    }
+                }
+            }
+
+            if !shift.is_zero() {
+                if let Some(nonce) = tx.nonce().copied() {
+                    tx.set_nonce(nonce - shift);
+                }
+            }
+            remaining.push_back(btx);
+        }
+
+        Ok(remaining)
+    }
+
+    /// Flags the zero-value calls of the simulated transactions to addresses without code, since
+    /// they would be no-ops.
+    ///
+    /// Addresses that are created by an earlier transaction of the sequence, including the
+    /// contracts created by factories during the simulation, are not flagged.
+    pub(crate) async fn flag_noop_calls(&self, deployments: &[ScriptSequence]) -> Result<()> {
+        let mut manager = ProvidersManager::default();
+
+        for sequence in deployments {
+            let mut created: HashSet<Address> = HashSet::new();
+            for tx in sequence.transactions.iter() {
+                let typed_tx = tx.typed_tx();
+                let value = typed_tx.value().copied().unwrap_or_default();
+                if let (Some(NameOrAddress::Address(to)), Some(rpc)) = (typed_tx.to(), &tx.rpc) {
+                    if value.is_zero() && !created.contains(to) {
+                        let provider_info = manager.get_or_init_provider(rpc).await?;
+                        let code = provider_info.provider.get_code(*to, None).await?;
+                        if code.is_empty() {
+                            flag_noop_call(*to, provider_info.network)?;
+                        }
+                    }
+                }
+
+                created.extend(tx.contract_address);
+                created.extend(tx.additional_contracts.iter().map(|contract| contract.address));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the name of the contract that is deployed at `address` by `init_code`
+///
+/// Fails if the deployed code doesn't match the runtime code of the contract's artifact, apart from
+/// its immutables. If the artifact is unknown the code is trusted, since the CREATE2 address is
+/// derived from the init code.
+fn existing_contract_name(
+    address: Address,
+    init_code: &[u8],
+    code: &[u8],
+    creation_contracts: &ContractsByArtifact,
+    highlevel_known_contracts: &ArtifactContracts<ContractBytecodeSome>,
+) -> Result<String> {
+    let Some((artifact, _)) = creation_contracts
+        .iter()
+        .find(|(_, (_, bytecode))| !bytecode.is_empty() && init_code.starts_with(bytecode))
+    else {
+        return Ok("unknown contract".to_string())
+    };
+
+    let deployed_bytecode = highlevel_known_contracts.get(artifact).map(|c| &c.deployed_bytecode);
+    if let Some((runtime_code, immutable_references)) = deployed_bytecode.and_then(|deployed| {
+        let runtime_code = deployed.bytecode.as_ref()?.object.as_bytes()?;
+        Some((runtime_code, &deployed.immutable_references))
+    }) {
+        if !runtime_code_matches(runtime_code, code, immutable_references) {
+            eyre::bail!(
+                "{address:?} already holds code that doesn't match the runtime code of {}",
+                artifact.name
+            )
+        }
+    }
+    Ok(artifact.name.clone())
+}
+
+/// Warns about a zero-value call to an address without code
+fn flag_noop_call(to: Address, network: Network) -> Result<()> {
+    shell::println(format!(
+        "{} {to:?} has no code on {network}, calling it without value is a no-op",
+        Paint::yellow("Warning:"),
+    ))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trusts_code_of_unknown_contracts() {
+        let name = existing_contract_name(
+            Address::random(),
+            &[0x60, 0x80],
+            &[0x60],
+            &ContractsByArtifact::default(),
+            &ArtifactContracts(Default::default()),
+        )
+        .unwrap();
+        assert_eq!(name, "unknown contract");
+    }
+}
@@ -2,7 +2,8 @@ use super::*;
 use crate::cmd::{
     ensure_clean_constructor, needs_setup,
     spark::script::{
-        artifacts::ArtifactInfo, runner::SimulationStage, transaction::AdditionalContract,
+        artifacts::ArtifactInfo, review::StateDiff, runner::SimulationStage,
+        transaction::AdditionalContract,
     },
 };
 use corebc::ylem::artifacts::CompactContractBytecode;
@@ -181,7 +182,7 @@ impl ScriptArgs {
                     println!("Gas limit was set in script to {:}", tx.energy.unwrap());
                 }
 
-                let tx = TransactionWithMetadata::new(
+                let mut tx = TransactionWithMetadata::new(
                     tx.into(),
                     transaction.rpc,
                     &result,
@@ -191,6 +192,7 @@ impl ScriptArgs {
                     is_fixed_energy_limit,
                     &self.evm_opts.env.network_id.unwrap(),
                 )?;
+                tx.state_diff = StateDiff::new(&result.traces, result.state_changeset.as_ref());
 
                 Ok((Some(tx), result.traces))
             })
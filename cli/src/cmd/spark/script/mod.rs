@@ -33,7 +33,7 @@ use probe::{
 use spark::{
     debug::DebugArena,
     decode::decode_console_logs,
-    executor::{opts::EvmOpts, Backend},
+    executor::{opts::EvmOpts, Backend, StateChangeset},
     trace::{
         identifier::{EtherscanIdentifier, LocalTraceIdentifier, SignaturesIdentifier},
         CallTraceDecoder, CallTraceDecoderBuilder, RawOrDecodedCall, RawOrDecodedReturnData,
@@ -66,10 +66,13 @@ use ui::{TUIExitReason, Tui, Ui};
 
 mod artifacts;
 mod cmd;
+mod deterministic;
 mod executor;
 mod multi;
+pub mod offline;
 mod providers;
 mod receipts;
+mod review;
 mod sequence;
 pub mod transaction;
 mod verify;
@@ -150,6 +153,29 @@ pub struct ScriptArgs {
     #[clap(long)]
     pub resume: bool,
 
+    /// Writes the simulated transactions with their nonces and energy filled in to an unsigned
+    /// batch file instead of broadcasting them.
+    ///
+    /// The batch can be signed offline with `probe wallet sign-batch`, once per sender, and
+    /// broadcasted with `--resume --signed-batch`.
+    #[clap(
+        long,
+        value_name = "PATH",
+        value_hint = ValueHint::FilePath,
+        conflicts_with_all = &["broadcast", "resume", "unlocked"],
+    )]
+    pub export_unsigned: Option<PathBuf>,
+
+    /// Broadcasts the transactions of a batch signed with `probe wallet sign-batch`.
+    #[clap(
+        long,
+        value_name = "PATH",
+        value_hint = ValueHint::FilePath,
+        requires = "resume",
+        conflicts_with_all = &["unlocked", "multi"],
+    )]
+    pub signed_batch: Option<PathBuf>,
+
     /// If present, --resume or --verify will be assumed to be a multi chain deployment.
     #[clap(long)]
     pub multi: bool,
@@ -190,6 +216,13 @@ pub struct ScriptArgs {
 
     #[clap(flatten)]
     pub retry: RetryArgs,
+
+    #[clap(flatten)]
+    pub stuck: receipts::StuckTransactionArgs,
+
+    /// Broadcasts the transactions without asking for a confirmation after their review.
+    #[clap(long)]
+    pub yes: bool,
 }
 
 // === impl ScriptArgs ===
@@ -631,6 +664,7 @@ pub struct ScriptResult {
     pub returned: bytes::Bytes,
     pub address: Option<Address>,
     pub script_wallets: Vec<LocalWallet>,
+    pub state_changeset: Option<StateChangeset>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -750,6 +784,34 @@ mod tests {
         assert!(args.is_err());
     }
 
+    #[test]
+    fn can_parse_stuck_transaction_args() {
+        let args: ScriptArgs = ScriptArgs::parse_from(["foxar-cli", "Contract.sol"]);
+        assert_eq!(args.stuck.bump_timeout, None);
+        assert_eq!(args.stuck.bump_percent, 10);
+        assert_eq!(args.stuck.max_bumps, 3);
+        assert!(!args.stuck.fill_nonce_gaps);
+
+        let args: ScriptArgs = ScriptArgs::parse_from([
+            "foxar-cli",
+            "Contract.sol",
+            "--bump-timeout",
+            "60",
+            "--bump-percent",
+            "25",
+            "--max-bumps",
+            "5",
+            "--fill-nonce-gaps",
+        ]);
+        assert_eq!(args.stuck.timeout(), Some(std::time::Duration::from_secs(60)));
+        assert_eq!(args.stuck.bump(U256::from(100u64)), U256::from(125u64));
+        assert_eq!(args.stuck.max_bumps, 5);
+        assert!(args.stuck.fill_nonce_gaps);
+
+        assert!(ScriptArgs::try_parse_from(["foxar-cli", "Contract.sol", "--bump-percent", "0"])
+            .is_err());
+    }
+
     #[test]
     fn can_merge_script_config() {
         let args: ScriptArgs =
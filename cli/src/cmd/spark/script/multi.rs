@@ -171,6 +171,7 @@ impl ScriptArgs {
                     .await
                 {
                     Ok(_) => {
+                        sequence.record_deployments(&verify)?;
                         if self.verify {
                             return sequence.verify_contracts(config, verify.clone()).await;
                         }
@@ -0,0 +1,323 @@
+//! Support for signing the transactions of a script on an offline machine
+//!
+//! `spark script --export-unsigned` writes the simulated transactions to an [UnsignedBatch],
+//! `probe wallet sign-batch` turns it into a [SignedBatch] and `spark script --resume
+//! --signed-batch` broadcasts it.
+
+use super::{receipts::clear_pendings, sequence::ScriptSequence, ScriptArgs};
+use crate::{init_progress, opts::WalletSigner, update_progress};
+use corebc::{
+    prelude::{Signer, TxHash},
+    providers::Middleware,
+    types::{transaction::eip2718::TypedTransaction, Address, Bytes, Network, U256},
+    utils::{rlp::Rlp, sha3},
+};
+use eyre::{bail, ContextCompat, Result, WrapErr};
+use foxar_common::{fs, shell, try_get_http_provider};
+use serde::{Deserialize, Serialize};
+use std::{path::Path, sync::Arc};
+
+/// The transactions of a simulated script with their nonces, energy limits and energy prices
+/// filled in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsignedBatch {
+    pub network: Network,
+    pub transactions: Vec<TypedTransaction>,
+}
+
+impl UnsignedBatch {
+    /// Loads the batch from the json file
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(fs::read_json_file(path)?)
+    }
+
+    /// Writes the batch to the json file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        Ok(fs::write_json_file(path, self)?)
+    }
+
+    /// Signs the transactions sent by the signer and adds them to the `signed` batch
+    ///
+    /// A batch with several senders is signed by running this once per sender on the same signed
+    /// batch. Returns the number of signed transactions and fails if the signer sends none.
+    pub async fn sign(&self, signer: &WalletSigner, signed: &mut SignedBatch) -> Result<usize> {
+        if signed.network != self.network || signed.transactions.len() != self.transactions.len() {
+            bail!("The signed batch doesn't belong to the unsigned batch");
+        }
+
+        let mut count = 0;
+        for (index, tx) in self.transactions.iter().enumerate() {
+            let from = *tx.from().wrap_err_with(|| format!("Transaction {index} has no sender"))?;
+            if let Some(previous) = &signed.transactions[index] {
+                ensure_signed_matches(index, previous, tx, self.network)?;
+                continue
+            }
+            if from != signer.address() {
+                continue
+            }
+            let nonce =
+                *tx.nonce().wrap_err_with(|| format!("Transaction {index} has no nonce"))?;
+            let signature = signer
+                .sign_transaction(tx)
+                .await
+                .wrap_err_with(|| format!("Failed to sign transaction {index}"))?;
+            let raw = tx.rlp_signed(&signature);
+            signed.transactions[index] =
+                Some(SignedTransaction { hash: sha3(&raw).into(), from, nonce, raw });
+            count += 1;
+        }
+        if count == 0 {
+            bail!("No unsigned transaction of the batch is sent by {:?}", signer.address());
+        }
+        Ok(count)
+    }
+}
+
+/// A signed transaction of a [SignedBatch]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedTransaction {
+    pub hash: TxHash,
+    pub from: Address,
+    pub nonce: U256,
+    pub raw: Bytes,
+}
+
+/// The signed transactions of an [UnsignedBatch]
+///
+/// Transactions of senders that haven't signed yet are `None`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedBatch {
+    pub network: Network,
+    pub transactions: Vec<Option<SignedTransaction>>,
+}
+
+impl SignedBatch {
+    /// Returns an empty signed batch for the given unsigned batch
+    pub fn new(batch: &UnsignedBatch) -> Self {
+        Self { network: batch.network, transactions: vec![None; batch.transactions.len()] }
+    }
+
+    /// Loads the batch from the json file
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(fs::read_json_file(path)?)
+    }
+
+    /// Writes the batch to the json file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        Ok(fs::write_json_file(path, self)?)
+    }
+
+    /// Ensures that the batch contains the transactions of the given sequence, in the same order
+    fn ensure_matches(&self, sequence: &ScriptSequence) -> Result<()> {
+        if self.network != sequence.network {
+            bail!(
+                "The signed batch is for network `{}`, but the deployment is on `{}`",
+                self.network,
+                sequence.network
+            );
+        }
+        if self.transactions.len() != sequence.transactions.len() {
+            bail!(
+                "The signed batch contains {} transactions, but the deployment has {}",
+                self.transactions.len(),
+                sequence.transactions.len()
+            );
+        }
+        for (index, (signed, tx)) in
+            self.transactions.iter().zip(sequence.transactions.iter()).enumerate()
+        {
+            let tx = tx.typed_tx();
+            let Some(signed) = signed else {
+                bail!(
+                    "Transaction {index} isn't signed, sign the batch with the wallet of {:?}",
+                    tx.from().copied().unwrap_or_default()
+                )
+            };
+            ensure_signed_matches(index, signed, tx, self.network)?;
+        }
+        Ok(())
+    }
+}
+
+/// Ensures that the signed transaction at `index` has the sender, nonce, receiver, value, energy
+/// limit, data and network of `tx`
+///
+/// The energy price isn't compared, because it's filled in when the batch is exported.
+fn ensure_signed_matches(
+    index: usize,
+    signed: &SignedTransaction,
+    tx: &TypedTransaction,
+    network: Network,
+) -> Result<()> {
+    let (decoded, _) = TypedTransaction::decode_signed(&Rlp::new(&signed.raw))
+        .map_err(|err| eyre::eyre!("Failed to decode signed transaction {index}: {err}"))?;
+
+    // unset values are encoded as zero
+    let value = |tx: &TypedTransaction| tx.value().copied().unwrap_or_default();
+    let data = |tx: &TypedTransaction| tx.data().cloned().unwrap_or_default();
+    let mismatches: Vec<_> = [
+        ("sender", decoded.from() == Some(&signed.from) && tx.from() == Some(&signed.from)),
+        ("nonce", decoded.nonce() == Some(&signed.nonce) && tx.nonce() == Some(&signed.nonce)),
+        ("receiver", decoded.to() == tx.to()),
+        ("value", value(&decoded) == value(tx)),
+        ("energy limit", decoded.energy() == tx.energy()),
+        ("data", data(&decoded) == data(tx)),
+        ("network", decoded.network_id().map_or(true, |id| id.as_u64() == u64::from(network))),
+    ]
+    .into_iter()
+    .filter_map(|(field, matches)| (!matches).then_some(field))
+    .collect();
+
+    if !mismatches.is_empty() {
+        bail!(
+            "Signed transaction {index} doesn't match the {} of the deployment",
+            mismatches.join(", ")
+        );
+    }
+    Ok(())
+}
+
+impl ScriptArgs {
+    /// Writes the transactions of the sequence to an [UnsignedBatch] at the given path, with the
+    /// current energy price of the network filled in.
+    pub async fn export_unsigned_batch(
+        &self,
+        deployment_sequence: &ScriptSequence,
+        path: &Path,
+    ) -> Result<()> {
+        let energy_price = match self.with_energy_price {
+            Some(energy_price) => energy_price,
+            None => {
+                let rpc = deployment_sequence
+                    .transactions
+                    .front()
+                    .and_then(|tx| tx.rpc.as_deref())
+                    .wrap_err("No RPC to fetch the energy price from")?;
+                try_get_http_provider(rpc)?.get_energy_price().await?
+            }
+        };
+
+        let transactions = deployment_sequence
+            .transactions
+            .iter()
+            .map(|tx| {
+                let mut tx = tx.typed_tx().clone();
+                tx.set_energy_price(energy_price);
+                tx
+            })
+            .collect();
+        let batch = UnsignedBatch { network: deployment_sequence.network, transactions };
+        batch.save(path)?;
+
+        shell::println(format!(
+            "\nUnsigned transactions saved to: {}\n\nSign them with `probe wallet sign-batch` and broadcast the signed batch with `--resume --signed-batch`.",
+            path.display()
+        ))?;
+        Ok(())
+    }
+
+    /// Broadcasts the transactions of the signed batch which haven't been broadcasted yet and
+    /// waits for their receipts.
+    pub async fn send_signed_batch(
+        &self,
+        deployment_sequence: &mut ScriptSequence,
+        fork_url: &str,
+        batch: SignedBatch,
+    ) -> Result<()> {
+        batch.ensure_matches(deployment_sequence)?;
+
+        let provider = Arc::new(try_get_http_provider(fork_url)?);
+        let already_broadcasted = deployment_sequence.receipts.len();
+
+        let pb = init_progress!(deployment_sequence.transactions, "txes");
+        shell::println("##\nSending signed transactions.")?;
+        // all transactions are signed, checked by `ensure_matches`
+        for (index, tx) in
+            batch.transactions.into_iter().flatten().enumerate().skip(already_broadcasted)
+        {
+            let pending = provider.send_raw_transaction(tx.raw).await?;
+            let tx_hash = pending.tx_hash();
+            if tx_hash != tx.hash {
+                bail!("The network returned hash {tx_hash:?} for signed transaction {:?}", tx.hash)
+            }
+            deployment_sequence.add_pending(index, tx_hash);
+            update_progress!(pb, index);
+
+            if self.slow {
+                clear_pendings(provider.clone(), deployment_sequence, Some(vec![tx_hash])).await?;
+            }
+        }
+        // Checkpoint save
+        deployment_sequence.save()?;
+
+        shell::println("##\nWaiting for receipts.")?;
+        clear_pendings(provider, deployment_sequence, None).await?;
+        deployment_sequence.save()?;
+
+        self.show_onchain_summary(deployment_sequence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::spark::script::transaction::TransactionWithMetadata;
+    use corebc::{core::rand::thread_rng, prelude::TransactionRequest, signers::LocalWallet};
+    use foxar_cli_test_utils::tempfile::tempdir;
+
+    #[tokio::test]
+    async fn signs_batch_once_per_sender_and_checks_signed_fields() {
+        let network = Network::Private(1337);
+        let (alice, bob) = (
+            LocalWallet::new(&mut thread_rng(), network),
+            LocalWallet::new(&mut thread_rng(), network),
+        );
+        let transfer = |from: Address| -> TypedTransaction {
+            TransactionRequest::new()
+                .from(from)
+                .to(Address::random())
+                .value(1u64)
+                .nonce(0u64)
+                .energy(21_000u64)
+                .energy_price(1u64)
+                .network_id(u64::from(network))
+                .into()
+        };
+        let batch = UnsignedBatch {
+            network,
+            transactions: vec![transfer(alice.address()), transfer(bob.address())],
+        };
+
+        let mut signed = SignedBatch::new(&batch);
+        assert_eq!(batch.sign(&alice.clone().into(), &mut signed).await.unwrap(), 1);
+        assert!(signed.transactions[1].is_none());
+        assert_eq!(batch.sign(&bob.clone().into(), &mut signed).await.unwrap(), 1);
+        assert!(batch.sign(&alice.into(), &mut signed).await.is_err());
+
+        // the sequence is saved when it's dropped
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("sensitive")).unwrap();
+        let mut sequence = ScriptSequence {
+            network,
+            path: dir.path().join("run-latest.json"),
+            sensitive_path: dir.path().join("sensitive").join("run-latest.json"),
+            transactions: batch
+                .transactions
+                .iter()
+                .cloned()
+                .map(TransactionWithMetadata::from_typed_transaction)
+                .collect(),
+            ..Default::default()
+        };
+        signed.ensure_matches(&sequence).unwrap();
+
+        let mut tampered = batch.transactions[1].clone();
+        tampered.set_value(2u64);
+        sequence.transactions[1].set_tx(tampered);
+        let err = signed.ensure_matches(&sequence).unwrap_err();
+        assert!(err.to_string().contains("doesn't match the value"), "{err}");
+    }
+}
@@ -2,23 +2,86 @@ use crate::{
     cmd::spark::script::sequence::ScriptSequence, init_progress, update_progress,
     utils::print_receipt,
 };
+use clap::{builder::RangedU64ValueParser, Parser};
 use corebc::{
     prelude::{PendingTransaction, TxHash},
     providers::Middleware,
-    types::TransactionReceipt,
+    types::{TransactionReceipt, U256},
 };
 use foxar_common::RetryProvider;
 use futures::StreamExt;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use tracing::{trace, warn};
 
+/// How to handle transactions that don't get mined
+#[derive(Debug, Clone, Copy, Parser)]
+pub struct StuckTransactionArgs {
+    /// Re-send a transaction with a bumped energy price if it isn't mined after this many
+    /// seconds.
+    #[clap(long, value_name = "SECONDS")]
+    pub bump_timeout: Option<u64>,
+
+    /// The percentage by which the energy price of a stuck transaction is bumped.
+    #[clap(
+        long,
+        value_name = "PERCENT",
+        value_parser = RangedU64ValueParser::<u64>::new().range(1..),
+        default_value = "10"
+    )]
+    pub bump_percent: u64,
+
+    /// How many times a transaction is replaced before giving up.
+    #[clap(long, value_name = "NUM", default_value = "3")]
+    pub max_bumps: u32,
+
+    /// Fill nonce gaps left by dropped transactions with zero-value self-transfers.
+    #[clap(long)]
+    pub fill_nonce_gaps: bool,
+}
+
+impl Default for StuckTransactionArgs {
+    fn default() -> Self {
+        Self { bump_timeout: None, bump_percent: 10, max_bumps: 3, fill_nonce_gaps: false }
+    }
+}
+
+impl StuckTransactionArgs {
+    /// Returns the timeout after which a transaction is considered stuck
+    pub fn timeout(&self) -> Option<Duration> {
+        self.bump_timeout.map(Duration::from_secs)
+    }
+
+    /// Returns the given energy price bumped by the configured percentage, rounded up
+    pub fn bump(&self, energy_price: U256) -> U256 {
+        let bumped = energy_price.saturating_mul(U256::from(100 + self.bump_percent));
+        (bumped + 99) / 100
+    }
+}
+
 /// Convenience enum for internal signalling of transaction status
 enum TxStatus {
     Dropped,
+    /// Still in the mempool after the timeout
+    Stuck,
     Success(TransactionReceipt),
     Revert(TransactionReceipt),
 }
 
+/// Transactions that were neither mined nor failed while waiting for their receipts
+#[derive(Debug, Default)]
+pub struct UnresolvedTransactions {
+    /// Transactions that were still in the mempool after the timeout
+    pub stuck: Vec<TxHash>,
+    /// Transactions that were dropped from the mempool
+    pub dropped: Vec<TxHash>,
+}
+
+impl UnresolvedTransactions {
+    pub fn is_empty(&self) -> bool {
+        self.stuck.is_empty() && self.dropped.is_empty()
+    }
+}
+
 impl From<TransactionReceipt> for TxStatus {
     fn from(receipt: TransactionReceipt) -> Self {
         let status = receipt.status.expect("receipt is from an ancient, pre-EIP658 block");
@@ -59,16 +122,33 @@ pub async fn clear_pendings(
     deployment_sequence: &mut ScriptSequence,
     tx_hashes: Option<Vec<TxHash>>,
 ) -> eyre::Result<()> {
+    clear_pendings_with_timeout(provider, deployment_sequence, tx_hashes, None, false).await?;
+    Ok(())
+}
+
+/// Same as [clear_pendings], but stops waiting for a transaction that is still in the mempool
+/// after `timeout` and keeps it pending.
+///
+/// If `keep_dropped` is set, dropped transactions are returned instead of failing, so they can be
+/// re-sent. A dropped transaction counts as mined if a transaction it replaced was mined.
+pub async fn clear_pendings_with_timeout(
+    provider: Arc<RetryProvider>,
+    deployment_sequence: &mut ScriptSequence,
+    tx_hashes: Option<Vec<TxHash>>,
+    timeout: Option<Duration>,
+    keep_dropped: bool,
+) -> eyre::Result<UnresolvedTransactions> {
     let to_query = tx_hashes.unwrap_or_else(|| deployment_sequence.pending.clone());
 
     let count = deployment_sequence.pending.len();
 
     trace!("Checking status of {count} pending transactions");
 
-    let futs = to_query.iter().copied().map(|tx| check_tx_status(&provider, tx));
+    let futs = to_query.iter().copied().map(|tx| check_tx_status(&provider, tx, timeout));
     let mut tasks = futures::stream::iter(futs).buffer_unordered(10);
 
     let mut errors: Vec<String> = vec![];
+    let mut unresolved = UnresolvedTransactions::default();
     let mut receipts = Vec::<TransactionReceipt>::with_capacity(count);
 
     // set up progress bar
@@ -84,7 +164,30 @@ pub async fn clear_pendings(
             Ok(TxStatus::Dropped) => {
                 // We want to remove it from pending so it will be re-broadcast.
                 deployment_sequence.remove_pending(tx_hash);
-                errors.push(format!("Transaction dropped from the mempool: {tx_hash:?}"));
+                // the replacement is dropped if the replaced transaction was mined instead
+                let mut replaced_receipt = None;
+                for replaced in deployment_sequence.replaced_hashes(tx_hash) {
+                    if let Some(receipt) = provider.get_transaction_receipt(replaced).await? {
+                        replaced_receipt = Some(receipt);
+                        break
+                    }
+                }
+                if let Some(receipt) = replaced_receipt {
+                    trace!(tx_hash = ?receipt.transaction_hash, "replaced transaction was mined");
+                    if receipt.status == Some(1u64.into()) {
+                        receipts.push(receipt);
+                    } else {
+                        errors.push(format!("Transaction Failure: {:?}", receipt.transaction_hash));
+                    }
+                } else if keep_dropped {
+                    unresolved.dropped.push(tx_hash);
+                } else {
+                    errors.push(format!("Transaction dropped from the mempool: {tx_hash:?}"));
+                }
+            }
+            Ok(TxStatus::Stuck) => {
+                trace!(tx_hash = ?tx_hash, "transaction is stuck in the mempool");
+                unresolved.stuck.push(tx_hash);
             }
             Ok(TxStatus::Success(receipt)) => {
                 trace!(tx_hash = ?tx_hash, "received tx receipt");
@@ -124,7 +227,7 @@ pub async fn clear_pendings(
         eyre::bail!(error_msg);
     }
 
-    Ok(())
+    Ok(unresolved)
 }
 
 /// Checks the status of a txhash by first polling for a receipt, then for
@@ -132,6 +235,7 @@ pub async fn clear_pendings(
 async fn check_tx_status(
     provider: &RetryProvider,
     hash: TxHash,
+    timeout: Option<Duration>,
 ) -> (TxHash, Result<TxStatus, eyre::Report>) {
     // We use the inner future so that we can use ? operator in the future, but
     // still neatly return the tuple
@@ -144,7 +248,14 @@ async fn check_tx_status(
 
         // If the tx is present in the mempool, run the pending tx future, and
         // assume the next drop is really really real
-        let pending_res = PendingTransaction::new(hash, provider).await?;
+        let pending = PendingTransaction::new(hash, provider);
+        let pending_res = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, pending).await {
+                Ok(res) => res?,
+                Err(_) => return Ok(TxStatus::Stuck),
+            },
+            None => pending.await?,
+        };
         match pending_res {
             Some(receipt) => Ok(receipt.into()),
             None => Ok(TxStatus::Dropped),
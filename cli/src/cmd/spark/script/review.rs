@@ -0,0 +1,295 @@
+//! Review of the simulated transactions before they are broadcasted
+
+use super::{
+    providers::ProvidersManager, sequence::ScriptSequence, ScriptArgs, TransactionWithMetadata,
+};
+use corebc::{
+    types::{Address, NameOrAddress, I256, U256},
+    utils::format_units,
+};
+use dialoguer::Confirm;
+use eyre::Result;
+use foxar_common::{contracts::get_contract_name, shell};
+use foxar_evm::utils::{b176_to_h176, ru256_to_u256};
+use spark::{
+    executor::StateChangeset,
+    trace::{CallTraceDecoder, Traces},
+    CallKind,
+};
+use std::collections::{BTreeMap, HashMap};
+use yansi::Paint;
+
+/// The state changes of a simulated transaction
+#[derive(Debug, Clone, Default)]
+pub struct StateDiff {
+    /// The value each address received (positive) or sent (negative)
+    pub balance_changes: BTreeMap<Address, I256>,
+    /// The size of the runtime code of each created contract
+    pub created_contracts: BTreeMap<Address, usize>,
+    /// The previous and new value of each written storage slot, by address
+    pub storage_changes: BTreeMap<Address, BTreeMap<U256, (U256, U256)>>,
+}
+
+impl StateDiff {
+    /// Collects the value transfers and created contracts of the calls in `traces` that weren't
+    /// reverted, and the storage slots written according to the changeset.
+    pub fn new(traces: &Traces, state_changeset: Option<&StateChangeset>) -> Self {
+        let mut diff = Self::default();
+
+        for (_, arena) in traces {
+            let nodes = &arena.arena;
+            for node in nodes {
+                // a call is reverted if any of its parents failed
+                let mut reverted = !node.trace.success;
+                let mut parent = node.parent;
+                while let Some(idx) = parent {
+                    reverted |= !nodes[idx].trace.success;
+                    parent = nodes[idx].parent;
+                }
+                if reverted {
+                    continue
+                }
+
+                let trace = &node.trace;
+                // delegate and static calls don't transfer value
+                if !matches!(trace.kind, CallKind::Call | CallKind::Create | CallKind::Create2) {
+                    continue
+                }
+                if !trace.value.is_zero() {
+                    let value = I256::from_raw(trace.value);
+                    *diff.balance_changes.entry(trace.caller).or_default() -= value;
+                    *diff.balance_changes.entry(trace.address).or_default() += value;
+                }
+                if matches!(trace.kind, CallKind::Create | CallKind::Create2) {
+                    diff.created_contracts.insert(trace.address, trace.output.to_raw().len());
+                }
+            }
+        }
+        diff.balance_changes.retain(|_, change| !change.is_zero());
+
+        for (address, account) in state_changeset.into_iter().flatten() {
+            let slots: BTreeMap<_, _> = account
+                .storage
+                .iter()
+                .filter(|(_, slot)| slot.original_value() != slot.present_value())
+                .map(|(index, slot)| {
+                    (
+                        ru256_to_u256(*index),
+                        (ru256_to_u256(slot.original_value()), ru256_to_u256(slot.present_value())),
+                    )
+                })
+                .collect();
+            if !slots.is_empty() {
+                diff.storage_changes.insert(b176_to_h176(*address), slots);
+            }
+        }
+
+        diff
+    }
+}
+
+impl ScriptArgs {
+    /// Prints the simulated transactions of the sequences with their state changes and the maximum
+    /// energy cost, and asks for a confirmation before they are broadcasted.
+    ///
+    /// The maximum cost is derived from the energy limits of the transactions, so it's an upper
+    /// bound. The confirmation is skipped with `--yes`, which is required if stdin isn't a
+    /// terminal.
+    pub(crate) async fn review_transactions(
+        &self,
+        deployments: &[ScriptSequence],
+        decoder: &CallTraceDecoder,
+    ) -> Result<()> {
+        let mut manager = ProvidersManager::default();
+
+        shell::println("\n==========================")?;
+        shell::println(format!("\n{}", Paint::yellow("Review of the transactions to broadcast")))?;
+
+        for sequence in deployments {
+            let labels = address_labels(sequence, decoder);
+            shell::println(format!("\nChain {}", sequence.network))?;
+
+            let mut total_energy = U256::zero();
+            for (idx, tx) in sequence.transactions.iter().enumerate() {
+                review_transaction(idx, tx, &labels)?;
+                total_energy += tx.typed_tx().energy().copied().unwrap_or_default();
+            }
+
+            let Some(rpc) = sequence.transactions.front().and_then(|tx| tx.rpc.as_ref()) else {
+                continue
+            };
+            let energy_price = match self.with_energy_price {
+                Some(energy_price) => energy_price,
+                None => manager.get_or_init_provider(rpc).await?.energy_price()?,
+            };
+            shell::println(format!(
+                "\nMaximum energy cost: {} XCB ({total_energy} energy at {} nucle)",
+                format_xcb(total_energy.saturating_mul(energy_price)),
+                format_units(energy_price, 9)
+                    .unwrap_or_else(|_| "[Could not calculate]".to_string())
+                    .trim_end_matches('0')
+                    .trim_end_matches('.')
+            ))?;
+        }
+        shell::println("\n==========================")?;
+
+        if self.yes {
+            return Ok(())
+        }
+        if !is_terminal::is_terminal(std::io::stdin()) {
+            eyre::bail!("Can't confirm the transactions without a terminal, pass --yes to broadcast non-interactively.");
+        }
+        if !Confirm::new()
+            .with_prompt("Do you wish to broadcast these transactions?".to_string())
+            .interact()?
+        {
+            eyre::bail!("User canceled the script.");
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the labels of the addresses, set with `vm.label`, or the names of the contracts
+/// identified locally or created by the sequence
+fn address_labels(
+    sequence: &ScriptSequence,
+    decoder: &CallTraceDecoder,
+) -> HashMap<Address, String> {
+    let mut labels: HashMap<Address, String> = decoder
+        .contracts
+        .iter()
+        .map(|(address, identifier)| (*address, get_contract_name(identifier).to_string()))
+        .collect();
+    labels.extend(sequence.transactions.iter().filter_map(|tx| {
+        let name = tx.contract_name.as_deref().filter(|name| !name.is_empty())?;
+        Some((tx.contract_address?, get_contract_name(name).to_string()))
+    }));
+    labels.extend(decoder.labels.iter().map(|(address, label)| (*address, label.clone())));
+    labels
+}
+
+/// Prints a transaction with the state changes of its simulation
+fn review_transaction(
+    idx: usize,
+    tx: &TransactionWithMetadata,
+    labels: &HashMap<Address, String>,
+) -> Result<()> {
+    let label = |address: &Address| match labels.get(address) {
+        Some(label) => format!("{label} ({address:?})"),
+        None => format!("{address:?}"),
+    };
+
+    let typed_tx = tx.typed_tx();
+    let from = typed_tx.from().map(label).unwrap_or_else(|| "unknown sender".to_string());
+    let target = match (tx.opcode, tx.contract_address.as_ref()) {
+        (CallKind::Create | CallKind::Create2, Some(address)) => {
+            format!("create {}", label(address))
+        }
+        _ => match typed_tx.to() {
+            Some(NameOrAddress::Address(to)) => label(to),
+            Some(NameOrAddress::Name(name)) => name.clone(),
+            None => "unknown".to_string(),
+        },
+    };
+    let call = match (&tx.function, &tx.arguments) {
+        (Some(function), Some(arguments)) => format!("{function} ({})", arguments.join(", ")),
+        (Some(function), None) => function.clone(),
+        (None, Some(arguments)) => format!("constructor ({})", arguments.join(", ")),
+        // calldata that couldn't be decoded is shown raw
+        (None, None) => typed_tx
+            .data()
+            .filter(|data| tx.opcode == CallKind::Call && !data.is_empty())
+            .map(|data| format!("0x{}", hex::encode(data)))
+            .unwrap_or_default(),
+    };
+
+    shell::println(format!("\n[{}] {from} -> {target}", idx + 1))?;
+    if !call.is_empty() {
+        shell::println(format!("    call: {call}"))?;
+    }
+    if let Some(value) = typed_tx.value().filter(|value| !value.is_zero()) {
+        shell::println(format!("    value: {} XCB", format_xcb(*value)))?;
+    }
+    if let Some(energy) = typed_tx.energy() {
+        shell::println(format!("    energy limit: {energy}"))?;
+    }
+
+    let diff = &tx.state_diff;
+    for (address, size) in &diff.created_contracts {
+        shell::println(format!("    created: {} ({size} bytes)", label(address)))?;
+    }
+    for (address, change) in &diff.balance_changes {
+        let sign = if change.is_negative() { "-" } else { "+" };
+        shell::println(format!(
+            "    balance: {} {sign}{} XCB",
+            label(address),
+            format_xcb(change.unsigned_abs())
+        ))?;
+    }
+    for (address, slots) in &diff.storage_changes {
+        for (slot, (previous, value)) in slots {
+            shell::println(format!(
+                "    storage: {} slot {slot:#x}: {previous:#x} -> {value:#x}",
+                label(address)
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+/// Formats an amount of ore as XCB
+fn format_xcb(amount: U256) -> String {
+    let formatted =
+        format_units(amount, 18).unwrap_or_else(|_| "[Could not calculate]".to_string());
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spark::trace::{node::CallTraceNode, CallTrace, CallTraceArena, TraceKind};
+
+    fn node(idx: usize, parent: Option<usize>, trace: CallTrace) -> CallTraceNode {
+        CallTraceNode { idx, parent, trace, ..Default::default() }
+    }
+
+    #[test]
+    fn collects_value_transfers_of_successful_calls() {
+        let (sender, target, nested) = (Address::random(), Address::random(), Address::random());
+        let arena = CallTraceArena {
+            arena: vec![
+                node(
+                    0,
+                    None,
+                    CallTrace {
+                        success: true,
+                        caller: sender,
+                        address: target,
+                        kind: CallKind::Call,
+                        value: 10.into(),
+                        ..Default::default()
+                    },
+                ),
+                node(
+                    1,
+                    Some(0),
+                    CallTrace {
+                        success: false,
+                        caller: target,
+                        address: nested,
+                        kind: CallKind::Call,
+                        value: 5.into(),
+                        ..Default::default()
+                    },
+                ),
+            ],
+        };
+
+        let diff = StateDiff::new(&vec![(TraceKind::Execution, arena)], None);
+        assert_eq!(diff.balance_changes[&sender], I256::from(-10));
+        assert_eq!(diff.balance_changes[&target], I256::from(10));
+        assert!(!diff.balance_changes.contains_key(&nested));
+        assert!(diff.created_contracts.is_empty());
+    }
+}
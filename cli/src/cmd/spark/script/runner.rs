@@ -173,6 +173,7 @@ impl ScriptRunner {
                 debug,
                 address: None,
                 script_wallets,
+                state_changeset: None,
             },
         ))
     }
@@ -216,23 +217,30 @@ impl ScriptRunner {
         if let Some(NameOrAddress::Address(to)) = to {
             self.call(from, to, calldata.unwrap_or_default(), value.unwrap_or(U256::zero()), true)
         } else if to.is_none() {
-            let (address, energy_used, logs, traces, debug) = match self.executor.deploy(
-                from,
-                calldata.expect("No data for create transaction").0,
-                value.unwrap_or(U256::zero()),
-                None,
-            ) {
-                Ok(DeployResult { address, energy_used, logs, traces, debug, .. }) => {
-                    (address, energy_used, logs, traces, debug)
-                }
-                Err(EvmError::Execution(err)) => {
-                    let ExecutionErr { reason, traces, energy_used, logs, debug, .. } = *err;
-                    println!("{}", Paint::red(format!("\nFailed with `{reason}`:\n")));
+            let (address, energy_used, logs, traces, debug, state_changeset) =
+                match self.executor.deploy(
+                    from,
+                    calldata.expect("No data for create transaction").0,
+                    value.unwrap_or(U256::zero()),
+                    None,
+                ) {
+                    Ok(DeployResult {
+                        address,
+                        energy_used,
+                        logs,
+                        traces,
+                        debug,
+                        state_changeset,
+                        ..
+                    }) => (address, energy_used, logs, traces, debug, state_changeset),
+                    Err(EvmError::Execution(err)) => {
+                        let ExecutionErr { reason, traces, energy_used, logs, debug, .. } = *err;
+                        println!("{}", Paint::red(format!("\nFailed with `{reason}`:\n")));
 
-                    (Address::zero(), energy_used, logs, traces, debug)
-                }
-                Err(e) => eyre::bail!("Failed deploying contract: {e:?}"),
-            };
+                        (Address::zero(), energy_used, logs, traces, debug, None)
+                    }
+                    Err(e) => eyre::bail!("Failed deploying contract: {e:?}"),
+                };
 
             Ok(ScriptResult {
                 returned: bytes::Bytes::new(),
@@ -252,6 +260,7 @@ impl ScriptRunner {
                 transactions: Default::default(),
                 address: Some(address),
                 script_wallets: vec![],
+                state_changeset,
             })
         } else {
             eyre::bail!("ENS not supported.");
@@ -294,6 +303,7 @@ impl ScriptRunner {
             debug,
             transactions,
             script_wallets,
+            state_changeset,
             ..
         } = res;
 
@@ -314,6 +324,7 @@ impl ScriptRunner {
             transactions,
             address: None,
             script_wallets,
+            state_changeset,
         })
     }
 
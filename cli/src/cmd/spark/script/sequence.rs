@@ -1,5 +1,6 @@
 use super::NestedValue;
 use crate::cmd::spark::{
+    deployments::{Deployment, DeploymentsRegistry},
     init::get_commit_hash,
     script::{
         transaction::{wrapper, AdditionalContract, TransactionWithMetadata},
@@ -9,7 +10,7 @@ use crate::cmd::spark::{
 use corebc::{
     abi::Address,
     prelude::{artifacts::Libraries, ArtifactId, TransactionReceipt, TxHash},
-    types::{transaction::eip2718::TypedTransaction, Network},
+    types::{transaction::eip2718::TypedTransaction, Network, U256},
 };
 use eyre::{ContextCompat, WrapErr};
 use foxar_common::{fs, shell, SELECTOR_LEN};
@@ -45,6 +46,34 @@ pub struct ScriptSequence {
     /// If `True`, the sequence belongs to a `MultiChainSequence` and won't save to disk as usual.
     pub multi: bool,
     pub commit: Option<String>,
+    /// Transactions that were sent to replace stuck or dropped transactions, or to fill nonce gaps
+    #[serde(default)]
+    pub replacements: Vec<TransactionReplacement>,
+}
+
+/// Why a [TransactionReplacement] was sent
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReplacementKind {
+    /// Re-sent a stuck transaction with a bumped energy price
+    Bump,
+    /// Re-sent a transaction that was dropped from the mempool
+    Resend,
+    /// Sent a zero-value self-transfer to fill a nonce gap
+    GapFill,
+}
+
+/// A transaction that was sent with the nonce of another transaction
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionReplacement {
+    pub kind: ReplacementKind,
+    pub from: Address,
+    pub nonce: U256,
+    /// The hash of the transaction that was replaced, if any
+    pub replaced: Option<TxHash>,
+    pub hash: TxHash,
+    pub energy_price: U256,
 }
 
 /// Sensitive values from the transactions in a script sequence
@@ -109,6 +138,7 @@ impl ScriptSequence {
             network: chain,
             multi: is_multi,
             commit,
+            replacements: vec![],
         })
     }
 
@@ -211,6 +241,40 @@ impl ScriptSequence {
         self.pending.retain(|element| element != &tx_hash);
     }
 
+    /// Records a replacement transaction
+    ///
+    /// If the replacement belongs to the transaction at `index`, it takes the place of the replaced
+    /// transaction in the pending transactions.
+    pub fn add_replacement(&mut self, index: Option<usize>, replacement: TransactionReplacement) {
+        if let Some(replaced) = replacement.replaced {
+            self.remove_pending(replaced);
+        }
+        if let Some(index) = index {
+            self.add_pending(index, replacement.hash);
+        }
+        self.replacements.push(replacement);
+    }
+
+    /// Returns the hashes of all transactions that were replaced by the given transaction, most
+    /// recent first
+    pub fn replaced_hashes(&self, tx_hash: TxHash) -> Vec<TxHash> {
+        let mut hashes = vec![];
+        let mut current = tx_hash;
+        while let Some(replaced) = self
+            .replacements
+            .iter()
+            .find(|replacement| replacement.hash == current)
+            .and_then(|replacement| replacement.replaced)
+        {
+            if hashes.contains(&replaced) {
+                break
+            }
+            hashes.push(replaced);
+            current = replaced;
+        }
+        hashes
+    }
+
     pub fn add_libraries(&mut self, libraries: Libraries) {
         self.libraries = libraries
             .libs
@@ -324,6 +388,63 @@ impl ScriptSequence {
         Ok(())
     }
 
+    /// Records the contracts created by the broadcasted transactions in the project's deployments
+    /// registry
+    pub fn record_deployments(&mut self, verify: &VerifyBundle) -> eyre::Result<()> {
+        let Some(root) = verify.project_paths.root.as_ref() else { return Ok(()) };
+        let registry = DeploymentsRegistry::new(root);
+
+        // Make sure the receipts have the right order first.
+        self.sort_receipts();
+
+        let mut recorded = 0;
+        for (receipt, tx) in self.receipts.iter().zip(self.transactions.iter()) {
+            let mut created = vec![];
+            if let Some(data) = tx.typed_tx().data() {
+                if tx.is_create2() {
+                    // the init code of a CREATE2 transaction starts after the 32-byte salt
+                    if let Some(address) = tx.contract_address {
+                        created.push((address, data.0.get(32..).unwrap_or_default()));
+                    }
+                } else if let Some(address) = receipt.contract_address {
+                    created.push((address, &data.0[..]));
+                }
+            }
+            for AdditionalContract { address, init_code, .. } in &tx.additional_contracts {
+                created.push((*address, &init_code[..]));
+            }
+
+            for (address, init_code) in created {
+                let Some((artifact, (_, bytecode))) =
+                    verify.known_contracts.iter().find(|(_, (_, bytecode))| {
+                        !bytecode.is_empty() && init_code.starts_with(bytecode)
+                    })
+                else {
+                    continue
+                };
+                let deployment = Deployment::new(
+                    root,
+                    artifact,
+                    bytecode,
+                    init_code,
+                    address,
+                    receipt.transaction_hash,
+                    receipt.block_number,
+                );
+                registry.add(self.network, &deployment)?;
+                recorded += 1;
+            }
+        }
+
+        if recorded > 0 {
+            shell::println(format!(
+                "Recorded {recorded} deployments in: {}\n",
+                registry.dir().join(self.network.to_string()).display()
+            ))?;
+        }
+        Ok(())
+    }
+
     /// Let the user know if there are any contracts which can not be verified. Also, present some
     /// hints on potential causes.
     fn check_unverified(&self, unverifiable_contracts: Vec<Address>, verify: VerifyBundle) {
@@ -404,4 +525,25 @@ mod tests {
             "522bb704"
         );
     }
+
+    #[test]
+    fn can_follow_replacements() {
+        let (first, second, third) = (TxHash::random(), TxHash::random(), TxHash::random());
+        let replacement = |replaced, hash| TransactionReplacement {
+            kind: ReplacementKind::Bump,
+            from: Address::zero(),
+            nonce: 0u64.into(),
+            replaced: Some(replaced),
+            hash,
+            energy_price: 1u64.into(),
+        };
+
+        let mut sequence = ScriptSequence { pending: vec![first], ..Default::default() };
+        sequence.add_replacement(None, replacement(first, second));
+        sequence.add_replacement(None, replacement(second, third));
+
+        assert!(sequence.pending.is_empty());
+        assert_eq!(sequence.replaced_hashes(third), vec![second, first]);
+        assert!(sequence.replaced_hashes(first).is_empty());
+    }
 }
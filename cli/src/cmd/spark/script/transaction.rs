@@ -1,4 +1,4 @@
-use crate::cmd::spark::script::{artifacts::ArtifactInfo, ScriptResult};
+use crate::cmd::spark::script::{artifacts::ArtifactInfo, review::StateDiff, ScriptResult};
 use corebc::{
     abi,
     abi::Address,
@@ -46,6 +46,9 @@ pub struct TransactionWithMetadata {
     pub transaction: TypedTransaction,
     pub additional_contracts: Vec<AdditionalContract>,
     pub is_fixed_energy_limit: bool,
+    /// The state changes of the onchain simulation of the transaction
+    #[serde(skip)]
+    pub state_diff: StateDiff,
 }
 
 fn default_string() -> Option<String> {
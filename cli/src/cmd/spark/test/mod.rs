@@ -11,7 +11,7 @@ use corebc::types::{Network, U256};
 use foxar_common::{
     compile::{self, ProjectCompiler},
     evm::EvmArgs,
-    get_contract_name, get_file_name,
+    fs, get_contract_name, get_file_name,
 };
 use foxar_config::{figment, get_available_profiles, Config};
 use probe::fuzz::CounterExample;
@@ -21,6 +21,7 @@ use spark::{
     executor::inspector::CheatsConfig,
     gas_report::GasReport,
     result::{SuiteResult, TestKind, TestResult, TestStatus},
+    test_report::{JunitReporter, TapReporter},
     trace::{
         identifier::{EtherscanIdentifier, LocalTraceIdentifier, SignaturesIdentifier},
         CallTraceDecoderBuilder, TraceKind,
@@ -81,10 +82,25 @@ pub struct TestArgs {
     #[clap(long, short, help_heading = "Display options")]
     json: bool,
 
+    /// Output test results as a TAP (Test Anything Protocol) stream.
+    #[clap(long, help_heading = "Display options", conflicts_with = "json")]
+    tap: bool,
+
+    /// Write a JUnit XML report of the test results to the given file.
+    #[clap(long, value_name = "PATH", help_heading = "Display options")]
+    junit: Option<PathBuf>,
+
     /// Stop running tests after the first failure
     #[clap(long)]
     pub fail_fast: bool,
 
+    /// Only run the fuzz and invariant tests that failed in a previous run.
+    ///
+    /// Failing inputs are persisted in the cache directory and replayed first on every run, so
+    /// this requires the cache to be enabled.
+    #[clap(long)]
+    pub rerun_failures: bool,
+
     #[clap(flatten)]
     evm_opts: EvmArgs,
 
@@ -118,7 +134,7 @@ impl TestArgs {
 
     pub async fn run(self) -> eyre::Result<TestOutcome> {
         trace!(target: "spark::test", "executing test command");
-        shell::set_shell(shell::Shell::from_args(self.opts.silent, self.json))?;
+        shell::set_shell(shell::Shell::from_args(self.opts.silent || self.tap, self.json))?;
         self.execute_tests().await
     }
 
@@ -151,7 +167,7 @@ impl TestArgs {
         let compiler = ProjectCompiler::default();
         let output = if config.sparse_mode {
             compiler.compile_sparse(&project, filter.clone())
-        } else if self.opts.silent {
+        } else if self.opts.silent || self.tap {
             compile::suppress_compile(&project)
         } else {
             compiler.compile(&project)
@@ -163,12 +179,21 @@ impl TestArgs {
         let toml = config.get_config_path();
         let profiles = get_available_profiles(toml)?;
 
-        let test_options: TestOptions = TestOptionsBuilder::default()
+        let mut test_options_builder = TestOptionsBuilder::default()
             .fuzz(config.fuzz)
             .invariant(config.invariant)
             .compile_output(&output)
             .profiles(profiles)
-            .build(project_root)?;
+            .rerun_failures(self.rerun_failures);
+        if config.cache {
+            test_options_builder = test_options_builder
+                .failure_persist_dir(project_root.join(&config.cache_path).join("fuzz/failures"));
+        } else if self.rerun_failures {
+            eyre::bail!(
+                "`--rerun-failures` requires the cache, failures aren't persisted without it"
+            )
+        }
+        let test_options: TestOptions = test_options_builder.build(project_root)?;
 
         // Determine print verbosity and executor verbosity
         let verbosity = evm_opts.verbosity;
@@ -242,18 +267,25 @@ impl TestArgs {
         } else if self.list {
             list(runner, filter, self.json)
         } else {
-            test(
+            let outcome = test(
                 config,
                 runner,
                 verbosity,
                 filter,
                 self.json,
+                self.tap,
                 self.allow_failure,
                 test_options,
                 self.gas_report,
                 self.fail_fast,
             )
-            .await
+            .await?;
+
+            if let Some(path) = &self.junit {
+                JunitReporter::new(&mut fs::create_file(path)?).report(&outcome.results)?;
+            }
+
+            Ok(outcome)
         }
     }
 
@@ -509,6 +541,7 @@ async fn test(
     verbosity: u8,
     filter: ProjectPathsAwareFilter,
     json: bool,
+    tap: bool,
     allow_failure: bool,
     test_options: TestOptions,
     gas_reporting: bool,
@@ -538,6 +571,28 @@ async fn test(
     if json {
         let results = runner.test(filter, None, test_options).await;
         println!("{}", serde_json::to_string(&results)?);
+        Ok(TestOutcome::new(results, allow_failure))
+    } else if tap {
+        let (tx, rx) = channel::<(String, SuiteResult)>();
+        let handle =
+            tokio::task::spawn(async move { runner.test(filter, Some(tx), test_options).await });
+
+        let mut stdout = std::io::stdout();
+        let mut reporter = TapReporter::new(&mut stdout)?;
+        let mut results: BTreeMap<String, SuiteResult> = BTreeMap::new();
+        for (contract_name, suite_result) in rx {
+            reporter.suite(&contract_name, &suite_result)?;
+            let failed = suite_result.failures().next().is_some();
+            results.insert(contract_name, suite_result);
+            if fail_fast && failed {
+                break;
+            }
+        }
+        reporter.finish()?;
+
+        // reattach the thread
+        let _results = handle.await?;
+
         Ok(TestOutcome::new(results, allow_failure))
     } else {
         // Set up identifiers
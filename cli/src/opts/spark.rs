@@ -5,6 +5,7 @@ use crate::cmd::spark::{
     config, coverage,
     create::CreateArgs,
     debug::DebugArgs,
+    deployments::DeploymentsArgs,
     doc::DocArgs,
     flatten,
     fmt::FmtArgs,
@@ -98,6 +99,10 @@ pub enum Subcommands {
     /// Create a new Spark project.
     Init(InitArgs),
 
+    /// Manage the project's registry of deployed contracts.
+    #[clap(visible_alias = "dep")]
+    Deployments(DeploymentsArgs),
+
     /// Generate shell completions script.
     #[clap(visible_alias = "com")]
     Completions {
@@ -2,7 +2,7 @@ use clap::{CommandFactory, Parser};
 use clap_complete::generate;
 use foxar_cli::{
     cmd::{
-        spark::{cache::CacheSubcommands, watch},
+        spark::{cache::CacheSubcommands, deployments::DeploymentsSubcommands, watch},
         Cmd,
     },
     handler,
@@ -56,6 +56,11 @@ fn main() -> eyre::Result<()> {
         Subcommands::Remove(cmd) => cmd.run(),
         Subcommands::Remappings(cmd) => cmd.run(),
         Subcommands::Init(cmd) => cmd.run(),
+        Subcommands::Deployments(cmd) => match cmd.sub {
+            DeploymentsSubcommands::List(cmd) => cmd.run(),
+            DeploymentsSubcommands::Diff(cmd) => utils::block_on(cmd.run()),
+            DeploymentsSubcommands::Export(cmd) => cmd.run(),
+        },
         Subcommands::Completions { shell } => {
             generate(shell, &mut Opts::command(), "spark", &mut std::io::stdout());
             Ok(())
@@ -168,7 +168,7 @@ impl ScriptTester {
     }
 
     pub fn broadcast(&mut self, expected: ScriptOutcome) -> &mut Self {
-        self.cmd.arg("--broadcast");
+        self.cmd.args(["--broadcast", "--yes"]);
         self.run(expected)
     }
 
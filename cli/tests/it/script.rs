@@ -176,6 +176,7 @@ contract DeployScript is Script {
             "-vvvvv",
             "--slow",
             "--broadcast",
+            "--yes",
             "--unlocked",
             "--network",
             "1",
@@ -187,6 +188,218 @@ contract DeployScript is Script {
     }
 );
 
+// Tests that the transactions of a script can be exported, signed offline and broadcasted
+sparktest_async!(
+    can_broadcast_offline_signed_batch,
+    |prj: TestProject, mut cmd: TestCommand| async move {
+        foxar_cli_test_utils::util::initialize(prj.root());
+        let deploy_script = prj
+            .inner()
+            .add_source(
+                "Foo",
+                r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity >=1.1.0;
+import "spark-std/Script.sol";
+
+contract Greeter {}
+contract DeployScript is Script {
+    function run() external {
+        vm.startBroadcast();
+        new Greeter();
+    }
+}
+   "#,
+            )
+            .unwrap();
+
+        let deploy_contract = deploy_script.display().to_string() + ":DeployScript";
+
+        let (_api, handle) = spawn(NodeConfig::test()).await;
+        let wallet = handle.dev_wallets().next().unwrap();
+        let sender = format!("{:?}", wallet.address());
+        let private_key = hex::encode(wallet.signer().to_bytes());
+        let unsigned = prj.root().join("unsigned.json");
+        let signed = prj.root().join("signed.json");
+        cmd.set_current_dir(prj.root());
+
+        cmd.args([
+            "script",
+            &deploy_contract,
+            "--root",
+            prj.root().to_str().unwrap(),
+            "--fork-url",
+            &handle.http_endpoint(),
+            "--sender",
+            &sender,
+            "--export-unsigned",
+            unsigned.to_str().unwrap(),
+        ]);
+        assert!(cmd.stdout_lossy().contains("Unsigned transactions saved to"));
+
+        // sign without network access
+        cmd.probe_fuse().args([
+            "wallet",
+            "sign-batch",
+            unsigned.to_str().unwrap(),
+            "--out",
+            signed.to_str().unwrap(),
+            "--private-key",
+            &private_key,
+        ]);
+        assert!(cmd.stdout_lossy().contains("Signed 1 transactions"));
+
+        cmd.spark_fuse().args([
+            "script",
+            &deploy_contract,
+            "--root",
+            prj.root().to_str().unwrap(),
+            "--fork-url",
+            &handle.http_endpoint(),
+            "--sender",
+            &sender,
+            "--resume",
+            "--signed-batch",
+            signed.to_str().unwrap(),
+        ]);
+        assert!(cmd.stdout_lossy().contains("ONCHAIN EXECUTION COMPLETE & SUCCESSFUL"));
+    }
+);
+
+// Tests that broadcasted deployments are recorded in the project's deployments registry
+sparktest_async!(
+    records_script_deployments_in_registry,
+    |prj: TestProject, mut cmd: TestCommand| async move {
+        foxar_cli_test_utils::util::initialize(prj.root());
+        let deploy_script = prj
+            .inner()
+            .add_source(
+                "Foo",
+                r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity >=1.1.0;
+import "spark-std/Script.sol";
+
+contract Greeter {}
+contract DeployScript is Script {
+    function run() external {
+        vm.startBroadcast();
+        new Greeter();
+    }
+}
+   "#,
+            )
+            .unwrap();
+
+        let deploy_contract = deploy_script.display().to_string() + ":DeployScript";
+
+        let (api, handle) = spawn(NodeConfig::test()).await;
+        let wallet = handle.dev_wallets().next().unwrap();
+        let network = Network::from(api.eth_chain_id().unwrap().unwrap().as_u64());
+        cmd.set_current_dir(prj.root());
+
+        cmd.args([
+            "script",
+            &deploy_contract,
+            "--root",
+            prj.root().to_str().unwrap(),
+            "--fork-url",
+            &handle.http_endpoint(),
+            "--private-key",
+            &hex::encode(wallet.signer().to_bytes()),
+            "--broadcast",
+            "--yes",
+        ]);
+        assert!(cmd.stdout_lossy().contains("ONCHAIN EXECUTION COMPLETE & SUCCESSFUL"));
+
+        let deployment: Value = serde_json::from_str(
+            &std::fs::read_to_string(
+                prj.root().join("deployments").join(network.to_string()).join("Greeter.json"),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(deployment["contract"], "src/Foo.sol:Greeter");
+        assert!(deployment["blockNumber"].is_string());
+
+        cmd.spark_fuse().args(["deployments", "list", "--root", prj.root().to_str().unwrap()]);
+        assert!(cmd.stdout_lossy().contains("Greeter"));
+    }
+);
+
+sparktest_async!(
+    reviews_transactions_before_broadcast,
+    |prj: TestProject, mut cmd: TestCommand| async move {
+        foxar_cli_test_utils::util::initialize(prj.root());
+        let deploy_script = prj
+            .inner()
+            .add_source(
+                "Foo",
+                r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity >=1.1.0;
+import "spark-std/Script.sol";
+
+contract Counter {
+    uint256 public count;
+
+    function set(uint256 value) external {
+        count = value;
+    }
+}
+contract DeployScript is Script {
+    function run() external {
+        vm.startBroadcast();
+        Counter counter = new Counter();
+        vm.label(address(counter), "MyCounter");
+        counter.set(42);
+    }
+}
+   "#,
+            )
+            .unwrap();
+
+        let deploy_contract = deploy_script.display().to_string() + ":DeployScript";
+
+        let (_api, handle) = spawn(NodeConfig::test()).await;
+        let wallet = handle.dev_wallets().next().unwrap();
+        cmd.set_current_dir(prj.root());
+
+        cmd.args([
+            "script",
+            &deploy_contract,
+            "--root",
+            prj.root().to_str().unwrap(),
+            "--fork-url",
+            &handle.http_endpoint(),
+            "--private-key",
+            &hex::encode(wallet.signer().to_bytes()),
+            "--broadcast",
+            "--yes",
+        ]);
+        let output = cmd.stdout_lossy();
+        assert!(output.contains("Review of the transactions to broadcast"));
+        assert!(output.contains("call: set(uint256) (42)"));
+        assert!(output.contains("storage: MyCounter"));
+        assert!(output.contains("Maximum energy cost:"));
+        assert!(output.contains("ONCHAIN EXECUTION COMPLETE & SUCCESSFUL"));
+
+        // the transactions can't be confirmed without a terminal
+        cmd.spark_fuse().args([
+            "script",
+            &deploy_contract,
+            "--root",
+            prj.root().to_str().unwrap(),
+            "--fork-url",
+            &handle.http_endpoint(),
+            "--private-key",
+            &hex::encode(wallet.signer().to_bytes()),
+            "--broadcast",
+        ]);
+        assert!(cmd.stderr_lossy().contains("pass --yes to broadcast non-interactively"));
+    }
+);
+
 // Tests that the manually specified gas limit is used.
 sparktest_async!(
     can_execute_script_command_with_manual_gas_limit,
@@ -237,6 +450,7 @@ contract DeployScript is Script {
             "-vvvvv",
             "--slow",
             "--broadcast",
+            "--yes",
             "--private-key",
             &private_key,
             "--network",
@@ -642,10 +856,39 @@ sparktest_async!(can_deploy_with_create2, |prj: TestProject, cmd: TestCommand| a
         .broadcast(ScriptOutcome::OkBroadcast)
         .assert_nonce_increment(vec![(0, 2)])
         .await
-        // Running again results in error, since we're repeating the salt passed to CREATE2
-        .run(ScriptOutcome::FailedScript);
+        // Running again skips the CREATE2 deployment, since the salt is repeated, and only
+        // broadcasts the CREATE deployment
+        .run(ScriptOutcome::OkBroadcast)
+        .assert_nonce_increment(vec![(0, 3)])
+        .await;
 });
 
+sparktest_async!(
+    can_skip_existing_create2_deployments,
+    |prj: TestProject, cmd: TestCommand| async move {
+        let (api, handle) = spawn(NodeConfig::test()).await;
+        let mut tester = ScriptTester::new_broadcast(cmd, &handle.http_endpoint(), prj.root());
+
+        // Prepare CREATE2 Deployer
+        let addr = Address::from_str("cb063edadf999cb7b8b3ebc71f5e97783176d289d640").unwrap();
+        let code = hex::decode("7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe03601600081602082378035828234f58015156039578182fd5b8082525050506014600cf3").expect("Could not decode create2 deployer init_code").into();
+        api.shuttle_set_code(addr, code).await.unwrap();
+
+        tester
+            .add_deployer(0)
+            .load_private_keys(vec![0])
+            .await
+            .add_sig("BroadcastTestNoLinking", "deployCreate2AndTransfer()")
+            .broadcast(ScriptOutcome::OkBroadcast)
+            .assert_nonce_increment(vec![(0, 2)])
+            .await
+            // the deployment is skipped while the script is executed, so only the transfer is sent
+            .run(ScriptOutcome::OkBroadcast)
+            .assert_nonce_increment(vec![(0, 3)])
+            .await;
+    }
+);
+
 sparktest_async!(
     #[serial_test::serial]
     can_deploy_and_simulate_25_txes_concurrently,
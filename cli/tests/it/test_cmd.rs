@@ -201,6 +201,102 @@ contract FailTest is DSTest {
     cmd.stdout().contains("[PASS]") && !cmd.stdout().contains("[FAIL]")
 });
 
+// tests that `--junit` writes a JUnit report and `--tap` streams TAP output
+sparktest!(can_write_junit_and_tap_reports, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "ReportTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity >=1.1.0;
+import "./test.sol";
+contract ReportTest is DSTest {
+    function testPass() external {
+        assertTrue(true);
+    }
+
+    function testFail() external {
+        assertTrue(false);
+    }
+
+    function testFuzzFail(uint256 x) external {
+        require(x == 0, "not zero");
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let junit = prj.root().join("junit.xml");
+    cmd.args(["test", "--allow-failure", "--junit"]).arg(&junit);
+    cmd.assert_non_empty_stdout();
+
+    let report = std::fs::read_to_string(&junit).unwrap();
+    assert!(report.contains(r#"<testsuites name="spark" tests="3" failures="1" skipped="0""#));
+    assert!(report.contains(r#"<testcase name="testPass()""#));
+    assert!(report.contains(r#"<property name="kind" value="fuzz"/>"#));
+    assert!(report.contains("not zero"));
+    assert!(report.contains("calldata=0x"));
+
+    cmd.spark_fuse().args(["test", "--allow-failure", "--tap"]);
+    let tap = cmd.stdout_lossy();
+    assert!(tap.starts_with("TAP version 13"));
+    assert!(tap.contains("not ok"));
+    assert!(tap.contains("message: 'not zero'"));
+    assert!(tap.trim_end().ends_with("1..3"));
+});
+
+// tests that fuzz failures are persisted and replayed with `--rerun-failures`
+sparktest!(can_persist_and_rerun_fuzz_failures, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "PersistTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity >=1.1.0;
+import "./test.sol";
+contract PersistTest is DSTest {
+    function testPass() external {
+        assertTrue(true);
+    }
+
+    function testFuzzFail(uint256 x) external {
+        require(x < 1000, "too large");
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["test", "--allow-failure"]);
+    assert!(cmd.stdout_lossy().contains("[FAIL. Reason: too large"));
+
+    let failures = prj.root().join("cache/fuzz/failures");
+    let persisted = std::fs::read_dir(&failures).unwrap().next().unwrap().unwrap().path();
+    // failures are keyed by the `<path>:<name>` identifier of the contract
+    assert!(persisted
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .ends_with("PersistTest.t.sol_PersistTest"));
+    assert!(persisted.join("testFuzzFail_uint256_.json").exists());
+
+    // only the failing test is run again
+    cmd.spark_fuse().args(["test", "--allow-failure", "--rerun-failures"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("testFuzzFail(uint256)"));
+    assert!(!stdout.contains("testPass()"));
+
+    // without the cache there are no persisted failures to rerun
+    prj.write_config(Config { cache: false, ..Default::default() });
+    cmd.spark_fuse().args(["test", "--rerun-failures"]);
+    assert!(cmd.stderr_lossy().contains("`--rerun-failures` requires the cache"));
+});
+
 // tests that `spark test` will pick up tests that are stored in the `test = <path>` config value
 sparktest!(can_run_test_in_custom_test_folder, |prj: TestProject, mut cmd: TestCommand| {
     prj.insert_ds_test();
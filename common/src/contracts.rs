@@ -5,7 +5,10 @@ use corebc_core::{
     types::{Address, H256},
     utils::hex,
 };
-use corebc_ylem::{artifacts::ContractBytecodeSome, ArtifactId, ProjectPathsConfig};
+use corebc_ylem::{
+    artifacts::{ContractBytecodeSome, Offsets},
+    ArtifactId, ProjectPathsConfig,
+};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::{
@@ -113,6 +116,27 @@ pub fn diff_score(a: &[u8], b: &[u8]) -> f64 {
     diff_chars as f64 / cutoff_len as f64
 }
 
+/// Returns `true` if `code` is exactly the runtime code `deployed`, after the immutables at the
+/// given offsets are zeroed out in both, since their values are only filled in by the constructor.
+pub fn runtime_code_matches(
+    deployed: &[u8],
+    code: &[u8],
+    immutable_references: &BTreeMap<String, Vec<Offsets>>,
+) -> bool {
+    if deployed.len() != code.len() {
+        return false
+    }
+
+    let (mut deployed, mut code) = (deployed.to_vec(), code.to_vec());
+    for offsets in immutable_references.values().flatten() {
+        let start = (offsets.start as usize).min(code.len());
+        let end = (start + offsets.length as usize).min(code.len());
+        deployed[start..end].fill(0);
+        code[start..end].fill(0);
+    }
+    deployed == code
+}
+
 /// Flattens the contracts into  (`id` -> (`Abi`, `Vec<u8>`)) pairs
 pub fn flatten_contracts(
     contracts: &BTreeMap<ArtifactId, ContractBytecodeSome>,
@@ -4,7 +4,8 @@ use corebc_core::types::U256;
 use serde::{Deserialize, Serialize};
 
 use crate::inline::{
-    parse_config_u32, InlineConfigParser, InlineConfigParserError, INLINE_CONFIG_FUZZ_KEY,
+    parse_config_bool, parse_config_u32, InlineConfigParser, InlineConfigParserError,
+    INLINE_CONFIG_FUZZ_KEY,
 };
 
 /// Contains for fuzz testing
@@ -26,6 +27,8 @@ pub struct FuzzConfig {
     /// The fuzz dictionary configuration
     #[serde(flatten)]
     pub dictionary: FuzzDictionaryConfig,
+    /// Keep the inputs that reach new coverage in a corpus and mutate them into new cases
+    pub coverage_guided: bool,
 }
 
 impl Default for FuzzConfig {
@@ -35,6 +38,7 @@ impl Default for FuzzConfig {
             max_test_rejects: 65536,
             seed: None,
             dictionary: FuzzDictionaryConfig::default(),
+            coverage_guided: false,
         }
     }
 }
@@ -63,6 +67,7 @@ impl InlineConfigParser for FuzzConfig {
                 "dictionary-weight" => {
                     conf_clone.dictionary.dictionary_weight = parse_config_u32(key, value)?
                 }
+                "coverage-guided" => conf_clone.coverage_guided = parse_config_bool(key, value)?,
                 _ => Err(InlineConfigParserError::InvalidConfigProperty(key))?,
             }
         }
@@ -126,11 +131,13 @@ mod tests {
         let configs = &[
             "spark-config: default.fuzz.runs = 42424242".to_string(),
             "spark-config: default.fuzz.dictionary-weight = 42".to_string(),
+            "spark-config: default.fuzz.coverage-guided = true".to_string(),
         ];
         let base_config = FuzzConfig::default();
         let merged: FuzzConfig = base_config.try_merge(configs).expect("No errors").unwrap();
         assert_eq!(merged.runs, 42424242);
         assert_eq!(merged.dictionary.dictionary_weight, 42);
+        assert!(merged.coverage_guided);
     }
 
     #[test]
@@ -26,6 +26,8 @@ pub struct InvariantConfig {
     pub dictionary: FuzzDictionaryConfig,
     /// Attempt to shrink the failure case to its smallest sequence of calls
     pub shrink_sequence: bool,
+    /// Keep the calls that reach new coverage in a corpus and mutate them into new calls
+    pub coverage_guided: bool,
 }
 
 impl Default for InvariantConfig {
@@ -37,6 +39,7 @@ impl Default for InvariantConfig {
             call_override: false,
             dictionary: FuzzDictionaryConfig { dictionary_weight: 80, ..Default::default() },
             shrink_sequence: true,
+            coverage_guided: false,
         }
     }
 }
@@ -65,6 +68,7 @@ impl InlineConfigParser for InvariantConfig {
                 "fail-on-revert" => conf_clone.fail_on_revert = parse_config_bool(key, value)?,
                 "call-override" => conf_clone.call_override = parse_config_bool(key, value)?,
                 "shrink-sequence" => conf_clone.shrink_sequence = parse_config_bool(key, value)?,
+                "coverage-guided" => conf_clone.coverage_guided = parse_config_bool(key, value)?,
                 _ => Err(InlineConfigParserError::InvalidConfigProperty(key.to_string()))?,
             }
         }
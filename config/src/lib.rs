@@ -24,6 +24,7 @@ use figment::{
     value::{Dict, Map, Value},
     Error, Figment, Metadata, Profile, Provider,
 };
+use fs2::FileExt;
 use inflector::Inflector;
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -1321,9 +1322,9 @@ impl Config {
     }
 
     /// Returns the path to the cache file of the `block` on the `network`
-    /// `~/.foxar/cache/rpc/<network>/<block>/storage.json`
+    /// `~/.foxar/cache/rpc/<network>/<block>/storage.bin`
     pub fn foxar_block_cache_file(network_id: impl Into<Network>, block: u64) -> Option<PathBuf> {
-        Some(Self::foxar_block_cache_dir(network_id, block)?.join("storage.json"))
+        Some(Self::foxar_block_cache_dir(network_id, block)?.join("storage.bin"))
     }
 
     #[doc = r#"Returns the path to `foxar`'s data directory inside the user's data directory
@@ -1371,6 +1372,7 @@ impl Config {
     pub fn clean_foxar_cache() -> eyre::Result<()> {
         if let Some(cache_dir) = Config::foxar_cache_dir() {
             let path = cache_dir.as_path();
+            let _locks = Self::lock_block_cache_files(path);
             let _ = fs::remove_dir_all(path);
         } else {
             eyre::bail!("failed to get foxar_cache_dir");
@@ -1383,6 +1385,7 @@ impl Config {
     pub fn clean_foxar_network_cache(network: Network) -> eyre::Result<()> {
         if let Some(cache_dir) = Config::foxar_network_cache_dir(network) {
             let path = cache_dir.as_path();
+            let _locks = Self::lock_block_cache_files(path);
             let _ = fs::remove_dir_all(path);
         } else {
             eyre::bail!("failed to get foxar_network_cache_dir");
@@ -1395,6 +1398,7 @@ impl Config {
     pub fn clean_foxar_block_cache(network: Network, block: u64) -> eyre::Result<()> {
         if let Some(cache_dir) = Config::foxar_block_cache_dir(network, block) {
             let path = cache_dir.as_path();
+            let _locks = Self::lock_block_cache_files(path);
             let _ = fs::remove_dir_all(path);
         } else {
             eyre::bail!("failed to get foxar_block_cache_dir");
@@ -1482,15 +1486,30 @@ impl Config {
         }
         for block in network_path.read_dir()?.flatten().filter(|x| x.file_type().unwrap().is_dir())
         {
-            let filepath = block.path().join("storage.json");
-            blocks.push((
-                block.file_name().to_string_lossy().into_owned(),
-                fs::metadata(filepath)?.len(),
-            ));
+            // caches that weren't used since the binary format was introduced are still json
+            let size = ["storage.bin", "storage.json"]
+                .iter()
+                .filter_map(|file| fs::metadata(block.path().join(file)).ok())
+                .map(|metadata| metadata.len())
+                .sum();
+            blocks.push((block.file_name().to_string_lossy().into_owned(), size));
         }
         Ok(blocks)
     }
 
+    /// Locks the block cache files in `dir`, waiting until no other process is writing to them.
+    ///
+    /// The returned files hold the locks, so they must be kept until the files are removed.
+    fn lock_block_cache_files(dir: &Path) -> Vec<fs::File> {
+        walkdir::WalkDir::new(dir)
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.file_name() == "storage.bin")
+            .filter_map(|entry| fs::File::open(entry.path()).ok())
+            .filter(|file| file.lock_exclusive().is_ok())
+            .collect()
+    }
+
     //The path provided to this function should point to the etherscan cache for a network
     fn get_cached_block_explorer_data(network_path: &Path) -> eyre::Result<u64> {
         if !network_path.exists() {
@@ -4235,31 +4254,42 @@ mod tests {
 
     #[test]
     fn list_cached_blocks() -> eyre::Result<()> {
-        fn fake_block_cache(network_path: &Path, block_number: &str, size_bytes: usize) {
+        fn fake_block_cache(
+            network_path: &Path,
+            block_number: &str,
+            file_name: &str,
+            size_bytes: usize,
+        ) {
             let block_path = network_path.join(block_number);
-            fs::create_dir(block_path.as_path()).unwrap();
-            let file_path = block_path.join("storage.json");
+            let _ = fs::create_dir(block_path.as_path());
+            let file_path = block_path.join(file_name);
             let mut file = File::create(file_path).unwrap();
             writeln!(file, "{}", vec![' '; size_bytes - 1].iter().collect::<String>()).unwrap();
         }
 
         let network_dir = tempdir()?;
 
-        fake_block_cache(network_dir.path(), "1", 100);
-        fake_block_cache(network_dir.path(), "2", 500);
+        fake_block_cache(network_dir.path(), "1", "storage.json", 100);
+        fake_block_cache(network_dir.path(), "2", "storage.bin", 500);
+        // a json cache that wasn't migrated yet
+        fake_block_cache(network_dir.path(), "3", "storage.bin", 200);
+        fake_block_cache(network_dir.path(), "3", "storage.json", 300);
         // Pollution file that should not show up in the cached block
         let mut pol_file = File::create(network_dir.path().join("pol.txt")).unwrap();
         writeln!(pol_file, "{}", [' '; 10].iter().collect::<String>()).unwrap();
 
         let result = Config::get_cached_blocks(network_dir.path())?;
 
-        assert_eq!(result.len(), 2);
+        assert_eq!(result.len(), 3);
         let block1 = &result.iter().find(|x| x.0 == "1").unwrap();
         let block2 = &result.iter().find(|x| x.0 == "2").unwrap();
+        let block3 = &result.iter().find(|x| x.0 == "3").unwrap();
         assert_eq!(block1.0, "1");
         assert_eq!(block1.1, 100);
         assert_eq!(block2.0, "2");
         assert_eq!(block2.1, 500);
+        assert_eq!(block3.0, "3");
+        assert_eq!(block3.1, 500);
 
         network_dir.close()?;
         Ok(())
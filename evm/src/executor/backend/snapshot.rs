@@ -6,7 +6,7 @@ use revm::{
 use serde::{Deserialize, Serialize};
 
 /// A minimal abstraction of a state at a certain point in time
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct StateSnapshot {
     pub accounts: Map<B176, AccountInfo>,
     pub storage: Map<B176, Map<U256, U256>>,
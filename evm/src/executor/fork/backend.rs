@@ -3,7 +3,7 @@
 use crate::{
     executor::{
         backend::error::{DatabaseError, DatabaseResult},
-        fork::{cache::FlushJsonBlockCacheDB, BlockchainDb},
+        fork::{cache::FlushBlockCacheDB, BlockchainDb},
     },
     utils::{b176_to_h176, b256_to_h256, h176_to_b176, h256_to_b256, ru256_to_u256, u256_to_ru256},
 };
@@ -514,8 +514,8 @@ pub struct SharedBackend {
     /// Ensures that the underlying cache gets flushed once the last `SharedBackend` is dropped.
     ///
     /// There is only one instance of the type, so as soon as the last `SharedBackend` is deleted,
-    /// `FlushJsonBlockCacheDB` is also deleted and the cache is flushed.
-    cache: Arc<FlushJsonBlockCacheDB>,
+    /// `FlushBlockCacheDB` is also deleted and the cache is flushed.
+    cache: Arc<FlushBlockCacheDB>,
 }
 
 impl SharedBackend {
@@ -577,7 +577,7 @@ impl SharedBackend {
         M: Middleware + Unpin + 'static + Clone,
     {
         let (backend, backend_rx) = channel(1);
-        let cache = Arc::new(FlushJsonBlockCacheDB(Arc::clone(db.cache())));
+        let cache = Arc::new(FlushBlockCacheDB(Arc::clone(db.cache())));
         let handler = BackendHandler::new(provider, db, backend_rx, pin_block);
         (Self { backend, cache }, handler)
     }
@@ -799,7 +799,7 @@ mod tests {
 
         let db = BlockchainDb::new(
             meta,
-            Some(Config::foxar_block_cache_dir(Network::Mainnet, block_num).unwrap()),
+            Some(Config::foxar_block_cache_file(Network::Mainnet, block_num).unwrap()),
         );
         assert!(db.accounts().read().contains_key(&address));
         assert!(db.storage().read().contains_key(&address));
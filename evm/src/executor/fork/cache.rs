@@ -1,12 +1,19 @@
 //! Cache related abstraction
+use super::cache_file::{CacheFile, CacheFileData, CacheFrame};
 use crate::{executor::backend::snapshot::StateSnapshot, HashMap as Map};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use revm::{
     primitives::{Account, AccountInfo, B176, B256, SHA3_EMPTY, U256},
     DatabaseCommit,
 };
 use serde::{ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
-use std::{collections::BTreeSet, fs, io::BufWriter, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs,
+    io::BufWriter,
+    path::PathBuf,
+    sync::Arc,
+};
 
 use url::Url;
 
@@ -20,14 +27,15 @@ pub struct BlockchainDb {
     /// metadata of the current config
     meta: Arc<RwLock<BlockchainDbMeta>>,
     /// the cache that can be flushed
-    cache: Arc<JsonBlockCacheDB>,
+    cache: Arc<BlockCacheDB>,
 }
 
 impl BlockchainDb {
     /// Creates a new instance of the [BlockchainDb]
     ///
-    /// if a `cache_path` is provided it attempts to load a previously stored [JsonBlockCacheData]
-    /// and will try to use the cached entries it holds.
+    /// if a `cache_path` is provided it attempts to load a previously stored [BlockCacheDB] and
+    /// will try to use the cached entries it holds. A legacy [JsonBlockCacheDB] next to the
+    /// `cache_path` is migrated to the binary format.
     ///
     /// This will return a new and empty [MemDb] if
     ///   - `cache_path` is `None`
@@ -41,8 +49,9 @@ impl BlockchainDb {
     /// Creates a new instance of the [BlockchainDb] and skips check when comparing meta
     /// This is useful for offline-start mode when we don't want to fetch metadata of `block`.
     ///
-    /// if a `cache_path` is provided it attempts to load a previously stored [JsonBlockCacheData]
-    /// and will try to use the cached entries it holds.
+    /// if a `cache_path` is provided it attempts to load a previously stored [BlockCacheDB] and
+    /// will try to use the cached entries it holds. A legacy [JsonBlockCacheDB] next to the
+    /// `cache_path` is migrated to the binary format.
     ///
     /// This will return a new and empty [MemDb] if
     ///   - `cache_path` is `None`
@@ -56,24 +65,29 @@ impl BlockchainDb {
     fn new_db(meta: BlockchainDbMeta, cache_path: Option<PathBuf>, skip_check: bool) -> Self {
         trace!(target : "spark::cache", cache=?cache_path, "initialising blockchain db");
         // read cache and check if metadata matches
-        let cache = cache_path
-            .as_ref()
-            .and_then(|p| {
-                JsonBlockCacheDB::load(p).ok().filter(|cache| {
-                    if skip_check {
-                        return true
-                    }
-                    let mut existing = cache.meta().write();
-                    existing.hosts.extend(meta.hosts.clone());
-                    if meta != *existing {
-                        warn!(target : "cache", "non-matching block metadata");
-                        false
+        let cache = match cache_path {
+            Some(path) => match BlockCacheDB::load(&path) {
+                Ok(cache) => {
+                    let matches = skip_check || {
+                        let mut existing = cache.meta().write();
+                        existing.hosts.extend(meta.hosts.clone());
+                        meta == *existing
+                    };
+                    if matches {
+                        cache
                     } else {
-                        true
+                        warn!(target : "cache", "non-matching block metadata");
+                        BlockCacheDB::new(Arc::new(RwLock::new(meta)), Some(path), true)
                     }
-                })
-            })
-            .unwrap_or_else(|| JsonBlockCacheDB::new(Arc::new(RwLock::new(meta)), cache_path));
+                }
+                // discard the existing file if it couldn't be read
+                Err(_) => {
+                    let reset = path.exists();
+                    BlockCacheDB::new(Arc::new(RwLock::new(meta)), Some(path), reset)
+                }
+            },
+            None => BlockCacheDB::new(Arc::new(RwLock::new(meta)), None, false),
+        };
 
         Self { db: Arc::clone(cache.db()), meta: Arc::clone(cache.meta()), cache: Arc::new(cache) }
     }
@@ -99,7 +113,7 @@ impl BlockchainDb {
     }
 
     /// Returns the inner cache
-    pub fn cache(&self) -> &Arc<JsonBlockCacheDB> {
+    pub fn cache(&self) -> &Arc<BlockCacheDB> {
         &self.cache
     }
 
@@ -298,7 +312,212 @@ impl DatabaseCommit for MemDb {
     }
 }
 
-/// A [BlockCacheDB] that stores the cached content in a json file
+/// The number of frames after which a cache file is compacted when it's loaded
+const MAX_CACHE_FRAMES: usize = 64;
+
+/// A cache that stores the cached content in a compact binary file, see [CacheFile]
+///
+/// Flushing only appends the entries that were added since the previous flush, and the file is
+/// locked while it's read or written, so the same cache file can be shared by multiple processes.
+/// A file with more than [MAX_CACHE_FRAMES] frames is compacted when it's loaded.
+#[derive(Debug)]
+pub struct BlockCacheDB {
+    /// Where this cache file is stored.
+    ///
+    /// If this is a [None] then caching is disabled
+    cache_file: Option<CacheFile>,
+    /// The cached data
+    data: JsonBlockCacheData,
+    /// The entries that were already written to the cache file
+    persisted: Mutex<PersistedEntries>,
+}
+
+impl BlockCacheDB {
+    /// Creates a new instance.
+    ///
+    /// If `reset` is set, the existing cache file is discarded on the first flush.
+    fn new(meta: Arc<RwLock<BlockchainDbMeta>>, cache_path: Option<PathBuf>, reset: bool) -> Self {
+        Self {
+            cache_file: cache_path.map(CacheFile::new),
+            data: JsonBlockCacheData { meta, data: Arc::new(Default::default()) },
+            persisted: Mutex::new(PersistedEntries { reset, ..Default::default() }),
+        }
+    }
+
+    /// Loads the contents of the cache file
+    ///
+    /// If the file does not exist, but a legacy [JsonBlockCacheDB] with the same name and a
+    /// `json` extension does, the json cache is migrated to a new binary cache file.
+    ///
+    /// # Errors
+    /// This will fail if
+    ///   - neither the `path` nor the json cache exist
+    ///   - the format does not match [CacheFile]
+    pub fn load(path: impl Into<PathBuf>) -> eyre::Result<Self> {
+        let path = path.into();
+        if !path.exists() {
+            let json_path = path.with_extension("json");
+            if json_path.exists() {
+                return Self::migrate(JsonBlockCacheDB::load(&json_path)?, path)
+            }
+        }
+
+        trace!(target : "cache", ?path, "reading binary cache");
+        let cache_file = CacheFile::new(path);
+        let mut data = cache_file.read().map_err(|err| {
+            warn!(target : "cache", ?err, path=?cache_file.path(), "Failed to read cache file");
+            err
+        })?;
+        if data.frames > MAX_CACHE_FRAMES {
+            trace!(target : "cache", frames=data.frames, "compacting binary cache");
+            match cache_file.compact() {
+                Ok(compacted) => data = compacted,
+                Err(err) => warn!(target : "cache", ?err, "Failed to compact cache file"),
+            }
+        }
+        let CacheFileData { meta, state, .. } = data;
+        let meta = meta.ok_or_else(|| eyre::eyre!("cache file is missing the metadata"))?;
+
+        let persisted = PersistedEntries::new(&meta, &state);
+        let StateSnapshot { accounts, storage, block_hashes } = state;
+        Ok(Self {
+            cache_file: Some(cache_file),
+            data: JsonBlockCacheData {
+                meta: Arc::new(RwLock::new(meta)),
+                data: Arc::new(MemDb {
+                    accounts: RwLock::new(accounts),
+                    storage: RwLock::new(storage),
+                    block_hashes: RwLock::new(block_hashes),
+                }),
+            },
+            persisted: Mutex::new(persisted),
+        })
+    }
+
+    /// Writes the content of the json cache to a binary cache file at `path` and removes the json
+    /// cache file
+    fn migrate(json: JsonBlockCacheDB, path: PathBuf) -> eyre::Result<Self> {
+        trace!(target : "cache", ?path, "migrating json cache");
+        let cache = Self {
+            cache_file: Some(CacheFile::new(path)),
+            data: json.data,
+            persisted: Default::default(),
+        };
+        cache.try_flush()?;
+        if let Some(json_path) = json.cache_path {
+            let _ = fs::remove_file(json_path);
+        }
+        Ok(cache)
+    }
+
+    /// Returns the [MemDb] it holds access to
+    pub fn db(&self) -> &Arc<MemDb> {
+        &self.data.data
+    }
+
+    /// Metadata stored alongside the data
+    pub fn meta(&self) -> &Arc<RwLock<BlockchainDbMeta>> {
+        &self.data.meta
+    }
+
+    /// Returns `true` if this is a transient cache and nothing will be flushed
+    pub fn is_transient(&self) -> bool {
+        self.cache_file.is_none()
+    }
+
+    /// Flushes all new entries to disk if caching is enabled
+    pub fn flush(&self) {
+        if let Some(ref cache_file) = self.cache_file {
+            trace!(target: "cache", "saving binary cache path={:?}", cache_file.path());
+            let _ = self
+                .try_flush()
+                .map_err(|e| warn!(target: "cache", "Failed to write to binary cache: {}", e));
+            trace!(target: "cache", "saved binary cache path={:?}", cache_file.path());
+        }
+    }
+
+    fn try_flush(&self) -> eyre::Result<()> {
+        let Some(ref cache_file) = self.cache_file else { return Ok(()) };
+        let mut persisted = self.persisted.lock();
+        let reset = std::mem::take(&mut persisted.reset);
+        let res = cache_file.write_frame(reset, |is_new| {
+            if is_new {
+                *persisted = Default::default();
+            }
+            self.unpersisted_entries(&mut persisted)
+        });
+        if res.is_err() {
+            // we don't know what made it to disk, so the file is rewritten on the next flush
+            persisted.reset = true;
+        }
+        res
+    }
+
+    /// Returns a frame with all entries that are not yet written to the cache file and marks them
+    /// as written
+    fn unpersisted_entries(&self, persisted: &mut PersistedEntries) -> CacheFrame {
+        let mut frame = CacheFrame::default();
+
+        let meta = self.data.meta.read();
+        if persisted.meta.as_ref().map_or(true, |m| *m != *meta || m.hosts != meta.hosts) {
+            frame.push_meta(&meta);
+            persisted.meta = Some(meta.clone());
+        }
+        drop(meta);
+
+        for (address, info) in self.data.data.accounts.read().iter() {
+            if persisted.accounts.insert(*address) {
+                frame.push_account(*address, info);
+            }
+        }
+        for (address, slots) in self.data.data.storage.read().iter() {
+            for (slot, value) in slots.iter() {
+                if persisted.storage.insert((*address, *slot)) {
+                    frame.push_storage(*address, *slot, *value);
+                }
+            }
+        }
+        for (number, hash) in self.data.data.block_hashes.read().iter() {
+            if persisted.block_hashes.insert(*number) {
+                frame.push_block_hash(*number, *hash);
+            }
+        }
+        frame
+    }
+}
+
+/// Tracks which entries of a [BlockCacheDB] were already written to its cache file
+///
+/// Only the keys are tracked, since the remote state of the forked block never changes.
+#[derive(Debug, Default)]
+struct PersistedEntries {
+    /// Whether the cache file has to be discarded on the next flush
+    reset: bool,
+    meta: Option<BlockchainDbMeta>,
+    accounts: HashSet<B176>,
+    storage: HashSet<(B176, U256)>,
+    block_hashes: HashSet<U256>,
+}
+
+impl PersistedEntries {
+    fn new(meta: &BlockchainDbMeta, state: &StateSnapshot) -> Self {
+        Self {
+            reset: false,
+            meta: Some(meta.clone()),
+            accounts: state.accounts.keys().copied().collect(),
+            storage: state
+                .storage
+                .iter()
+                .flat_map(|(address, slots)| slots.keys().map(|slot| (*address, *slot)))
+                .collect(),
+            block_hashes: state.block_hashes.keys().copied().collect(),
+        }
+    }
+}
+
+/// A legacy cache that stores the cached content in a json file
+///
+/// This is superseded by [BlockCacheDB] and only used to migrate existing json caches.
 #[derive(Debug)]
 pub struct JsonBlockCacheDB {
     /// Where this cache file is stored.
@@ -310,11 +529,6 @@ pub struct JsonBlockCacheDB {
 }
 
 impl JsonBlockCacheDB {
-    /// Creates a new instance.
-    fn new(meta: Arc<RwLock<BlockchainDbMeta>>, cache_path: Option<PathBuf>) -> Self {
-        Self { cache_path, data: JsonBlockCacheData { meta, data: Arc::new(Default::default()) } }
-    }
-
     /// Loads the contents of the diskmap file and returns the read object
     ///
     /// # Errors
@@ -433,14 +647,14 @@ impl<'de> Deserialize<'de> for JsonBlockCacheData {
     }
 }
 
-/// A type that flushes a `JsonBlockCacheDB` on drop
+/// A type that flushes a `BlockCacheDB` on drop
 ///
 /// This type intentionally does not implement `Clone` since it's intended that there's only once
 /// instance that will flush the cache.
 #[derive(Debug)]
-pub struct FlushJsonBlockCacheDB(pub Arc<JsonBlockCacheDB>);
+pub struct FlushBlockCacheDB(pub Arc<BlockCacheDB>);
 
-impl Drop for FlushJsonBlockCacheDB {
+impl Drop for FlushBlockCacheDB {
     fn drop(&mut self) {
         trace!(target: "fork::cache", "flushing cache");
         self.0.flush();
@@ -519,4 +733,34 @@ mod tests {
 
         let _s = serde_json::to_string(&cache).unwrap();
     }
+
+    #[test]
+    fn can_migrate_json_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_path = dir.path().join("storage.json");
+        fs::copy(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test-data/storage.json"),
+            &json_path,
+        )
+        .unwrap();
+        let json = JsonBlockCacheDB::load(&json_path).unwrap();
+        let accounts = json.db().accounts.read().len();
+
+        let path = dir.path().join("storage.bin");
+        let cache = BlockCacheDB::load(&path).unwrap();
+        assert!(!json_path.exists());
+        assert_eq!(cache.db().accounts.read().len(), accounts);
+        assert_eq!(*cache.meta().read(), *json.meta().read());
+
+        // only the new slot is appended
+        let len = fs::metadata(&path).unwrap().len();
+        let address = B176([1u8; 22]);
+        cache.db().storage.write().entry(address).or_default().insert(U256::from(1), U256::from(2));
+        cache.flush();
+        assert_eq!(fs::metadata(&path).unwrap().len(), len + 8 + 1 + 22 + 64);
+
+        let cache = BlockCacheDB::load(&path).unwrap();
+        assert_eq!(cache.db().accounts.read().len(), accounts);
+        assert_eq!(cache.db().storage.read()[&address][&U256::from(1)], U256::from(2));
+    }
 }
@@ -0,0 +1,512 @@
+//! Compact binary format of the fork cache files
+//!
+//! A cache file starts with a header, followed by a sequence of frames. Every flush appends a
+//! single frame that holds the entries that were fetched since the previous flush, so flushing
+//! only costs as much as the new data, no matter how large the cache already is.
+//!
+//! ```text
+//! file   := MAGIC VERSION:u32 frame*
+//! frame  := len:u64 record*
+//! record := META len:u32 json
+//!         | ACCOUNT address balance nonce:u64 code_hash code_len:u32 code
+//!         | STORAGE address slot value
+//!         | BLOCK_HASH number hash
+//! ```
+//!
+//! Integers are little endian, words are stored as 32 big endian bytes. Records of later frames
+//! replace those of earlier frames.
+//!
+//! Readers take a shared and writers an exclusive lock on a `.lock` file next to the cache file, so
+//! concurrent processes never observe a partially written frame. A frame that was cut short
+//! because a process died while writing it is ignored by readers and truncated by the next writer.
+//!
+//! Since frames are only ever appended, a file that was flushed by many processes is
+//! [compacted](CacheFile::compact) into a single frame that holds only the latest records. The
+//! compacted file is written next to the cache file and then renamed over it, so the cache is
+//! never lost if the process dies while compacting.
+
+use crate::executor::{backend::snapshot::StateSnapshot, fork::BlockchainDbMeta};
+use fs2::FileExt;
+use revm::primitives::{AccountInfo, Bytecode, B176, B256, U256};
+use std::{
+    fs,
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+/// Identifies a binary fork cache file
+const MAGIC: &[u8; 8] = b"FXRCACHE";
+/// The version of the format
+const VERSION: u32 = 1;
+/// The length of [MAGIC] and [VERSION]
+const HEADER_LEN: u64 = 12;
+
+const META: u8 = 0;
+const ACCOUNT: u8 = 1;
+const STORAGE: u8 = 2;
+const BLOCK_HASH: u8 = 3;
+
+/// The `code_len` of an account without code
+const NO_CODE: u32 = u32::MAX;
+
+/// The contents of a [CacheFile]
+#[derive(Debug, Default)]
+pub struct CacheFileData {
+    /// The most recently written metadata
+    pub meta: Option<BlockchainDbMeta>,
+    /// All cached entries
+    pub state: StateSnapshot,
+    /// The number of frames the entries were read from
+    pub frames: usize,
+}
+
+/// A batch of entries that is appended to a [CacheFile] at once
+#[derive(Debug, Default)]
+pub struct CacheFrame {
+    buf: Vec<u8>,
+}
+
+// === impl CacheFrame ===
+
+impl CacheFrame {
+    /// Returns `true` if this frame holds no entries
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Adds the metadata of the cache
+    pub fn push_meta(&mut self, meta: &BlockchainDbMeta) {
+        let json = serde_json::to_vec(meta).expect("can't fail;");
+        self.buf.push(META);
+        self.buf.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        self.buf.extend_from_slice(&json);
+    }
+
+    /// Adds an account
+    pub fn push_account(&mut self, address: B176, info: &AccountInfo) {
+        self.buf.push(ACCOUNT);
+        self.buf.extend_from_slice(&address.0);
+        self.buf.extend_from_slice(&info.balance.to_be_bytes::<32>());
+        self.buf.extend_from_slice(&info.nonce.to_le_bytes());
+        self.buf.extend_from_slice(&info.code_hash.0);
+        match info.code.as_ref() {
+            Some(code) => {
+                let code = &code.bytes()[..code.len()];
+                self.buf.extend_from_slice(&(code.len() as u32).to_le_bytes());
+                self.buf.extend_from_slice(code);
+            }
+            None => self.buf.extend_from_slice(&NO_CODE.to_le_bytes()),
+        }
+    }
+
+    /// Adds a storage slot of an account
+    pub fn push_storage(&mut self, address: B176, slot: U256, value: U256) {
+        self.buf.push(STORAGE);
+        self.buf.extend_from_slice(&address.0);
+        self.buf.extend_from_slice(&slot.to_be_bytes::<32>());
+        self.buf.extend_from_slice(&value.to_be_bytes::<32>());
+    }
+
+    /// Adds the hash of a block
+    pub fn push_block_hash(&mut self, number: U256, hash: B256) {
+        self.buf.push(BLOCK_HASH);
+        self.buf.extend_from_slice(&number.to_be_bytes::<32>());
+        self.buf.extend_from_slice(&hash.0);
+    }
+
+    /// Adds all entries of `data`
+    fn push_data(&mut self, data: &CacheFileData) {
+        if let Some(meta) = data.meta.as_ref() {
+            self.push_meta(meta);
+        }
+        for (address, info) in data.state.accounts.iter() {
+            self.push_account(*address, info);
+        }
+        for (address, slots) in data.state.storage.iter() {
+            for (slot, value) in slots.iter() {
+                self.push_storage(*address, *slot, *value);
+            }
+        }
+        for (number, hash) in data.state.block_hashes.iter() {
+            self.push_block_hash(*number, *hash);
+        }
+    }
+}
+
+/// A fork cache file in the binary format, see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct CacheFile {
+    path: PathBuf,
+}
+
+// === impl CacheFile ===
+
+impl CacheFile {
+    /// Creates a new instance for the file at `path`, the file is created on the first write
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the path of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads all entries of the file
+    ///
+    /// # Errors
+    /// This will fail if
+    ///   - the file does not exist
+    ///   - the file is not a binary cache file, or a record is malformed
+    pub fn read(&self) -> eyre::Result<CacheFileData> {
+        let lock = self.lock(false)?;
+        let mut buf = Vec::new();
+        fs::File::open(&self.path)?.read_to_end(&mut buf)?;
+        drop(lock);
+        decode(&buf)
+    }
+
+    /// Appends the frame returned by `f` to the file, while the file is exclusively locked.
+    ///
+    /// `f` is called with `true` if the file is new, or had to be discarded because `reset` is set
+    /// or its header is invalid, in which case the frame must hold all entries of the cache.
+    pub fn write_frame(&self, reset: bool, f: impl FnOnce(bool) -> CacheFrame) -> eyre::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let _lock = self.lock(true)?;
+        let mut file =
+            fs::OpenOptions::new().read(true).write(true).create(true).open(&self.path)?;
+
+        let valid_len = if reset { None } else { complete_len(&mut file)? };
+        let is_new = valid_len.is_none();
+        // drops a frame that was cut short, or the entire file if it has to be rewritten
+        file.set_len(valid_len.unwrap_or_default())?;
+
+        let frame = f(is_new);
+        let mut buf = Vec::with_capacity(HEADER_LEN as usize + 8 + frame.buf.len());
+        if is_new {
+            buf.extend_from_slice(MAGIC);
+            buf.extend_from_slice(&VERSION.to_le_bytes());
+        }
+        if !frame.is_empty() {
+            buf.extend_from_slice(&(frame.buf.len() as u64).to_le_bytes());
+            buf.extend_from_slice(&frame.buf);
+        }
+        file.seek(SeekFrom::End(0))?;
+        file.write_all(&buf)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Rewrites the file as a single frame, so records that were replaced by later frames and
+    /// frame headers don't take up space anymore, and returns its entries.
+    ///
+    /// The file is exclusively locked, so frames appended by other processes are never lost. The
+    /// compacted file is written to a temporary file in the same directory, which then replaces
+    /// the file.
+    pub fn compact(&self) -> eyre::Result<CacheFileData> {
+        let _lock = self.lock(true)?;
+        let buf = fs::read(&self.path)?;
+        let mut data = decode(&buf)?;
+
+        let mut frame = CacheFrame::default();
+        frame.push_data(&data);
+        let mut buf = Vec::with_capacity(HEADER_LEN as usize + 8 + frame.buf.len());
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&VERSION.to_le_bytes());
+        if !frame.is_empty() {
+            buf.extend_from_slice(&(frame.buf.len() as u64).to_le_bytes());
+            buf.extend_from_slice(&frame.buf);
+        }
+
+        let tmp_path = self.sibling_path("tmp");
+        let mut tmp = fs::File::create(&tmp_path)?;
+        tmp.write_all(&buf)?;
+        tmp.sync_all()?;
+        drop(tmp);
+        fs::rename(&tmp_path, &self.path)?;
+
+        data.frames = usize::from(!frame.is_empty());
+        Ok(data)
+    }
+
+    /// Opens and locks the lock file of the cache file, the lock is released when the returned
+    /// file is dropped
+    ///
+    /// The lock file is separate from the cache file, because [Self::compact()] replaces the cache
+    /// file.
+    fn lock(&self, exclusive: bool) -> std::io::Result<fs::File> {
+        let lock = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(self.sibling_path("lock"))?;
+        if exclusive {
+            lock.lock_exclusive()?;
+        } else {
+            lock.lock_shared()?;
+        }
+        Ok(lock)
+    }
+
+    /// Returns the path of the file next to the cache file with the given extension appended
+    fn sibling_path(&self, extension: &str) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".");
+        name.push(extension);
+        self.path.with_file_name(name)
+    }
+}
+
+/// Returns the length of the header and all complete frames of the file, or `None` if the file
+/// doesn't start with a valid header
+fn complete_len(file: &mut fs::File) -> std::io::Result<Option<u64>> {
+    let file_len = file.metadata()?.len();
+    if file_len < HEADER_LEN {
+        return Ok(None)
+    }
+    let mut header = [0u8; HEADER_LEN as usize];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_exact(&mut header)?;
+    if !is_valid_header(&header) {
+        return Ok(None)
+    }
+
+    let mut pos = HEADER_LEN;
+    while file_len - pos >= 8 {
+        let mut len = [0u8; 8];
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut len)?;
+        match (pos + 8).checked_add(u64::from_le_bytes(len)) {
+            Some(end) if end <= file_len => pos = end,
+            _ => break,
+        }
+    }
+    Ok(Some(pos))
+}
+
+fn is_valid_header(header: &[u8]) -> bool {
+    header[..8] == MAGIC[..] && header[8..12] == VERSION.to_le_bytes()
+}
+
+/// Decodes the contents of a cache file
+fn decode(buf: &[u8]) -> eyre::Result<CacheFileData> {
+    let mut reader = Reader { buf };
+    if !is_valid_header(reader.take(HEADER_LEN as usize)?) {
+        eyre::bail!("not a binary fork cache file")
+    }
+
+    let mut data = CacheFileData::default();
+    while reader.buf.len() >= 8 {
+        let len = reader.u64()?;
+        if len > reader.buf.len() as u64 {
+            // the last frame was cut short
+            break
+        }
+        let mut frame = Reader { buf: reader.take(len as usize)? };
+        while !frame.buf.is_empty() {
+            decode_record(&mut frame, &mut data)?;
+        }
+        data.frames += 1;
+    }
+    Ok(data)
+}
+
+fn decode_record(reader: &mut Reader<'_>, data: &mut CacheFileData) -> eyre::Result<()> {
+    match reader.u8()? {
+        META => {
+            let len = reader.u32()? as usize;
+            data.meta = Some(serde_json::from_slice(reader.take(len)?)?);
+        }
+        ACCOUNT => {
+            let address = B176(reader.array()?);
+            let balance = reader.word()?;
+            let nonce = reader.u64()?;
+            let code_hash = B256(reader.array()?);
+            let code = match reader.u32()? {
+                NO_CODE => None,
+                len => Some(
+                    Bytecode::new_raw(bytes::Bytes::copy_from_slice(reader.take(len as usize)?))
+                        .to_checked(),
+                ),
+            };
+            data.state.accounts.insert(address, AccountInfo { balance, nonce, code_hash, code });
+        }
+        STORAGE => {
+            let address = B176(reader.array()?);
+            let slot = reader.word()?;
+            let value = reader.word()?;
+            data.state.storage.entry(address).or_default().insert(slot, value);
+        }
+        BLOCK_HASH => {
+            let number = reader.word()?;
+            let hash = B256(reader.array()?);
+            data.state.block_hashes.insert(number, hash);
+        }
+        tag => eyre::bail!("unknown cache record {tag}"),
+    }
+    Ok(())
+}
+
+/// Reads values from the front of a buffer
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> eyre::Result<&'a [u8]> {
+        if self.buf.len() < len {
+            eyre::bail!("malformed cache record")
+        }
+        let (head, tail) = self.buf.split_at(len);
+        self.buf = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> eyre::Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("has length N"))
+    }
+
+    fn u8(&mut self) -> eyre::Result<u8> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> eyre::Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> eyre::Result<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn word(&mut self) -> eyre::Result<U256> {
+        Ok(U256::from_be_bytes(self.array::<32>()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::SHA3_EMPTY;
+
+    fn meta() -> BlockchainDbMeta {
+        BlockchainDbMeta {
+            cfg_env: Default::default(),
+            block_env: Default::default(),
+            hosts: Default::default(),
+        }
+    }
+
+    #[test]
+    fn can_append_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = CacheFile::new(dir.path().join("storage.bin"));
+        let address = B176([1u8; 22]);
+
+        file.write_frame(false, |is_new| {
+            assert!(is_new);
+            let mut frame = CacheFrame::default();
+            frame.push_meta(&meta());
+            frame.push_account(
+                address,
+                &AccountInfo {
+                    balance: U256::from(10u64),
+                    nonce: 1,
+                    code_hash: SHA3_EMPTY,
+                    code: None,
+                },
+            );
+            frame.push_storage(address, U256::from(1u64), U256::from(2u64));
+            frame
+        })
+        .unwrap();
+        file.write_frame(false, |is_new| {
+            assert!(!is_new);
+            let mut frame = CacheFrame::default();
+            frame.push_storage(address, U256::from(1u64), U256::from(3u64));
+            frame.push_block_hash(U256::from(5u64), B256([5u8; 32]));
+            frame
+        })
+        .unwrap();
+
+        let data = file.read().unwrap();
+        assert_eq!(data.meta, Some(meta()));
+        assert_eq!(data.state.accounts[&address].balance, U256::from(10u64));
+        assert_eq!(data.state.accounts[&address].nonce, 1);
+        assert_eq!(data.state.storage[&address][&U256::from(1u64)], U256::from(3u64));
+        assert_eq!(data.state.block_hashes[&U256::from(5u64)], B256([5u8; 32]));
+    }
+
+    #[test]
+    fn truncates_incomplete_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = CacheFile::new(dir.path().join("storage.bin"));
+        let address = B176([1u8; 22]);
+        file.write_frame(false, |_| {
+            let mut frame = CacheFrame::default();
+            frame.push_meta(&meta());
+            frame.push_storage(address, U256::from(1u64), U256::from(1u64));
+            frame
+        })
+        .unwrap();
+        let complete = fs::metadata(file.path()).unwrap().len();
+
+        // simulate a process that died while writing a frame
+        let mut f = fs::OpenOptions::new().append(true).open(file.path()).unwrap();
+        f.write_all(&100u64.to_le_bytes()).unwrap();
+        f.write_all(&[STORAGE]).unwrap();
+        drop(f);
+        assert_eq!(file.read().unwrap().state.storage[&address].len(), 1);
+
+        file.write_frame(false, |_| {
+            let mut frame = CacheFrame::default();
+            frame.push_storage(address, U256::from(2u64), U256::from(2u64));
+            frame
+        })
+        .unwrap();
+        assert_eq!(fs::metadata(file.path()).unwrap().len(), complete + 8 + 1 + 22 + 64);
+        assert_eq!(file.read().unwrap().state.storage[&address].len(), 2);
+    }
+
+    #[test]
+    fn can_compact_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = CacheFile::new(dir.path().join("storage.bin"));
+        let address = B176([1u8; 22]);
+        for value in 0..10u64 {
+            file.write_frame(false, |_| {
+                let mut frame = CacheFrame::default();
+                frame.push_meta(&meta());
+                frame.push_storage(address, U256::from(1u64), U256::from(value));
+                frame
+            })
+            .unwrap();
+        }
+        let len = fs::metadata(file.path()).unwrap().len();
+        assert_eq!(file.read().unwrap().frames, 10);
+
+        let compacted = file.compact().unwrap();
+        assert_eq!(compacted.frames, 1);
+        assert!(fs::metadata(file.path()).unwrap().len() < len);
+
+        let data = file.read().unwrap();
+        assert_eq!(data.frames, 1);
+        assert_eq!(data.meta, Some(meta()));
+        assert_eq!(data.state.storage[&address].len(), 1);
+        assert_eq!(data.state.storage[&address][&U256::from(1u64)], U256::from(9u64));
+        assert!(!dir.path().join("storage.bin.tmp").exists());
+
+        // frames are appended to the compacted file
+        file.write_frame(false, |is_new| {
+            assert!(!is_new);
+            let mut frame = CacheFrame::default();
+            frame.push_storage(address, U256::from(2u64), U256::from(2u64));
+            frame
+        })
+        .unwrap();
+        let data = file.read().unwrap();
+        assert_eq!(data.frames, 2);
+        assert_eq!(data.state.storage[&address].len(), 2);
+    }
+}
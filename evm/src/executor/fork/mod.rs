@@ -9,7 +9,9 @@ mod init;
 pub use init::environment;
 
 mod cache;
-pub use cache::{BlockchainDb, BlockchainDbMeta, JsonBlockCacheDB, MemDb};
+pub use cache::{BlockCacheDB, BlockchainDb, BlockchainDbMeta, JsonBlockCacheDB, MemDb};
+
+mod cache_file;
 
 pub mod database;
 
@@ -498,7 +498,7 @@ async fn create_fork(
 
     // determine the cache path if caching is enabled
     let cache_path = if fork.enable_caching {
-        Config::foxar_block_cache_dir(meta.cfg_env.network_id, number)
+        Config::foxar_block_cache_file(meta.cfg_env.network_id, number)
     } else {
         None
     };
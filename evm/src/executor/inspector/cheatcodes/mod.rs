@@ -3,7 +3,8 @@ use self::{
     expect::{handle_expect_emit, handle_expect_revert, ExpectedCallType},
     mapping::MappingSlots,
     util::{
-        check_if_fixed_energy_limit, process_create, BroadcastableTransactions, MAGIC_SKIP_BYTES,
+        check_if_fixed_energy_limit, existing_create2_deployment, process_create,
+        BroadcastableTransactions, MAGIC_SKIP_BYTES,
     },
 };
 use crate::{
@@ -1008,6 +1009,29 @@ where
                 data.env.tx.caller = h176_to_b176(broadcast.new_origin);
 
                 if data.journaled_state.depth() == broadcast.depth {
+                    // deterministic deployments that already exist are neither executed nor
+                    // broadcasted, so that scripts can be re-run
+                    match existing_create2_deployment(data, call) {
+                        Ok(Some(address)) => {
+                            trace!(?address, "skipping existing CREATE2 deployment");
+                            return (
+                                InstructionResult::Return,
+                                Some(address),
+                                Energy::new(call.energy_limit),
+                                bytes::Bytes::new(),
+                            )
+                        }
+                        Ok(None) => {}
+                        Err(err) => {
+                            return (
+                                InstructionResult::Revert,
+                                None,
+                                Energy::new(call.energy_limit),
+                                err.encode_string().0,
+                            )
+                        }
+                    }
+
                     let (bytecode, to, nonce) = match process_create(
                         broadcast.new_origin,
                         call.init_code.clone(),
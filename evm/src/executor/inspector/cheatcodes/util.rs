@@ -22,7 +22,7 @@ use corebc::{
 use foxar_common::{fmt::*, RpcUrl};
 use revm::{
     interpreter::CreateInputs,
-    primitives::{Account, TransactTo},
+    primitives::{Account, TransactTo, B176, SHA3_EMPTY},
     Database, EVMData, JournaledState,
 };
 use std::{collections::VecDeque, str::FromStr};
@@ -369,6 +369,37 @@ where
     }
 }
 
+/// Returns the address of the CREATE2 deployment of `call` if the CREATE2 deployer already
+/// deployed code there, for example in an earlier run of a script.
+pub fn existing_create2_deployment<DB>(
+    data: &mut EVMData<'_, DB>,
+    call: &CreateInputs,
+) -> DatabaseResult<Option<B176>>
+where
+    DB: Database<Error = DatabaseError>,
+{
+    let revm::primitives::CreateScheme::Create2 { salt } = call.scheme else { return Ok(None) };
+
+    let network = Network::from(data.env.cfg.network_id);
+    let mut salt_bytes = [0u8; 32];
+    ru256_to_u256(salt).to_big_endian(&mut salt_bytes);
+    let address = h176_to_b176(get_create2_address(
+        default_create2_address(Some(network)),
+        salt_bytes,
+        call.init_code.clone(),
+        network,
+    ));
+
+    data.journaled_state.load_account(address, data.db)?;
+    let info = &data.journaled_state.account(address).info;
+    let has_code = match &info.code {
+        Some(code) => !code.is_empty(),
+        // forked db
+        None => info.code_hash != SHA3_EMPTY && !data.db.code_by_hash(info.code_hash)?.is_empty(),
+    };
+    Ok(has_code.then_some(address))
+}
+
 pub fn parse_array<I, T>(values: I, ty: &ParamType) -> Result
 where
     I: IntoIterator<Item = T>,
@@ -56,6 +56,7 @@ impl Tracer {
         value: U256,
         kind: CallKind,
         caller: Address,
+        energy_limit: u64,
     ) {
         self.trace_stack.push(self.traces.push_trace(
             0,
@@ -67,6 +68,7 @@ impl Tracer {
                 value,
                 status: InstructionResult::Continue,
                 caller,
+                energy_limit,
                 ..Default::default()
             },
         ));
@@ -221,6 +223,7 @@ where
             ru256_to_u256(inputs.transfer.value),
             inputs.context.scheme.into(),
             b176_to_h176(from),
+            inputs.energy_limit,
         );
 
         (InstructionResult::Continue, Energy::new(inputs.energy_limit), Bytes::new())
@@ -260,6 +263,7 @@ where
             ru256_to_u256(inputs.value),
             inputs.scheme.into(),
             b176_to_h176(inputs.caller),
+            inputs.energy_limit,
         );
 
         (InstructionResult::Continue, None, Energy::new(inputs.energy_limit), Bytes::new())
@@ -444,6 +444,7 @@ impl Executor {
             debug,
             script_wallets,
             env,
+            state_changeset,
             ..
         } = result;
 
@@ -506,6 +507,7 @@ impl Executor {
             logs,
             traces,
             debug,
+            state_changeset,
             env,
         })
     }
@@ -688,6 +690,8 @@ pub struct DeployResult {
     pub traces: Option<CallTraceArena>,
     /// The debug nodes of the call
     pub debug: Option<DebugArena>,
+    /// The changeset of the state that was committed by the deployment
+    pub state_changeset: Option<StateChangeset>,
     /// The `revm::Env` after deployment
     pub env: Env,
 }
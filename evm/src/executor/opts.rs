@@ -132,10 +132,10 @@ impl EvmOpts {
     ///   - storage is allowed (`no_storage_caching = false`)
     ///
     /// If all these criteria are met, then storage caching is enabled and storage info will be
-    /// written to [Config::foxar_cache_dir()]/<str(chainid)>/<block>/storage.json
+    /// written to [Config::foxar_cache_dir()]/<str(chainid)>/<block>/storage.bin
     ///
     /// for `mainnet` and `--fork-block-number 14435000` on mac the corresponding storage cache will
-    /// be at `~/.foxar/cache/mainnet/14435000/storage.json`
+    /// be at `~/.foxar/cache/mainnet/14435000/storage.bin`
     pub fn get_fork(&self, config: &Config, env: revm::primitives::Env) -> Option<CreateFork> {
         let url = self.fork_url.clone()?;
         let enable_caching = config.enable_caching(&url, env.cfg.network_id);
@@ -10,7 +10,7 @@ use crate::{
     fuzz::{
         strategies::{
             build_initial_state, collect_created_contracts, collect_state_from_call,
-            invariant_strat, override_call_strat, EvmFuzzState,
+            invariant_strat, override_call_strat, CoverageCorpus, EvmFuzzState, SharedCorpus,
         },
         FuzzCase, FuzzedCases,
     },
@@ -33,9 +33,14 @@ use proptest::{
 use revm::{primitives::B176, DatabaseCommit};
 use std::{cell::RefCell, collections::BTreeMap, sync::Arc};
 
-/// Alias for (Dictionary for fuzzing, initial contracts to fuzz and an InvariantStrategy).
-type InvariantPreparation =
-    (EvmFuzzState, FuzzRunIdentifiedContracts, BoxedStrategy<Vec<BasicTxDetails>>);
+/// Alias for (Dictionary for fuzzing, initial contracts to fuzz, an InvariantStrategy and the
+/// coverage corpus, if coverage-guided).
+type InvariantPreparation = (
+    EvmFuzzState,
+    FuzzRunIdentifiedContracts,
+    BoxedStrategy<Vec<BasicTxDetails>>,
+    Option<SharedCorpus<BasicTxDetails>>,
+);
 
 /// Wrapper around any [`Executor`] implementor which provides fuzzing support using [`proptest`](https://docs.rs/proptest/1.0.0/proptest/).
 ///
@@ -82,7 +87,21 @@ impl<'a> InvariantExecutor<'a> {
         &mut self,
         invariant_contract: InvariantContract,
     ) -> eyre::Result<InvariantFuzzTestResult> {
-        let (fuzz_state, targeted_contracts, strat) = self.prepare_fuzzing(&invariant_contract)?;
+        // Coverage-guided fuzzing enables coverage collection, which is restored even if the run
+        // fails.
+        let collect_coverage = self.executor.inspector_config().coverage;
+        let result = self.run_invariant_fuzz(invariant_contract);
+        self.executor.inspector_config_mut().coverage = collect_coverage;
+        result
+    }
+
+    /// Runs the invariant fuzz cases, see [InvariantExecutor::invariant_fuzz]
+    fn run_invariant_fuzz(
+        &mut self,
+        invariant_contract: InvariantContract,
+    ) -> eyre::Result<InvariantFuzzTestResult> {
+        let (fuzz_state, targeted_contracts, strat, corpus) =
+            self.prepare_fuzzing(&invariant_contract)?;
 
         // Stores the consumed energy and calldata of every successful fuzz call.
         let fuzz_cases: RefCell<Vec<FuzzedCases>> = RefCell::new(Default::default());
@@ -146,6 +165,10 @@ impl<'a> InvariantExecutor<'a> {
                         .call_raw(*sender, *address, calldata.0.clone(), U256::zero())
                         .expect("could not make raw evm call");
 
+                    if let (Some(corpus), Some(coverage)) = (&corpus, &call_result.coverage) {
+                        corpus.write().record(&(*sender, (*address, calldata.clone())), coverage);
+                    }
+
                     // Collect data for fuzzing from the state changeset.
                     let mut state_changeset =
                         call_result.state_changeset.to_owned().expect("no changesets");
@@ -256,12 +279,19 @@ impl<'a> InvariantExecutor<'a> {
         let targeted_contracts: FuzzRunIdentifiedContracts =
             Arc::new(Mutex::new(targeted_contracts));
 
+        // Calls reaching new coverage are kept and mutated by the strategy.
+        let corpus = self.config.coverage_guided.then(|| {
+            self.executor.inspector_config_mut().coverage = true;
+            Arc::new(RwLock::new(CoverageCorpus::default()))
+        });
+
         // Creates the invariant strategy.`
         let strat = invariant_strat(
             fuzz_state.clone(),
             targeted_senders,
             targeted_contracts.clone(),
             self.config.dictionary.dictionary_weight,
+            corpus.clone(),
             &Network::from(self.executor.env().cfg.network_id),
         )
         .no_shrink()
@@ -289,7 +319,7 @@ impl<'a> InvariantExecutor<'a> {
         self.executor.inspector_config_mut().fuzzer =
             Some(Fuzzer { call_generator, fuzz_state: fuzz_state.clone(), collect: true });
 
-        Ok((fuzz_state, targeted_contracts, strat))
+        Ok((fuzz_state, targeted_contracts, strat, corpus))
     }
 
     /// Fills the `InvariantExecutor` with the artifact identifier filters (in `path:name` string
@@ -122,6 +122,48 @@ pub fn assert_invariants(
     Ok(call_results)
 }
 
+/// Replays a previously recorded call sequence and checks the given invariant after every call.
+///
+/// Returns the error of the first check that breaks the invariant, or `None` if the invariant
+/// holds for the whole sequence.
+pub fn replay_sequence(
+    invariant_contract: &InvariantContract,
+    func: &Function,
+    executor: &Executor,
+    calls: &[BasicTxDetails],
+) -> eyre::Result<Option<InvariantFuzzError>> {
+    let mut executor = executor.clone();
+    let sender = Config::default_sender(Some(&Network::from(executor.env().cfg.network_id)));
+    let calldata: Bytes = func.encode_input(&[])?.into();
+
+    for (idx, (caller, (target, bytes))) in calls.iter().enumerate() {
+        executor.call_raw_committing(*caller, *target, bytes.0.clone(), U256::zero())?;
+
+        let mut call_result =
+            executor.call_raw(sender, invariant_contract.address, calldata.0.clone(), U256::zero())?;
+        let broken = call_result.reverted ||
+            !executor.is_success(
+                invariant_contract.address,
+                call_result.reverted,
+                call_result.state_changeset.take().unwrap_or_default(),
+                false,
+            );
+
+        if broken {
+            return Ok(Some(InvariantFuzzError::new(
+                invariant_contract,
+                Some(func),
+                &calls[..=idx],
+                call_result,
+                &[],
+                false,
+            )))
+        }
+    }
+
+    Ok(None)
+}
+
 /// The outcome of an invariant fuzz test
 #[derive(Debug)]
 pub struct InvariantFuzzTestResult {
@@ -14,11 +14,12 @@ use foxar_common::{calc, contracts::ContractsByAddress};
 use foxar_config::FuzzConfig;
 pub use proptest::test_runner::Reason;
 use proptest::test_runner::{TestCaseError, TestError, TestRunner};
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
-use std::{cell::RefCell, collections::BTreeMap, fmt};
+use std::{cell::RefCell, collections::BTreeMap, fmt, sync::Arc};
 use strategies::{
-    build_initial_state, collect_state_from_call, fuzz_calldata, fuzz_calldata_from_state,
-    EvmFuzzState,
+    build_initial_state, collect_state_from_call, fuzz_calldata, fuzz_calldata_from_corpus,
+    fuzz_calldata_from_state, CoverageCorpus, EvmFuzzState, SharedCorpus, CORPUS_WEIGHT,
 };
 
 pub mod error;
@@ -86,6 +87,25 @@ impl<'a> FuzzedExecutor<'a> {
             build_initial_state(self.executor.backend().mem_db(), &self.config.dictionary)
         };
 
+        // Coverage-guided fuzzing needs the coverage of every case, but it's only reported if
+        // the executor was already collecting it.
+        let report_coverage = self.executor.inspector_config().coverage;
+        let guided_executor;
+        let executor = if self.config.coverage_guided && !report_coverage {
+            let mut executor = self.executor.clone();
+            executor.inspector_config_mut().coverage = true;
+            guided_executor = executor;
+            &guided_executor
+        } else {
+            self.executor
+        };
+
+        // Stores the inputs that reached new coverage, if coverage-guided
+        let corpus: Option<SharedCorpus<Bytes>> = self
+            .config
+            .coverage_guided
+            .then(|| Arc::new(RwLock::new(CoverageCorpus::default())));
+
         let mut weights = vec![];
         let network = Network::from(self.executor.env().cfg.network_id);
 
@@ -99,12 +119,21 @@ impl<'a> FuzzedExecutor<'a> {
                 fuzz_calldata_from_state(func.clone(), state.clone(), &network),
             ));
         }
+        if let Some(corpus) = &corpus {
+            weights.push((
+                CORPUS_WEIGHT,
+                fuzz_calldata_from_corpus(
+                    func.clone(),
+                    corpus.clone(),
+                    fuzz_calldata(func.clone(), &network),
+                ),
+            ));
+        }
 
         let strat = proptest::strategy::Union::new_weighted(weights);
         debug!(func = ?func.name, should_fail, "fuzzing");
         let run_result = self.runner.clone().run(&strat, |calldata| {
-            let call = self
-                .executor
+            let call = executor
                 .call_raw(self.sender, address, calldata.0.clone(), 0.into())
                 .map_err(|_| TestCaseError::fail(FuzzError::FailedContractCall))?;
             let state_changeset = call
@@ -125,7 +154,11 @@ impl<'a> FuzzedExecutor<'a> {
                 return Err(TestCaseError::reject(FuzzError::AssumeReject));
             }
 
-            let success = self.executor.is_success(
+            if let (Some(corpus), Some(hits)) = (&corpus, &call.coverage) {
+                corpus.write().record(&calldata, hits);
+            }
+
+            let success = executor.is_success(
                 address,
                 call.reverted,
                 state_changeset.clone(),
@@ -145,12 +178,14 @@ impl<'a> FuzzedExecutor<'a> {
 
                 traces.replace(call.traces);
 
-                if let Some(prev) = coverage.take() {
-                    // Safety: If `Option::or` evaluates to `Some`, then `call.coverage` must
-                    // necessarily also be `Some`
-                    coverage.replace(Some(prev.merge(call.coverage.unwrap())));
-                } else {
-                    coverage.replace(call.coverage);
+                if report_coverage {
+                    if let Some(prev) = coverage.take() {
+                        // Safety: If `Option::or` evaluates to `Some`, then `call.coverage` must
+                        // necessarily also be `Some`
+                        coverage.replace(Some(prev.merge(call.coverage.unwrap())));
+                    } else {
+                        coverage.replace(call.coverage);
+                    }
                 }
 
                 Ok(())
@@ -217,6 +252,61 @@ impl<'a> FuzzedExecutor<'a> {
 
         result
     }
+
+    /// Replays a single, previously recorded fuzz case against the function.
+    ///
+    /// Returns the failed test result if the case still breaks the test, or `None` if it passes
+    /// now.
+    pub fn replay(
+        &self,
+        func: &Function,
+        address: Address,
+        should_fail: bool,
+        errors: Option<&Abi>,
+        calldata: Bytes,
+    ) -> Option<FuzzTestResult> {
+        let call = self.executor.call_raw(self.sender, address, calldata.0.clone(), 0.into()).ok()?;
+        let state_changeset = call.state_changeset.clone()?;
+
+        if call.result.as_ref() == ASSUME_MAGIC_RETURN_CODE ||
+            self.executor.is_success(address, call.reverted, state_changeset, should_fail)
+        {
+            return None
+        }
+
+        let reason = decode::decode_revert(call.result.as_ref(), errors, Some(call.exit_reason))
+            .unwrap_or_default();
+        let args = calldata
+            .as_ref()
+            .get(4..)
+            .and_then(|input| func.decode_input(input).ok())
+            .unwrap_or_default();
+
+        Some(FuzzTestResult {
+            first_case: FuzzCase {
+                calldata: calldata.clone(),
+                energy: call.energy_used,
+                stipend: call.stipend,
+            },
+            energy_by_case: vec![(call.energy_used, call.stipend)],
+            success: false,
+            reason: (!reason.is_empty()).then_some(reason),
+            counterexample: Some(CounterExample::Single(BaseCounterExample {
+                sender: None,
+                addr: None,
+                signature: None,
+                contract_name: None,
+                traces: call.traces.clone(),
+                calldata,
+                args,
+            })),
+            decoded_logs: decode_console_logs(&call.logs),
+            logs: call.logs,
+            labeled_addresses: call.labels,
+            traces: call.traces,
+            coverage: call.coverage,
+        })
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
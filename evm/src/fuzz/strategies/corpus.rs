@@ -0,0 +1,306 @@
+use crate::{
+    coverage::HitMaps,
+    fuzz::invariant::{BasicTxDetails, FuzzRunIdentifiedContracts},
+};
+use corebc::{
+    abi::{Function, ParamType, Token},
+    types::{Bytes, H256, U256},
+};
+use hashbrown::HashSet;
+use parking_lot::RwLock;
+use proptest::prelude::*;
+use std::sync::Arc;
+
+/// The maximum number of inputs kept in a [CoverageCorpus].
+const MAX_CORPUS_SIZE: usize = 4096;
+
+/// The weight of mutated corpus inputs, relative to the other input strategies which have a
+/// combined weight of 100.
+pub const CORPUS_WEIGHT: u32 = 50;
+
+/// Inputs that reached new coverage during a fuzz run.
+///
+/// Coverage is tracked as the set of `(code hash, program counter)` pairs hit by all the inputs
+/// executed so far, as reported by the coverage inspector.
+#[derive(Debug)]
+pub struct CoverageCorpus<T> {
+    /// Every instruction hit so far.
+    seen: HashSet<(H256, usize)>,
+    /// The inputs that hit new instructions, in the order they were found.
+    inputs: Vec<T>,
+}
+
+impl<T> Default for CoverageCorpus<T> {
+    fn default() -> Self {
+        Self { seen: HashSet::new(), inputs: Vec::new() }
+    }
+}
+
+impl<T: Clone> CoverageCorpus<T> {
+    /// Records the coverage of an executed input, and keeps the input if it hit instructions no
+    /// previous input reached.
+    ///
+    /// Returns `true` if the input reached new coverage.
+    pub fn record(&mut self, input: &T, coverage: &HitMaps) -> bool {
+        let mut new_coverage = false;
+        for (code_hash, hit_map) in coverage.iter() {
+            for pc in hit_map.hits.keys() {
+                new_coverage |= self.seen.insert((*code_hash, *pc));
+            }
+        }
+
+        if new_coverage && self.inputs.len() < MAX_CORPUS_SIZE {
+            self.inputs.push(input.clone());
+        }
+        new_coverage
+    }
+
+    /// The inputs in the corpus.
+    pub fn inputs(&self) -> &[T] {
+        &self.inputs
+    }
+
+    /// Whether the corpus has no inputs yet.
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+}
+
+/// A [CoverageCorpus] shared between the executor and the strategies.
+pub type SharedCorpus<T> = Arc<RwLock<CoverageCorpus<T>>>;
+
+/// Given a function and a corpus of calldata, returns a strategy which mutates a random input of
+/// the corpus.
+///
+/// Values of the `fallback` strategy are used as long as the corpus is empty.
+pub fn fuzz_calldata_from_corpus(
+    func: Function,
+    corpus: SharedCorpus<Bytes>,
+    fallback: BoxedStrategy<Bytes>,
+) -> BoxedStrategy<Bytes> {
+    (
+        fallback,
+        any::<prop::sample::Index>(),
+        any::<prop::sample::Index>(),
+        any::<u8>(),
+        any::<[u8; 32]>(),
+    )
+        .prop_map(move |(fallback, entry, param, op, value)| {
+            let corpus = corpus.read();
+            if corpus.is_empty() {
+                return fallback
+            }
+            mutate_calldata(&func, entry.get(corpus.inputs()), param, op, value)
+                .unwrap_or(fallback)
+        })
+        .boxed()
+}
+
+/// Given the fuzzed contracts and a corpus of calls, returns a strategy which mutates the calldata
+/// of a random call of the corpus, keeping its sender and target.
+///
+/// Values of the `fallback` strategy are used as long as the corpus is empty.
+pub fn fuzz_call_from_corpus(
+    contracts: FuzzRunIdentifiedContracts,
+    corpus: SharedCorpus<BasicTxDetails>,
+    fallback: BoxedStrategy<BasicTxDetails>,
+) -> BoxedStrategy<BasicTxDetails> {
+    (
+        fallback,
+        any::<prop::sample::Index>(),
+        any::<prop::sample::Index>(),
+        any::<u8>(),
+        any::<[u8; 32]>(),
+    )
+        .prop_map(move |(fallback, entry, param, op, value)| {
+            let corpus = corpus.read();
+            if corpus.is_empty() {
+                return fallback
+            }
+
+            let (sender, (target, calldata)) = entry.get(corpus.inputs());
+            let contracts = contracts.lock();
+            let func = contracts.get(target).and_then(|(_, abi, _)| {
+                let selector = calldata.as_ref().get(..4)?;
+                abi.functions().find(|func| func.short_signature().as_slice() == selector)
+            });
+
+            match func.and_then(|func| mutate_calldata(func, calldata, param, op, value)) {
+                Some(calldata) => (*sender, (*target, calldata)),
+                None => fallback,
+            }
+        })
+        .boxed()
+}
+
+/// Mutates a single parameter of ABI-encoded calldata, keeping it valid for the function.
+///
+/// Returns `None` if the function has no parameters or the calldata can't be decoded.
+pub fn mutate_calldata(
+    func: &Function,
+    calldata: &Bytes,
+    param: prop::sample::Index,
+    op: u8,
+    value: [u8; 32],
+) -> Option<Bytes> {
+    if func.inputs.is_empty() {
+        return None
+    }
+
+    let mut tokens = func.decode_input(calldata.as_ref().get(4..)?).ok()?;
+    let idx = param.index(tokens.len());
+    mutate_token(&mut tokens[idx], &func.inputs[idx].kind, op, value);
+    func.encode_input(&tokens).ok().map(Into::into)
+}
+
+/// Mutates a token in place, keeping it in the range of its type.
+fn mutate_token(token: &mut Token, kind: &ParamType, op: u8, value: [u8; 32]) {
+    match (token, kind) {
+        (Token::Uint(n), ParamType::Uint(size)) => *n = mutate_uint(*n, *size, op, value),
+        (Token::Int(n), ParamType::Int(size)) => *n = mutate_int(*n, *size, op, value),
+        (Token::Bool(b), ParamType::Bool) => *b = !*b,
+        (Token::FixedBytes(bytes), _) => {
+            if !bytes.is_empty() {
+                let idx = value[0] as usize % bytes.len();
+                bytes[idx] = value[1];
+            }
+        }
+        (Token::Bytes(bytes), _) => {
+            if bytes.is_empty() || op % 2 == 0 {
+                bytes.push(value[1]);
+            } else {
+                let idx = value[0] as usize % bytes.len();
+                bytes[idx] = value[1];
+            }
+        }
+        (Token::String(s), _) => {
+            let c = (b'a' + value[1] % 26) as char;
+            if s.is_empty() || op % 2 == 0 {
+                s.push(c);
+            } else {
+                let mut chars = s.chars().collect::<Vec<_>>();
+                let idx = value[0] as usize % chars.len();
+                chars[idx] = c;
+                *s = chars.into_iter().collect();
+            }
+        }
+        (Token::Array(tokens), ParamType::Array(inner)) |
+        (Token::FixedArray(tokens), ParamType::FixedArray(inner, _)) => {
+            if !tokens.is_empty() {
+                let idx = value[0] as usize % tokens.len();
+                mutate_token(&mut tokens[idx], inner, op, value);
+            }
+        }
+        (Token::Tuple(tokens), ParamType::Tuple(kinds)) => {
+            if !tokens.is_empty() && tokens.len() == kinds.len() {
+                let idx = value[0] as usize % tokens.len();
+                mutate_token(&mut tokens[idx], &kinds[idx], op, value);
+            }
+        }
+        // Addresses are left as is, since random ones are unlikely to be meaningful
+        _ => {}
+    }
+}
+
+/// Mutates an unsigned integer of `size` bits.
+fn mutate_uint(n: U256, size: usize, op: u8, value: [u8; 32]) -> U256 {
+    let max = if size >= 256 { U256::MAX } else { (U256::one() << size) - 1 };
+    match op % 5 {
+        0 => U256::from_big_endian(&value) & max,
+        1 => {
+            if n >= max {
+                U256::zero()
+            } else {
+                n + U256::one()
+            }
+        }
+        2 => {
+            if n.is_zero() {
+                max
+            } else {
+                n - U256::one()
+            }
+        }
+        3 => [U256::zero(), U256::one(), max][value[0] as usize % 3],
+        _ => (n ^ (U256::one() << (value[0] as usize % size))) & max,
+    }
+}
+
+/// Mutates a two's complement signed integer of `size` bits.
+fn mutate_int(n: U256, size: usize, op: u8, value: [u8; 32]) -> U256 {
+    let mask = if size >= 256 { U256::MAX } else { (U256::one() << size) - 1 };
+    let sign_extend = |v: U256| {
+        let v = v & mask;
+        if size < 256 && v.bit(size - 1) {
+            v | !mask
+        } else {
+            v
+        }
+    };
+    let max = mask >> 1;
+    let min = !max;
+
+    match op % 5 {
+        0 => sign_extend(U256::from_big_endian(&value)),
+        1 => {
+            if n == max {
+                min
+            } else {
+                n.overflowing_add(U256::one()).0
+            }
+        }
+        2 => {
+            if n == min {
+                max
+            } else {
+                n.overflowing_sub(U256::one()).0
+            }
+        }
+        // 0, 1, -1, min and max
+        3 => [U256::zero(), U256::one(), U256::MAX, min, max][value[0] as usize % 5],
+        _ => sign_extend(n ^ (U256::one() << (value[0] as usize % size))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mutated_uints_stay_in_range() {
+        for op in 0..5 {
+            for byte in [0u8, 7, 8, 255] {
+                let mutated = mutate_uint(U256::from(255), 8, op, [byte; 32]);
+                assert!(mutated <= U256::from(255), "op {op}: {mutated}");
+            }
+        }
+    }
+
+    #[test]
+    fn mutated_ints_stay_in_range() {
+        let min = !(U256::from(127));
+        for op in 0..5 {
+            for byte in [0u8, 7, 8, 255] {
+                let mutated = mutate_int(U256::from(127), 8, op, [byte; 32]);
+                assert!(mutated <= U256::from(127) || mutated >= min, "op {op}: {mutated}");
+            }
+        }
+        // -1 + 1 == 0
+        assert_eq!(mutate_int(U256::MAX, 8, 1, [0; 32]), U256::zero());
+        // min - 1 wraps to max
+        assert_eq!(mutate_int(min, 8, 2, [0; 32]), U256::from(127));
+    }
+
+    #[test]
+    fn records_new_coverage_only() {
+        let mut hit_map = crate::coverage::HitMap::new(Default::default());
+        hit_map.hit(1);
+        let mut coverage = HitMaps::default();
+        coverage.insert(H256::zero(), hit_map);
+
+        let mut corpus = CoverageCorpus::default();
+        assert!(corpus.record(&Bytes::from(vec![1]), &coverage));
+        assert!(!corpus.record(&Bytes::from(vec![2]), &coverage));
+        assert_eq!(corpus.inputs().len(), 1);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::fuzz::{
     fuzz_calldata, fuzz_calldata_from_state,
     invariant::{BasicTxDetails, FuzzRunIdentifiedContracts, SenderFilters},
-    strategies::fuzz_param,
+    strategies::{fuzz_call_from_corpus, fuzz_param, SharedCorpus, CORPUS_WEIGHT},
     EvmFuzzState,
 };
 use corebc::{
@@ -54,16 +54,30 @@ pub fn override_call_strat(
 /// contract:
 ///
 /// `targetContracts()`, `targetSenders()`, `excludeContracts()`, `targetSelectors()`
+///
+/// If a coverage `corpus` is given, calls are also generated by mutating the calls that reached
+/// new coverage.
 pub fn invariant_strat(
     fuzz_state: EvmFuzzState,
     senders: SenderFilters,
     contracts: FuzzRunIdentifiedContracts,
     dictionary_weight: u32,
+    corpus: Option<SharedCorpus<BasicTxDetails>>,
     network: &Network,
 ) -> BoxedStrategy<Vec<BasicTxDetails>> {
+    let mut call =
+        generate_call(fuzz_state, senders, contracts.clone(), dictionary_weight, *network);
+    if let Some(corpus) = corpus {
+        call = proptest::strategy::Union::new_weighted(vec![
+            (100, call.clone()),
+            (CORPUS_WEIGHT, fuzz_call_from_corpus(contracts, corpus, call)),
+        ])
+        .boxed();
+    }
+
     // We only want to seed the first value, since we want to generate the rest as we mutate the
     // state
-    vec![generate_call(fuzz_state, senders, contracts, dictionary_weight, *network); 1].boxed()
+    vec![call; 1].boxed()
 }
 
 /// Strategy to generate a transaction where the `sender`, `target` and `calldata` are all generated
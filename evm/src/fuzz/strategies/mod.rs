@@ -17,3 +17,9 @@ pub use state::{
 
 mod invariants;
 pub use invariants::*;
+
+mod corpus;
+pub use corpus::{
+    fuzz_call_from_corpus, fuzz_calldata_from_corpus, mutate_calldata, CoverageCorpus,
+    SharedCorpus, CORPUS_WEIGHT,
+};
@@ -4,7 +4,10 @@ use crate::{
 };
 use corebc::{
     abi::{ethereum_types::BigEndianHash, Address, RawLog},
-    types::{Bytes, DefaultFrame, GoCoreDebugTracingOptions, Network, StructLog, H256, U256},
+    types::{
+        Bytes, DefaultFrame, ExecutedInstruction, GoCoreDebugTracingOptions, Network, StorageDiff,
+        StructLog, VMExecutedOperation, VMOperation, VMTrace, H256, U256,
+    },
 };
 pub use decoder::{CallTraceDecoder, CallTraceDecoderBuilder};
 use foxar_common::contracts::{ContractsByAddress, ContractsByArtifact};
@@ -91,6 +94,37 @@ impl CallTraceArena {
             .collect()
     }
 
+    /// Returns the `traceAddress` of the node in the arena
+    ///
+    /// The `traceAddress` field of all returned traces, gives the exact location in the call trace
+    /// [index in root, index in first CALL, index in second CALL, …].
+    ///
+    /// # Panics
+    ///
+    /// if the `idx` does not belong to a node
+    pub fn trace_address(&self, idx: usize) -> Vec<usize> {
+        if idx == 0 {
+            // root call has empty traceAddress
+            return vec![];
+        }
+        let mut graph = vec![];
+        let mut node = &self.arena[idx];
+        while let Some(parent) = node.parent {
+            // the index of the child call in the arena
+            let child_idx = node.idx;
+            node = &self.arena[parent];
+            // find the index of the child call in the parent node
+            let call_idx = node
+                .children
+                .iter()
+                .position(|child| *child == child_idx)
+                .expect("child exists in parent");
+            graph.push(call_idx);
+        }
+        graph.reverse();
+        graph
+    }
+
     // Recursively fill in the geth trace by going through the traces
     fn add_to_geth_trace(
         &self,
@@ -176,6 +210,81 @@ impl CallTraceArena {
 
         acc
     }
+
+    /// Generate a parity-style vm trace e.g. for `trace_call` with `vmTrace`
+    ///
+    /// `code` returns the code that was executed by a node, this requires steps tracing.
+    pub fn parity_vm_trace(&self, code: impl Fn(&CallTraceNode) -> Bytes) -> VMTrace {
+        if self.arena.is_empty() {
+            return Default::default();
+        }
+        self.add_to_vm_trace(&self.arena[0], &code, "0")
+    }
+
+    // Recursively build the vm trace of a node and its children
+    fn add_to_vm_trace(
+        &self,
+        trace_node: &CallTraceNode,
+        code: &impl Fn(&CallTraceNode) -> Bytes,
+        idx: &str,
+    ) -> VMTrace {
+        let steps = &trace_node.trace.steps;
+        let mut child_id = 0;
+        let mut ops = Vec::with_capacity(steps.len());
+        for (step_idx, step) in steps.iter().enumerate() {
+            let op_idx = format!("{idx}-{step_idx}");
+
+            // the values pushed by the operation are on the stack of the next step
+            let push = steps
+                .get(step_idx + 1)
+                .map(|next| {
+                    let before = step.stack.data();
+                    let after = next.stack.data();
+                    let unchanged =
+                        before.iter().zip(after.iter()).take_while(|(a, b)| a == b).count();
+                    after[unchanged..].iter().copied().map(ru256_to_u256).collect()
+                })
+                .unwrap_or_default();
+
+            let mut store = None;
+            let mut sub = None;
+            if let Instruction::OpCode(opc) = step.op {
+                match opc {
+                    opcode::SSTORE => {
+                        store = step.state_diff.map(|(key, val)| StorageDiff { key, val });
+                    }
+                    opcode::CREATE |
+                    opcode::CREATE2 |
+                    opcode::DELEGATECALL |
+                    opcode::CALL |
+                    opcode::STATICCALL |
+                    opcode::CALLCODE => {
+                        if let Some(child) = trace_node.children.get(child_id) {
+                            sub = Some(self.add_to_vm_trace(&self.arena[*child], code, &op_idx));
+                        }
+                        child_id += 1;
+                    }
+                    _ => {}
+                }
+            }
+
+            ops.push(VMOperation {
+                pc: step.pc,
+                cost: step.energy_cost,
+                ex: Some(VMExecutedOperation {
+                    used: step.energy.saturating_sub(step.energy_cost),
+                    push,
+                    mem: None,
+                    store,
+                }),
+                sub,
+                op: ExecutedInstruction::Unknown(step.op.to_string()),
+                idx: op_idx,
+            });
+        }
+
+        VMTrace { code: code(trace_node), ops }
+    }
 }
 
 const PIPE: &str = "  │ ";
@@ -464,6 +573,9 @@ pub struct CallTrace {
     /// The return data of the call if this was not a contract creation, otherwise it is the
     /// runtime bytecode of the created contract
     pub output: RawOrDecodedReturnData,
+    /// The energy limit of the call
+    #[serde(default)]
+    pub energy_limit: u64,
     /// The energy cost of the call
     pub energy_cost: u64,
     /// The status of the trace's call
@@ -496,6 +608,7 @@ impl Default for CallTrace {
             value: Default::default(),
             data: Default::default(),
             output: Default::default(),
+            energy_limit: Default::default(),
             energy_cost: Default::default(),
             status: InstructionResult::Continue,
             call_context: Default::default(),
@@ -9,7 +9,9 @@ use crate::{
 };
 use corebc::{
     abi::{Abi, Function},
-    types::{Action, Address, Call, CallResult, Create, CreateResult, Res, Suicide},
+    types::{
+        Action, ActionType, Address, Call, CallResult, Create, CreateResult, Res, Suicide,
+    },
 };
 use foxar_common::SELECTOR_LEN;
 use revm::interpreter::InstructionResult;
@@ -62,6 +64,15 @@ impl CallTraceNode {
         }
     }
 
+    /// Returns the `ActionType` for a parity trace
+    pub fn parity_action_type(&self) -> ActionType {
+        if self.status() == InstructionResult::SelfDestruct {
+            ActionType::Suicide
+        } else {
+            self.kind().into()
+        }
+    }
+
     /// Returns the `Action` for a parity trace
     pub fn parity_action(&self) -> Action {
         if self.status() == InstructionResult::SelfDestruct {
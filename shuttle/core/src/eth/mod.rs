@@ -1,22 +1,27 @@
-use self::state::StateOverride;
+use self::{simulate::SimulatePayload, state::StateOverride};
 use crate::{
     eth::{
         subscription::{SubscriptionId, SubscriptionKind, SubscriptionParams},
         transaction::EthTransactionRequest,
     },
-    types::{EvmMineOptions, Forking, Index},
+    types::{
+        DumpStateOptions, EvmMineOptions, Forking, Index, MiningModeOptions, ReorgOptions,
+        TraceFilter,
+    },
 };
 use corebc_core::{
     abi::ethereum_types::H64,
     types::{
         transaction::cip712::TypedData, Address, BlockId, BlockNumber, Bytes, Filter,
-        GoCoreDebugTracingOptions, TxHash, H256, U256,
+        GoCoreDebugTracingOptions, TraceType, TxHash, H256, U256,
     },
 };
 
 pub mod block;
+pub mod otterscan;
 pub mod proof;
 pub mod receipt;
+pub mod simulate;
 pub mod state;
 pub mod subscription;
 pub mod transaction;
@@ -161,6 +166,10 @@ pub enum EthRequest {
         #[cfg_attr(feature = "serde", serde(default))] Option<StateOverride>,
     ),
 
+    /// Simulates a sequence of calls across one or more blocks without mining anything
+    #[cfg_attr(feature = "serde", serde(rename = "xcb_simulateV1"))]
+    EthSimulateV1(SimulatePayload, #[cfg_attr(feature = "serde", serde(default))] Option<BlockId>),
+
     #[cfg_attr(feature = "serde", serde(rename = "xcb_estimateEnergy"))]
     EthEstimateGas(
         EthTransactionRequest,
@@ -252,6 +261,33 @@ pub enum EthRequest {
         #[cfg_attr(feature = "serde", serde(default))] GoCoreDebugTracingOptions,
     ),
 
+    /// geth's `debug_traceBlockByNumber`  endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "debug_traceBlockByNumber"))]
+    DebugTraceBlockByNumber(
+        BlockNumber,
+        #[cfg_attr(feature = "serde", serde(default))] GoCoreDebugTracingOptions,
+    ),
+
+    /// geth's `debug_traceBlockByHash`  endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "debug_traceBlockByHash"))]
+    DebugTraceBlockByHash(
+        H256,
+        #[cfg_attr(feature = "serde", serde(default))] GoCoreDebugTracingOptions,
+    ),
+
+    /// geth's `debug_storageRangeAt` endpoint: block hash, transaction index, account, first
+    /// hashed key and maximum number of slots
+    #[cfg_attr(feature = "serde", serde(rename = "debug_storageRangeAt"))]
+    DebugStorageRangeAt(H256, usize, Address, H256, usize),
+
+    /// geth's `debug_getRawTransaction` endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "debug_getRawTransaction", with = "sequence"))]
+    DebugGetRawTransaction(H256),
+
+    /// geth's `debug_getRawReceipts` endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "debug_getRawReceipts", with = "sequence"))]
+    DebugGetRawReceipts(BlockId),
+
     /// Trace transaction endpoint for parity's `trace_transaction`
     #[cfg_attr(feature = "serde", serde(rename = "trace_transaction", with = "sequence"))]
     TraceTransaction(H256),
@@ -263,6 +299,80 @@ pub enum EthRequest {
     )]
     TraceBlock(BlockNumber),
 
+    /// Trace endpoint for parity's `trace_filter`
+    #[cfg_attr(feature = "serde", serde(rename = "trace_filter", with = "sequence"))]
+    TraceFilter(TraceFilter),
+
+    /// Trace endpoint for parity's `trace_call`
+    #[cfg_attr(feature = "serde", serde(rename = "trace_call"))]
+    TraceCall(
+        EthTransactionRequest,
+        Vec<TraceType>,
+        #[cfg_attr(feature = "serde", serde(default))] Option<BlockId>,
+    ),
+
+    /// Trace endpoint for parity's `trace_replayBlockTransactions`
+    #[cfg_attr(feature = "serde", serde(rename = "trace_replayBlockTransactions"))]
+    TraceReplayBlockTransactions(BlockNumber, Vec<TraceType>),
+
+    /// Trace endpoint for parity's `trace_replayTransaction`
+    #[cfg_attr(feature = "serde", serde(rename = "trace_replayTransaction"))]
+    TraceReplayTransaction(H256, Vec<TraceType>),
+
+    /// Otterscan's `ots_getApiLevel` endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "ots_getApiLevel", with = "empty_params"))]
+    OtsGetApiLevel(()),
+
+    /// Otterscan's `ots_getInternalOperations` endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "ots_getInternalOperations", with = "sequence"))]
+    OtsGetInternalOperations(H256),
+
+    /// Otterscan's `ots_hasCode` endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "ots_hasCode"))]
+    OtsHasCode(
+        Address,
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "lenient_block_number"))]
+        BlockNumber,
+    ),
+
+    /// Otterscan's `ots_getTransactionError` endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "ots_getTransactionError", with = "sequence"))]
+    OtsGetTransactionError(H256),
+
+    /// Otterscan's `ots_traceTransaction` endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "ots_traceTransaction", with = "sequence"))]
+    OtsTraceTransaction(H256),
+
+    /// Otterscan's `ots_getBlockDetails` endpoint
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "ots_getBlockDetails", deserialize_with = "lenient_block_number_seq")
+    )]
+    OtsGetBlockDetails(BlockNumber),
+
+    /// Otterscan's `ots_getBlockTransactions` endpoint: block number, page number and page size
+    #[cfg_attr(feature = "serde", serde(rename = "ots_getBlockTransactions"))]
+    OtsGetBlockTransactions(u64, usize, usize),
+
+    /// Otterscan's `ots_searchTransactionsBefore` endpoint: address, block number and page size
+    #[cfg_attr(feature = "serde", serde(rename = "ots_searchTransactionsBefore"))]
+    OtsSearchTransactionsBefore(Address, u64, usize),
+
+    /// Otterscan's `ots_searchTransactionsAfter` endpoint: address, block number and page size
+    #[cfg_attr(feature = "serde", serde(rename = "ots_searchTransactionsAfter"))]
+    OtsSearchTransactionsAfter(Address, u64, usize),
+
+    /// Otterscan's `ots_getTransactionBySenderAndNonce` endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "ots_getTransactionBySenderAndNonce"))]
+    OtsGetTransactionBySenderAndNonce(
+        Address,
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_number"))] U256,
+    ),
+
+    /// Otterscan's `ots_getContractCreator` endpoint
+    #[cfg_attr(feature = "serde", serde(rename = "ots_getContractCreator", with = "sequence"))]
+    OtsGetContractCreator(Address),
+
     // Custom endpoints, they're not extracted to a separate type out of serde convenience
     /// send transactions impersonating specific account and contract addresses.
     #[cfg_attr(
@@ -336,6 +446,10 @@ pub enum EthRequest {
     )]
     SetIntervalMining(u64),
 
+    /// Sets the mining behavior, for example to mine once the block energy limit is filled
+    #[cfg_attr(feature = "serde", serde(rename = "shuttle_setMiningMode", with = "sequence"))]
+    SetMiningMode(MiningModeOptions),
+
     /// Removes transactions from the pool
     #[cfg_attr(
         feature = "serde",
@@ -351,6 +465,22 @@ pub enum EthRequest {
     #[cfg_attr(feature = "serde", serde(rename = "shuttle_reset", alias = "hardhat_reset"))]
     Reset(#[cfg_attr(feature = "serde", serde(default))] Option<Params<Option<Forking>>>),
 
+    /// Replaces the most recent blocks with new blocks that contain the given transactions
+    #[cfg_attr(feature = "serde", serde(rename = "shuttle_reorg", with = "sequence"))]
+    Reorg(ReorgOptions),
+
+    /// Removes the most recent blocks, `1` block if not set
+    #[cfg_attr(feature = "serde", serde(rename = "shuttle_rollback"))]
+    Rollback(#[cfg_attr(feature = "serde", serde(default))] Option<Params<Option<u64>>>),
+
+    /// Partitions the node from the local network it follows
+    #[cfg_attr(feature = "serde", serde(rename = "shuttle_partition", with = "empty_params"))]
+    Partition(()),
+
+    /// Reconnects the node to the local network it follows
+    #[cfg_attr(feature = "serde", serde(rename = "shuttle_heal", with = "empty_params"))]
+    Heal(()),
+
     /// Sets the backend rpc url
     #[cfg_attr(feature = "serde", serde(rename = "shuttle_setRpcUrl", with = "sequence"))]
     SetRpcUrl(String),
@@ -439,12 +569,20 @@ pub enum EthRequest {
     EvmSetTime(U256),
 
     /// Serializes the current state (including contracts code, contract's storage, accounts
-    /// properties, etc.) into a savable data blob
+    /// properties, etc.) into a savable, gzip compressed data blob
+    ///
+    /// Optionally includes the history of the chain, or only the given accounts
     #[cfg_attr(
         feature = "serde",
-        serde(rename = "shuttle_dumpState", alias = "hardhat_dumpState", with = "empty_params")
+        serde(rename = "shuttle_dumpState", alias = "hardhat_dumpState")
     )]
-    DumpState(()),
+    DumpState(
+        #[cfg_attr(feature = "serde", serde(default))] Option<Params<Option<DumpStateOptions>>>,
+    ),
+
+    /// Returns the current state and chain configuration as a go-core compatible `genesis.json`
+    #[cfg_attr(feature = "serde", serde(rename = "shuttle_dumpGenesis", with = "empty_params"))]
+    DumpGenesis(()),
 
     /// Adds state previously dumped with `DumpState` to the current chain
     #[cfg_attr(
@@ -453,6 +591,11 @@ pub enum EthRequest {
     )]
     LoadState(Bytes),
 
+    /// Returns the accounts of a state previously dumped with `DumpState` that differ from the
+    /// current state, without loading it
+    #[cfg_attr(feature = "serde", serde(rename = "shuttle_stateConflicts", with = "sequence"))]
+    StateConflicts(Bytes),
+
     /// Retrieves the Shuttle node configuration params
     #[cfg_attr(feature = "serde", serde(rename = "shuttle_nodeInfo", with = "empty_params"))]
     NodeInfo(()),
@@ -603,6 +746,7 @@ pub enum EthRpcCall {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::TransactionData;
 
     #[test]
     fn test_web3_client_version() {
@@ -853,6 +997,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_custom_reorg() {
+        let s = r#"{"method": "shuttle_reorg", "params": [{"depth": 2, "txBlockPairs": [
+            [{"from": "0x0000d84de507f3fada7df80908082d3239466db55a71", "value": "0x1"}, 0],
+            ["0xf85f800182520894095e7baea6a6c7c4c2dfeb977efac326af552d870a801ba048b55bfa915ac795c431978d8a6a992b628d557da5ff759b307d495a36649353a0efffd310ac743f371de3b9f7f9cb56c0b28ad43601b4ab949f53faa07bd2c804", 1]
+        ]}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::Reorg(options) => {
+                assert_eq!(options.depth, 2);
+                assert!(matches!(options.tx_block_pairs[0], (TransactionData::Json(_), 0)));
+                assert!(matches!(options.tx_block_pairs[1], (TransactionData::Raw(_), 1)));
+            }
+            _ => unreachable!(),
+        }
+
+        let s = r#"{"method": "shuttle_reorg", "params": [{"depth": 1}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_custom_rollback() {
+        let s = r#"{"method": "shuttle_rollback", "params": [3]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(req, EthRequest::Rollback(Some(Params { params: Some(3) })));
+
+        let s = r#"{"method": "shuttle_rollback", "params": []}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(req, EthRequest::Rollback(Some(Params { params: None })));
+
+        let s = r#"{"method": "shuttle_rollback"}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(req, EthRequest::Rollback(None));
+    }
+
+    #[test]
+    fn test_custom_partition() {
+        let s = r#"{"method": "shuttle_partition", "params": []}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(req, EthRequest::Partition(()));
+
+        let s = r#"{"method": "shuttle_heal", "params": []}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(req, EthRequest::Heal(()));
+    }
+
     #[test]
     fn test_custom_set_balance() {
         let s = r#"{"method": "shuttle_setBalance", "params": ["0x0000d84de507f3fada7df80908082d3239466db55a71", "0x0"]}"#;
@@ -935,6 +1132,32 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_custom_set_mining_mode() {
+        let s = r#"{"method": "shuttle_setMiningMode", "params": [{"mode": "transactionCount", "transactions": 10}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(
+            req,
+            EthRequest::SetMiningMode(MiningModeOptions::TransactionCount { transactions: 10 })
+        );
+
+        let s = r#"{"method": "shuttle_setMiningMode", "params": [{"mode": "intervalOrEnergyFill", "blockTime": 12, "fill": 0.5}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(
+            req,
+            EthRequest::SetMiningMode(MiningModeOptions::IntervalOrEnergyFill {
+                block_time: 12,
+                fill: 0.5
+            })
+        );
+
+        let s = r#"{"method": "shuttle_setMiningMode", "params": [{"mode": "auto"}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_serde_custom_dump_state() {
         let s = r#"{"method": "shuttle_dumpState", "params": [] }"#;
@@ -942,6 +1165,39 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_custom_dump_state_options() {
+        let s = r#"{"method": "shuttle_dumpState", "params": [{
+            "includeHistory": true,
+            "addresses": ["0x00000000000000000000000000000000000000000001"]
+        }]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::DumpState(options) => {
+                let options = options.and_then(|p| p.params).unwrap();
+                assert!(options.include_history);
+                assert_eq!(options.addresses.unwrap().len(), 1);
+            }
+            _ => unreachable!(),
+        }
+
+        let s = r#"{"method": "shuttle_dumpState"}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::DumpState(options) => assert!(options.is_none()),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_serde_custom_dump_genesis() {
+        let s = r#"{"method": "shuttle_dumpGenesis", "params": [] }"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_serde_custom_load_state() {
         let s = r#"{"method": "shuttle_loadState", "params": ["0x0001"] }"#;
@@ -949,6 +1205,13 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_custom_state_conflicts() {
+        let s = r#"{"method": "shuttle_stateConflicts", "params": ["0x0001"] }"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_serde_custom_snapshot() {
         let s = r#"{"method": "shuttle_snapshot", "params": [] }"#;
@@ -1176,6 +1439,22 @@ mod tests {
         let s = r#"{"id": 1, "method": "xcb_subscribe", "params": ["syncing"]}"#;
         let value: serde_json::Value = serde_json::from_str(s).unwrap();
         let _req = serde_json::from_value::<EthPubSub>(value).unwrap();
+
+        let s =
+            r#"{"id": 1, "method": "xcb_subscribe", "params": ["newPendingTransactions", true]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthPubSub>(value).unwrap();
+        match req {
+            EthPubSub::EthSubscribe(SubscriptionKind::NewPendingTransactions, params) => {
+                assert!(params.full_transactions);
+                assert!(params.filter.is_none());
+            }
+            _ => unreachable!(),
+        }
+
+        let s = r#"{"id": 1, "method": "xcb_subscribe", "params": ["shuttleTransfers"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthPubSub>(value).unwrap();
     }
 
     #[test]
@@ -1216,6 +1495,137 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_debug_trace_block() {
+        let s = r#"{"method": "debug_traceBlockByNumber", "params": ["0x1"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "debug_traceBlockByNumber", "params": ["latest", {"tracer": "callTracer"}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "debug_traceBlockByHash", "params": ["0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3", {"disableStorage": true}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_debug_storage_range_at() {
+        let s = r#"{"method": "debug_storageRangeAt", "params": ["0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3", 0, "0x0000cbe828fdc46e3b1c351ec90b1a5e7d9742c0398d", "0x0000000000000000000000000000000000000000000000000000000000000000", 10]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_debug_raw() {
+        let s = r#"{"method": "debug_getRawTransaction", "params": ["0x4a3b0fce2cb9707b0baa68640cf2fe858c8bb4121b2a8cb904ff369d38a560ff"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "debug_getRawReceipts", "params": ["latest"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "debug_getRawReceipts", "params": ["0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_ots() {
+        let s = r#"{"method": "ots_getApiLevel", "params": []}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "ots_hasCode", "params": ["0x0000cbe828fdc46e3b1c351ec90b1a5e7d9742c0398d", 5]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert!(
+            matches!(req, EthRequest::OtsHasCode(_, BlockNumber::Number(num)) if num == 5u64.into())
+        );
+
+        let s = r#"{"method": "ots_getBlockDetails", "params": [3]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(req, EthRequest::OtsGetBlockDetails(BlockNumber::Number(3u64.into())));
+
+        let s = r#"{"method": "ots_getBlockTransactions", "params": [3, 0, 25]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(req, EthRequest::OtsGetBlockTransactions(3, 0, 25));
+
+        let s = r#"{"method": "ots_searchTransactionsBefore", "params": ["0x0000cbe828fdc46e3b1c351ec90b1a5e7d9742c0398d", 0, 25]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "ots_getTransactionBySenderAndNonce", "params": ["0x0000cbe828fdc46e3b1c351ec90b1a5e7d9742c0398d", 1]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert!(
+            matches!(req, EthRequest::OtsGetTransactionBySenderAndNonce(_, nonce) if nonce == 1u64.into())
+        );
+
+        let s = r#"{"method": "ots_getContractCreator", "params": ["0x0000cbe828fdc46e3b1c351ec90b1a5e7d9742c0398d"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_trace_filter() {
+        let s = r#"{"method": "trace_filter", "params": [{"fromBlock": "0x3", "toBlock": "latest", "toAddress": ["0x0000cbe828fdc46e3b1c351ec90b1a5e7d9742c0398d"], "after": 1, "count": 10}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::TraceFilter(filter) => {
+                assert_eq!(filter.from_block, Some(BlockNumber::Number(3u64.into())));
+                assert_eq!(filter.to_block, Some(BlockNumber::Latest));
+                assert!(filter.from_address.is_none());
+                assert_eq!(filter.to_address.unwrap().len(), 1);
+                assert_eq!(filter.after, Some(1));
+                assert_eq!(filter.count, Some(10));
+            }
+            _ => unreachable!(),
+        }
+
+        let s = r#"{"method": "trace_filter", "params": [{}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_trace_call() {
+        let s = r#"{"method": "trace_call", "params": [{"data":"0xcfae3217","from":"0x0000d84de507f3fada7df80908082d3239466db55a71","to":"0x0000cbe828fdc46e3b1c351ec90b1a5e7d9742c0398d"}, ["trace", "vmTrace", "stateDiff"]]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "trace_call", "params": [{"data":"0xcfae3217","from":"0x0000d84de507f3fada7df80908082d3239466db55a71","to":"0x0000cbe828fdc46e3b1c351ec90b1a5e7d9742c0398d"}, ["trace"], "latest"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_trace_replay() {
+        let s = r#"{"method": "trace_replayBlockTransactions", "params": ["0x1", ["trace", "stateDiff"]]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "trace_replayTransaction", "params": ["0x4a3b0fce2cb9707b0baa68640cf2fe858c8bb4121b2a8cb904ff369d38a560ff", ["vmTrace"]]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_xcb_simulate() {
+        let s = r#"{"method": "xcb_simulateV1", "params": [{"blockStateCalls": [{"blockOverrides": {"number": "0x10", "time": "0x64", "energyLimit": "0x1c9c380", "coinbase": "0x0000cbe828fdc46e3b1c351ec90b1a5e7d9742c0398d"}, "stateOverrides": {"0x0000d84de507f3fada7df80908082d3239466db55a71": {"balance": "0x3635c9adc5dea00000"}}, "calls": [{"from":"0x0000d84de507f3fada7df80908082d3239466db55a71","to":"0x0000cbe828fdc46e3b1c351ec90b1a5e7d9742c0398d","data":"0xcfae3217"}]}, {"calls": []}]}, "latest"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "xcb_simulateV1", "params": [{"blockStateCalls": [{}]}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_serde_xcb_storage() {
         let s = r#"{"method": "xcb_getStorageAt", "params": ["0x0000295a70b2de5e3953354a6a8344e616ed314d7251", "0x0", "latest"]}"#;
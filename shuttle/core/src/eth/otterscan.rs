@@ -0,0 +1,179 @@
+//! Types for the Otterscan `ots_*` endpoints
+use corebc_core::types::{
+    Address, Block, Bytes, Transaction, TransactionReceipt, TxHash, H256, U256, U64,
+};
+
+/// The version of the Otterscan API that is returned by `ots_getApiLevel`
+pub const OTS_API_LEVEL: u64 = 8;
+
+/// A block of `ots_getBlockDetails` and `ots_getBlockTransactions`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct OtsBlock<TX> {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub block: Block<TX>,
+    /// The number of transactions of the block, the block itself may only contain a page of them
+    pub transaction_count: usize,
+}
+
+/// The result of `ots_getBlockDetails`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct OtsBlockDetails {
+    /// The block without its transactions and logs bloom
+    pub block: OtsBlock<TxHash>,
+    pub issuance: OtsIssuance,
+    /// The sum of the fees paid by all transactions of the block
+    pub total_fees: U256,
+}
+
+/// The rewards of a block
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct OtsIssuance {
+    pub block_reward: U256,
+    pub uncle_reward: U256,
+    pub issuance: U256,
+}
+
+/// The result of `ots_getBlockTransactions`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct OtsBlockTransactions {
+    /// The block with a page of its transactions, their input is truncated to the selector
+    pub fullblock: OtsBlock<Transaction>,
+    /// The receipts of the transactions of the page, without logs
+    pub receipts: Vec<TransactionReceipt>,
+}
+
+/// A receipt of `ots_searchTransactionsBefore` and `ots_searchTransactionsAfter`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct OtsTransactionReceipt {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub receipt: TransactionReceipt,
+    /// The timestamp of the block of the transaction
+    pub timestamp: U64,
+}
+
+/// The result of `ots_searchTransactionsBefore` and `ots_searchTransactionsAfter`
+///
+/// The transactions are ordered from the most recent to the oldest.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct OtsSearchTransactions {
+    pub txs: Vec<Transaction>,
+    pub receipts: Vec<OtsTransactionReceipt>,
+    /// Whether the page contains the most recent transactions of the address
+    pub first_page: bool,
+    /// Whether the page contains the oldest transactions of the address
+    pub last_page: bool,
+}
+
+/// The result of `ots_getContractCreator`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OtsContractCreator {
+    /// The hash of the transaction that created the contract
+    pub hash: H256,
+    /// The address that executed the `CREATE` or `CREATE2`
+    pub creator: Address,
+}
+
+/// An operation of `ots_getInternalOperations`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OtsInternalOperation {
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub kind: OtsInternalOperationKind,
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+}
+
+/// The kind of an internal operation, serialized as its number
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum OtsInternalOperationKind {
+    Transfer = 0,
+    SelfDestruct = 1,
+    Create = 2,
+    Create2 = 3,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for OtsInternalOperationKind {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for OtsInternalOperationKind {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match <u8 as serde::Deserialize>::deserialize(deserializer)? {
+            0 => Ok(Self::Transfer),
+            1 => Ok(Self::SelfDestruct),
+            2 => Ok(Self::Create),
+            3 => Ok(Self::Create2),
+            kind => Err(serde::de::Error::custom(format!("unknown internal operation {kind}"))),
+        }
+    }
+}
+
+/// A call of `ots_traceTransaction`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OtsTrace {
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub kind: OtsTraceKind,
+    /// The depth of the call, `0` is the transaction itself
+    pub depth: usize,
+    pub from: Address,
+    pub to: Address,
+    /// The transferred value, `None` for calls that can't transfer value
+    pub value: Option<U256>,
+    pub input: Bytes,
+    pub output: Bytes,
+}
+
+/// The kind of call of an [OtsTrace]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "UPPERCASE"))]
+pub enum OtsTraceKind {
+    Call,
+    StaticCall,
+    DelegateCall,
+    CallCode,
+    Create,
+    Create2,
+}
+
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_ots_types() {
+        let op = OtsInternalOperation {
+            kind: OtsInternalOperationKind::Create2,
+            from: Address::zero(),
+            to: Address::zero(),
+            value: 1u64.into(),
+        };
+        let value = serde_json::to_value(&op).unwrap();
+        assert_eq!(value["type"], 3);
+        assert_eq!(serde_json::from_value::<OtsInternalOperation>(value).unwrap(), op);
+
+        let kind = serde_json::to_value(OtsTraceKind::DelegateCall).unwrap();
+        assert_eq!(kind, "DELEGATECALL");
+    }
+}
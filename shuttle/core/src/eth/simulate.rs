@@ -0,0 +1,69 @@
+//! Types for the `xcb_simulateV1` endpoint
+use crate::eth::{state::StateOverride, transaction::EthTransactionRequest};
+use corebc_core::types::{Address, Bytes, Log, U256, U64};
+
+/// The payload of `xcb_simulateV1`
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SimulatePayload {
+    /// The blocks to simulate, in order
+    pub block_state_calls: Vec<BlockStateCall>,
+}
+
+/// A simulated block with its overrides and calls
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
+pub struct BlockStateCall {
+    /// Overrides of the block's environment
+    pub block_overrides: Option<BlockOverrides>,
+    /// State overrides that are applied before the block's calls
+    pub state_overrides: Option<StateOverride>,
+    /// The calls to execute in the block
+    pub calls: Vec<EthTransactionRequest>,
+}
+
+/// Overrides of the environment of a simulated block
+///
+/// Every field defaults to the value of the previous block, the number and the timestamp are
+/// increased by one.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct BlockOverrides {
+    pub number: Option<U256>,
+    pub time: Option<U64>,
+    pub energy_limit: Option<U256>,
+    pub coinbase: Option<Address>,
+}
+
+/// The result of a simulated block
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SimulatedBlock {
+    pub number: U256,
+    pub timestamp: U64,
+    pub energy_limit: U256,
+    /// The total energy used by all calls of the block
+    pub energy_used: U256,
+    pub coinbase: Address,
+    pub calls: Vec<SimulatedCall>,
+}
+
+/// The result of a simulated call
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SimulatedCall {
+    /// `1` if the call succeeded, `0` if it reverted or halted
+    pub status: U64,
+    /// The output of the call, or the revert data if it reverted
+    pub return_data: Bytes,
+    pub energy_used: U256,
+    pub logs: Vec<Log>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub error: Option<String>,
+}
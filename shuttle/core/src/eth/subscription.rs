@@ -3,7 +3,7 @@
 use crate::eth::block::Header;
 use corebc_core::{
     rand::{distributions::Alphanumeric, thread_rng, Rng},
-    types::{Filter, Log, TxHash},
+    types::{Address, Filter, Log, Transaction, TxHash, H256, U256, U64},
     utils::hex,
 };
 use std::fmt;
@@ -19,6 +19,10 @@ pub enum SubscriptionResult {
     Log(Box<Log>),
     /// Transaction hash
     TransactionHash(TxHash),
+    /// Full transaction
+    Transaction(Box<Transaction>),
+    /// Value transfer
+    Transfer(Box<ValueTransfer>),
     /// SyncStatus
     Sync(SyncStatus),
 }
@@ -31,11 +35,30 @@ pub struct SyncStatus {
     pub syncing: bool,
 }
 
+/// A value transfer of a mined transaction, either of the transaction itself or of one of its
+/// internal calls
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ValueTransfer {
+    pub transaction_hash: TxHash,
+    pub block_hash: H256,
+    pub block_number: U64,
+    pub from: Address,
+    /// The recipient of the value, or the created contract
+    pub to: Address,
+    pub value: U256,
+    /// The depth of the call, `0` is the transaction itself
+    pub depth: usize,
+}
+
 /// Params for a subscription request
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
 pub struct SubscriptionParams {
     /// holds the filter params field if present in the request
     pub filter: Option<Filter>,
+    /// whether `newPendingTransactions` returns full transactions instead of their hashes
+    pub full_transactions: bool,
 }
 
 #[cfg(feature = "serde")]
@@ -51,9 +74,13 @@ impl<'a> serde::Deserialize<'a> for SubscriptionParams {
             return Ok(SubscriptionParams::default())
         }
 
+        if let Some(full_transactions) = val.as_bool() {
+            return Ok(SubscriptionParams { filter: None, full_transactions })
+        }
+
         let filter: Filter = serde_json::from_value(val)
             .map_err(|e| D::Error::custom(format!("Invalid Subscription parameters: {e}")))?;
-        Ok(SubscriptionParams { filter: Some(filter) })
+        Ok(SubscriptionParams { filter: Some(filter), full_transactions: false })
     }
 }
 
@@ -71,6 +98,8 @@ pub enum SubscriptionKind {
     NewPendingTransactions,
     /// syncing subscription
     Syncing,
+    /// subscribe to the value transfers of mined transactions, including internal calls
+    ShuttleTransfers,
 }
 
 /// Unique subscription id
@@ -624,6 +624,16 @@ impl PendingTransaction {
         Self { hash, transaction, sender }
     }
 
+    /// Creates a pending transaction of an already mined transaction, with its recorded sender
+    /// and hash
+    pub fn from_mined(
+        transaction: MaybeImpersonatedTransaction,
+        hash: TxHash,
+        sender: Address,
+    ) -> Self {
+        Self { hash, transaction, sender }
+    }
+
     pub fn nonce(&self) -> &U256 {
         self.transaction.nonce()
     }
@@ -676,6 +686,7 @@ impl PendingTransaction {
 
 /// Represents all relevant information of an executed transaction
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TransactionInfo {
     pub transaction_hash: H256,
     pub transaction_index: u32,
@@ -701,26 +712,7 @@ impl TransactionInfo {
     ///
     /// if the `idx` does not belong to a node
     pub fn trace_address(&self, idx: usize) -> Vec<usize> {
-        if idx == 0 {
-            // root call has empty traceAddress
-            return vec![];
-        }
-        let mut graph = vec![];
-        let mut node = &self.traces.arena[idx];
-        while let Some(parent) = node.parent {
-            // the index of the child call in the arena
-            let child_idx = node.idx;
-            node = &self.traces.arena[parent];
-            // find the index of the child call in the parent node
-            let call_idx = node
-                .children
-                .iter()
-                .position(|child| *child == child_idx)
-                .expect("child exists in parent");
-            graph.push(call_idx);
-        }
-        graph.reverse();
-        graph
+        self.traces.trace_address(idx)
     }
 }
 
@@ -1,5 +1,7 @@
-use corebc_core::types::{H256, U256, U64};
+use crate::eth::transaction::EthTransactionRequest;
+use corebc_core::types::{Address, BlockNumber, Bytes, GoCoreTrace, H256, U256, U64};
 use revm::primitives::SpecId;
+use std::collections::BTreeMap;
 
 #[cfg(feature = "serde")]
 use serde::{de::Error, Deserializer, Serializer};
@@ -82,6 +84,65 @@ impl Default for EvmMineOptions {
     }
 }
 
+/// The options of `shuttle_reorg`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ReorgOptions {
+    /// The number of blocks that are replaced
+    pub depth: u64,
+    /// The transactions of the new blocks together with the index of the block they're included
+    /// in, `0` is the first new block
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tx_block_pairs: Vec<(TransactionData, u64)>,
+}
+
+/// A transaction of `shuttle_reorg`, either a request that's signed by the node or a signed raw
+/// transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum TransactionData {
+    Json(EthTransactionRequest),
+    Raw(Bytes),
+}
+
+/// The options of `shuttle_dumpState`
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct DumpStateOptions {
+    /// Whether to include the blocks, transactions and receipts of the chain
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub include_history: bool,
+    /// Only dump these accounts, all accounts of the state if not set
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub addresses: Option<Vec<Address>>,
+}
+
+/// The mining mode of `shuttle_setMiningMode`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "mode", rename_all = "camelCase"))]
+pub enum MiningModeOptions {
+    /// Only mine on demand
+    None,
+    /// Mine a new block for every ready transaction
+    Auto,
+    /// Mine a new block every `block_time` seconds
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    Interval { block_time: u64 },
+    /// Mine a new block once the ready transactions use the `fill` fraction of the block energy
+    /// limit
+    EnergyFill { fill: f64 },
+    /// Mine a new block for every `transactions` ready transactions
+    TransactionCount { transactions: usize },
+    /// Mine a new block every `block_time` seconds, or earlier once the ready transactions use
+    /// the `fill` fraction of the block energy limit
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    IntervalOrEnergyFill { block_time: u64, fill: f64 },
+}
+
 /// Represents the result of `eth_getWork`
 /// This may or may not include the block number
 #[derive(Debug, PartialEq, Eq, Default)]
@@ -168,6 +229,79 @@ impl<'a> serde::Deserialize<'a> for Index {
     }
 }
 
+/// The filter of parity's `trace_filter`
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct TraceFilter {
+    /// The first block to include, defaults to the genesis block
+    pub from_block: Option<BlockNumber>,
+    /// The last block to include, defaults to the latest block
+    pub to_block: Option<BlockNumber>,
+    /// Only include traces of calls from these addresses
+    pub from_address: Option<Vec<Address>>,
+    /// Only include traces of calls to these addresses
+    pub to_address: Option<Vec<Address>>,
+    /// The number of matching traces to skip
+    pub after: Option<usize>,
+    /// The maximum number of traces to return
+    pub count: Option<usize>,
+}
+
+/// The trace of a single transaction as returned by `debug_traceBlockByNumber` and
+/// `debug_traceBlockByHash`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct TraceResult {
+    pub tx_hash: H256,
+    pub result: GoCoreTrace,
+}
+
+/// The result of `debug_storageRangeAt`
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct StorageRange {
+    /// The storage slots, keyed by the hash of the slot
+    pub storage: BTreeMap<H256, StorageEntry>,
+    /// The hashed key of the first slot that was not included, if any
+    pub next_key: Option<H256>,
+}
+
+/// A storage slot of a [StorageRange]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StorageEntry {
+    /// The slot, the preimage of its hashed key
+    pub key: Option<H256>,
+    pub value: H256,
+}
+
+/// The transactions of the pool, grouped by sender and nonce, as returned by `txpool_content` and
+/// `txpool_inspect`
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TxpoolContent<T> {
+    /// Transactions that are ready to be included in the next block
+    pub pending: BTreeMap<Address, BTreeMap<String, T>>,
+    /// Transactions that wait for a transaction with a lower nonce
+    pub queued: BTreeMap<Address, BTreeMap<String, T>>,
+    /// Transactions that were recently evicted because the pool was full
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "BTreeMap::is_empty"))]
+    pub evicted: BTreeMap<Address, BTreeMap<String, T>>,
+}
+
+impl<T> Default for TxpoolContent<T> {
+    fn default() -> Self {
+        Self {
+            pending: Default::default(),
+            queued: Default::default(),
+            evicted: Default::default(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
@@ -176,11 +310,22 @@ pub struct NodeInfo {
     pub current_block_timestamp: u64,
     pub current_block_hash: H256,
     pub hard_fork: SpecId,
+    pub fork_id: NodeForkId,
     pub transaction_order: String,
     pub environment: NodeEnvironment,
     pub fork_config: NodeForkConfig,
 }
 
+/// The [EIP-2124](https://eips.ethereum.org/EIPS/eip-2124) fork id of the node's head
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeForkId {
+    /// The crc32 checksum of the genesis hash and all passed activations
+    pub hash: Bytes,
+    /// The next activation, or `0` if there is none
+    pub next: u64,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
@@ -1,6 +1,6 @@
-use crate::HeaderValue;
+use crate::{jwt::JwtSecret, HeaderValue};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::str::FromStr;
+use std::{num::NonZeroU32, str::FromStr};
 
 /// Additional server options.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +24,62 @@ pub struct ServerConfig {
         clap(long, help = "Disable CORS", conflicts_with = "allow-origin")
     )]
     pub no_cors: bool,
+    /// The secret to authenticate HS256 signed JWTs, required for every http and websocket request
+    /// if set
+    ///
+    /// IPC requests aren't authenticated with a JWT, the unix socket is only accessible by its
+    /// owner instead, see `IpcEndpoint::with_access`
+    #[cfg_attr(
+        feature = "clap",
+        clap(
+            long,
+            help = "Require a JWT signed with the hex encoded 32 byte HS256 secret in the file for \
+                    every http and websocket request. IPC requests carry no JWT, so the IPC \
+                    socket is only accessible by the user running the node instead, and IPC \
+                    can't be used together with a JWT secret on Windows",
+            value_name = "PATH",
+            value_parser = JwtSecret::parse
+        )
+    )]
+    #[serde(skip)]
+    pub jwt_secret: Option<JwtSecret>,
+    /// The namespaces and methods that can be called, all if empty
+    #[cfg_attr(
+        feature = "clap",
+        clap(
+            long,
+            help = "Only allow these namespaces, e.g. `eth,net,web3`, or methods, e.g. \
+                    `shuttle_mine`",
+            value_name = "NAMESPACE|METHOD",
+            value_delimiter = ','
+        )
+    )]
+    #[serde(default)]
+    pub rpc_allow: Vec<String>,
+    /// The namespaces and methods that can't be called
+    #[cfg_attr(
+        feature = "clap",
+        clap(
+            long,
+            help = "Deny these namespaces, e.g. `shuttle,evm`, or methods, e.g. \
+                    `shuttle_setBalance`",
+            value_name = "NAMESPACE|METHOD",
+            value_delimiter = ','
+        )
+    )]
+    #[serde(default)]
+    pub rpc_deny: Vec<String>,
+    /// The maximum number of calls per second
+    #[cfg_attr(
+        feature = "clap",
+        clap(
+            long,
+            help = "Limit the number of calls per second, across all connections",
+            value_name = "CALLS"
+        )
+    )]
+    #[serde(default)]
+    pub rpc_rate_limit: Option<NonZeroU32>,
 }
 
 // === impl ServerConfig ===
@@ -40,11 +96,42 @@ impl ServerConfig {
         self.no_cors = cors;
         self
     }
+
+    /// Sets the secret that authenticates requests
+    pub fn with_jwt_secret(mut self, jwt_secret: Option<JwtSecret>) -> Self {
+        self.jwt_secret = jwt_secret;
+        self
+    }
+
+    /// Sets the namespaces and methods that can be called
+    pub fn with_rpc_allow(mut self, allow: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.rpc_allow = allow.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the namespaces and methods that can't be called
+    pub fn with_rpc_deny(mut self, deny: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.rpc_deny = deny.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the maximum number of calls per second
+    pub fn with_rpc_rate_limit(mut self, calls_per_second: Option<NonZeroU32>) -> Self {
+        self.rpc_rate_limit = calls_per_second;
+        self
+    }
 }
 
 impl Default for ServerConfig {
     fn default() -> Self {
-        Self { allow_origin: "*".parse::<HeaderValue>().unwrap().into(), no_cors: false }
+        Self {
+            allow_origin: "*".parse::<HeaderValue>().unwrap().into(),
+            no_cors: false,
+            jwt_secret: None,
+            rpc_allow: vec![],
+            rpc_deny: vec![],
+            rpc_rate_limit: None,
+        }
     }
 }
 
@@ -1,23 +1,36 @@
-use crate::RpcHandler;
+use crate::{
+    jwt::{JwtError, JwtSecret},
+    RpcHandler, ServerConfig,
+};
 use axum::{
     extract::{rejection::JsonRejection, State},
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    response::IntoResponse,
     Json,
 };
 use futures::{future, FutureExt};
+use parking_lot::Mutex;
 use shuttle_rpc::{
-    error::RpcError,
+    error::{ErrorCode, RpcError},
     request::{Request, RpcCall},
     response::{Response, RpcResponse},
 };
+use std::{borrow::Cow, sync::Arc, time::Instant};
 
 /// Handles incoming JSON-RPC Request.
 // NOTE: `handler` must come first because the `request` extractor consumes the request body.
 pub async fn handle<Http: RpcHandler, Ws>(
-    State((handler, _)): State<(Http, Ws)>,
+    State((handler, _, access)): State<(Http, Ws, AccessControl)>,
+    headers: HeaderMap,
     request: Result<Json<Request>, JsonRejection>,
-) -> Json<Response> {
+) -> axum::response::Response {
+    if let Err(err) = access.authorize(&headers) {
+        warn!(target: "rpc", %err, "unauthorized request");
+        return (StatusCode::UNAUTHORIZED, err.to_string()).into_response()
+    }
+
     Json(match request {
-        Ok(Json(req)) => handle_request(req, handler)
+        Ok(Json(req)) => handle_request(req, handler, &access)
             .await
             .unwrap_or_else(|| Response::error(RpcError::invalid_request())),
         Err(err) => {
@@ -25,6 +38,7 @@ pub async fn handle<Http: RpcHandler, Ws>(
             Response::error(RpcError::invalid_request())
         }
     })
+    .into_response()
 }
 
 /// Handle the JSON-RPC [Request]
@@ -34,6 +48,7 @@ pub async fn handle<Http: RpcHandler, Ws>(
 pub async fn handle_request<Handler: RpcHandler>(
     req: Request,
     handler: Handler,
+    access: &AccessControl,
 ) -> Option<Response> {
     /// processes batch calls
     fn responses_as_batch(outs: Vec<Option<RpcResponse>>) -> Option<Response> {
@@ -42,20 +57,30 @@ pub async fn handle_request<Handler: RpcHandler>(
     }
 
     match req {
-        Request::Single(call) => handle_call(call, handler).await.map(Response::Single),
+        Request::Single(call) => handle_call(call, handler, access).await.map(Response::Single),
         Request::Batch(calls) => {
-            future::join_all(calls.into_iter().map(move |call| handle_call(call, handler.clone())))
-                .map(responses_as_batch)
-                .await
+            future::join_all(
+                calls.into_iter().map(move |call| handle_call(call, handler.clone(), access)),
+            )
+            .map(responses_as_batch)
+            .await
         }
     }
 }
 
 /// handle a single RPC method call
-async fn handle_call<Handler: RpcHandler>(call: RpcCall, handler: Handler) -> Option<RpcResponse> {
+async fn handle_call<Handler: RpcHandler>(
+    call: RpcCall,
+    handler: Handler,
+    access: &AccessControl,
+) -> Option<RpcResponse> {
     match call {
         RpcCall::MethodCall(call) => {
             trace!(target: "rpc", id = ?call.id , method = ?call.method,  "handling call");
+            if let Err(err) = access.check_call(&call.method) {
+                warn!(target: "rpc", method = ?call.method, %err, "rejected call");
+                return Some(RpcResponse::new(call.id, err))
+            }
             Some(handler.on_call(call).await)
         }
         RpcCall::Notification(notification) => {
@@ -68,3 +93,123 @@ async fn handle_call<Handler: RpcHandler>(call: RpcCall, handler: Handler) -> Op
         }
     }
 }
+
+/// Enforces the authentication, the allowed methods and the rate limit of the [ServerConfig]
+#[derive(Debug, Clone, Default)]
+pub struct AccessControl {
+    jwt_secret: Option<JwtSecret>,
+    allow: Vec<String>,
+    deny: Vec<String>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+// === impl AccessControl ===
+
+impl AccessControl {
+    /// Creates the access control of the server's config
+    pub fn new(config: &ServerConfig) -> Self {
+        Self {
+            jwt_secret: config.jwt_secret.clone(),
+            allow: config.rpc_allow.iter().map(|entry| canonical_method(entry)).collect(),
+            deny: config.rpc_deny.iter().map(|entry| canonical_method(entry)).collect(),
+            rate_limiter: config
+                .rpc_rate_limit
+                .map(|calls_per_second| Arc::new(RateLimiter::new(calls_per_second.get()))),
+        }
+    }
+
+    /// Returns true if requests must be authenticated with a JWT
+    pub fn requires_auth(&self) -> bool {
+        self.jwt_secret.is_some()
+    }
+
+    /// Validates the `Authorization: Bearer <token>` header of an http or websocket request, if
+    /// a JWT secret is configured
+    pub fn authorize(&self, headers: &HeaderMap) -> Result<(), JwtError> {
+        let Some(secret) = self.jwt_secret.as_ref() else { return Ok(()) };
+        let token = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(JwtError::MissingToken)?;
+        secret.validate(token.trim())
+    }
+
+    /// Checks whether the method can be called and the rate limit is not exceeded
+    pub fn check_call(&self, method: &str) -> Result<(), RpcError> {
+        if !self.is_allowed(method) {
+            return Err(RpcError {
+                code: ErrorCode::MethodNotFound,
+                message: format!("the method {method} is not available").into(),
+                data: None,
+            })
+        }
+        if let Some(limiter) = self.rate_limiter.as_ref() {
+            if !limiter.try_acquire() {
+                return Err(RpcError {
+                    code: ErrorCode::ServerError(-32005),
+                    message: Cow::Borrowed("rate limit exceeded"),
+                    data: None,
+                })
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true if the method is allowed and not denied, either by its name or namespace
+    fn is_allowed(&self, method: &str) -> bool {
+        let method = canonical_method(method);
+        let namespace = method.split_once('_').map(|(namespace, _)| namespace).unwrap_or(&method);
+        let matches = |entry: &String| *entry == method || entry == namespace;
+        (self.allow.is_empty() || self.allow.iter().any(matches)) && !self.deny.iter().any(matches)
+    }
+}
+
+/// Maps the aliased namespaces to their canonical namespace, `xcb` to `eth` and `hardhat` to
+/// `shuttle`
+fn canonical_method(method: &str) -> String {
+    let (namespace, name) = method.split_once('_').unwrap_or((method, ""));
+    let namespace = match namespace {
+        "xcb" => "eth",
+        "hardhat" => "shuttle",
+        namespace => namespace,
+    };
+    if name.is_empty() {
+        namespace.to_string()
+    } else {
+        format!("{namespace}_{name}")
+    }
+}
+
+/// A token bucket that allows a number of calls per second, across all connections
+#[derive(Debug)]
+pub struct RateLimiter {
+    calls_per_second: f64,
+    /// the available calls and when they were last refilled
+    bucket: Mutex<(f64, Instant)>,
+}
+
+// === impl RateLimiter ===
+
+impl RateLimiter {
+    /// Creates a new limiter with a full bucket
+    pub fn new(calls_per_second: u32) -> Self {
+        let calls_per_second = calls_per_second as f64;
+        Self { calls_per_second, bucket: Mutex::new((calls_per_second, Instant::now())) }
+    }
+
+    /// Takes one call from the bucket, returns false if it's empty
+    pub fn try_acquire(&self) -> bool {
+        let mut bucket = self.bucket.lock();
+        let (available, refilled_at) = &mut *bucket;
+        let now = Instant::now();
+        let refill = now.duration_since(*refilled_at).as_secs_f64() * self.calls_per_second;
+        *available = (*available + refill).min(self.calls_per_second);
+        *refilled_at = now;
+        if *available < 1.0 {
+            return false
+        }
+        *available -= 1.0;
+        true
+    }
+}
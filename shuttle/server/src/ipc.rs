@@ -1,6 +1,6 @@
 //! IPC handling
 
-use crate::{error::RequestError, pubsub::PubSubConnection, PubSubRpcHandler};
+use crate::{error::RequestError, pubsub::PubSubConnection, AccessControl, PubSubRpcHandler};
 use bytes::BytesMut;
 use futures::{ready, Sink, Stream, StreamExt};
 use parity_tokio_ipc::Endpoint;
@@ -21,12 +21,26 @@ pub struct IpcEndpoint<Handler> {
     handler: Handler,
     /// The endpoint we listen for incoming transactions
     endpoint: Endpoint,
+    /// the method filter and rate limit of the server
+    access: AccessControl,
 }
 
 impl<Handler: PubSubRpcHandler> IpcEndpoint<Handler> {
     /// Creates a new endpoint with the given handler
     pub fn new(handler: Handler, endpoint: impl Into<String>) -> Self {
-        Self { handler, endpoint: Endpoint::new(endpoint.into()) }
+        Self { handler, endpoint: Endpoint::new(endpoint.into()), access: Default::default() }
+    }
+
+    /// Sets the access control of the endpoint
+    ///
+    /// The method filter and the rate limit apply to IPC requests as well. IPC requests can't carry
+    /// a JWT, so no JWT is checked: if the access control requires authentication, the unix socket
+    /// is made accessible only by the user that runs the node instead, which protects every
+    /// request from other users of the machine. Named pipes can't be restricted like this, so on
+    /// Windows the endpoint fails to start if authentication is required.
+    pub fn with_access(mut self, access: AccessControl) -> Self {
+        self.access = access;
+        self
     }
 
     /// Returns a stream of incoming connection handlers
@@ -35,9 +49,17 @@ impl<Handler: PubSubRpcHandler> IpcEndpoint<Handler> {
     /// connections, See [`PubSubConnection`] that should be spawned
     #[tracing::instrument(target = "ipc", skip_all)]
     pub fn incoming(self) -> io::Result<impl Stream<Item = impl Future<Output = ()>>> {
-        let IpcEndpoint { handler, endpoint } = self;
+        let IpcEndpoint { handler, endpoint, access } = self;
         trace!( endpoint=?endpoint.path(), "starting ipc server" );
 
+        #[cfg(not(unix))]
+        if access.requires_auth() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "IPC requests can't be authenticated with a JWT on this platform",
+            ))
+        }
+
         if cfg!(unix) {
             // ensure the file does not exist
             if std::fs::remove_file(endpoint.path()).is_ok() {
@@ -53,15 +75,22 @@ impl<Handler: PubSubRpcHandler> IpcEndpoint<Handler> {
             }
         };
 
+        #[cfg(unix)]
+        if access.requires_auth() {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(endpoint.path(), std::fs::Permissions::from_mode(0o600))?;
+        }
+
         trace!("established connection listener");
 
         let connections = connections.filter_map(move |stream| {
             let handler = handler.clone();
+            let access = access.clone();
             Box::pin(async move {
                 if let Ok(stream) = stream {
                     trace!("successful incoming IPC connection");
                     let framed = tokio_util::codec::Decoder::framed(JsonRpcCodec, stream);
-                    Some(PubSubConnection::new(IpcConn(framed), handler))
+                    Some(PubSubConnection::new(IpcConn(framed), handler, access))
                 } else {
                     None
                 }
@@ -0,0 +1,137 @@
+//! HS256 JWT authentication, as used by the engine API
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::{
+    fmt,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The maximum difference in seconds between the `iat` claim of a token and the current time
+const IAT_LEEWAY: u64 = 60;
+
+/// An error that can occur when loading a [JwtSecret] or validating a token
+#[derive(Debug, thiserror::Error)]
+pub enum JwtError {
+    /// The secret file couldn't be read
+    #[error("failed to read jwt secret: {0}")]
+    Io(#[from] std::io::Error),
+    /// The secret is not 32 hex encoded bytes
+    #[error("jwt secret must be 32 hex encoded bytes")]
+    InvalidSecret,
+    /// The request has no `Authorization: Bearer <token>` header
+    #[error("missing bearer token")]
+    MissingToken,
+    /// The token is not a valid JWT
+    #[error("malformed token")]
+    Malformed,
+    /// The token is not signed with HS256
+    #[error("unsupported signing algorithm {0}, expected HS256")]
+    UnsupportedAlgorithm(String),
+    /// The token is not signed with the secret
+    #[error("invalid token signature")]
+    InvalidSignature,
+    /// The token was issued too far from the current time
+    #[error("token issued at {0} is not within {IAT_LEEWAY}s of the current time")]
+    Stale(u64),
+    /// The token expired
+    #[error("token expired at {0}")]
+    Expired(u64),
+}
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    iat: u64,
+    #[serde(default)]
+    exp: Option<u64>,
+}
+
+/// The 32 byte secret that signs and validates HS256 JWTs
+#[derive(Clone, PartialEq, Eq)]
+pub struct JwtSecret([u8; 32]);
+
+// === impl JwtSecret ===
+
+impl JwtSecret {
+    /// Creates the secret from 32 hex encoded bytes, with or without `0x` prefix
+    pub fn from_hex(hex: &str) -> Result<Self, JwtError> {
+        let hex = hex.trim();
+        let bytes = hex::decode(hex.strip_prefix("0x").unwrap_or(hex))
+            .map_err(|_| JwtError::InvalidSecret)?;
+        Ok(Self(bytes.try_into().map_err(|_| JwtError::InvalidSecret)?))
+    }
+
+    /// Reads the hex encoded secret from the file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, JwtError> {
+        Self::from_hex(&std::fs::read_to_string(path)?)
+    }
+
+    /// The clap `value_parser` function
+    pub fn parse(path: &str) -> Result<Self, String> {
+        Self::from_file(path).map_err(|err| err.to_string())
+    }
+
+    /// Returns a token that was issued now
+    pub fn encode(&self) -> String {
+        let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256","typ":"JWT"}"#);
+        let claims = URL_SAFE_NO_PAD.encode(format!(r#"{{"iat":{}}}"#, now()));
+        let message = format!("{header}.{claims}");
+        let signature = URL_SAFE_NO_PAD.encode(self.mac(&message).finalize().into_bytes());
+        format!("{message}.{signature}")
+    }
+
+    /// Validates the signature and the `iat` and `exp` claims of the token
+    pub fn validate(&self, token: &str) -> Result<(), JwtError> {
+        let (message, signature) = token.rsplit_once('.').ok_or(JwtError::Malformed)?;
+        let (header, claims) = message.split_once('.').ok_or(JwtError::Malformed)?;
+
+        let header: Header = decode_json(header)?;
+        if header.alg != "HS256" {
+            return Err(JwtError::UnsupportedAlgorithm(header.alg))
+        }
+
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| JwtError::Malformed)?;
+        self.mac(message).verify_slice(&signature).map_err(|_| JwtError::InvalidSignature)?;
+
+        let claims: Claims = decode_json(claims)?;
+        let now = now();
+        if now.abs_diff(claims.iat) > IAT_LEEWAY {
+            return Err(JwtError::Stale(claims.iat))
+        }
+        if let Some(exp) = claims.exp.filter(|exp| *exp <= now) {
+            return Err(JwtError::Expired(exp))
+        }
+        Ok(())
+    }
+
+    fn mac(&self, message: &str) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.0).expect("HMAC accepts keys of any size");
+        mac.update(message.as_bytes());
+        mac
+    }
+}
+
+impl fmt::Debug for JwtSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // never log the secret
+        f.write_str("JwtSecret(..)")
+    }
+}
+
+fn decode_json<T: serde::de::DeserializeOwned>(part: &str) -> Result<T, JwtError> {
+    let json = URL_SAFE_NO_PAD.decode(part).map_err(|_| JwtError::Malformed)?;
+    serde_json::from_slice(&json).map_err(|_| JwtError::Malformed)
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
@@ -27,11 +27,14 @@ mod error;
 mod handler;
 #[cfg(feature = "ipc")]
 pub mod ipc;
+mod jwt;
 mod pubsub;
 mod ws;
 
 pub use crate::pubsub::{PubSubContext, PubSubRpcHandler};
 pub use config::ServerConfig;
+pub use handler::AccessControl;
+pub use jwt::{JwtError, JwtSecret};
 
 /// Type alias for the configured axum server
 pub type ShuttleServer = Server<AddrIncoming, IntoMakeService<Router>>;
@@ -48,11 +51,12 @@ where
     Http: RpcHandler,
     Ws: PubSubRpcHandler,
 {
-    let ServerConfig { allow_origin, no_cors } = config;
+    let access = AccessControl::new(&config);
+    let ServerConfig { allow_origin, no_cors, .. } = config;
 
     let svc = Router::new()
         .route("/", post(handler::handle).get(ws::handle_ws))
-        .with_state((http, ws))
+        .with_state((http, ws, access))
         .layer(TraceLayer::new_for_http());
 
     let svc = if no_cors {
@@ -63,7 +67,7 @@ where
             // for more details
             CorsLayer::new()
                 .allow_origin(allow_origin.0)
-                .allow_headers(vec![header::CONTENT_TYPE])
+                .allow_headers(vec![header::CONTENT_TYPE, header::AUTHORIZATION])
                 .allow_methods(vec![Method::GET, Method::POST]),
         )
     }
@@ -76,11 +80,12 @@ pub fn serve_http<Http>(addr: SocketAddr, config: ServerConfig, http: Http) -> S
 where
     Http: RpcHandler,
 {
-    let ServerConfig { allow_origin, no_cors } = config;
+    let access = AccessControl::new(&config);
+    let ServerConfig { allow_origin, no_cors, .. } = config;
 
     let svc = Router::new()
         .route("/", post(handler::handle))
-        .with_state((http, ()))
+        .with_state((http, (), access))
         .layer(TraceLayer::new_for_http());
     let svc = if no_cors {
         svc
@@ -90,7 +95,7 @@ where
             // for more details
             CorsLayer::new()
                 .allow_origin(allow_origin.0)
-                .allow_headers(vec![header::CONTENT_TYPE])
+                .allow_headers(vec![header::CONTENT_TYPE, header::AUTHORIZATION])
                 .allow_methods(vec![Method::GET, Method::POST]),
         )
     }
@@ -1,4 +1,4 @@
-use crate::{error::RequestError, handler::handle_request, RpcHandler};
+use crate::{error::RequestError, handler::handle_request, AccessControl, RpcHandler};
 use shuttle_rpc::{
     error::RpcError,
     request::Request,
@@ -118,6 +118,8 @@ pub struct PubSubConnection<Handler: PubSubRpcHandler, Connection> {
     handler: Handler,
     /// contains all the subscription related context
     context: PubSubContext<Handler>,
+    /// the method filter and rate limit of the server
+    access: AccessControl,
     /// The established connection
     connection: Connection,
     /// currently in progress requests
@@ -129,11 +131,12 @@ pub struct PubSubConnection<Handler: PubSubRpcHandler, Connection> {
 // === impl PubSubConnection ===
 
 impl<Handler: PubSubRpcHandler, Connection> PubSubConnection<Handler, Connection> {
-    pub fn new(connection: Connection, handler: Handler) -> Self {
+    pub fn new(connection: Connection, handler: Handler, access: AccessControl) -> Self {
         Self {
             connection,
             handler,
             context: Default::default(),
+            access,
             pending: Default::default(),
             processing: Default::default(),
         }
@@ -146,9 +149,10 @@ impl<Handler: PubSubRpcHandler, Connection> PubSubConnection<Handler, Connection
 
     fn process_request(&mut self, req: serde_json::Result<Request>) {
         let handler = self.compat_helper();
+        let access = self.access.clone();
         self.processing.push(Box::pin(async move {
             match req {
-                Ok(req) => handle_request(req, handler)
+                Ok(req) => handle_request(req, handler, &access)
                     .await
                     .unwrap_or_else(|| Response::error(RpcError::invalid_request())),
                 Err(err) => {
@@ -1,10 +1,11 @@
-use crate::{error::RequestError, pubsub::PubSubConnection, PubSubRpcHandler};
+use crate::{error::RequestError, pubsub::PubSubConnection, AccessControl, PubSubRpcHandler};
 use axum::{
     extract::{
         ws::{Message, WebSocket},
         State, WebSocketUpgrade,
     },
-    response::Response,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
 };
 use futures::{ready, Sink, Stream};
 use shuttle_rpc::request::Request;
@@ -18,9 +19,15 @@ use std::{
 /// This is the entrypoint invoked by the axum server for a websocket request
 pub async fn handle_ws<Http, Ws: PubSubRpcHandler>(
     ws: WebSocketUpgrade,
-    State((_, handler)): State<(Http, Ws)>,
+    State((_, handler, access)): State<(Http, Ws, AccessControl)>,
+    headers: HeaderMap,
 ) -> Response {
-    ws.on_upgrade(|socket| PubSubConnection::new(SocketConn(socket), handler))
+    // the token is only checked once, before the connection is upgraded
+    if let Err(err) = access.authorize(&headers) {
+        warn!(target: "rpc::ws", %err, "unauthorized websocket upgrade");
+        return (StatusCode::UNAUTHORIZED, err.to_string()).into_response()
+    }
+    ws.on_upgrade(|socket| PubSubConnection::new(SocketConn(socket), handler, access))
 }
 
 #[pin_project::pin_project]
@@ -1,14 +1,22 @@
 use crate::{
     config::DEFAULT_MNEMONIC,
-    eth::{backend::db::SerializableState, pool::transactions::TransactionOrder, EthApi},
+    eth::{
+        backend::db::SerializableState,
+        pool::{transactions::TransactionOrder, PoolConfig},
+        EthApi,
+    },
     genesis::Genesis,
-    AccountGenerator, Hardfork, NodeConfig, CHAIN_ID,
+    AccountGenerator, Hardfork, HardforkSchedule, NodeConfig, CHAIN_ID,
 };
 use clap::Parser;
 use core::fmt;
-use corebc::{core::types::Network, utils::ORE_IN_CORE};
+use corebc::{
+    core::types::{Address, Network},
+    utils::ORE_IN_CORE,
+};
 use foxar_config::Config;
 use futures::FutureExt;
+use shuttle_core::types::DumpStateOptions;
 use shuttle_server::ServerConfig;
 use std::{
     future::Future,
@@ -71,6 +79,18 @@ pub struct NodeArgs {
     #[clap(long, value_parser = Hardfork::from_str)]
     pub hardfork: Option<Hardfork>,
 
+    /// The hardforks and the blocks or timestamps at which they activate.
+    ///
+    /// Either `mainnet`, `devin` or a comma separated list of `<hardfork>=<block>` and
+    /// `<hardfork>=@<timestamp>`, e.g. `byzantium=0,istanbul=100,latest=@1700000000`.
+    #[clap(
+        long,
+        value_name = "SCHEDULE",
+        value_parser = HardforkSchedule::from_str,
+        conflicts_with = "hardfork"
+    )]
+    pub hardfork_schedule: Option<HardforkSchedule>,
+
     /// Block time in seconds for interval mining.
     #[clap(short, long, visible_alias = "blockTime", name = "block-time", value_name = "SECONDS")]
     pub block_time: Option<u64>,
@@ -80,9 +100,41 @@ pub struct NodeArgs {
     pub config_out: Option<String>,
 
     /// Disable auto and interval mining, and mine on demand instead.
-    #[clap(long, visible_alias = "no-mine", conflicts_with = "block-time")]
+    #[clap(
+        long,
+        visible_alias = "no-mine",
+        conflicts_with_all = &["block-time", "mine_on_fill", "mine_every"]
+    )]
     pub no_mining: bool,
 
+    /// Mine a block once the ready transactions use the given fraction of the block energy limit,
+    /// e.g. `0.5`.
+    ///
+    /// Combined with --block-time, a block is mined at the block time or as soon as the fraction
+    /// is reached, whichever comes first.
+    #[clap(long, value_name = "FRACTION", value_parser = parse_fill)]
+    pub mine_on_fill: Option<f64>,
+
+    /// Mine a block for every given number of ready transactions.
+    #[clap(
+        long,
+        value_name = "NUM",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with_all = &["block-time", "mine_on_fill"]
+    )]
+    pub mine_every: Option<u64>,
+
+    /// Start a local network of the given number of nodes that share the genesis.
+    ///
+    /// The first node mines blocks, the other nodes follow it and listen on the consecutive ports.
+    #[clap(
+        long,
+        default_value = "1",
+        value_name = "NUM",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub nodes: u64,
+
     /// The host the server will listen on.
     #[clap(long, value_name = "IP_ADDR", env = "SHUTTLE_IP_ADDR", help_heading = "Server options")]
     pub host: Option<IpAddr>,
@@ -119,11 +171,36 @@ pub struct NodeArgs {
 
     /// Dump the state of chain on exit to the given file.
     ///
-    /// If the value is a directory, the state will be written to `<VALUE>/state.json`.
+    /// If the value is a directory, the state will be written to `<VALUE>/state.json`. The state
+    /// is always gzip compressed.
     #[clap(long, value_name = "PATH", conflicts_with = "init")]
     pub dump_state: Option<PathBuf>,
 
+    /// Include the blocks, transactions and receipts of the chain in the dumped state.
+    ///
+    /// See --state and --dump-state
+    #[clap(long)]
+    pub dump_state_history: bool,
+
+    /// Only include the given accounts in the dumped state.
+    ///
+    /// Accounts of a fork that were never accessed are fetched without their storage.
+    ///
+    /// See --state and --dump-state
+    #[clap(long, value_name = "ADDRESSES", value_delimiter = ',')]
+    pub dump_state_accounts: Option<Vec<Address>>,
+
+    /// Write the state and chain configuration of the chain on exit as a `genesis.json` to the
+    /// given file, which can be used to start a new chain with --init or a go-core node.
+    ///
+    /// If the value is a directory, the genesis will be written to `<VALUE>/genesis.json`.
+    #[clap(long, value_name = "PATH")]
+    pub dump_genesis: Option<PathBuf>,
+
     /// Initialize the chain from a previously saved state snapshot.
+    ///
+    /// The snapshot may be gzip compressed. If it includes the history of the chain, the chain
+    /// starts at its last block.
     #[clap(
         long,
         value_name = "PATH",
@@ -133,6 +210,7 @@ pub struct NodeArgs {
     pub load_state: Option<SerializableState>,
 
     #[clap(long, help = IPC_HELP, value_name = "PATH", visible_alias = "ipcpath")]
+    #[cfg_attr(windows, clap(conflicts_with = "jwt_secret"))]
     pub ipc: Option<Option<String>>,
 
     /// Don't keep full chain history.
@@ -143,6 +221,42 @@ pub struct NodeArgs {
     /// Number of blocks with transactions to keep in memory.
     #[clap(long)]
     pub transaction_block_keeper: Option<usize>,
+
+    /// Persist blocks, transactions, receipts, logs and state in the given directory.
+    ///
+    /// If the directory already contains a chain, the node resumes from it with its full history.
+    #[clap(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = &[
+            "fork_url",
+            "prune_history",
+            "state",
+            "load_state",
+            "init"
+        ]
+    )]
+    pub data_dir: Option<PathBuf>,
+
+    /// The maximum number of transactions in the pool.
+    ///
+    /// Once exceeded, the lowest priced transactions are evicted.
+    #[clap(long, value_name = "NUM", help_heading = "Transaction pool options")]
+    pub txpool_max_transactions: Option<usize>,
+
+    /// The minimum energy price increase, in percent, to replace a pending transaction with the
+    /// same nonce.
+    #[clap(
+        long,
+        value_name = "PERCENT",
+        default_value = "0",
+        help_heading = "Transaction pool options"
+    )]
+    pub txpool_price_bump: u64,
+
+    /// The maximum number of queued transactions, that wait for a lower nonce, per sender.
+    #[clap(long, value_name = "NUM", help_heading = "Transaction pool options")]
+    pub txpool_max_queued_per_sender: Option<usize>,
 }
 
 #[cfg(windows)]
@@ -171,8 +285,11 @@ impl NodeArgs {
             .disable_block_energy_limit(self.evm_opts.disable_block_energy_limit)
             .with_energy_price(self.evm_opts.energy_price)
             .with_hardfork(self.hardfork)
+            .with_hardfork_schedule(self.hardfork_schedule)
             .with_blocktime(self.block_time.map(Duration::from_secs))
             .with_no_mining(self.no_mining)
+            .with_mine_on_fill(self.mine_on_fill)
+            .with_mine_every(self.mine_every.map(|num| num as usize))
             .with_account_generator(self.account_generator())
             .with_genesis_balance(genesis_balance)
             .with_genesis_timestamp(self.timestamp)
@@ -202,6 +319,12 @@ impl NodeArgs {
             .set_pruned_history(self.prune_history)
             .with_init_state(self.load_state.or_else(|| self.state.and_then(|s| s.state)))
             .with_transaction_block_keeper(self.transaction_block_keeper)
+            .with_data_dir(self.data_dir)
+            .with_pool_config(PoolConfig {
+                max_transactions: self.txpool_max_transactions,
+                price_bump: self.txpool_price_bump,
+                max_queued_per_sender: self.txpool_max_queued_per_sender,
+            })
     }
 
     fn account_generator(&self) -> AccountGenerator {
@@ -227,10 +350,19 @@ impl NodeArgs {
     /// See also [crate::spawn()]
     pub async fn run(self) -> Result<(), Box<dyn std::error::Error>> {
         let dump_state = self.dump_state_path();
+        let dump_options = DumpStateOptions {
+            include_history: self.dump_state_history,
+            addresses: self.dump_state_accounts.clone(),
+        };
+        let dump_genesis = self.dump_genesis.clone();
         let dump_interval =
             self.state_interval.map(Duration::from_secs).unwrap_or(DEFAULT_DUMP_INTERVAL);
 
-        let (api, mut handle) = crate::spawn(self.into_node_config()).await;
+        let nodes = self.nodes as usize;
+        let mut network = crate::network::spawn_network(self.into_node_config(), nodes).await?;
+        let (api, mut handle) = network.remove(0);
+        // the following nodes shut down once their handles are dropped
+        let _followers = network;
 
         // sets the signal handler to gracefully shutdown.
         let mut fork = api.get_fork().cloned();
@@ -243,7 +375,9 @@ impl NodeArgs {
         let task_manager = handle.task_manager();
         let mut on_shutdown = task_manager.on_shutdown();
 
-        let mut state_dumper = PeriodicStateDumper::new(api, dump_state, dump_interval);
+        let chain_api = api.clone();
+        let mut state_dumper =
+            PeriodicStateDumper::new(api, dump_state, dump_options, dump_interval);
 
         task_manager.spawn(async move {
             // wait for the SIGTERM signal on unix systems
@@ -273,6 +407,12 @@ impl NodeArgs {
 
             // shutdown received
             state_dumper.dump().await;
+            if let Some(path) = dump_genesis {
+                dump_genesis_json(&chain_api, path).await;
+            }
+
+            // make sure the chain in the data dir is up to date, if configured
+            chain_api.flush_chain_db().await;
 
             // cleaning up and shutting down
             // this will make sure that the fork RPC cache is flushed if caching is configured
@@ -456,16 +596,32 @@ impl ShuttleEvmArgs {
     }
 }
 
+/// Parses the fraction of the block energy limit for --mine-on-fill, which must be in `(0, 1]`
+fn parse_fill(s: &str) -> Result<f64, String> {
+    let fill = s.parse::<f64>().map_err(|err| err.to_string())?;
+    if fill > 0.0 && fill <= 1.0 {
+        Ok(fill)
+    } else {
+        Err(format!("the fraction must be in (0, 1], got {fill}"))
+    }
+}
+
 /// Helper type to periodically dump the state of the chain to disk
 struct PeriodicStateDumper {
     in_progress_dump: Option<Pin<Box<dyn Future<Output = ()> + Send + Sync + 'static>>>,
     api: EthApi,
     dump_state: Option<PathBuf>,
+    options: DumpStateOptions,
     interval: Interval,
 }
 
 impl PeriodicStateDumper {
-    fn new(api: EthApi, dump_state: Option<PathBuf>, interval: Duration) -> Self {
+    fn new(
+        api: EthApi,
+        dump_state: Option<PathBuf>,
+        options: DumpStateOptions,
+        interval: Duration,
+    ) -> Self {
         let dump_state = dump_state.map(|mut dump_state| {
             if dump_state.is_dir() {
                 dump_state = dump_state.join("state.json");
@@ -475,21 +631,21 @@ impl PeriodicStateDumper {
 
         // periodically flush the state
         let interval = tokio::time::interval_at(Instant::now() + interval, interval);
-        Self { in_progress_dump: None, api, dump_state, interval }
+        Self { in_progress_dump: None, api, dump_state, options, interval }
     }
 
     async fn dump(&self) {
         if let Some(state) = self.dump_state.clone() {
-            Self::dump_state(self.api.clone(), state).await
+            Self::dump_state(self.api.clone(), state, self.options.clone()).await
         }
     }
 
     /// Infallible state dump
-    async fn dump_state(api: EthApi, dump_state: PathBuf) {
+    async fn dump_state(api: EthApi, dump_state: PathBuf, options: DumpStateOptions) {
         trace!(path=?dump_state, "Dumping state on shutdown");
-        match api.serialized_state().await {
+        match api.serialized_state(&options).await {
             Ok(state) => {
-                if let Err(err) = foxar_common::fs::write_json_file(&dump_state, &state) {
+                if let Err(err) = state.write(&dump_state) {
                     error!(?err, "Failed to dump state");
                 } else {
                     trace!(path=?dump_state, "Dumped state on shutdown");
@@ -528,7 +684,9 @@ impl Future for PeriodicStateDumper {
             if this.interval.poll_tick(cx).is_ready() {
                 let api = this.api.clone();
                 let path = this.dump_state.clone().expect("exists; see above");
-                this.in_progress_dump = Some(Box::pin(PeriodicStateDumper::dump_state(api, path)));
+                let options = this.options.clone();
+                this.in_progress_dump =
+                    Some(Box::pin(PeriodicStateDumper::dump_state(api, path, options)));
             } else {
                 break;
             }
@@ -538,6 +696,26 @@ impl Future for PeriodicStateDumper {
     }
 }
 
+/// Infallible dump of the chain as a `genesis.json`
+async fn dump_genesis_json(api: &EthApi, mut path: PathBuf) {
+    if path.is_dir() {
+        path = path.join("genesis.json");
+    }
+    trace!(?path, "Dumping genesis on shutdown");
+    match api.shuttle_dump_genesis().await {
+        Ok(genesis) => {
+            if let Err(err) = foxar_common::fs::write_json_file(&path, &genesis) {
+                error!(?err, "Failed to dump genesis");
+            } else {
+                trace!(?path, "Dumped genesis on shutdown");
+            }
+        }
+        Err(err) => {
+            error!(?err, "Failed to extract genesis");
+        }
+    }
+}
+
 /// Represents the --state flag and where to load from, or dump the state to
 #[derive(Debug, Clone)]
 pub struct StateFile {
@@ -607,6 +785,7 @@ impl FromStr for ForkUrl {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ForkCondition;
 
     #[test]
     fn test_parse_fork_url() {
@@ -644,6 +823,57 @@ mod tests {
         assert_eq!(args.hardfork, Some(Hardfork::Istanbul));
     }
 
+    #[test]
+    fn can_parse_hardfork_schedule() {
+        let args: NodeArgs =
+            NodeArgs::parse_from(["shuttle", "--hardfork-schedule", "byzantium=0,istanbul=@100"]);
+        assert_eq!(
+            args.hardfork_schedule,
+            Some(HardforkSchedule::new([
+                (Hardfork::Byzantium, ForkCondition::Block(0)),
+                (Hardfork::Istanbul, ForkCondition::Timestamp(100)),
+            ]))
+        );
+
+        let args: NodeArgs = NodeArgs::parse_from(["shuttle", "--hardfork-schedule", "devin"]);
+        assert_eq!(args.hardfork_schedule, Some(HardforkSchedule::devin()));
+
+        let args = NodeArgs::try_parse_from([
+            "shuttle",
+            "--hardfork",
+            "istanbul",
+            "--hardfork-schedule",
+            "mainnet",
+        ]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn can_parse_mining_modes() {
+        let args: NodeArgs =
+            NodeArgs::parse_from(["shuttle", "--mine-on-fill", "0.5", "--block-time", "12"]);
+        assert_eq!(args.mine_on_fill, Some(0.5));
+        assert_eq!(args.block_time, Some(12));
+
+        let args: NodeArgs = NodeArgs::parse_from(["shuttle", "--mine-every", "10"]);
+        assert_eq!(args.into_node_config().mine_every, Some(10));
+
+        assert!(NodeArgs::try_parse_from(["shuttle", "--mine-on-fill", "1.5"]).is_err());
+        assert!(NodeArgs::try_parse_from(["shuttle", "--mine-every", "0"]).is_err());
+        assert!(NodeArgs::try_parse_from(["shuttle", "--mine-every", "10", "--no-mining"]).is_err());
+    }
+
+    #[test]
+    fn can_parse_nodes() {
+        let args: NodeArgs = NodeArgs::parse_from(["shuttle"]);
+        assert_eq!(args.nodes, 1);
+
+        let args: NodeArgs = NodeArgs::parse_from(["shuttle", "--nodes", "3"]);
+        assert_eq!(args.nodes, 3);
+
+        assert!(NodeArgs::try_parse_from(["shuttle", "--nodes", "0"]).is_err());
+    }
+
     #[test]
     fn can_parse_prune_config() {
         let args: NodeArgs = NodeArgs::parse_from(["shuttle", "--prune-history"]);
@@ -653,6 +883,58 @@ mod tests {
         assert_eq!(args.prune_history, Some(Some(100)));
     }
 
+    #[test]
+    fn can_parse_data_dir() {
+        let args: NodeArgs = NodeArgs::parse_from(["shuttle", "--data-dir", "chain"]);
+        assert_eq!(args.data_dir, Some(PathBuf::from("chain")));
+
+        let args = NodeArgs::try_parse_from(["shuttle", "--data-dir", "chain", "--prune-history"]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn can_parse_txpool_config() {
+        let args: NodeArgs = NodeArgs::parse_from(["shuttle"]);
+        assert_eq!(args.into_node_config().pool_config, PoolConfig::default());
+
+        let args: NodeArgs = NodeArgs::parse_from([
+            "shuttle",
+            "--txpool-max-transactions",
+            "100",
+            "--txpool-price-bump",
+            "10",
+            "--txpool-max-queued-per-sender",
+            "4",
+        ]);
+        assert_eq!(
+            args.into_node_config().pool_config,
+            PoolConfig {
+                max_transactions: Some(100),
+                price_bump: 10,
+                max_queued_per_sender: Some(4)
+            }
+        );
+    }
+
+    #[test]
+    fn can_parse_rpc_access_config() {
+        let args: NodeArgs = NodeArgs::parse_from([
+            "shuttle",
+            "--rpc-allow",
+            "eth,net,web3",
+            "--rpc-deny",
+            "eth_sendTransaction",
+            "--rpc-rate-limit",
+            "50",
+        ]);
+        assert_eq!(args.server_config.rpc_allow, vec!["eth", "net", "web3"]);
+        assert_eq!(args.server_config.rpc_deny, vec!["eth_sendTransaction"]);
+        assert_eq!(args.server_config.rpc_rate_limit.map(|limit| limit.get()), Some(50));
+        assert!(args.server_config.jwt_secret.is_none());
+
+        assert!(NodeArgs::try_parse_from(["shuttle", "--jwt-secret", "does-not-exist"]).is_err());
+    }
+
     #[test]
     fn can_parse_disable_block_energy_limit() {
         let args: NodeArgs = NodeArgs::parse_from(["shuttle", "--disable-block-energy-limit"]);
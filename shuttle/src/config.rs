@@ -4,16 +4,17 @@ use crate::{
             db::{Db, SerializableState},
             fork::{ClientFork, ClientForkConfig},
             genesis::GenesisConfig,
-            mem::fork_db::ForkedDatabase,
+            mem::{chain_db::ChainDb, fork_db::ForkedDatabase},
             time::duration_since_unix_epoch,
         },
+        error::BlockchainError,
         fees::INITIAL_ENERGY_PRICE,
-        pool::transactions::TransactionOrder,
+        pool::{transactions::TransactionOrder, PoolConfig},
     },
     genesis::Genesis,
     mem,
     mem::in_memory_db::MemDb,
-    FeeManager, Hardfork,
+    FeeManager, Hardfork, HardforkSchedule,
 };
 use corebc::{
     core::libgoldilocks::SigningKey,
@@ -90,6 +91,8 @@ pub struct NodeConfig {
     pub energy_price: Option<U256>,
     /// The hardfork to use
     pub hardfork: Option<Hardfork>,
+    /// The hardforks and when they activate, takes precedence over `hardfork`
+    pub hardfork_schedule: Option<HardforkSchedule>,
     /// Signer accounts that will be initialised with `genesis_balance` in the genesis block
     pub genesis_accounts: Vec<Wallet<SigningKey>>,
     /// Native token balance of every genesis account in the genesis block
@@ -102,6 +105,11 @@ pub struct NodeConfig {
     pub block_time: Option<Duration>,
     /// Disable auto, interval mining mode uns use `MiningMode::None` instead
     pub no_mining: bool,
+    /// Mine a new block once the ready transactions use this fraction of the block energy limit,
+    /// or after `block_time` if that comes first
+    pub mine_on_fill: Option<f64>,
+    /// Mine a new block for every number of ready transactions
+    pub mine_every: Option<usize>,
     /// port to use for the server
     pub port: u16,
     /// maximum number of transactions in a block
@@ -152,6 +160,10 @@ pub struct NodeConfig {
     pub init_state: Option<SerializableState>,
     /// max number of blocks with transactions in memory
     pub transaction_block_keeper: Option<usize>,
+    /// The directory where the chain is persisted, and resumed from on restart
+    pub data_dir: Option<PathBuf>,
+    /// The policy of the transaction pool
+    pub pool_config: PoolConfig,
 }
 
 impl NodeConfig {
@@ -336,6 +348,7 @@ impl Default for NodeConfig {
             disable_block_energy_limit: false,
             energy_price: None,
             hardfork: None,
+            hardfork_schedule: None,
             signer_accounts: genesis_accounts.clone(),
             genesis_timestamp: None,
             genesis_accounts,
@@ -343,6 +356,8 @@ impl Default for NodeConfig {
             genesis_balance: ORE_IN_CORE.saturating_mul(100u64.into()),
             block_time: None,
             no_mining: false,
+            mine_on_fill: None,
+            mine_every: None,
             port: NODE_PORT,
             // TODO make this something dependent on block capacity
             max_transactions: 1_000,
@@ -369,6 +384,8 @@ impl Default for NodeConfig {
             prune_history: Default::default(),
             init_state: None,
             transaction_block_keeper: None,
+            data_dir: None,
+            pool_config: Default::default(),
         }
     }
 }
@@ -384,6 +401,23 @@ impl NodeConfig {
         self.hardfork.unwrap_or_default()
     }
 
+    /// Returns the hardforks of the chain and when they activate
+    ///
+    /// Without an explicit schedule, the schedule of the `genesis.json` is used, unless a single
+    /// hardfork was configured, which is then active from genesis.
+    pub fn get_hardfork_schedule(&self) -> HardforkSchedule {
+        if let Some(schedule) = self.hardfork_schedule.clone() {
+            return schedule
+        }
+        if self.hardfork.is_none() {
+            let genesis_config = self.genesis.as_ref().and_then(|genesis| genesis.config.as_ref());
+            if let Some(schedule) = genesis_config.and_then(|config| config.hardfork_schedule()) {
+                return schedule
+            }
+        }
+        HardforkSchedule::single(self.get_hardfork())
+    }
+
     /// Sets a custom code size limit
     #[must_use]
     pub fn with_code_size_limit(mut self, code_size_limit: Option<usize>) -> Self {
@@ -466,6 +500,20 @@ impl NodeConfig {
         self
     }
 
+    /// Sets the directory where the chain is persisted
+    #[must_use]
+    pub fn with_data_dir(mut self, data_dir: Option<PathBuf>) -> Self {
+        self.data_dir = data_dir;
+        self
+    }
+
+    /// Sets the policy of the transaction pool
+    #[must_use]
+    pub fn with_pool_config(mut self, pool_config: PoolConfig) -> Self {
+        self.pool_config = pool_config;
+        self
+    }
+
     /// Sets the init genesis (genesis.json)
     #[must_use]
     pub fn with_genesis(mut self, genesis: Option<Genesis>) -> Self {
@@ -496,6 +544,13 @@ impl NodeConfig {
         self
     }
 
+    /// Sets the hardforks and when they activate
+    #[must_use]
+    pub fn with_hardfork_schedule(mut self, hardfork_schedule: Option<HardforkSchedule>) -> Self {
+        self.hardfork_schedule = hardfork_schedule;
+        self
+    }
+
     /// Sets the genesis accounts
     #[must_use]
     pub fn with_genesis_accounts(mut self, accounts: Vec<Wallet<SigningKey>>) -> Self {
@@ -540,6 +595,21 @@ impl NodeConfig {
         self
     }
 
+    /// Sets the fraction of the block energy limit the ready transactions must use to mine a new
+    /// block
+    #[must_use]
+    pub fn with_mine_on_fill(mut self, fill: Option<f64>) -> Self {
+        self.mine_on_fill = fill;
+        self
+    }
+
+    /// Sets the number of ready transactions to mine a new block for
+    #[must_use]
+    pub fn with_mine_every(mut self, transactions: Option<usize>) -> Self {
+        self.mine_every = transactions;
+        self
+    }
+
     /// Sets the port to use
     #[must_use]
     pub fn with_port(mut self, port: u16) -> Self {
@@ -724,7 +794,9 @@ impl NodeConfig {
     /// [Backend](mem::Backend)
     ///
     /// *Note*: only memory based backend for now
-    pub(crate) async fn setup(&mut self) -> mem::Backend {
+    ///
+    /// Returns an error if the configured init state can't be loaded.
+    pub(crate) async fn setup(&mut self) -> Result<mem::Backend, BlockchainError> {
         // configure the revm environment
         let mut env = revm::primitives::Env {
             cfg: CfgEnv {
@@ -910,30 +982,31 @@ latest block number: {latest_block}"
             genesis_init: self.genesis.clone(),
         };
 
+        let chain_db = self.data_dir.as_ref().map(|data_dir| {
+            ChainDb::open(data_dir).expect("Failed to open chain database in the data dir")
+        });
+
         // only memory based backend for now
         let backend = mem::Backend::with_genesis(
             db,
             Arc::new(RwLock::new(env)),
             genesis,
+            self.get_hardfork_schedule(),
             fees,
             fork,
             self.enable_steps_tracing,
             self.prune_history,
             self.transaction_block_keeper,
             self.block_time,
+            chain_db,
         )
         .await;
 
         if let Some(ref state) = self.init_state {
-            backend
-                .get_db()
-                .write()
-                .await
-                .load_state(state.clone())
-                .expect("Failed to load init state");
+            backend.load_serialized_state(state.clone()).await?;
         }
 
-        backend
+        Ok(backend)
     }
 }
 
@@ -3,8 +3,8 @@ use crate::{
         backend,
         backend::{
             db::SerializableState,
-            mem::{MIN_CREATE_GAS, MIN_TRANSACTION_GAS},
-            notifications::NewBlockNotifications,
+            mem::{storage::ReorgOutcome, MIN_CREATE_GAS, MIN_TRANSACTION_GAS},
+            notifications::{ChainNotifications, NewBlockNotifications},
             validate::TransactionValidator,
         },
         error::{
@@ -24,18 +24,19 @@ use crate::{
         sign::Signer,
     },
     filter::{EthFilter, Filters, LogsFilter},
-    mem::transaction_build,
+    genesis::Genesis,
+    mem::{pending_transaction_build, transaction_build},
+    network::NetworkLink,
     revm::primitives::Output,
     ClientFork, LoggingManager, Miner, MiningMode, StorageInfo,
 };
 use corebc::{
     abi::ethereum_types::H64,
-    prelude::{DefaultFrame, TxpoolInspect},
     providers::ProviderError,
     types::{
-        transaction::cip712::TypedData, Address, Block, BlockId, BlockNumber, Bytes, Filter,
-        FilteredParams, GoCoreDebugTracingOptions, GoCoreTrace, Log, Trace, Transaction,
-        TransactionReceipt, TxHash, TxpoolContent, TxpoolInspectSummary, TxpoolStatus, H256, U256,
+        transaction::cip712::TypedData, Address, Block, BlockId, BlockNumber, BlockTrace, Bytes,
+        Filter, FilteredParams, GoCoreDebugTracingOptions, GoCoreTrace, Log, Trace, TraceType,
+        Transaction, TransactionReceipt, TxHash, TxpoolInspectSummary, TxpoolStatus, H256, U256,
         U64,
     },
     utils::rlp,
@@ -51,7 +52,12 @@ use parking_lot::RwLock;
 use shuttle_core::{
     eth::{
         block::BlockInfo,
+        otterscan::{
+            OtsBlockDetails, OtsBlockTransactions, OtsContractCreator, OtsInternalOperation,
+            OtsSearchTransactions, OtsTrace, OTS_API_LEVEL,
+        },
         proof::AccountProof,
+        simulate::{SimulatePayload, SimulatedBlock},
         state::StateOverride,
         transaction::{
             EthTransactionRequest, LegacyTransaction, PendingTransaction, TransactionKind,
@@ -59,11 +65,15 @@ use shuttle_core::{
         },
         EthRequest,
     },
-    types::{EvmMineOptions, Forking, Index, NodeEnvironment, NodeForkConfig, NodeInfo, Work},
+    types::{
+        DumpStateOptions, EvmMineOptions, Forking, Index, MiningModeOptions, NodeEnvironment,
+        NodeForkConfig, NodeForkId, NodeInfo, ReorgOptions, StorageRange, TraceFilter, TraceResult,
+        TransactionData, TxpoolContent, Work,
+    },
 };
 use shuttle_rpc::{error::RpcError, response::ResponseResult};
 use spark::{executor::DatabaseRef, revm::primitives::BlockEnv};
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 use tracing::{trace, warn};
 
 use super::{backend::mem::BlockRequest, sign::build_typed_transaction};
@@ -98,6 +108,8 @@ pub struct EthApi {
     transaction_order: Arc<RwLock<TransactionOrder>>,
     /// Whether we're listening for RPC calls
     net_listening: bool,
+    /// The connection to the mining node, if this node follows a local network
+    network_link: Option<NetworkLink>,
 }
 
 // === impl Eth RPC API ===
@@ -124,9 +136,16 @@ impl EthApi {
             filters,
             net_listening: true,
             transaction_order: Arc::new(RwLock::new(transactions_order)),
+            network_link: None,
         }
     }
 
+    /// Sets the connection to the mining node of a local network, see [crate::network]
+    pub fn with_network_link(mut self, network_link: Option<NetworkLink>) -> Self {
+        self.network_link = network_link;
+        self
+    }
+
     /// Executes the [EthRequest] and returns an RPC [RpcResponse]
     pub async fn execute(&self, request: EthRequest) -> ResponseResult {
         trace!(target: "rpc::api", "executing eth request");
@@ -204,6 +223,9 @@ impl EthApi {
             EthRequest::EthCall(call, block, overrides) => {
                 self.call(call, block, overrides).await.to_rpc_result()
             }
+            EthRequest::EthSimulateV1(payload, block) => {
+                self.simulate_v1(payload, block).await.to_rpc_result()
+            }
             EthRequest::EthEstimateGas(call, block) => {
                 self.estimate_energy(call, block).await.to_rpc_result()
             }
@@ -240,8 +262,65 @@ impl EthApi {
             EthRequest::DebugTraceCall(tx, block, opts) => {
                 self.debug_trace_call(tx, block, opts).await.to_rpc_result()
             }
+            EthRequest::DebugTraceBlockByNumber(block, opts) => {
+                self.debug_trace_block_by_number(block, opts).await.to_rpc_result()
+            }
+            EthRequest::DebugTraceBlockByHash(hash, opts) => {
+                self.debug_trace_block_by_hash(hash, opts).await.to_rpc_result()
+            }
+            EthRequest::DebugStorageRangeAt(hash, tx_index, address, key_start, max_result) => self
+                .debug_storage_range_at(hash, tx_index, address, key_start, max_result)
+                .await
+                .to_rpc_result(),
+            EthRequest::DebugGetRawTransaction(hash) => {
+                self.debug_get_raw_transaction(hash).await.to_rpc_result()
+            }
+            EthRequest::DebugGetRawReceipts(block) => {
+                self.debug_get_raw_receipts(block).await.to_rpc_result()
+            }
             EthRequest::TraceTransaction(tx) => self.trace_transaction(tx).await.to_rpc_result(),
             EthRequest::TraceBlock(block) => self.trace_block(block).await.to_rpc_result(),
+            EthRequest::TraceFilter(filter) => self.trace_filter(filter).await.to_rpc_result(),
+            EthRequest::TraceCall(tx, trace_types, block) => {
+                self.trace_call(tx, trace_types, block).await.to_rpc_result()
+            }
+            EthRequest::TraceReplayBlockTransactions(block, trace_types) => {
+                self.trace_replay_block_transactions(block, trace_types).await.to_rpc_result()
+            }
+            EthRequest::TraceReplayTransaction(tx, trace_types) => {
+                self.trace_replay_transaction(tx, trace_types).await.to_rpc_result()
+            }
+            EthRequest::OtsGetApiLevel(_) => self.ots_get_api_level().to_rpc_result(),
+            EthRequest::OtsGetInternalOperations(hash) => {
+                self.ots_get_internal_operations(hash).await.to_rpc_result()
+            }
+            EthRequest::OtsHasCode(address, block) => {
+                self.ots_has_code(address, block).await.to_rpc_result()
+            }
+            EthRequest::OtsGetTransactionError(hash) => {
+                self.ots_get_transaction_error(hash).await.to_rpc_result()
+            }
+            EthRequest::OtsTraceTransaction(hash) => {
+                self.ots_trace_transaction(hash).await.to_rpc_result()
+            }
+            EthRequest::OtsGetBlockDetails(block) => {
+                self.ots_get_block_details(block).await.to_rpc_result()
+            }
+            EthRequest::OtsGetBlockTransactions(block, page, page_size) => {
+                self.ots_get_block_transactions(block, page, page_size).await.to_rpc_result()
+            }
+            EthRequest::OtsSearchTransactionsBefore(address, block, page_size) => {
+                self.ots_search_transactions_before(address, block, page_size).await.to_rpc_result()
+            }
+            EthRequest::OtsSearchTransactionsAfter(address, block, page_size) => {
+                self.ots_search_transactions_after(address, block, page_size).await.to_rpc_result()
+            }
+            EthRequest::OtsGetTransactionBySenderAndNonce(sender, nonce) => {
+                self.ots_get_transaction_by_sender_and_nonce(sender, nonce).await.to_rpc_result()
+            }
+            EthRequest::OtsGetContractCreator(address) => {
+                self.ots_get_contract_creator(address).await.to_rpc_result()
+            }
             EthRequest::ImpersonateAccount(addr) => {
                 self.shuttle_impersonate_account(addr).await.to_rpc_result()
             }
@@ -261,12 +340,19 @@ impl EthApi {
             EthRequest::SetIntervalMining(interval) => {
                 self.shuttle_set_interval_mining(interval).to_rpc_result()
             }
+            EthRequest::SetMiningMode(mode) => self.shuttle_set_mining_mode(mode).to_rpc_result(),
             EthRequest::DropTransaction(tx) => {
                 self.shuttle_drop_transaction(tx).await.to_rpc_result()
             }
             EthRequest::Reset(fork) => {
                 self.shuttle_reset(fork.and_then(|p| p.params)).await.to_rpc_result()
             }
+            EthRequest::Reorg(options) => self.shuttle_reorg(options).await.to_rpc_result(),
+            EthRequest::Rollback(depth) => {
+                self.shuttle_rollback(depth.and_then(|p| p.params)).await.to_rpc_result()
+            }
+            EthRequest::Partition(_) => self.shuttle_partition().await.to_rpc_result(),
+            EthRequest::Heal(_) => self.shuttle_heal().await.to_rpc_result(),
             EthRequest::SetBalance(addr, val) => {
                 self.shuttle_set_balance(addr, val).await.to_rpc_result()
             }
@@ -284,8 +370,14 @@ impl EthApi {
             EthRequest::SetMinGasPrice(energy) => {
                 self.shuttle_set_min_energy_price(energy).await.to_rpc_result()
             }
-            EthRequest::DumpState(_) => self.shuttle_dump_state().await.to_rpc_result(),
+            EthRequest::DumpState(options) => {
+                self.shuttle_dump_state(options.and_then(|p| p.params)).await.to_rpc_result()
+            }
+            EthRequest::DumpGenesis(_) => self.shuttle_dump_genesis().await.to_rpc_result(),
             EthRequest::LoadState(buf) => self.shuttle_load_state(buf).await.to_rpc_result(),
+            EthRequest::StateConflicts(buf) => {
+                self.shuttle_state_conflicts(buf).await.to_rpc_result()
+            }
             EthRequest::NodeInfo(_) => self.shuttle_node_info().await.to_rpc_result(),
             EthRequest::EvmSnapshot(_) => self.evm_snapshot().await.to_rpc_result(),
             EthRequest::EvmRevert(id) => self.evm_revert(id).await.to_rpc_result(),
@@ -350,6 +442,27 @@ impl EthApi {
         Err(BlockchainError::NoSignerAvailable)
     }
 
+    /// Signs the request with the signer of `from` or, if `from` is impersonated, bypasses the
+    /// signature
+    fn build_pending_transaction(
+        &self,
+        from: Address,
+        request: TypedTransactionRequest,
+    ) -> Result<PendingTransaction> {
+        // if the sender is currently impersonated we need to "bypass" signing
+        if self.is_impersonated(from) {
+            let bypass_signature = self.backend.cheats().bypass_signature();
+            let transaction = sign::build_typed_transaction(request, bypass_signature)?;
+            self.ensure_typed_transaction_supported(&transaction)?;
+            trace!(target : "node", ?from, "impersonating sender");
+            Ok(PendingTransaction::with_impersonated(transaction, from))
+        } else {
+            let transaction = self.sign_request(&from, request)?;
+            self.ensure_typed_transaction_supported(&transaction)?;
+            Ok(PendingTransaction::new(transaction)?)
+        }
+    }
+
     /// Queries the current energy limit
     fn current_energy_limit(&self) -> Result<U256> {
         Ok(self.backend.energy_limit())
@@ -747,19 +860,7 @@ impl EthApi {
         let (nonce, on_chain_nonce) = self.request_nonce(&request, from).await?;
 
         let request = self.build_typed_tx_request(request, nonce)?;
-
-        // if the sender is currently impersonated we need to "bypass" signing
-        let pending_transaction = if self.is_impersonated(from) {
-            let bypass_signature = self.backend.cheats().bypass_signature();
-            let transaction = sign::build_typed_transaction(request, bypass_signature)?;
-            self.ensure_typed_transaction_supported(&transaction)?;
-            trace!(target : "node", ?from, "eth_sendTransaction: impersonating");
-            PendingTransaction::with_impersonated(transaction, from)
-        } else {
-            let transaction = self.sign_request(&from, request)?;
-            self.ensure_typed_transaction_supported(&transaction)?;
-            PendingTransaction::new(transaction)?
-        };
+        let pending_transaction = self.build_pending_transaction(from, request)?;
 
         // pre-validate
         self.backend.validate_pool_transaction(&pending_transaction).await?;
@@ -860,6 +961,30 @@ impl EthApi {
         ensure_return_ok(exit, &out)
     }
 
+    /// Simulates a sequence of calls across one or more blocks, without mining anything.
+    ///
+    /// Handler for ETH RPC call: `xcb_simulateV1`
+    pub async fn simulate_v1(
+        &self,
+        payload: SimulatePayload,
+        block_number: Option<BlockId>,
+    ) -> Result<Vec<SimulatedBlock>> {
+        node_info!("xcb_simulateV1");
+        let block_request = self.block_request(block_number).await?;
+        if let BlockRequest::Number(number) = &block_request {
+            if let Some(fork) = self.get_fork() {
+                if fork.predates_fork(number.as_u64()) {
+                    return Err(RpcError::invalid_params(
+                        "simulations are not available on past forked blocks",
+                    )
+                    .into());
+                }
+            }
+        }
+
+        self.backend.simulate(payload, Some(block_request)).await
+    }
+
     /// Estimate energy needed for execution of given contract.
     /// If no block parameter is given, it will use the pending block by default
     ///
@@ -882,14 +1007,7 @@ impl EthApi {
     /// Handler for ETH RPC call: `eth_getTransactionByHash`
     pub async fn transaction_by_hash(&self, hash: H256) -> Result<Option<Transaction>> {
         node_info!("eth_getTransactionByHash");
-        let mut tx = self.pool.get_transaction(hash).map(|pending| {
-            let from = *pending.sender();
-            let mut tx = transaction_build(Some(*pending.hash()), pending.transaction, None, None);
-            // we set the from field here explicitly to the set sender of the pending transaction,
-            // in case the transaction is impersonated.
-            tx.from = from;
-            tx
-        });
+        let mut tx = self.pool.get_transaction(hash).map(pending_transaction_build);
         if tx.is_none() {
             tx = self.backend.transaction_by_hash(hash).await?
         }
@@ -1024,7 +1142,7 @@ impl EthApi {
             vec![]
         };
         let filter = EthFilter::Logs(Box::new(LogsFilter {
-            blocks: self.new_block_notifications(),
+            blocks: self.chain_notifications(),
             storage: self.storage_info(),
             filter: FilteredParams::new(Some(filter)),
             historic: Some(historic),
@@ -1085,10 +1203,6 @@ impl EthApi {
         opts: GoCoreDebugTracingOptions,
     ) -> Result<GoCoreTrace> {
         node_info!("debug_traceTransaction");
-        if opts.tracer.is_some() {
-            return Err(RpcError::invalid_params("non-default tracer not supported yet").into());
-        }
-
         self.backend.debug_trace_transaction(tx_hash, opts).await
     }
 
@@ -1100,17 +1214,71 @@ impl EthApi {
         request: EthTransactionRequest,
         block_number: Option<BlockId>,
         opts: GoCoreDebugTracingOptions,
-    ) -> Result<DefaultFrame> {
+    ) -> Result<GoCoreTrace> {
         node_info!("debug_traceCall");
-        if opts.tracer.is_some() {
-            return Err(RpcError::invalid_params("non-default tracer not supported yet").into());
-        }
         let block_request = self.block_request(block_number).await?;
         let fees = FeeDetails::new(request.energy_price)?.or_zero_fees();
 
         self.backend.call_with_tracing(request, fees, Some(block_request), opts).await
     }
 
+    /// Returns the traces of all transactions of the block for geth's tracing endpoint
+    ///
+    /// Handler for RPC call: `debug_traceBlockByNumber`
+    pub async fn debug_trace_block_by_number(
+        &self,
+        block: BlockNumber,
+        opts: GoCoreDebugTracingOptions,
+    ) -> Result<Vec<TraceResult>> {
+        node_info!("debug_traceBlockByNumber");
+        self.backend.debug_trace_block(block.into(), opts).await
+    }
+
+    /// Returns the traces of all transactions of the block for geth's tracing endpoint
+    ///
+    /// Handler for RPC call: `debug_traceBlockByHash`
+    pub async fn debug_trace_block_by_hash(
+        &self,
+        hash: H256,
+        opts: GoCoreDebugTracingOptions,
+    ) -> Result<Vec<TraceResult>> {
+        node_info!("debug_traceBlockByHash");
+        self.backend.debug_trace_block(hash.into(), opts).await
+    }
+
+    /// Returns the storage of the account before the transaction at the index of the block
+    ///
+    /// Handler for RPC call: `debug_storageRangeAt`
+    pub async fn debug_storage_range_at(
+        &self,
+        block_hash: H256,
+        tx_index: usize,
+        address: Address,
+        key_start: H256,
+        max_result: usize,
+    ) -> Result<StorageRange> {
+        node_info!("debug_storageRangeAt");
+        self.backend
+            .debug_storage_range_at(block_hash, tx_index, address, key_start, max_result)
+            .await
+    }
+
+    /// Returns the RLP encoded transaction
+    ///
+    /// Handler for RPC call: `debug_getRawTransaction`
+    pub async fn debug_get_raw_transaction(&self, tx_hash: H256) -> Result<Option<Bytes>> {
+        node_info!("debug_getRawTransaction");
+        self.backend.raw_transaction(tx_hash).await
+    }
+
+    /// Returns the RLP encoded receipts of all transactions of the block
+    ///
+    /// Handler for RPC call: `debug_getRawReceipts`
+    pub async fn debug_get_raw_receipts(&self, block: BlockId) -> Result<Vec<Bytes>> {
+        node_info!("debug_getRawReceipts");
+        self.backend.raw_receipts(block).await
+    }
+
     /// Returns traces for the transaction hash via parity's tracing endpoint
     ///
     /// Handler for RPC call: `trace_transaction`
@@ -1126,6 +1294,172 @@ impl EthApi {
         node_info!("trace_block");
         self.backend.trace_block(block).await
     }
+
+    /// Returns all traces of the blocks in the range of the filter that match its addresses
+    ///
+    /// Handler for RPC call: `trace_filter`
+    pub async fn trace_filter(&self, filter: TraceFilter) -> Result<Vec<Trace>> {
+        node_info!("trace_filter");
+        self.backend.trace_filter(filter)
+    }
+
+    /// Executes the call and returns the requested traces, state diff and vm trace
+    ///
+    /// Handler for RPC call: `trace_call`
+    pub async fn trace_call(
+        &self,
+        request: EthTransactionRequest,
+        trace_types: Vec<TraceType>,
+        block_number: Option<BlockId>,
+    ) -> Result<BlockTrace> {
+        node_info!("trace_call");
+        let block_request = self.block_request(block_number).await?;
+        let fees = FeeDetails::new(request.energy_price)?.or_zero_fees();
+
+        self.backend.trace_call(request, fees, Some(block_request), trace_types).await
+    }
+
+    /// Replays all transactions of the block and returns their requested traces
+    ///
+    /// Handler for RPC call: `trace_replayBlockTransactions`
+    pub async fn trace_replay_block_transactions(
+        &self,
+        block: BlockNumber,
+        trace_types: Vec<TraceType>,
+    ) -> Result<Vec<BlockTrace>> {
+        node_info!("trace_replayBlockTransactions");
+        self.backend.trace_replay_block_transactions(block, trace_types).await
+    }
+
+    /// Replays the transaction and returns its requested traces
+    ///
+    /// Handler for RPC call: `trace_replayTransaction`
+    pub async fn trace_replay_transaction(
+        &self,
+        tx_hash: H256,
+        trace_types: Vec<TraceType>,
+    ) -> Result<BlockTrace> {
+        node_info!("trace_replayTransaction");
+        self.backend.trace_replay_transaction(tx_hash, trace_types).await
+    }
+}
+
+// == impl EthApi otterscan endpoints ==
+
+impl EthApi {
+    /// Returns the version of the supported Otterscan API
+    ///
+    /// Handler for RPC call: `ots_getApiLevel`
+    pub fn ots_get_api_level(&self) -> Result<u64> {
+        node_info!("ots_getApiLevel");
+        Ok(OTS_API_LEVEL)
+    }
+
+    /// Returns the value transfers and contract creations of the internal calls of the transaction
+    ///
+    /// Handler for RPC call: `ots_getInternalOperations`
+    pub async fn ots_get_internal_operations(
+        &self,
+        hash: H256,
+    ) -> Result<Vec<OtsInternalOperation>> {
+        node_info!("ots_getInternalOperations");
+        self.backend.ots_internal_operations(hash)
+    }
+
+    /// Returns whether the address has code at the block
+    ///
+    /// Handler for RPC call: `ots_hasCode`
+    pub async fn ots_has_code(&self, address: Address, block_number: BlockNumber) -> Result<bool> {
+        node_info!("ots_hasCode");
+        let code = self.get_code(address, Some(block_number.into())).await?;
+        Ok(!code.is_empty())
+    }
+
+    /// Returns the revert data of the transaction
+    ///
+    /// Handler for RPC call: `ots_getTransactionError`
+    pub async fn ots_get_transaction_error(&self, hash: H256) -> Result<Bytes> {
+        node_info!("ots_getTransactionError");
+        self.backend.ots_transaction_error(hash)
+    }
+
+    /// Returns all calls of the transaction
+    ///
+    /// Handler for RPC call: `ots_traceTransaction`
+    pub async fn ots_trace_transaction(&self, hash: H256) -> Result<Vec<OtsTrace>> {
+        node_info!("ots_traceTransaction");
+        self.backend.ots_trace_transaction(hash)
+    }
+
+    /// Returns the block without its transactions together with its fees and rewards
+    ///
+    /// Handler for RPC call: `ots_getBlockDetails`
+    pub async fn ots_get_block_details(&self, number: BlockNumber) -> Result<OtsBlockDetails> {
+        node_info!("ots_getBlockDetails");
+        self.backend.ots_block_details(number).await
+    }
+
+    /// Returns a page of the transactions of the block and their receipts
+    ///
+    /// Handler for RPC call: `ots_getBlockTransactions`
+    pub async fn ots_get_block_transactions(
+        &self,
+        number: u64,
+        page: usize,
+        page_size: usize,
+    ) -> Result<OtsBlockTransactions> {
+        node_info!("ots_getBlockTransactions");
+        self.backend.ots_block_transactions(number, page, page_size).await
+    }
+
+    /// Returns a page of the transactions of the address before the block
+    ///
+    /// Handler for RPC call: `ots_searchTransactionsBefore`
+    pub async fn ots_search_transactions_before(
+        &self,
+        address: Address,
+        block_number: u64,
+        page_size: usize,
+    ) -> Result<OtsSearchTransactions> {
+        node_info!("ots_searchTransactionsBefore");
+        self.backend.ots_search_transactions_before(address, block_number, page_size)
+    }
+
+    /// Returns a page of the transactions of the address after the block
+    ///
+    /// Handler for RPC call: `ots_searchTransactionsAfter`
+    pub async fn ots_search_transactions_after(
+        &self,
+        address: Address,
+        block_number: u64,
+        page_size: usize,
+    ) -> Result<OtsSearchTransactions> {
+        node_info!("ots_searchTransactionsAfter");
+        self.backend.ots_search_transactions_after(address, block_number, page_size)
+    }
+
+    /// Returns the hash of the transaction of the sender with the nonce
+    ///
+    /// Handler for RPC call: `ots_getTransactionBySenderAndNonce`
+    pub async fn ots_get_transaction_by_sender_and_nonce(
+        &self,
+        sender: Address,
+        nonce: U256,
+    ) -> Result<Option<H256>> {
+        node_info!("ots_getTransactionBySenderAndNonce");
+        Ok(self.backend.ots_transaction_by_sender_and_nonce(sender, nonce))
+    }
+
+    /// Returns the transaction that created the contract and its creator
+    ///
+    /// Handler for RPC call: `ots_getContractCreator`
+    pub async fn ots_get_contract_creator(
+        &self,
+        address: Address,
+    ) -> Result<Option<OtsContractCreator>> {
+        node_info!("ots_getContractCreator");
+        Ok(self.backend.ots_contract_creator(address))
+    }
 }
 
 // == impl EthApi shuttle endpoints ==
@@ -1231,6 +1565,52 @@ impl EthApi {
         Ok(())
     }
 
+    /// Sets the mining behavior, which can also mine once the ready transactions fill a fraction
+    /// of the block energy limit or every number of transactions
+    ///
+    /// Handler for RPC call: `shuttle_setMiningMode`
+    pub fn shuttle_set_mining_mode(&self, mode: MiningModeOptions) -> Result<()> {
+        node_info!("shuttle_setMiningMode");
+        let ensure_fill = |fill: f64| -> Result<()> {
+            if fill > 0.0 && fill <= 1.0 {
+                Ok(())
+            } else {
+                Err(RpcError::invalid_params(format!("fill must be in (0, 1], got {fill}")).into())
+            }
+        };
+        let mining_mode = match mode {
+            MiningModeOptions::None => MiningMode::None,
+            MiningModeOptions::Auto => MiningMode::instant(1_000, self.pool.add_ready_listener()),
+            MiningModeOptions::Interval { block_time } => {
+                return self.shuttle_set_interval_mining(block_time)
+            }
+            MiningModeOptions::EnergyFill { fill } => {
+                ensure_fill(fill)?;
+                let env = self.backend.env().clone();
+                MiningMode::energy_fill(fill, env, self.pool.add_ready_listener())
+            }
+            MiningModeOptions::TransactionCount { transactions } => {
+                if transactions == 0 {
+                    return Err(RpcError::invalid_params("transactions must not be zero").into())
+                }
+                MiningMode::transaction_count(transactions, self.pool.add_ready_listener())
+            }
+            MiningModeOptions::IntervalOrEnergyFill { block_time, fill } => {
+                ensure_fill(fill)?;
+                if block_time == 0 {
+                    return Err(RpcError::invalid_params("block time must not be zero").into())
+                }
+                let block_time = Duration::from_secs(block_time);
+                self.backend.update_interval_mine_block_time(block_time);
+                let env = self.backend.env().clone();
+                let listener = self.pool.add_ready_listener();
+                MiningMode::interval_or_energy_fill(block_time, fill, env, listener)
+            }
+        };
+        self.miner.set_mining_mode(mining_mode);
+        Ok(())
+    }
+
     /// Removes transactions from the pool
     ///
     /// Handler for RPC call: `shuttle_dropTransaction`
@@ -1253,6 +1633,127 @@ impl EthApi {
         }
     }
 
+    /// Replaces the most recent `depth` blocks with the same number of new blocks that contain the
+    /// given transactions.
+    ///
+    /// Transaction requests without a nonce use the next nonce of the sender at the first replaced
+    /// block. Subscribers and filters receive the logs of the replaced blocks with `removed: true`.
+    /// The transactions of the replaced blocks that are not part of the new blocks are added back
+    /// to the pool. Fails if a given transaction is invalid, the new blocks are mined without it.
+    ///
+    /// Handler for RPC call: `shuttle_reorg`
+    pub async fn shuttle_reorg(&self, options: ReorgOptions) -> Result<()> {
+        node_info!("shuttle_reorg");
+        let ReorgOptions { depth, tx_block_pairs } = options;
+        let common_block = self.backend.rollback_target(depth)?;
+
+        let mut blocks = vec![Vec::new(); depth as usize];
+        let mut nonces = HashMap::<Address, U256>::new();
+        for (tx, index) in tx_block_pairs {
+            let block = blocks.get_mut(index as usize).ok_or_else(|| {
+                RpcError::invalid_params(format!(
+                    "block index {index} must be lower than the depth {depth}"
+                ))
+            })?;
+            let pending_transaction = match tx {
+                TransactionData::Raw(tx) => {
+                    let transaction = rlp::decode::<LegacyTransaction>(tx.as_ref())
+                        .map_err(|_| BlockchainError::FailedToDecodeSignedTransaction)?;
+                    PendingTransaction::new(TypedTransaction::Legacy(transaction))?
+                }
+                TransactionData::Json(request) => {
+                    let from = request.from.map(Ok).unwrap_or_else(|| {
+                        self.accounts()?.first().cloned().ok_or(BlockchainError::NoSignerAvailable)
+                    })?;
+                    let nonce = match request.nonce.or_else(|| nonces.get(&from).copied()) {
+                        Some(nonce) => nonce,
+                        None => {
+                            let block_request = BlockRequest::Number(common_block.into());
+                            self.backend.get_nonce(from, Some(block_request)).await?
+                        }
+                    };
+                    let request = self.build_typed_tx_request(request, nonce)?;
+                    self.build_pending_transaction(from, request)?
+                }
+            };
+
+            let from = *pending_transaction.sender();
+            let nonce = *pending_transaction.transaction.nonce();
+            nonces.insert(from, nonce + 1);
+            let priority = self.transaction_priority(&pending_transaction.transaction);
+            block.push(Arc::new(PoolTransaction {
+                requires: vec![],
+                provides: vec![to_marker(nonce.as_u64(), from)],
+                pending_transaction,
+                priority,
+            }));
+        }
+
+        let ReorgOutcome { orphaned, invalid } = self.backend.reorg(depth, blocks).await?;
+
+        // the transactions of the removed blocks can be mined again, unless a transaction of the
+        // new blocks used their nonce
+        for pending_transaction in orphaned {
+            let from = *pending_transaction.sender();
+            let nonce = *pending_transaction.nonce();
+            let on_chain_nonce = self.backend.current_nonce(from).await?;
+            if nonce < on_chain_nonce {
+                continue
+            }
+            let requires = required_marker(nonce, on_chain_nonce, from);
+            let provides = vec![to_marker(nonce.as_u64(), from)];
+            if let Err(err) = self.add_pending_transaction(pending_transaction, requires, provides)
+            {
+                warn!(target: "node", ?err, "failed to re-add transaction of a removed block");
+            }
+        }
+
+        if !invalid.is_empty() {
+            return Err(RpcError::invalid_params(format!(
+                "the transactions {invalid:?} were invalid and are not included in the new blocks"
+            ))
+            .into())
+        }
+        Ok(())
+    }
+
+    /// Removes the most recent `depth` blocks, `1` if not set.
+    ///
+    /// Subscribers and filters receive the logs of the removed blocks with `removed: true`.
+    ///
+    /// Handler for RPC call: `shuttle_rollback`
+    pub async fn shuttle_rollback(&self, depth: Option<u64>) -> Result<()> {
+        node_info!("shuttle_rollback");
+        self.backend.rollback(depth.unwrap_or(1)).await
+    }
+
+    /// Partitions this node from the local network, see [crate::network]
+    ///
+    /// The node mines its own blocks until it's healed, only following nodes can be partitioned.
+    ///
+    /// Handler for RPC call: `shuttle_partition`
+    pub async fn shuttle_partition(&self) -> Result<()> {
+        node_info!("shuttle_partition");
+        if self.network_link()?.partition() {
+            let listener = self.pool.add_ready_listener();
+            self.miner.set_mining_mode(MiningMode::instant(1_000, listener));
+        }
+        Ok(())
+    }
+
+    /// Reconnects this node to the local network, see [crate::network]
+    ///
+    /// The blocks the node mined while partitioned are replaced with the chain of the mining node.
+    ///
+    /// Handler for RPC call: `shuttle_heal`
+    pub async fn shuttle_heal(&self) -> Result<()> {
+        node_info!("shuttle_heal");
+        if self.network_link()?.heal() {
+            self.miner.set_mining_mode(MiningMode::None);
+        }
+        Ok(())
+    }
+
     /// Modifies the balance of an account.
     ///
     /// Handler for RPC call: `shuttle_setBalance`
@@ -1324,32 +1825,61 @@ impl EthApi {
     /// Create a bufer that represents all state on the chain, which can be loaded to separate
     /// process by calling `shuttle_loadState`
     ///
+    /// The options can include the history of the chain or restrict the dump to a set of accounts.
+    ///
     /// Handler for RPC call: `shuttle_dumpState`
-    pub async fn shuttle_dump_state(&self) -> Result<Bytes> {
+    pub async fn shuttle_dump_state(&self, options: Option<DumpStateOptions>) -> Result<Bytes> {
         node_info!("shuttle_dumpState");
-        self.backend.dump_state().await
+        self.backend.dump_state(&options.unwrap_or_default()).await
+    }
+
+    /// Returns the current state and chain configuration as a `genesis.json`, which can be used to
+    /// start a new chain, for example with `shuttle --init`
+    ///
+    /// Handler for RPC call: `shuttle_dumpGenesis`
+    pub async fn shuttle_dump_genesis(&self) -> Result<Genesis> {
+        node_info!("shuttle_dumpGenesis");
+        self.backend.dump_genesis().await
     }
 
     /// Returns the current state
-    pub async fn serialized_state(&self) -> Result<SerializableState> {
-        self.backend.serialized_state().await
+    pub async fn serialized_state(&self, options: &DumpStateOptions) -> Result<SerializableState> {
+        self.backend.serialized_state(options).await
+    }
+
+    /// Flushes the chain database, if `--data-dir` is configured
+    pub async fn flush_chain_db(&self) {
+        self.backend.flush_chain_db().await
     }
 
     /// Append chain state buffer to current chain. Will overwrite any conflicting addresses or
     /// storage.
     ///
+    /// Returns the accounts that conflicted with the current state and were overwritten, see also
+    /// `shuttle_stateConflicts`.
+    ///
     /// Handler for RPC call: `shuttle_loadState`
-    pub async fn shuttle_load_state(&self, buf: Bytes) -> Result<bool> {
+    pub async fn shuttle_load_state(&self, buf: Bytes) -> Result<Vec<Address>> {
         node_info!("shuttle_loadState");
         self.backend.load_state(buf).await
     }
 
+    /// Returns the accounts of a state buffer that already exist with a different nonce, balance,
+    /// code or storage, and would be overwritten by `shuttle_loadState`
+    ///
+    /// Handler for RPC call: `shuttle_stateConflicts`
+    pub async fn shuttle_state_conflicts(&self, buf: Bytes) -> Result<Vec<Address>> {
+        node_info!("shuttle_stateConflicts");
+        self.backend.state_conflicts(buf).await
+    }
+
     /// Retrieves the Shuttle node configuration params.
     ///
     /// Handler for RPC call: `shuttle_nodeInfo`
     pub async fn shuttle_node_info(&self) -> Result<NodeInfo> {
         node_info!("shuttle_nodeInfo");
 
+        let fork_id = self.backend.fork_id();
         let env = self.backend.env().read();
         let fork_config = self.backend.get_fork();
         let tx_order = self.transaction_order.read();
@@ -1359,6 +1889,7 @@ impl EthApi {
             current_block_timestamp: env.block.timestamp.try_into().unwrap_or(u64::MAX),
             current_block_hash: self.backend.best_hash(),
             hard_fork: env.cfg.spec_id,
+            fork_id: NodeForkId { hash: fork_id.hash.0.to_vec().into(), next: fork_id.next },
             transaction_order: match *tx_order {
                 TransactionOrder::Fifo => "fifo".to_string(),
                 TransactionOrder::Fees => "fees".to_string(),
@@ -1613,9 +2144,8 @@ impl EthApi {
     /// See [here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_inspect) for more details
     ///
     /// Handler for ETH RPC call: `txpool_inspect`
-    pub async fn txpool_inspect(&self) -> Result<TxpoolInspect> {
+    pub async fn txpool_inspect(&self) -> Result<TxpoolContent<TxpoolInspectSummary>> {
         node_info!("txpool_inspect");
-        let mut inspect = TxpoolInspect::default();
 
         fn convert(tx: Arc<PoolTransaction>) -> TxpoolInspectSummary {
             let tx = &tx.pending_transaction.transaction;
@@ -1626,23 +2156,7 @@ impl EthApi {
             TxpoolInspectSummary { to, value, energy, energy_price }
         }
 
-        // Note: naming differs geth vs shuttle:
-        //
-        // _Pending transactions_ are transactions that are ready to be processed and included in
-        // the block. _Queued transactions_ are transactions where the transaction nonce is
-        // not in sequence. The transaction nonce is an incrementing number for each transaction
-        // with the same From address.
-        for pending in self.pool.ready_transactions() {
-            let entry = inspect.pending.entry(*pending.pending_transaction.sender()).or_default();
-            let key = pending.pending_transaction.nonce().to_string();
-            entry.insert(key, convert(pending));
-        }
-        for queued in self.pool.pending_transactions() {
-            let entry = inspect.pending.entry(*queued.pending_transaction.sender()).or_default();
-            let key = queued.pending_transaction.nonce().to_string();
-            entry.insert(key, convert(queued));
-        }
-        Ok(inspect)
+        Ok(self.txpool_content_with(convert))
     }
 
     /// Returns the details of all transactions currently pending for inclusion in the next
@@ -1651,9 +2165,8 @@ impl EthApi {
     /// See [here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_content) for more details
     ///
     /// Handler for ETH RPC call: `txpool_inspect`
-    pub async fn txpool_content(&self) -> Result<TxpoolContent> {
+    pub async fn txpool_content(&self) -> Result<TxpoolContent<Transaction>> {
         node_info!("txpool_content");
-        let mut content = TxpoolContent::default();
         fn convert(tx: Arc<PoolTransaction>) -> Transaction {
             let from = *tx.pending_transaction.sender();
             let mut tx = transaction_build(
@@ -1669,18 +2182,31 @@ impl EthApi {
             tx
         }
 
-        for pending in self.pool.ready_transactions() {
-            let entry = content.pending.entry(*pending.pending_transaction.sender()).or_default();
-            let key = pending.pending_transaction.nonce().to_string();
-            entry.insert(key, convert(pending));
-        }
-        for queued in self.pool.pending_transactions() {
-            let entry = content.pending.entry(*queued.pending_transaction.sender()).or_default();
-            let key = queued.pending_transaction.nonce().to_string();
-            entry.insert(key, convert(queued));
-        }
+        Ok(self.txpool_content_with(convert))
+    }
 
-        Ok(content)
+    /// Groups all transactions of the pool by sender and nonce, converted with `convert`
+    fn txpool_content_with<T>(&self, convert: fn(Arc<PoolTransaction>) -> T) -> TxpoolContent<T> {
+        // Note: naming differs geth vs shuttle:
+        //
+        // _Pending transactions_ are transactions that are ready to be processed and included in
+        // the block. _Queued transactions_ are transactions where the transaction nonce is
+        // not in sequence. The transaction nonce is an incrementing number for each transaction
+        // with the same From address.
+        let mut content = TxpoolContent::default();
+        let groups = [
+            (&mut content.pending, self.pool.ready_transactions().collect::<Vec<_>>()),
+            (&mut content.queued, self.pool.pending_transactions()),
+            (&mut content.evicted, self.pool.evicted_transactions()),
+        ];
+        for (group, transactions) in groups {
+            for tx in transactions {
+                let entry = group.entry(*tx.pending_transaction.sender()).or_default();
+                let key = tx.pending_transaction.nonce().to_string();
+                entry.insert(key, convert(tx));
+            }
+        }
+        content
     }
 }
 
@@ -1957,11 +2483,62 @@ impl EthApi {
         self.backend.new_block_notifications()
     }
 
+    /// Returns a new event stream that yields notifications about new and removed blocks
+    pub fn chain_notifications(&self) -> ChainNotifications {
+        self.backend.chain_notifications()
+    }
+
     /// Returns a new listeners for ready transactions
     pub fn new_ready_transactions(&self) -> Receiver<TxHash> {
         self.pool.add_ready_listener()
     }
 
+    /// Returns the transaction pool
+    pub fn pool(&self) -> &Arc<Pool> {
+        &self.pool
+    }
+
+    /// Returns the backend that holds the chain of this node
+    pub(crate) fn backend(&self) -> &Arc<backend::mem::Backend> {
+        &self.backend
+    }
+
+    /// Returns the connection to the mining node of the local network this node follows
+    fn network_link(&self) -> Result<&NetworkLink> {
+        self.network_link.as_ref().ok_or_else(|| {
+            RpcError::invalid_params(
+                "only following nodes of a local network can be partitioned or healed",
+            )
+            .into()
+        })
+    }
+
+    /// Imports a block that was mined by a node with the same genesis, see
+    /// [backend::mem::Backend::import_block]
+    pub(crate) async fn import_block(
+        &self,
+        block: &shuttle_core::eth::block::Block,
+        transactions: Vec<PendingTransaction>,
+    ) -> Result<()> {
+        let transactions = transactions
+            .into_iter()
+            .map(|pending_transaction| {
+                let from = *pending_transaction.sender();
+                let nonce = pending_transaction.transaction.nonce().as_u64();
+                let priority = self.transaction_priority(&pending_transaction.transaction);
+                Arc::new(PoolTransaction {
+                    requires: vec![],
+                    provides: vec![to_marker(nonce, from)],
+                    pending_transaction,
+                    priority,
+                })
+            })
+            .collect();
+        let outcome = self.backend.import_block(block, transactions).await?;
+        self.pool.on_mined_block(outcome);
+        Ok(())
+    }
+
     /// Returns a new accessor for certain storage elements
     pub fn storage_info(&self) -> StorageInfo {
         StorageInfo::new(Arc::clone(&self.backend))
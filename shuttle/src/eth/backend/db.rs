@@ -1,14 +1,20 @@
 //! Helper types for working with [revm](foxar_evm::revm)
-use crate::{mem::state::trie_hash_db, revm::primitives::AccountInfo, U256};
+use crate::{
+    mem::{state::trie_hash_db, storage::MinedTransaction},
+    revm::primitives::AccountInfo,
+    U256,
+};
 use corebc::{
     prelude::{Address, Bytes},
     types::H256,
     utils::sha3,
 };
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use foxar_common::errors::FsPathError;
 use foxar_evm::{
     executor::{
         backend::{snapshot::StateSnapshot, DatabaseError, DatabaseResult, MemDb},
+        fork::SharedBackend,
         DatabaseRef,
     },
     revm::{
@@ -22,9 +28,14 @@ use foxar_utils::types::ToRuint;
 use hash_db::HashDB;
 use revm::primitives::B176;
 use serde::{Deserialize, Serialize};
-use shuttle_core::eth::trie::KeccakHasher;
+use shuttle_core::eth::{block::Block, trie::KeccakHasher};
 use spark::revm::primitives::{B256, SHA3_EMPTY, U256 as rU256};
-use std::{collections::BTreeMap, fmt, path::Path};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, Write},
+    path::Path,
+};
 
 /// Type alias for the `HashDB` representation of the Database
 pub type AsHashDB = Box<dyn HashDB<KeccakHasher, Vec<u8>>>;
@@ -40,6 +51,17 @@ pub trait MaybeHashDatabase: DatabaseRef<Error = DatabaseError> {
     fn maybe_account_db(&self, _addr: Address) -> Option<(AsHashDB, H256)> {
         None
     }
+    /// Return the entire storage of the account, if the DB holds all of it
+    ///
+    /// This is never the case for a forked DB, which only holds the fetched and modified slots.
+    fn maybe_account_storage(&self, _addr: Address) -> Option<HashMap<rU256, rU256>> {
+        None
+    }
+    /// Return the locally modified state of a forked DB, which is layered on top of the state
+    /// fetched from the remote endpoint
+    fn maybe_fork_local(&self) -> Option<CacheDB<SharedBackend>> {
+        None
+    }
 
     /// Clear the state and move it into a new `StateSnapshot`
     fn clear_into_snapshot(&mut self) -> StateSnapshot;
@@ -61,6 +83,12 @@ where
     fn maybe_account_db(&self, addr: Address) -> Option<(AsHashDB, H256)> {
         T::maybe_account_db(self, addr)
     }
+    fn maybe_account_storage(&self, addr: Address) -> Option<HashMap<rU256, rU256>> {
+        T::maybe_account_storage(self, addr)
+    }
+    fn maybe_fork_local(&self) -> Option<CacheDB<SharedBackend>> {
+        T::maybe_fork_local(self)
+    }
 
     fn clear_into_snapshot(&mut self) -> StateSnapshot {
         unreachable!("never called for DatabaseRef")
@@ -155,6 +183,37 @@ pub trait Db:
         Ok(true)
     }
 
+    /// Returns the accounts of the given state that already exist in this db with a different
+    /// nonce, balance, code or storage
+    fn conflicting_accounts(&self, state: &SerializableState) -> DatabaseResult<Vec<Address>> {
+        let mut conflicts = Vec::new();
+        for (addr, account) in state.accounts.iter() {
+            let Some(info) = DatabaseRef::basic(self, (*addr).into())? else { continue };
+            if info.is_empty() {
+                continue
+            }
+            let code_hash = if account.code.as_ref().is_empty() {
+                SHA3_EMPTY
+            } else {
+                B256::from_slice(&sha3(account.code.as_ref())[..])
+            };
+            let mut conflicting = info.nonce != account.nonce ||
+                info.balance != account.balance.to_ruint() ||
+                info.code_hash != code_hash;
+            for (slot, value) in account.storage.iter() {
+                if conflicting {
+                    break
+                }
+                let current = DatabaseRef::storage(self, (*addr).into(), slot.to_ruint())?;
+                conflicting = current != rU256::ZERO && current != value.to_ruint();
+            }
+            if conflicting {
+                conflicts.push(*addr);
+            }
+        }
+        Ok(conflicts)
+    }
+
     /// Creates a new snapshot
     fn snapshot(&mut self) -> U256;
 
@@ -170,6 +229,10 @@ pub trait Db:
 
     /// Returns the current, standalone state of the Db
     fn current_state(&self) -> StateDb;
+
+    /// Replaces the locally modified state of a forked Db, see
+    /// [MaybeHashDatabase::maybe_fork_local]
+    fn restore_fork_local(&mut self, _local: CacheDB<SharedBackend>) {}
 }
 
 /// Convenience impl only used to use any `Db` on the fly as the db layer for revm's CacheDB
@@ -288,6 +351,14 @@ impl MaybeHashDatabase for StateDb {
         self.0.maybe_account_db(addr)
     }
 
+    fn maybe_account_storage(&self, addr: Address) -> Option<HashMap<rU256, rU256>> {
+        self.0.maybe_account_storage(addr)
+    }
+
+    fn maybe_fork_local(&self) -> Option<CacheDB<SharedBackend>> {
+        self.0.maybe_fork_local()
+    }
+
     fn clear_into_snapshot(&mut self) -> StateSnapshot {
         self.0.clear_into_snapshot()
     }
@@ -301,30 +372,88 @@ impl MaybeHashDatabase for StateDb {
     }
 }
 
+/// The version of the [SerializableState] format that's written by this node
+///
+/// Dumps without a version predate versioning and only contain the accounts.
+pub const STATE_DUMP_VERSION: u32 = 1;
+
+/// The first bytes of a gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct SerializableState {
+    /// The version of the format, `0` if the dump predates versioning
+    #[serde(default)]
+    pub version: u32,
+    /// The network id of the chain the state was dumped from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_id: Option<u64>,
     pub accounts: BTreeMap<Address, SerializableAccountRecord>,
+    /// The blocks, transactions and receipts of the chain, if dumped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<SerializableHistory>,
 }
 
 // === impl SerializableState ===
 
 impl SerializableState {
-    /// Loads the `Genesis` object from the given json file path
+    /// Creates a new state of the current version with the given accounts
+    pub fn new(accounts: BTreeMap<Address, SerializableAccountRecord>) -> Self {
+        Self { version: STATE_DUMP_VERSION, network_id: None, accounts, history: None }
+    }
+
+    /// Loads the state from the given json file path, which may be gzip compressed
     pub fn load(path: impl AsRef<Path>) -> Result<Self, FsPathError> {
         let path = path.as_ref();
-        if path.is_dir() {
-            foxar_common::fs::read_json_file(&path.join("state.json"))
+        let path = if path.is_dir() { path.join("state.json") } else { path.to_path_buf() };
+        let buf = foxar_common::fs::read(&path)?;
+        Self::decode(&buf).map_err(|source| FsPathError::ReadJson { source, path })
+    }
+
+    /// Decodes the state from json, which may be gzip compressed
+    pub fn decode(buf: &[u8]) -> serde_json::Result<Self> {
+        if buf.starts_with(&GZIP_MAGIC) {
+            serde_json::from_reader(GzDecoder::new(buf))
         } else {
-            foxar_common::fs::read_json_file(path)
+            serde_json::from_slice(buf)
         }
     }
 
+    /// Encodes the state as gzip compressed json
+    pub fn encode_gzip(&self) -> io::Result<Vec<u8>> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        serde_json::to_writer(&mut encoder, self)?;
+        encoder.flush()?;
+        encoder.finish()
+    }
+
+    /// Writes the state to the given file as gzip compressed json
+    ///
+    /// See also [Self::load()], which still reads uncompressed json
+    pub fn write(&self, path: &Path) -> Result<(), FsPathError> {
+        let buf = self.encode_gzip().map_err(|err| FsPathError::write(err, path))?;
+        foxar_common::fs::write(path, buf)
+    }
+
     /// This is used as the clap `value_parser` implementation
     pub(crate) fn parse(path: &str) -> Result<Self, String> {
         Self::load(path).map_err(|err| err.to_string())
     }
 }
 
+/// The history of the chain that's included in a [SerializableState]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializableHistory {
+    /// The total difficulty of the chain at the last block
+    pub total_difficulty: U256,
+    /// All blocks of the chain, ordered by number
+    pub blocks: Vec<Block>,
+    /// The mined transactions of the blocks, including their receipts
+    pub transactions: Vec<MinedTransaction>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SerializableAccountRecord {
     pub nonce: u64,
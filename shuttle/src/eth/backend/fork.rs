@@ -15,7 +15,10 @@ use parking_lot::{
     lock_api::{RwLockReadGuard, RwLockWriteGuard},
     RawRwLock, RwLock,
 };
-use shuttle_core::eth::{proof::AccountProof, transaction::EthTransactionRequest};
+use shuttle_core::{
+    eth::{proof::AccountProof, transaction::EthTransactionRequest},
+    types::{StorageRange, TraceResult},
+};
 use std::{collections::HashMap, sync::Arc, time::Duration};
 use tokio::sync::RwLock as AsyncRwLock;
 use tracing::trace;
@@ -347,6 +350,42 @@ impl ClientFork {
         Ok(traces)
     }
 
+    pub async fn debug_trace_block(
+        &self,
+        block: BlockId,
+        opts: GoCoreDebugTracingOptions,
+    ) -> Result<Vec<TraceResult>, ProviderError> {
+        match block {
+            BlockId::Hash(hash) => {
+                self.provider().request("debug_traceBlockByHash", (hash, opts)).await
+            }
+            BlockId::Number(number) => {
+                self.provider().request("debug_traceBlockByNumber", (number, opts)).await
+            }
+        }
+    }
+
+    pub async fn storage_range_at(
+        &self,
+        block_hash: H256,
+        tx_index: usize,
+        address: Address,
+        key_start: H256,
+        max_result: usize,
+    ) -> Result<StorageRange, ProviderError> {
+        self.provider()
+            .request("debug_storageRangeAt", (block_hash, tx_index, address, key_start, max_result))
+            .await
+    }
+
+    pub async fn raw_transaction(&self, hash: H256) -> Result<Option<Bytes>, ProviderError> {
+        self.provider().request("debug_getRawTransaction", [hash]).await
+    }
+
+    pub async fn raw_receipts(&self, block: BlockId) -> Result<Vec<Bytes>, ProviderError> {
+        self.provider().request("debug_getRawReceipts", [block]).await
+    }
+
     pub async fn transaction_receipt(
         &self,
         hash: H256,
@@ -1,8 +1,8 @@
 //! Handler that can get current storage related data
 
 use crate::mem::Backend;
-use corebc::types::{Block as EthersBlock, TxHash, H256};
-use shuttle_core::eth::{block::Block, receipt::TypedReceipt};
+use corebc::types::{Block as EthersBlock, Transaction, TxHash, H256};
+use shuttle_core::eth::{block::Block, receipt::TypedReceipt, subscription::ValueTransfer};
 use std::{fmt, sync::Arc};
 
 /// A type that can fetch data related to the ethereum storage.
@@ -42,6 +42,16 @@ impl StorageInfo {
         self.backend.get_block_by_hash(hash)
     }
 
+    /// Returns the value transfers of the transactions of the block with the given hash
+    pub fn value_transfers(&self, hash: H256) -> Option<Vec<ValueTransfer>> {
+        self.backend.mined_value_transfers(hash)
+    }
+
+    /// Returns the mined transaction with the given hash
+    pub fn transaction(&self, hash: TxHash) -> Option<Transaction> {
+        self.backend.mined_transaction_by_hash(hash)
+    }
+
     /// Returns the block with the given hash in the format of the ethereum API
     pub fn eth_block(&self, hash: H256) -> Option<EthersBlock<TxHash>> {
         let block = self.block(hash)?;
@@ -0,0 +1,305 @@
+//! Persistent chain database, used with `--data-dir`
+use crate::eth::backend::mem::storage::{BlockchainStorage, MinedTransaction};
+use corebc::prelude::{H256, U256, U64};
+use foxar_evm::executor::backend::snapshot::StateSnapshot;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use shuttle_core::eth::block::Block;
+use std::{fmt, path::Path};
+use tracing::{trace, warn};
+
+/// Key of the [ChainHead] in the meta tree
+const HEAD_KEY: &[u8] = b"head";
+/// Key of the latest state in the meta tree, this is the state after the best block, including
+/// changes made after it was mined
+const LATEST_STATE_KEY: &[u8] = b"latest_state";
+
+/// Errors that can occur while reading or writing the chain database
+#[derive(Debug, thiserror::Error)]
+pub enum ChainDbError {
+    #[error(transparent)]
+    Sled(#[from] sled::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("block {0:?} is missing from the chain database")]
+    MissingBlock(H256),
+}
+
+/// The metadata of the best block
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ChainHead {
+    best_hash: H256,
+    best_number: U64,
+    genesis_hash: H256,
+    total_difficulty: U256,
+}
+
+/// Stores blocks, transactions with their receipts and logs, and the state after every block in an
+/// embedded key-value store, so that a node can be restarted with its full history.
+///
+/// Block numbers are stored big-endian, so that the `hashes` tree iterates in block order.
+#[derive(Clone)]
+pub struct ChainDb {
+    db: sled::Db,
+    /// block hash -> block
+    blocks: sled::Tree,
+    /// block number -> block hash
+    hashes: sled::Tree,
+    /// transaction hash -> mined transaction
+    transactions: sled::Tree,
+    /// block hash -> state after the block
+    states: sled::Tree,
+    /// chain head and latest state
+    meta: sled::Tree,
+}
+
+// === impl ChainDb ===
+
+impl ChainDb {
+    /// Opens the database in the given directory, creating it if it doesn't exist yet
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ChainDbError> {
+        let path = path.as_ref();
+        trace!(target: "backend", ?path, "opening chain database");
+        let db = sled::open(path)?;
+        Ok(Self {
+            blocks: db.open_tree("blocks")?,
+            hashes: db.open_tree("hashes")?,
+            transactions: db.open_tree("transactions")?,
+            states: db.open_tree("states")?,
+            meta: db.open_tree("meta")?,
+            db,
+        })
+    }
+
+    /// Loads all blocks and transactions up to the persisted best block
+    ///
+    /// Returns `None` if no chain was persisted yet
+    pub fn load_storage(&self) -> Result<Option<BlockchainStorage>, ChainDbError> {
+        let Some(head) = get::<ChainHead>(&self.meta, HEAD_KEY)? else { return Ok(None) };
+
+        let mut storage = BlockchainStorage::empty();
+        storage.best_hash = head.best_hash;
+        storage.best_number = head.best_number;
+        storage.genesis_hash = head.genesis_hash;
+        storage.total_difficulty = head.total_difficulty;
+
+        // blocks past the head were not completely written
+        for entry in self.hashes.range(..=head.best_number.as_u64().to_be_bytes()) {
+            let (_, hash) = entry?;
+            let hash = H256::from_slice(&hash);
+            let block: Block = get(&self.blocks, hash)?.ok_or(ChainDbError::MissingBlock(hash))?;
+            for tx in block.transactions.iter() {
+                let tx_hash = tx.hash();
+                if let Some(mined) = get::<MinedTransaction>(&self.transactions, tx_hash)? {
+                    storage.transactions.insert(tx_hash, mined);
+                }
+            }
+            storage.hashes.insert(block.header.number.as_u64().into(), hash);
+            storage.blocks.insert(hash, block);
+        }
+
+        trace!(target: "backend", blocks = storage.blocks.len(), best = ?storage.best_number, "loaded chain database");
+        Ok(Some(storage))
+    }
+
+    /// Returns the state after the block with the given hash, if it was persisted
+    pub fn read_state(&self, hash: H256) -> Option<StateSnapshot> {
+        get(&self.states, hash)
+            .map_err(|err| warn!(target: "backend", ?err, ?hash, "failed to read state"))
+            .ok()
+            .flatten()
+    }
+
+    /// Returns the latest state, which includes changes made after the best block was mined
+    pub fn read_latest_state(&self) -> Option<StateSnapshot> {
+        get(&self.meta, LATEST_STATE_KEY)
+            .map_err(|err| warn!(target: "backend", ?err, "failed to read latest state"))
+            .ok()
+            .flatten()
+    }
+
+    /// Writes a block with its transactions and the state after it, makes it the chain head of the
+    /// given storage and flushes all writes to disk
+    ///
+    /// The state is kept as the state of the block and replaces the latest state.
+    pub fn write_block(
+        &self,
+        storage: &BlockchainStorage,
+        block: &Block,
+        transactions: &[MinedTransaction],
+        state: &StateSnapshot,
+    ) -> Result<(), ChainDbError> {
+        let hash = block.header.hash();
+        let number = block.header.number.as_u64();
+        for tx in transactions {
+            put(&self.transactions, tx.info.transaction_hash, tx)?;
+        }
+        put(&self.states, hash, state)?;
+        put(&self.blocks, hash, block)?;
+        self.hashes.insert(number.to_be_bytes(), hash.as_bytes())?;
+        self.flush(storage, state)
+    }
+
+    /// Replaces the persisted chain with the blocks and transactions of the given storage and
+    /// flushes it to disk
+    ///
+    /// The given state is kept as the state of the best block and as the latest state, the states
+    /// of all other blocks are removed.
+    pub fn replace_chain(
+        &self,
+        storage: &BlockchainStorage,
+        state: &StateSnapshot,
+    ) -> Result<(), ChainDbError> {
+        self.blocks.clear()?;
+        self.hashes.clear()?;
+        self.transactions.clear()?;
+        self.states.clear()?;
+        for (hash, block) in storage.blocks.iter() {
+            put(&self.blocks, hash, block)?;
+            self.hashes.insert(block.header.number.as_u64().to_be_bytes(), hash.as_bytes())?;
+        }
+        for (hash, tx) in storage.transactions.iter() {
+            put(&self.transactions, hash, tx)?;
+        }
+        put(&self.states, storage.best_hash, state)?;
+        self.flush(storage, state)
+    }
+
+    /// Removes a block, its transactions and its state
+    pub fn remove_block(&self, block: &Block) -> Result<(), ChainDbError> {
+        let hash = block.header.hash();
+        for tx in block.transactions.iter() {
+            self.transactions.remove(tx.hash())?;
+        }
+        self.states.remove(hash)?;
+        self.blocks.remove(hash)?;
+        self.hashes.remove(block.header.number.as_u64().to_be_bytes())?;
+        Ok(())
+    }
+
+    /// Makes the best block of the given storage the chain head
+    pub fn write_head(&self, storage: &BlockchainStorage) -> Result<(), ChainDbError> {
+        let head = ChainHead {
+            best_hash: storage.best_hash,
+            best_number: storage.best_number,
+            genesis_hash: storage.genesis_hash,
+            total_difficulty: storage.total_difficulty,
+        };
+        put(&self.meta, HEAD_KEY, &head)
+    }
+
+    /// Writes the chain head of the given storage and the latest state, and flushes all pending
+    /// writes to disk
+    pub fn flush(
+        &self,
+        storage: &BlockchainStorage,
+        latest_state: &StateSnapshot,
+    ) -> Result<(), ChainDbError> {
+        self.write_head(storage)?;
+        put(&self.meta, LATEST_STATE_KEY, latest_state)?;
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ChainDb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChainDb").field("blocks", &self.blocks.len()).finish_non_exhaustive()
+    }
+}
+
+/// Reads and deserializes the value of the given key
+fn get<T: DeserializeOwned>(
+    tree: &sled::Tree,
+    key: impl AsRef<[u8]>,
+) -> Result<Option<T>, ChainDbError> {
+    Ok(tree.get(key)?.map(|value| serde_json::from_slice(&value)).transpose()?)
+}
+
+/// Serializes and writes the value of the given key
+fn put<T: Serialize + ?Sized>(
+    tree: &sled::Tree,
+    key: impl AsRef<[u8]>,
+    value: &T,
+) -> Result<(), ChainDbError> {
+    tree.insert(key, serde_json::to_vec(value)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use corebc::types::Address;
+    use foxar_evm::revm::primitives::{AccountInfo, U256 as rU256};
+    use shuttle_core::eth::{block::PartialHeader, transaction::MaybeImpersonatedTransaction};
+
+    #[test]
+    fn can_reopen_chain_db() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let genesis = Block::new::<MaybeImpersonatedTransaction>(
+            PartialHeader { timestamp: 1337, ..Default::default() },
+            vec![],
+            vec![],
+        );
+        let genesis_hash = genesis.header.hash();
+        let mut storage = BlockchainStorage::empty();
+        storage.best_hash = genesis_hash;
+        storage.genesis_hash = genesis_hash;
+        storage.hashes.insert(0u64.into(), genesis_hash);
+
+        let mut state = StateSnapshot::default();
+        state.accounts.insert(Address::random().into(), AccountInfo::from_balance(rU256::from(1337)));
+
+        {
+            let chain_db = ChainDb::open(dir.path()).unwrap();
+            assert!(chain_db.load_storage().unwrap().is_none());
+            chain_db.write_block(&storage, &genesis, &[], &state).unwrap();
+        }
+
+        let chain_db = ChainDb::open(dir.path()).unwrap();
+        let loaded = chain_db.load_storage().unwrap().unwrap();
+        assert_eq!(loaded.best_hash, genesis_hash);
+        assert_eq!(loaded.blocks.get(&genesis_hash).unwrap().header.timestamp, 1337);
+        assert_eq!(chain_db.read_state(genesis_hash).unwrap().accounts.len(), 1);
+        assert!(chain_db.read_latest_state().is_some());
+    }
+
+    #[test]
+    fn keeps_state_of_every_block() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut storage = BlockchainStorage::empty();
+
+        let mut hashes = vec![];
+        {
+            let chain_db = ChainDb::open(dir.path()).unwrap();
+            for number in 0..40u64 {
+                let block = Block::new::<MaybeImpersonatedTransaction>(
+                    PartialHeader { number: number.into(), ..Default::default() },
+                    vec![],
+                    vec![],
+                );
+                let hash = block.header.hash();
+                storage.best_hash = hash;
+                storage.best_number = number.into();
+                storage.hashes.insert(number.into(), hash);
+
+                let mut state = StateSnapshot::default();
+                state.accounts.insert(
+                    Address::random().into(),
+                    AccountInfo::from_balance(rU256::from(number)),
+                );
+                chain_db.write_block(&storage, &block, &[], &state).unwrap();
+                hashes.push(hash);
+            }
+        }
+
+        // the states of all blocks are available after a restart
+        let chain_db = ChainDb::open(dir.path()).unwrap();
+        for (number, hash) in hashes.iter().enumerate() {
+            let state = chain_db.read_state(*hash).unwrap();
+            assert_eq!(state.accounts.values().next().unwrap().balance, rU256::from(number));
+        }
+        let latest = chain_db.read_latest_state().unwrap();
+        assert_eq!(latest.accounts.values().next().unwrap().balance, rU256::from(39));
+    }
+}
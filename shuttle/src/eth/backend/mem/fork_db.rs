@@ -7,9 +7,12 @@ use crate::{
 };
 use corebc::prelude::H256;
 pub use foxar_evm::executor::fork::database::ForkedDatabase;
-use foxar_evm::executor::{
-    backend::{snapshot::StateSnapshot, DatabaseResult},
-    fork::database::ForkDbSnapshot,
+use foxar_evm::{
+    executor::{
+        backend::{snapshot::StateSnapshot, DatabaseResult},
+        fork::{database::ForkDbSnapshot, SharedBackend},
+    },
+    revm::db::CacheDB,
 };
 use foxar_utils::types::{ToEthersU256, ToRuint};
 use spark::revm::Database;
@@ -59,7 +62,7 @@ impl Db for ForkedDatabase {
                 ))
             })
             .collect::<Result<_, _>>()?;
-        Ok(Some(SerializableState { accounts }))
+        Ok(Some(SerializableState::new(accounts)))
     }
 
     fn snapshot(&mut self) -> U256 {
@@ -73,6 +76,10 @@ impl Db for ForkedDatabase {
     fn current_state(&self) -> StateDb {
         StateDb::new(self.create_snapshot())
     }
+
+    fn restore_fork_local(&mut self, local: CacheDB<SharedBackend>) {
+        *self.database_mut() = local;
+    }
 }
 
 impl MaybeHashDatabase for ForkedDatabase {
@@ -98,6 +105,10 @@ impl MaybeHashDatabase for ForkedDatabase {
     }
 }
 impl MaybeHashDatabase for ForkDbSnapshot {
+    fn maybe_fork_local(&self) -> Option<CacheDB<SharedBackend>> {
+        Some(self.local.clone())
+    }
+
     fn clear_into_snapshot(&mut self) -> StateSnapshot {
         std::mem::take(&mut self.snapshot)
     }
@@ -10,13 +10,16 @@ use crate::{
 };
 use corebc::prelude::H256;
 use foxar_utils::types::{ToEthersU256, ToRuint};
-use spark::utils::h176_to_b176;
+use spark::{revm::primitives::U256 as rU256, utils::h176_to_b176};
 use tracing::{trace, warn};
 
 // reexport for convenience
 use crate::mem::state::storage_trie_db;
-use foxar_evm::executor::backend::{snapshot::StateSnapshot, DatabaseResult};
 pub use foxar_evm::executor::{backend::MemDb, DatabaseRef};
+use foxar_evm::{
+    executor::backend::{snapshot::StateSnapshot, DatabaseResult},
+    HashMap,
+};
 
 impl Db for MemDb {
     fn insert_account(&mut self, address: Address, account: AccountInfo) {
@@ -60,7 +63,7 @@ impl Db for MemDb {
             })
             .collect::<Result<_, _>>()?;
 
-        Ok(Some(SerializableState { accounts }))
+        Ok(Some(SerializableState::new(accounts)))
     }
 
     /// Creates a new snapshot
@@ -103,6 +106,16 @@ impl MaybeHashDatabase for MemDb {
         }
     }
 
+    fn maybe_account_storage(&self, addr: Address) -> Option<HashMap<rU256, rU256>> {
+        let storage = self
+            .inner
+            .accounts
+            .get(&h176_to_b176(addr))
+            .map(|acc| acc.storage.clone())
+            .unwrap_or_default();
+        Some(storage)
+    }
+
     fn clear_into_snapshot(&mut self) -> StateSnapshot {
         self.inner.clear_into_snapshot()
     }
@@ -4,12 +4,18 @@ use crate::{
     eth::{
         backend::{
             cheats::CheatsManager,
-            db::{AsHashDB, Db, MaybeHashDatabase, SerializableState},
+            db::{
+                AsHashDB, Db, MaybeHashDatabase, SerializableAccountRecord, SerializableHistory,
+                SerializableState, STATE_DUMP_VERSION,
+            },
             executor::{ExecutedTransactions, TransactionExecutor},
             fork::ClientFork,
             genesis::GenesisConfig,
             mem::storage::MinedTransactionReceipt,
-            notifications::{NewBlockNotification, NewBlockNotifications},
+            notifications::{
+                ChainNotification, ChainNotifications, NewBlockNotification, NewBlockNotifications,
+                RemovedBlock,
+            },
             time::{utc_from_secs, TimeManager},
             validate::TransactionValidator,
         },
@@ -19,35 +25,45 @@ use crate::{
         pool::transactions::PoolTransaction,
         util::get_precompiles_for,
     },
+    genesis::{self as genesis_json, Alloc, Genesis},
     mem::{
+        chain_db::ChainDb,
         inspector::Inspector,
-        storage::{BlockchainStorage, InMemoryBlockStates, MinedBlockOutcome},
+        storage::{BlockchainStorage, InMemoryBlockStates, MinedBlockOutcome, ReorgOutcome},
+        tracers::NativeTracer,
     },
     revm::{
         db::DatabaseRef,
         primitives::{AccountInfo, U256 as rU256},
     },
+    HardforkSchedule,
 };
 use corebc::{
     abi::ethereum_types::BigEndianHash,
     prelude::{BlockNumber, GoCoreTraceFrame, TxHash, H256, U256, U64},
     types::{
-        Address, Block as EthersBlock, BlockId, Bytes, DefaultFrame, Filter, FilteredParams,
-        GoCoreDebugTracingOptions, GoCoreTrace, Log, Trace, Transaction, TransactionReceipt, H176,
+        Address, Block as EthersBlock, BlockId, BlockTrace, Bytes, DefaultFrame, Filter,
+        FilteredParams, GoCoreDebugTracingOptions, GoCoreTrace, Log, Trace, TraceType, Transaction,
+        TransactionReceipt, H176,
     },
     utils::{hex, rlp, sha3},
 };
-use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use ethereum_forkid::ForkId;
 use foxar_evm::{
     decode::{decode_custom_error_args, decode_revert},
     executor::backend::{DatabaseError, DatabaseResult},
     revm::{
         db::CacheDB,
         interpreter::InstructionResult,
-        primitives::{Account, CreateScheme, Env, Output, SpecId, TransactTo, TxEnv, SHA3_EMPTY},
-        EVM,
+        primitives::{
+            Account, CreateScheme, Env, Output, ResultAndState, SpecId, TransactTo, TxEnv,
+            SHA3_EMPTY,
+        },
+        DatabaseCommit, EVM,
     },
+    trace::{node::CallTraceNode, CallTraceArena},
     utils::u256_to_h256_be,
+    CallKind,
 };
 use foxar_utils::types::*;
 use futures::channel::mpsc::{unbounded, UnboundedSender};
@@ -58,14 +74,16 @@ use shuttle_core::{
         block::{Block, BlockInfo, Header},
         proof::{AccountProof, BasicAccount, StorageProof},
         receipt::{EIP658Receipt, TypedReceipt},
+        simulate::{BlockOverrides, SimulatePayload, SimulatedBlock, SimulatedCall},
         state::StateOverride,
+        subscription::ValueTransfer,
         transaction::{
             EthTransactionRequest, MaybeImpersonatedTransaction, PendingTransaction,
             TransactionInfo,
         },
         trie::RefTrieDB,
     },
-    types::{Forking, Index},
+    types::{DumpStateOptions, Forking, Index, StorageRange, TraceFilter, TraceResult},
 };
 use shuttle_rpc::error::RpcError;
 use spark::{
@@ -77,8 +95,7 @@ use spark::{
     utils::{eval_to_instruction_result, halt_to_instruction_result},
 };
 use std::{
-    collections::HashMap,
-    io::{Read, Write},
+    collections::{BTreeMap, HashMap, HashSet},
     ops::Deref,
     sync::Arc,
     time::Duration,
@@ -89,11 +106,15 @@ use tracing::{trace, warn};
 use trie_db::{Recorder, Trie};
 
 pub mod cache;
+pub mod chain_db;
 pub mod fork_db;
 pub mod in_memory_db;
 pub mod inspector;
+pub mod otterscan;
+pub mod parity;
 pub mod state;
 pub mod storage;
+pub mod tracers;
 
 // Gas per transaction not creating a contract.
 pub const MIN_TRANSACTION_GAS: U256 = U256([21_000, 0, 0, 0]);
@@ -118,6 +139,15 @@ impl BlockRequest {
     }
 }
 
+/// A mined transaction that was executed again on top of the state before it
+pub struct ReplayedTransaction {
+    pub hash: H256,
+    pub result: ExecutionResult,
+    /// All accounts the transaction touched, with their state after the transaction
+    pub changes: State,
+    pub traces: CallTraceArena,
+}
+
 /// Gives access to the [revm::Database]
 #[derive(Clone)]
 pub struct Backend {
@@ -156,8 +186,12 @@ pub struct Backend {
     fees: FeeManager,
     /// initialised genesis
     genesis: GenesisConfig,
+    /// the hardforks and when they activate
+    hardforks: HardforkSchedule,
     /// listeners for new blocks that get notified when a new block was imported
     new_block_listeners: Arc<Mutex<Vec<UnboundedSender<NewBlockNotification>>>>,
+    /// listeners that get notified about new and removed blocks
+    chain_listeners: Arc<Mutex<Vec<UnboundedSender<ChainNotification>>>>,
     /// keeps track of active snapshots at a specific block
     active_snapshots: Arc<Mutex<HashMap<U256, (u64, H256)>>>,
     enable_steps_tracing: bool,
@@ -165,6 +199,9 @@ pub struct Backend {
     prune_state_history_config: PruneStateHistoryConfig,
     /// max number of blocks with transactions in memory
     transaction_block_keeper: Option<usize>,
+    /// persists the blocks, transactions and the state after every block if `--data-dir` is
+    /// configured
+    chain_db: Option<ChainDb>,
 }
 
 impl Backend {
@@ -174,25 +211,59 @@ impl Backend {
         db: Arc<AsyncRwLock<dyn Db>>,
         env: Arc<RwLock<Env>>,
         genesis: GenesisConfig,
+        hardforks: HardforkSchedule,
         fees: FeeManager,
         fork: Option<ClientFork>,
         enable_steps_tracing: bool,
         prune_state_history_config: PruneStateHistoryConfig,
         transaction_block_keeper: Option<usize>,
         automine_block_time: Option<Duration>,
+        chain_db: Option<ChainDb>,
     ) -> Self {
+        // resume the chain if it was persisted by a previous run
+        let persisted = chain_db.as_ref().and_then(|chain_db| {
+            chain_db.load_storage().expect("Failed to load chain from the data dir")
+        });
+        let persisted_head = persisted
+            .as_ref()
+            .and_then(|storage| storage.blocks.get(&storage.best_hash))
+            .map(|block| (block.header.number, block.header.timestamp));
+
         // if this is a fork then adjust the blockchain storage
-        let blockchain = if let Some(ref fork) = fork {
+        let blockchain = if let Some(storage) = persisted {
+            trace!(target: "backend", "resuming persisted blockchain at {}", storage.best_number);
+            Blockchain { storage: Arc::new(RwLock::new(storage)) }
+        } else if let Some(ref fork) = fork {
             trace!(target: "backend", "using forked blockchain at {}", fork.block_number());
             Blockchain::forked(fork.block_number(), fork.block_hash(), fork.total_difficulty())
         } else {
             Blockchain::new(&env.read(), genesis.timestamp)
         };
 
-        let start_timestamp =
-            if let Some(fork) = fork.as_ref() { fork.timestamp() } else { genesis.timestamp };
+        let start_timestamp = if let Some((_, timestamp)) = persisted_head {
+            timestamp
+        } else if let Some(fork) = fork.as_ref() {
+            fork.timestamp()
+        } else {
+            genesis.timestamp
+        };
+
+        if let Some((number, timestamp)) = persisted_head {
+            let mut env = env.write();
+            env.block.number = number.to_ruint();
+            env.block.timestamp = rU256::from(timestamp);
+        }
+
+        {
+            // the rules of the head the chain starts at
+            let mut env = env.write();
+            let number = env.block.number.to_ethers_u256().as_u64();
+            env.cfg.spec_id = hardforks.spec_id_at(number, start_timestamp);
+        }
 
-        let states = if prune_state_history_config.is_config_enabled() {
+        let states = if let Some(ref chain_db) = chain_db {
+            InMemoryBlockStates::default().with_chain_db(chain_db.clone())
+        } else if prune_state_history_config.is_config_enabled() {
             // if prune state history is enabled, configure the state cache only for memory
             prune_state_history_config
                 .max_memory_history
@@ -212,12 +283,15 @@ impl Backend {
             time: TimeManager::new(start_timestamp),
             cheats: Default::default(),
             new_block_listeners: Default::default(),
+            chain_listeners: Default::default(),
             fees,
             genesis,
+            hardforks,
             active_snapshots: Arc::new(Mutex::new(Default::default())),
             enable_steps_tracing,
             prune_state_history_config,
             transaction_block_keeper,
+            chain_db,
         };
 
         if let Some(interval_block_time) = automine_block_time {
@@ -226,9 +300,56 @@ impl Backend {
 
         // Note: this can only fail in forking mode, in which case we can't recover
         backend.apply_genesis().await.expect("Failed to create genesis");
+
+        if persisted_head.is_some() {
+            backend.restore_persisted_state().await;
+        } else {
+            backend.persist_block(backend.best_hash()).await;
+        }
+
         backend
     }
 
+    /// Replaces the genesis state with the latest state persisted in the chain database
+    async fn restore_persisted_state(&self) {
+        let Some(chain_db) = self.chain_db.as_ref() else { return };
+        let best_hash = self.best_hash();
+        let snapshot = chain_db
+            .read_latest_state()
+            .or_else(|| chain_db.read_state(best_hash))
+            .expect("Failed to load state from the data dir");
+        let mut db = self.db.write().await;
+        db.clear();
+        db.init_from_snapshot(snapshot);
+    }
+
+    /// Writes the block with the given hash, its transactions and the current state to the chain
+    /// database and flushes it to disk, if `--data-dir` is configured
+    async fn persist_block(&self, block_hash: H256) {
+        let Some(chain_db) = self.chain_db.as_ref() else { return };
+        let state = self.db.read().await.current_state().clear_into_snapshot();
+        let storage = self.blockchain.storage.read();
+        let Some(block) = storage.blocks.get(&block_hash) else { return };
+        let transactions = block
+            .transactions
+            .iter()
+            .filter_map(|tx| storage.transactions.get(&tx.hash()).cloned())
+            .collect::<Vec<_>>();
+        if let Err(err) = chain_db.write_block(&storage, block, &transactions, &state) {
+            warn!(target: "backend", ?err, ?block_hash, "failed to persist block");
+        }
+    }
+
+    /// Writes the chain head and the current state to the chain database and flushes it to disk,
+    /// if `--data-dir` is configured
+    pub async fn flush_chain_db(&self) {
+        let Some(chain_db) = self.chain_db.as_ref() else { return };
+        let state = self.db.read().await.current_state().clear_into_snapshot();
+        if let Err(err) = chain_db.flush(&self.blockchain.storage.read(), &state) {
+            warn!(target: "backend", ?err, "failed to flush chain database");
+        }
+    }
+
     /// Updates memory limits that should be more strict when auto-mine is enabled
     pub(crate) fn update_interval_mine_block_time(&self, block_time: Duration) {
         self.states.write().update_interval_mine_block_time(block_time)
@@ -339,6 +460,8 @@ impl Backend {
         if let Some(fork) = self.get_fork() {
             let block_number =
                 forking.block_number.map(BlockNumber::from).unwrap_or(BlockNumber::Latest);
+            // all blocks after the current fork block were mined locally
+            let first_local_block = fork.block_number();
             // reset the fork entirely and reapply the genesis config
             fork.reset(forking.json_rpc_url.clone(), block_number).await?;
             let fork_block_number = fork.block_number();
@@ -367,12 +490,18 @@ impl Backend {
             }
 
             // reset storage
-            *self.blockchain.storage.write() = BlockchainStorage::forked(
-                fork.block_number(),
-                fork.block_hash(),
-                fork.total_difficulty(),
-            );
+            let removed = {
+                let mut storage = self.blockchain.storage.write();
+                let removed = removed_blocks(&storage, first_local_block);
+                *storage = BlockchainStorage::forked(
+                    fork.block_number(),
+                    fork.block_hash(),
+                    fork.total_difficulty(),
+                );
+                removed
+            };
             self.states.write().clear();
+            self.notify_on_removed_blocks(removed);
 
             // insert back all genesis accounts, by reusing cached `AccountInfo`s we don't need to
             // fetch the data via RPC again
@@ -439,6 +568,31 @@ impl Backend {
         self.env.read().block.coinbase.into()
     }
 
+    /// Returns the hardforks of the chain and when they activate
+    pub fn hardforks(&self) -> &HardforkSchedule {
+        &self.hardforks
+    }
+
+    /// Returns the [EIP-2124](https://eips.ethereum.org/EIPS/eip-2124) fork id of the current head
+    pub fn fork_id(&self) -> ForkId {
+        let (genesis_hash, genesis_timestamp) = {
+            let storage = self.blockchain.storage.read();
+            let genesis_timestamp = storage
+                .blocks
+                .get(&storage.genesis_hash)
+                .map(|genesis| genesis.header.timestamp)
+                .unwrap_or(self.genesis.timestamp);
+            (storage.genesis_hash, genesis_timestamp)
+        };
+        let env = self.env.read();
+        self.hardforks.fork_id(
+            genesis_hash,
+            genesis_timestamp,
+            env.block.number.to_ethers_u256().as_u64(),
+            env.block.timestamp.to_ethers_u256().as_u64(),
+        )
+    }
+
     /// Returns the client networkId
     pub fn chain_id(&self) -> U256 {
         U256::from(self.env.read().cfg.network_id)
@@ -537,82 +691,381 @@ impl Backend {
     pub async fn revert_snapshot(&self, id: U256) -> Result<bool, BlockchainError> {
         let block = { self.active_snapshots.lock().remove(&id) };
         if let Some((num, hash)) = block {
-            let best_block_hash = {
-                // revert the storage that's newer than the snapshot
-                let current_height = self.best_number().as_u64();
-                let mut storage = self.blockchain.storage.write();
-
-                for n in ((num + 1)..=current_height).rev() {
-                    trace!(target: "backend", "reverting block {}", n);
-                    let n: U64 = n.into();
-                    if let Some(hash) = storage.hashes.remove(&n) {
-                        if let Some(block) = storage.blocks.remove(&hash) {
-                            for tx in block.transactions {
-                                let _ = storage.transactions.remove(&tx.hash());
-                            }
-                        }
-                    }
-                }
-
-                storage.best_number = num.into();
-                storage.best_hash = hash;
-                hash
-            };
-            let block =
-                self.block_by_hash(best_block_hash).await?.ok_or(BlockchainError::BlockNotFound)?;
+            // revert the storage that's newer than the snapshot
+            let removed = self.remove_blocks_after(num, hash);
+            let block = self.block_by_hash(hash).await?.ok_or(BlockchainError::BlockNotFound)?;
 
             // Note: In [`TimeManager::compute_next_timestamp`] we ensure that the next timestamp is
             // always increasing by at least one. By subtracting 1 here, this is mitigated.
             let reset_time = block.timestamp.as_u64().saturating_sub(1);
             self.time.reset(reset_time);
             self.set_block_number(num.into());
+            self.env.write().cfg.spec_id = self.hardforks.spec_id_at(num, block.timestamp.as_u64());
+            self.notify_on_removed_blocks(removed);
         }
         Ok(self.db.write().await.revert(id))
     }
 
+    /// Returns the number of the block that becomes the best block if the most recent `depth`
+    /// blocks are removed
+    ///
+    /// Only blocks that were mined locally can be removed.
+    pub fn rollback_target(&self, depth: u64) -> Result<u64, BlockchainError> {
+        let best_number = self.best_number().as_u64();
+        let first_block = self.get_fork().map(|fork| fork.block_number()).unwrap_or_default();
+        let mined = best_number.saturating_sub(first_block);
+        if depth == 0 || depth > mined {
+            return Err(RpcError::invalid_params(format!(
+                "depth must be between 1 and {mined}, the number of mined blocks"
+            ))
+            .into())
+        }
+        Ok(best_number - depth)
+    }
+
+    /// Removes the most recent `depth` blocks and restores the state of the new best block
+    ///
+    /// Unlike [Backend::revert_snapshot] this doesn't reset the time, so the blocks that are mined
+    /// afterwards differ from the removed blocks.
+    pub async fn rollback(&self, depth: u64) -> Result<(), BlockchainError> {
+        let number = self.rollback_target(depth)?;
+        let hash = self
+            .blockchain
+            .storage
+            .read()
+            .hashes
+            .get(&number.into())
+            .copied()
+            .ok_or(BlockchainError::BlockNotFound)?;
+        let state_unavailable =
+            || RpcError::invalid_params(format!("the state of block {number} is not available"));
+        let (snapshot, fork_local) = {
+            let mut states = self.states.write();
+            let snapshot = states.snapshot(&hash).ok_or_else(state_unavailable)?;
+            (snapshot, states.get(&hash).and_then(|state| state.maybe_fork_local()))
+        };
+        // the state of a fork is split into the fetched remote state and the local changes, both
+        // need to be restored
+        if self.is_fork() && fork_local.is_none() {
+            return Err(state_unavailable().into())
+        }
+        let block = self.block_by_hash(hash).await?.ok_or(BlockchainError::BlockNotFound)?;
+
+        {
+            let mut db = self.db.write().await;
+            db.clear();
+            db.init_from_snapshot(snapshot);
+            if let Some(local) = fork_local {
+                db.restore_fork_local(local);
+            }
+        }
+        let removed = self.remove_blocks_after(number, hash);
+
+        // snapshots of removed blocks can't be reverted to anymore
+        self.active_snapshots.lock().retain(|_, (snapshot_number, _)| *snapshot_number <= number);
+        self.set_block_number(number.into());
+        self.env.write().cfg.spec_id = self.hardforks.spec_id_at(number, block.timestamp.as_u64());
+        self.notify_on_removed_blocks(removed);
+        // the persisted latest state is the state of a removed block
+        self.flush_chain_db().await;
+        Ok(())
+    }
+
+    /// Replaces the most recent `depth` blocks with new blocks, one for each of the given lists of
+    /// transactions
+    ///
+    /// Returns the transactions of the removed blocks that are not part of the new blocks, and the
+    /// transactions that were invalid and not included in the new blocks.
+    pub async fn reorg(
+        &self,
+        depth: u64,
+        blocks: Vec<Vec<Arc<PoolTransaction>>>,
+    ) -> Result<ReorgOutcome, BlockchainError> {
+        let number = self.rollback_target(depth)?;
+        let removed = {
+            let storage = self.blockchain.storage.read();
+            ((number + 1)..=storage.best_number.as_u64())
+                .filter_map(|n| storage.hashes.get(&n.into()))
+                .filter_map(|hash| storage.blocks.get(hash).cloned())
+                .collect::<Vec<_>>()
+        };
+        let mut orphaned = Vec::new();
+        for block in removed.iter() {
+            orphaned.extend(self.mined_pending_transactions(block)?);
+        }
+
+        self.rollback(depth).await?;
+        let mut included = HashSet::new();
+        let mut invalid = Vec::new();
+        for transactions in blocks {
+            let outcome = self.mine_block(transactions).await;
+            included.extend(outcome.included.iter().map(|tx| *tx.hash()));
+            invalid.extend(outcome.invalid.iter().map(|tx| *tx.hash()));
+        }
+        orphaned.retain(|tx| !included.contains(tx.hash()));
+
+        Ok(ReorgOutcome { orphaned, invalid })
+    }
+
+    /// Removes the blocks after the given block and their states from the storage and makes it
+    /// the best block
+    ///
+    /// Returns the removed blocks, starting with the most recent block.
+    fn remove_blocks_after(&self, number: u64, hash: H256) -> Vec<RemovedBlock> {
+        let current_height = self.best_number().as_u64();
+        // the states are locked first, like in `with_database_at`
+        let mut states = self.states.write();
+        let mut storage = self.blockchain.storage.write();
+        let removed = removed_blocks(&storage, number);
+
+        for n in ((number + 1)..=current_height).rev() {
+            trace!(target: "backend", "reverting block {}", n);
+            let n: U64 = n.into();
+            if let Some(hash) = storage.hashes.remove(&n) {
+                states.remove(&hash);
+                if let Some(block) = storage.blocks.remove(&hash) {
+                    if let Some(chain_db) = self.chain_db.as_ref() {
+                        if let Err(err) = chain_db.remove_block(&block) {
+                            warn!(target: "backend", ?err, "failed to remove reverted block");
+                        }
+                    }
+                    for tx in block.transactions {
+                        let _ = storage.transactions.remove(&tx.hash());
+                    }
+                }
+            }
+        }
+
+        storage.best_number = number.into();
+        storage.best_hash = hash;
+        if let Some(chain_db) = self.chain_db.as_ref() {
+            if let Err(err) = chain_db.write_head(&storage) {
+                warn!(target: "backend", ?err, "failed to persist reverted chain head");
+            }
+        }
+        removed
+    }
+
     /// Get the current state.
-    pub async fn serialized_state(&self) -> Result<SerializableState, BlockchainError> {
-        let state = self.db.read().await.dump_state()?;
-        state.ok_or_else(|| {
+    ///
+    /// If set, only the given accounts are included, accounts of a fork that haven't been
+    /// fetched yet are included without their storage.
+    pub async fn serialized_state(
+        &self,
+        options: &DumpStateOptions,
+    ) -> Result<SerializableState, BlockchainError> {
+        let db = self.db.read().await;
+        let mut state = db.dump_state()?.ok_or_else(|| -> BlockchainError {
             RpcError::invalid_params("Dumping state not supported with the current configuration")
                 .into()
-        })
+        })?;
+        state.network_id = Some(self.env.read().cfg.network_id);
+
+        if let Some(addresses) = options.addresses.as_ref() {
+            let mut accounts = BTreeMap::new();
+            for address in addresses {
+                if let Some(record) = state.accounts.remove(address) {
+                    accounts.insert(*address, record);
+                    continue
+                }
+                let Some(info) = db.basic((*address).into())? else { continue };
+                let code = match info.code {
+                    Some(code) => code,
+                    None => db.code_by_hash(info.code_hash)?,
+                }
+                .to_checked();
+                let record = SerializableAccountRecord {
+                    nonce: info.nonce,
+                    balance: info.balance.to_ethers_u256(),
+                    code: code.bytes()[..code.len()].to_vec().into(),
+                    storage: Default::default(),
+                };
+                accounts.insert(*address, record);
+            }
+            state.accounts = accounts;
+        }
+
+        if options.include_history {
+            let storage = self.blockchain.storage.read();
+            let mut numbers = storage.hashes.keys().copied().collect::<Vec<_>>();
+            numbers.sort_unstable();
+            let mut blocks = Vec::with_capacity(numbers.len());
+            let mut transactions = Vec::new();
+            for number in numbers {
+                let Some(block) =
+                    storage.hashes.get(&number).and_then(|hash| storage.blocks.get(hash))
+                else {
+                    continue
+                };
+                transactions.extend(
+                    block
+                        .transactions
+                        .iter()
+                        .filter_map(|tx| storage.transactions.get(&tx.hash()).cloned()),
+                );
+                blocks.push(block.clone());
+            }
+            state.history = Some(SerializableHistory {
+                total_difficulty: storage.total_difficulty,
+                blocks,
+                transactions,
+            });
+        }
+        Ok(state)
     }
 
     /// Write all chain data to serialized bytes buffer
-    pub async fn dump_state(&self) -> Result<Bytes, BlockchainError> {
-        let state = self.serialized_state().await?;
-        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
-        encoder
-            .write_all(&serde_json::to_vec(&state).unwrap_or_default())
-            .map_err(|_| BlockchainError::DataUnavailable)?;
-        Ok(encoder.finish().unwrap_or_default().into())
+    pub async fn dump_state(&self, options: &DumpStateOptions) -> Result<Bytes, BlockchainError> {
+        let state = self.serialized_state(options).await?;
+        let buf = state.encode_gzip().map_err(|_| BlockchainError::DataUnavailable)?;
+        Ok(buf.into())
     }
 
-    /// Deserialize and add all chain data to the backend storage
-    pub async fn load_state(&self, buf: Bytes) -> Result<bool, BlockchainError> {
-        let orig_buf = &buf.0[..];
-        let mut decoder = GzDecoder::new(orig_buf);
-        let mut decoded_data = Vec::new();
-
-        let state: SerializableState = serde_json::from_slice(if decoder.header().is_some() {
-            decoder
-                .read_to_end(decoded_data.as_mut())
-                .map_err(|_| BlockchainError::FailedToDecodeStateDump)?;
-            &decoded_data
-        } else {
-            &buf.0
+    /// Returns the current state and chain configuration as a `genesis.json`, that a new chain can
+    /// be started from
+    pub async fn dump_genesis(&self) -> Result<Genesis, BlockchainError> {
+        let state = self.serialized_state(&Default::default()).await?;
+        let timestamp = {
+            let storage = self.blockchain.storage.read();
+            storage.blocks.get(&storage.best_hash).map(|block| block.header.timestamp)
+        };
+        let env = self.env.read();
+        Ok(Genesis {
+            config: Some(genesis_json::Config::new(env.cfg.network_id, &self.hardforks)),
+            nonce: None,
+            timestamp: Some(
+                timestamp.unwrap_or_else(|| env.block.timestamp.try_into().unwrap_or(u64::MAX)),
+            ),
+            extra_data: None,
+            energy_limit: env.block.energy_limit.try_into().unwrap_or(u64::MAX),
+            difficulty: env.block.difficulty.try_into().unwrap_or(u64::MAX),
+            coinbase: Some(env.block.coinbase.into()),
+            alloc: Alloc {
+                accounts: state
+                    .accounts
+                    .into_iter()
+                    .map(|(address, record)| (address, record.into()))
+                    .collect(),
+            },
+            number: None,
+            energy_used: None,
+            parent_hash: None,
         })
-        .map_err(|_| BlockchainError::FailedToDecodeStateDump)?;
+    }
 
-        if !self.db.write().await.load_state(state)? {
-            Err(RpcError::invalid_params(
+    /// Deserialize and add all chain data to the backend storage
+    ///
+    /// Returns the accounts of the state that conflicted with the current state.
+    pub async fn load_state(&self, buf: Bytes) -> Result<Vec<Address>, BlockchainError> {
+        let state = SerializableState::decode(&buf.0)
+            .map_err(|_| BlockchainError::FailedToDecodeStateDump)?;
+        self.load_serialized_state(state).await
+    }
+
+    /// Returns the accounts of the serialized state that conflict with the current state, without
+    /// loading it
+    pub async fn state_conflicts(&self, buf: Bytes) -> Result<Vec<Address>, BlockchainError> {
+        let state = SerializableState::decode(&buf.0)
+            .map_err(|_| BlockchainError::FailedToDecodeStateDump)?;
+        self.ensure_state_compatible(&state)?;
+        Ok(self.db.read().await.conflicting_accounts(&state)?)
+    }
+
+    /// Adds the state to the backend, and replaces the chain with its history if included
+    ///
+    /// Returns the accounts of the state that conflicted with the current state.
+    pub async fn load_serialized_state(
+        &self,
+        mut state: SerializableState,
+    ) -> Result<Vec<Address>, BlockchainError> {
+        self.ensure_state_compatible(&state)?;
+        let history = state.history.take();
+
+        let mut db = self.db.write().await;
+        let conflicts = db.conflicting_accounts(&state)?;
+        if !db.load_state(state)? {
+            return Err(RpcError::invalid_params(
                 "Loading state not supported with the current configuration",
             )
             .into())
-        } else {
-            Ok(true)
         }
+
+        let mut loaded_best_hash = None;
+        if let Some(history) = history.filter(|history| !history.blocks.is_empty()) {
+            let best = &history.blocks[history.blocks.len() - 1].header;
+            let (best_hash, best_number, timestamp) = (best.hash(), best.number, best.timestamp);
+            let mut storage = self.blockchain.storage.write();
+            storage.genesis_hash = history.blocks[0].header.hash();
+            storage.blocks.clear();
+            storage.hashes.clear();
+            storage.transactions.clear();
+            for block in history.blocks {
+                let hash = block.header.hash();
+                db.insert_block_hash(block.header.number, hash);
+                storage.hashes.insert(block.header.number.as_u64().into(), hash);
+                storage.blocks.insert(hash, block);
+            }
+            for tx in history.transactions {
+                storage.transactions.insert(tx.info.transaction_hash, tx);
+            }
+            storage.best_hash = best_hash;
+            storage.best_number = best_number.as_u64().into();
+            storage.total_difficulty = history.total_difficulty;
+
+            let mut env = self.env.write();
+            env.block.number = best_number.to_ruint();
+            env.block.timestamp = rU256::from(timestamp);
+            env.cfg.spec_id = self.hardforks.spec_id_at(best_number.as_u64(), timestamp);
+            self.time.reset(timestamp);
+            trace!(target: "backend", blocks = storage.blocks.len(), best = ?best_number, "loaded chain history");
+            loaded_best_hash = Some(best_hash);
+        }
+
+        if let Some(best_hash) = loaded_best_hash {
+            // the dump only includes the state after the best block, the states of the replaced
+            // blocks are no longer valid
+            if self.prune_state_history_config.is_state_history_supported() {
+                let mut states = self.states.write();
+                states.clear();
+                states.insert(best_hash, db.current_state());
+            }
+            if let Some(chain_db) = self.chain_db.as_ref() {
+                let state = db.current_state().clear_into_snapshot();
+                if let Err(err) = chain_db.replace_chain(&self.blockchain.storage.read(), &state) {
+                    warn!(target: "backend", ?err, "failed to persist loaded chain history");
+                }
+            }
+        }
+
+        if !conflicts.is_empty() {
+            warn!(target: "backend", ?conflicts, "loaded state conflicts with existing accounts");
+        }
+        Ok(conflicts)
+    }
+
+    /// Checks that the serialized state can be loaded into this chain
+    fn ensure_state_compatible(&self, state: &SerializableState) -> Result<(), BlockchainError> {
+        if state.version > STATE_DUMP_VERSION {
+            return Err(BlockchainError::IncompatibleStateDump(format!(
+                "version {} is newer than the supported version {STATE_DUMP_VERSION}",
+                state.version
+            )))
+        }
+        if state.history.is_none() {
+            return Ok(())
+        }
+        let network_id = self.env.read().cfg.network_id;
+        if let Some(dumped) = state.network_id.filter(|id| *id != network_id) {
+            return Err(BlockchainError::IncompatibleStateDump(format!(
+                "the history was dumped from network {dumped}, but this is network {network_id}"
+            )))
+        }
+        if self.blockchain.storage.read().hashes.len() > 1 {
+            return Err(BlockchainError::IncompatibleStateDump(
+                "the history can only be loaded into a chain without mined blocks".to_string(),
+            ))
+        }
+        Ok(())
     }
 
     /// Returns the environment for the next block
@@ -621,6 +1074,10 @@ impl Backend {
         // increase block number for this block
         env.block.number = env.block.number.saturating_add(rU256::from(1));
         env.block.timestamp = rU256::from(self.time.current_call_timestamp());
+        env.cfg.spec_id = self.hardforks.spec_id_at(
+            env.block.number.to_ethers_u256().as_u64(),
+            env.block.timestamp.to_ethers_u256().as_u64(),
+        );
         env
     }
 
@@ -705,6 +1162,74 @@ impl Backend {
         f(Box::new(cache_db), executed.block)
     }
 
+    /// Returns the transactions of the given block with the senders they were mined with
+    pub fn mined_pending_transactions(
+        &self,
+        block: &Block,
+    ) -> Result<Vec<PendingTransaction>, BlockchainError> {
+        let storage = self.blockchain.storage.read();
+        block
+            .transactions
+            .iter()
+            .map(|tx| {
+                let hash = tx.hash();
+                let mined =
+                    storage.transactions.get(&hash).ok_or(BlockchainError::DataUnavailable)?;
+                Ok(PendingTransaction::from_mined(tx.clone(), hash, mined.info.from))
+            })
+            .collect()
+    }
+
+    /// Mines a block with the header values and transactions of a block that was mined by a node
+    /// with the same genesis.
+    ///
+    /// Returns an error if the block doesn't extend the best block. If executing its transactions
+    /// results in a different block, that block is removed again and a
+    /// [BlockchainError::DivergedBlock] error is returned.
+    pub async fn import_block(
+        &self,
+        block: &Block,
+        pool_transactions: Vec<Arc<PoolTransaction>>,
+    ) -> Result<MinedBlockOutcome, BlockchainError> {
+        let header = &block.header;
+        let number = header.number.as_u64();
+        let parent =
+            self.get_block_by_hash(self.best_hash()).ok_or(BlockchainError::BlockNotFound)?;
+        if header.parent_hash != parent.header.hash() {
+            return Err(RpcError::invalid_params(format!(
+                "block {number} doesn't extend the best block"
+            ))
+            .into())
+        }
+
+        self.time.reset(parent.header.timestamp);
+        self.time.set_next_block_timestamp(header.timestamp)?;
+        // the header values only apply to the imported block, the node's own are restored after
+        let (coinbase, difficulty, energy_limit) = {
+            let mut env = self.env.write();
+            let previous = (env.block.coinbase, env.block.difficulty, env.block.energy_limit);
+            env.block.coinbase = header.beneficiary.into();
+            env.block.difficulty = header.difficulty.to_ruint();
+            env.block.energy_limit = header.energy_limit.to_ruint();
+            previous
+        };
+
+        let outcome = self.mine_block(pool_transactions).await;
+        {
+            let mut env = self.env.write();
+            env.block.coinbase = coinbase;
+            env.block.difficulty = difficulty;
+            env.block.energy_limit = energy_limit;
+        }
+
+        let hash = self.best_hash();
+        if hash != header.hash() {
+            self.rollback(1).await?;
+            return Err(BlockchainError::DivergedBlock(number, header.hash(), hash))
+        }
+        Ok(outcome)
+    }
+
     /// Mines a new block and stores it.
     ///
     /// this will execute all transaction in the order they come in and return all the markers they
@@ -729,6 +1254,17 @@ impl Backend {
             env.block.number = env.block.number.saturating_add(rU256::from(1));
             env.block.timestamp = rU256::from(self.time.next_timestamp());
 
+            // switch to the rules of the hardfork that is active at this block
+            let hardfork = self.hardforks.hardfork_at(
+                env.block.number.to_ethers_u256().as_u64(),
+                env.block.timestamp.to_ethers_u256().as_u64(),
+            );
+            let spec_id = hardfork.into();
+            if env.cfg.spec_id != spec_id {
+                node_info!("Activated hardfork: {:?}", hardfork);
+                env.cfg.spec_id = spec_id;
+            }
+
             let best_hash = self.blockchain.storage.read().best_hash;
 
             if self.prune_state_history_config.is_state_history_supported() {
@@ -876,6 +1412,8 @@ impl Backend {
             (outcome, header, block_hash)
         };
 
+        self.persist_block(block_hash).await;
+
         // notify all listeners
         self.notify_on_new_block(header, block_hash);
 
@@ -910,6 +1448,89 @@ impl Backend {
         }).await?
     }
 
+    /// Executes the calls of all simulated blocks on top of the state of the block without
+    /// mining anything
+    ///
+    /// State overrides and the state changes of the calls carry over to all following calls and
+    /// blocks. See `xcb_simulateV1`
+    pub async fn simulate(
+        &self,
+        payload: SimulatePayload,
+        block_request: Option<BlockRequest>,
+    ) -> Result<Vec<SimulatedBlock>, BlockchainError> {
+        self.with_database_at(block_request, |state, block| {
+            let mut cache_db = CacheDB::new(state);
+            let mut block_env = block;
+            let mut blocks = Vec::with_capacity(payload.block_state_calls.len());
+
+            for block_calls in payload.block_state_calls {
+                let overrides = block_calls.block_overrides.unwrap_or_default();
+                block_env = simulated_block_env(&block_env, overrides)?;
+                if let Some(overrides) = block_calls.state_overrides {
+                    state::apply_state_override_to(overrides, &mut cache_db)?;
+                }
+
+                let block_number = block_env.number.to_ethers_u256().as_u64();
+                let mut block_energy_used = U256::zero();
+                let mut calls = Vec::with_capacity(block_calls.calls.len());
+                for (tx_idx, request) in block_calls.calls.into_iter().enumerate() {
+                    let fees = FeeDetails::new(request.energy_price)?.or_zero_fees();
+                    let (exit, out, energy_used, changes, logs) =
+                        self.call_with_state_and_logs(&cache_db, request, fees, block_env.clone())?;
+                    block_energy_used += U256::from(energy_used);
+
+                    let logs = logs
+                        .into_iter()
+                        .enumerate()
+                        .map(|(log_idx, log)| Log {
+                            address: log.address.into(),
+                            topics: log.topics.into_iter().map(|topic| H256(topic.0)).collect(),
+                            data: log.data.into(),
+                            block_number: Some(block_number.into()),
+                            transaction_index: Some(U64::from(tx_idx)),
+                            log_index: Some(U256::from(log_idx)),
+                            removed: Some(false),
+                            ..Default::default()
+                        })
+                        .collect();
+                    let error = match exit {
+                        return_ok!() => None,
+                        InstructionResult::Revert => Some("execution reverted".to_string()),
+                        exit => Some(format!("{exit:?}")),
+                    };
+                    let return_data = match out {
+                        Some(Output::Call(data) | Output::Create(data, _)) => data.into(),
+                        None => Default::default(),
+                    };
+                    calls.push(SimulatedCall {
+                        status: U64::from(error.is_none() as u64),
+                        return_data,
+                        energy_used: U256::from(energy_used),
+                        logs,
+                        error,
+                    });
+
+                    let changes = changes
+                        .into_iter()
+                        .map(|(address, account)| (address.into(), account))
+                        .collect();
+                    cache_db.commit(changes);
+                }
+
+                blocks.push(SimulatedBlock {
+                    number: block_env.number.to_ethers_u256(),
+                    timestamp: block_env.timestamp.to_ethers_u256().as_u64().into(),
+                    energy_limit: block_env.energy_limit.to_ethers_u256(),
+                    energy_used: block_energy_used,
+                    coinbase: block_env.coinbase.into(),
+                    calls,
+                });
+            }
+            Ok(blocks)
+        })
+        .await?
+    }
+
     fn build_call_env(
         &self,
         request: EthTransactionRequest,
@@ -951,6 +1572,25 @@ impl Backend {
         fee_details: FeeDetails,
         block_env: BlockEnv,
     ) -> Result<(InstructionResult, Option<Output>, u64, State), BlockchainError>
+    where
+        D: DatabaseRef<Error = DatabaseError>,
+    {
+        let (exit_reason, out, energy_used, state, _) =
+            self.call_with_state_and_logs(state, request, fee_details, block_env)?;
+        Ok((exit_reason, out, energy_used, state))
+    }
+
+    /// Same as [Self::call_with_state] but also returns the logs emitted by the call
+    pub fn call_with_state_and_logs<D>(
+        &self,
+        state: D,
+        request: EthTransactionRequest,
+        fee_details: FeeDetails,
+        block_env: BlockEnv,
+    ) -> Result<
+        (InstructionResult, Option<Output>, u64, State, Vec<revm::primitives::Log>),
+        BlockchainError,
+    >
     where
         D: DatabaseRef<Error = DatabaseError>,
     {
@@ -967,19 +1607,19 @@ impl Backend {
         let state = result_and_state.state;
         let state: hashbrown::HashMap<H176, Account> =
             state.into_iter().map(|kv| (kv.0.into(), kv.1)).collect();
-        let (exit_reason, energy_used, out) = match result_and_state.result {
-            ExecutionResult::Success { reason, energy_used, output, .. } => {
-                (eval_to_instruction_result(reason), energy_used, Some(output))
+        let (exit_reason, energy_used, out, logs) = match result_and_state.result {
+            ExecutionResult::Success { reason, energy_used, logs, output, .. } => {
+                (eval_to_instruction_result(reason), energy_used, Some(output), Some(logs))
             }
             ExecutionResult::Revert { energy_used, output } => {
-                (InstructionResult::Revert, energy_used, Some(Output::Call(output)))
+                (InstructionResult::Revert, energy_used, Some(Output::Call(output)), None)
             }
             ExecutionResult::Halt { reason, energy_used } => {
-                (halt_to_instruction_result(reason), energy_used, None)
+                (halt_to_instruction_result(reason), energy_used, None, None)
             }
         };
         inspector.print_logs();
-        Ok((exit_reason, out, energy_used, state))
+        Ok((exit_reason, out, energy_used, state, logs.unwrap_or_default()))
     }
 
     pub async fn call_with_tracing(
@@ -988,13 +1628,14 @@ impl Backend {
         fee_details: FeeDetails,
         block_request: Option<BlockRequest>,
         opts: GoCoreDebugTracingOptions,
-    ) -> Result<DefaultFrame, BlockchainError> {
+    ) -> Result<GoCoreTrace, BlockchainError> {
+        let tracer = NativeTracer::from_options(&opts)?;
         self.with_database_at(block_request, |state, block| {
             let mut inspector = Inspector::default().with_steps_tracing();
             let block_number = block.number;
             let mut evm = revm::EVM::new();
             evm.env = self.build_call_env(request, fee_details, block);
-            evm.database(state);
+            evm.database(&*state);
             let result_and_state =
                 match evm.inspect_ref(&mut inspector) {
                     Ok(result_and_state) => result_and_state,
@@ -1011,7 +1652,21 @@ impl Backend {
                     (halt_to_instruction_result(reason), energy_used, None)
                 },
             };
-            let res = inspector.tracer.unwrap_or_default().traces.geth_trace(energy_used.into(), opts);
+            let traces = inspector.tracer.unwrap_or_default().traces;
+            let res = match tracer {
+                Some(tracer) => {
+                    let changes: State = result_and_state
+                        .state
+                        .into_iter()
+                        .map(|(address, account)| (address.into(), account))
+                        .collect();
+                    GoCoreTrace::Unknown(tracer.trace(&*state, &changes, &traces)?)
+                }
+                None => {
+                    let frame = traces.geth_trace(energy_used.into(), opts);
+                    GoCoreTrace::Known(GoCoreTraceFrame::Default(frame))
+                }
+            };
             trace!(target: "backend", "trace call return {:?} out: {:?} energy {} on block {}", exit_reason, out, energy_used, block_number);
             Ok(res)
         })
@@ -1612,7 +2267,11 @@ impl Backend {
         hash: H256,
         opts: GoCoreDebugTracingOptions,
     ) -> Result<GoCoreTrace, BlockchainError> {
-        if let Some(traces) = self.mined_geth_trace_transaction(hash, opts.clone()) {
+        if let Some(tracer) = NativeTracer::from_options(&opts)? {
+            if let Some(trace) = self.mined_native_trace_transaction(hash, tracer).await? {
+                return Ok(GoCoreTrace::Unknown(trace));
+            }
+        } else if let Some(traces) = self.mined_geth_trace_transaction(hash, opts.clone()) {
             return Ok(GoCoreTrace::Known(GoCoreTraceFrame::Default(traces)));
         }
 
@@ -1623,6 +2282,31 @@ impl Backend {
         Ok(GoCoreTrace::Known(GoCoreTraceFrame::Default(Default::default())))
     }
 
+    /// Returns the result of the native tracer for the given mined transaction
+    ///
+    /// This replays the transaction's block up to the transaction, because the tracers need the
+    /// state before the transaction.
+    async fn mined_native_trace_transaction(
+        &self,
+        hash: H256,
+        tracer: NativeTracer,
+    ) -> Result<Option<serde_json::Value>, BlockchainError> {
+        let Some(block_number) =
+            self.blockchain.storage.read().transactions.get(&hash).map(|tx| tx.block_number)
+        else {
+            return Ok(None);
+        };
+        let traces = self
+            .replay_block_transactions(block_number, |db, tx| {
+                if tx.hash != hash {
+                    return Ok(None);
+                }
+                tracer.trace(db, &tx.changes, &tx.traces).map(Some)
+            })
+            .await?;
+        Ok(traces.into_iter().flatten().next())
+    }
+
     fn mined_geth_trace_transaction(
         &self,
         hash: H256,
@@ -1631,6 +2315,126 @@ impl Backend {
         self.blockchain.storage.read().transactions.get(&hash).map(|tx| tx.geth_trace(opts))
     }
 
+    /// Returns the traces of all transactions of the given block
+    ///
+    /// See go-core's `debug_traceBlockByNumber` and `debug_traceBlockByHash`
+    pub async fn debug_trace_block(
+        &self,
+        block_id: BlockId,
+        opts: GoCoreDebugTracingOptions,
+    ) -> Result<Vec<TraceResult>, BlockchainError> {
+        let tracer = NativeTracer::from_options(&opts)?;
+        if let Some(block) = self.get_block(block_id) {
+            if let Some(tracer) = tracer {
+                return self
+                    .replay_block_transactions(block.header.number.as_u64(), |db, tx| {
+                        let trace = tracer.trace(db, &tx.changes, &tx.traces)?;
+                        Ok(TraceResult { tx_hash: tx.hash, result: GoCoreTrace::Unknown(trace) })
+                    })
+                    .await;
+            }
+
+            let storage = self.blockchain.storage.read();
+            return block
+                .transactions
+                .iter()
+                .map(|tx| {
+                    let hash = tx.hash();
+                    let mined =
+                        storage.transactions.get(&hash).ok_or(BlockchainError::DataUnavailable)?;
+                    let trace = GoCoreTraceFrame::Default(mined.geth_trace(opts.clone()));
+                    Ok(TraceResult { tx_hash: hash, result: GoCoreTrace::Known(trace) })
+                })
+                .collect();
+        }
+
+        if let Some(fork) = self.fork_of_block(block_id) {
+            return Ok(fork.debug_trace_block(block_id, opts).await?);
+        }
+
+        Err(BlockchainError::BlockNotFound)
+    }
+
+    /// Returns the storage of the account in the state before the transaction at `tx_index` of
+    /// the block, or after the block if the index is out of bounds
+    ///
+    /// Blocks of the forked chain are forwarded to the fork, blocks mined locally on top of a fork
+    /// aren't supported.
+    ///
+    /// See go-core's `debug_storageRangeAt`
+    pub async fn debug_storage_range_at(
+        &self,
+        block_hash: H256,
+        tx_index: usize,
+        address: Address,
+        key_start: H256,
+        max_result: usize,
+    ) -> Result<StorageRange, BlockchainError> {
+        let Some(block) = self.get_block(block_hash) else {
+            if let Some(fork) = self.get_fork() {
+                return Ok(fork
+                    .storage_range_at(block_hash, tx_index, address, key_start, max_result)
+                    .await?);
+            }
+            return Err(BlockchainError::BlockNotFound);
+        };
+        // the local state of a fork only holds the storage slots that were fetched or modified
+        if self.is_fork() {
+            return Err(RpcError::invalid_params(
+                "debug_storageRangeAt is not supported for blocks mined on top of a fork",
+            )
+            .into())
+        }
+        let number = block.header.number.as_u64();
+
+        if tx_index >= block.transactions.len() {
+            let storage = self
+                .with_database_at(Some(BlockRequest::Number(number.into())), |state, _| {
+                    state.maybe_account_storage(address)
+                })
+                .await?
+                .ok_or(BlockchainError::DataUnavailable)?;
+            return Ok(state::storage_range(&storage, key_start, max_result));
+        }
+
+        // replay the transactions before the index on top of the parent's storage
+        let parent = Some(BlockRequest::Number(number.saturating_sub(1).into()));
+        let mut storage = self
+            .with_database_at(parent, |state, _| state.maybe_account_storage(address))
+            .await?
+            .ok_or(BlockchainError::DataUnavailable)?;
+        let mut index = 0;
+        self.replay_block_transactions(number, |_, tx| {
+            if index < tx_index {
+                if let Some(account) = tx.changes.get(&address) {
+                    if account.is_destroyed {
+                        storage.clear();
+                    } else {
+                        for (slot, value) in account.storage.iter() {
+                            storage.insert(*slot, value.present_value());
+                        }
+                    }
+                }
+            }
+            index += 1;
+            Ok(())
+        })
+        .await?;
+
+        Ok(state::storage_range(&storage, key_start, max_result))
+    }
+
+    /// Returns the fork if the block is not part of the local chain but of the forked chain
+    fn fork_of_block(&self, block_id: BlockId) -> Option<&ClientFork> {
+        let fork = self.get_fork()?;
+        match block_id {
+            BlockId::Hash(_) => Some(fork),
+            BlockId::Number(number) => {
+                fork.predates_fork(self.convert_block_number(Some(number))).then_some(fork)
+            }
+        }
+    }
+
     /// Returns the traces for the given block
     pub async fn trace_block(&self, block: BlockNumber) -> Result<Vec<Trace>, BlockchainError> {
         let number = self.convert_block_number(Some(block));
@@ -1647,6 +2451,169 @@ impl Backend {
         Ok(vec![])
     }
 
+    /// Re-executes all transactions of the mined block on top of the state of its parent
+    ///
+    /// `f` is called with the state before every transaction and the outcome of the transaction.
+    async fn replay_block_transactions<F, T>(
+        &self,
+        number: u64,
+        mut f: F,
+    ) -> Result<Vec<T>, BlockchainError>
+    where
+        F: FnMut(&dyn MaybeHashDatabase, ReplayedTransaction) -> Result<T, BlockchainError>,
+    {
+        let block = self.get_block(number).ok_or(BlockchainError::BlockNotFound)?;
+        if block.transactions.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let transactions = self.mined_pending_transactions(&block)?;
+
+        let block_env = BlockEnv {
+            number: block.header.number.to_ruint(),
+            coinbase: block.header.beneficiary.into(),
+            timestamp: rU256::from(block.header.timestamp),
+            difficulty: block.header.difficulty.to_ruint(),
+            energy_limit: block.header.energy_limit.to_ruint(),
+        };
+        let mut cfg = self.env.read().cfg.clone();
+        // replay with the rules of the block's hardfork
+        cfg.spec_id = self.hardforks.spec_id_at(number, block.header.timestamp);
+        let parent = Some(BlockRequest::Number((number - 1).into()));
+
+        self.with_database_at(parent, |state, _| {
+            let mut cache_db = CacheDB::new(state);
+            let mut replayed = Vec::with_capacity(transactions.len());
+            for tx in transactions {
+                let mut inspector = Inspector::default().with_steps_tracing();
+                let mut evm = EVM::new();
+                evm.env =
+                    Env { cfg: cfg.clone(), block: block_env.clone(), tx: tx.to_revm_tx_env() };
+                evm.database(&mut cache_db);
+                let ResultAndState { result, state } = evm.inspect(&mut inspector)?;
+
+                let changes = state
+                    .iter()
+                    .map(|(address, account)| ((*address).into(), account.clone()))
+                    .collect();
+                let tx = ReplayedTransaction {
+                    hash: *tx.hash(),
+                    result,
+                    changes,
+                    traces: inspector.tracer.unwrap_or_default().traces,
+                };
+                replayed.push(f(&cache_db, tx)?);
+
+                cache_db.commit(state);
+            }
+            Ok(replayed)
+        })
+        .await?
+    }
+
+    /// Returns all traces of the mined blocks in the range of the filter that match its addresses
+    ///
+    /// See parity's `trace_filter`
+    pub fn trace_filter(&self, filter: TraceFilter) -> Result<Vec<Trace>, BlockchainError> {
+        let TraceFilter { from_block, to_block, from_address, to_address, after, count } = filter;
+        let best_number = self.best_number().as_u64();
+        let from = self.convert_block_number(Some(from_block.unwrap_or(BlockNumber::Earliest)));
+        let to = self.convert_block_number(to_block);
+        if to > best_number {
+            return Err(BlockchainError::BlockOutOfRange(best_number, to));
+        }
+
+        // an address filter matches if it's empty or contains the address
+        let is_match = |addresses: &Option<Vec<Address>>, address: Option<Address>| {
+            addresses.as_ref().map_or(true, |addresses| {
+                address.map_or(false, |address| addresses.contains(&address))
+            })
+        };
+
+        let traces = (from..=to)
+            .filter_map(|number| self.mined_parity_trace_block(number))
+            .flatten()
+            .filter(|trace| {
+                let (from, to) = parity::trace_addresses(trace);
+                is_match(&from_address, from) && is_match(&to_address, to)
+            })
+            .skip(after.unwrap_or_default())
+            .take(count.unwrap_or(usize::MAX))
+            .collect();
+        Ok(traces)
+    }
+
+    /// Executes the call and returns the requested parity traces
+    ///
+    /// See parity's `trace_call`
+    pub async fn trace_call(
+        &self,
+        request: EthTransactionRequest,
+        fee_details: FeeDetails,
+        block_request: Option<BlockRequest>,
+        trace_types: Vec<TraceType>,
+    ) -> Result<BlockTrace, BlockchainError> {
+        self.with_database_at(block_request, |state, block| {
+            let mut inspector = Inspector::default().with_steps_tracing();
+            let mut evm = EVM::new();
+            evm.env = self.build_call_env(request, fee_details, block);
+            evm.database(&*state);
+            let ResultAndState { result, state: changes } = evm.inspect_ref(&mut inspector)?;
+            let changes: State =
+                changes.into_iter().map(|(address, account)| (address.into(), account)).collect();
+            let traces = inspector.tracer.unwrap_or_default().traces;
+            parity::block_trace(&*state, &changes, &traces, execution_output(result), &trace_types)
+                .map_err(Into::into)
+        })
+        .await?
+    }
+
+    /// Re-executes all transactions of the mined block on top of the state of its parent and
+    /// returns the requested parity traces of every transaction
+    ///
+    /// See parity's `trace_replayBlockTransactions`
+    pub async fn trace_replay_block_transactions(
+        &self,
+        block: BlockNumber,
+        trace_types: Vec<TraceType>,
+    ) -> Result<Vec<BlockTrace>, BlockchainError> {
+        let number = self.convert_block_number(Some(block));
+        self.replay_block_transactions(number, |db, tx| {
+            let mut block_trace = parity::block_trace(
+                db,
+                &tx.changes,
+                &tx.traces,
+                execution_output(tx.result),
+                &trace_types,
+            )?;
+            block_trace.transaction_hash = Some(tx.hash);
+            Ok(block_trace)
+        })
+        .await
+    }
+
+    /// Re-executes the mined transaction, after all transactions before it in its block, and
+    /// returns the requested parity traces
+    ///
+    /// See parity's `trace_replayTransaction`
+    pub async fn trace_replay_transaction(
+        &self,
+        hash: H256,
+        trace_types: Vec<TraceType>,
+    ) -> Result<BlockTrace, BlockchainError> {
+        let (block_number, index) = {
+            let storage = self.blockchain.storage.read();
+            let tx = storage.transactions.get(&hash).ok_or(BlockchainError::DataUnavailable)?;
+            (tx.block_number, tx.info.transaction_index as usize)
+        };
+        let mut block_traces =
+            self.trace_replay_block_transactions(block_number.into(), trace_types).await?;
+        if index >= block_traces.len() {
+            return Err(BlockchainError::DataUnavailable);
+        }
+        Ok(block_traces.swap_remove(index))
+    }
+
     pub async fn transaction_receipt(
         &self,
         hash: H256,
@@ -1681,6 +2648,51 @@ impl Backend {
         Some(receipts)
     }
 
+    /// Returns the value transfers of the transactions of the mined block, including the value
+    /// sent by their internal calls
+    pub fn mined_value_transfers(&self, hash: H256) -> Option<Vec<ValueTransfer>> {
+        let storage = self.blockchain.storage.read();
+        let block = storage.blocks.get(&hash)?;
+        let block_number = block.header.number.as_u64().into();
+        let mut transfers = Vec::new();
+        for tx in block.transactions.iter() {
+            let tx = storage.transactions.get(&tx.hash())?;
+            let traces = &tx.info.traces;
+            let nodes = traces.arena.iter().filter(|node| {
+                let trace = &node.trace;
+                matches!(trace.kind, CallKind::Call | CallKind::Create | CallKind::Create2) &&
+                    !trace.value.is_zero() &&
+                    is_committed(traces, node)
+            });
+            transfers.extend(nodes.map(|node| ValueTransfer {
+                transaction_hash: tx.info.transaction_hash,
+                block_hash: hash,
+                block_number,
+                from: node.trace.caller,
+                to: node.trace.address,
+                value: node.trace.value,
+                depth: node.trace.depth,
+            }));
+        }
+        Some(transfers)
+    }
+
+    /// Returns the RLP encoded receipts of all transactions of the block
+    pub async fn raw_receipts(&self, block_id: BlockId) -> Result<Vec<Bytes>, BlockchainError> {
+        if let Some(receipts) =
+            self.get_block(block_id).and_then(|block| self.mined_receipts(block.header.hash()))
+        {
+            let receipts = receipts.iter().map(|receipt| rlp::encode(receipt).to_vec().into());
+            return Ok(receipts.collect());
+        }
+
+        if let Some(fork) = self.fork_of_block(block_id) {
+            return Ok(fork.raw_receipts(block_id).await?);
+        }
+
+        Err(BlockchainError::BlockNotFound)
+    }
+
     /// Returns the transaction receipt for the given hash
     pub(crate) fn mined_transaction_receipt(&self, hash: H256) -> Option<MinedTransactionReceipt> {
         let MinedTransaction { info, receipt, block_hash, .. } =
@@ -1801,6 +2813,27 @@ impl Backend {
         Some(transaction_build(Some(info.transaction_hash), tx, Some(&block), Some(info)))
     }
 
+    /// Returns the RLP encoded transaction
+    pub async fn raw_transaction(&self, hash: H256) -> Result<Option<Bytes>, BlockchainError> {
+        if let tx @ Some(_) = self.mined_raw_transaction(hash) {
+            return Ok(tx);
+        }
+
+        if let Some(fork) = self.get_fork() {
+            return Ok(fork.raw_transaction(hash).await?);
+        }
+
+        Ok(None)
+    }
+
+    fn mined_raw_transaction(&self, hash: H256) -> Option<Bytes> {
+        let storage = self.blockchain.storage.read();
+        let MinedTransaction { info, block_hash, .. } = storage.transactions.get(&hash)?;
+        let block = storage.blocks.get(block_hash)?;
+        let tx = block.transactions.get(info.transaction_index as usize)?;
+        Some(rlp::encode(tx).to_vec().into())
+    }
+
     pub async fn transaction_by_hash(
         &self,
         hash: H256,
@@ -1817,7 +2850,7 @@ impl Backend {
         Ok(None)
     }
 
-    fn mined_transaction_by_hash(&self, hash: H256) -> Option<Transaction> {
+    pub fn mined_transaction_by_hash(&self, hash: H256) -> Option<Transaction> {
         let (info, block) = {
             let storage = self.blockchain.storage.read();
             let MinedTransaction { info, block_hash, .. } =
@@ -1915,9 +2948,113 @@ impl Backend {
         self.new_block_listeners
             .lock()
             .retain(|tx| tx.unbounded_send(notification.clone()).is_ok());
+
+        self.notify_chain_listeners(ChainNotification::NewBlock(notification));
+    }
+
+    /// Returns a new stream of new and removed blocks
+    pub fn chain_notifications(&self) -> ChainNotifications {
+        let (tx, rx) = unbounded();
+        self.chain_listeners.lock().push(tx);
+        trace!(target: "backed", "added new chain listener");
+        rx
+    }
+
+    /// Notifies all `chain_listeners` about the blocks that were removed from the chain
+    fn notify_on_removed_blocks(&self, blocks: Vec<RemovedBlock>) {
+        if blocks.is_empty() {
+            return
+        }
+        self.notify_chain_listeners(ChainNotification::Removed(Arc::new(blocks)));
+    }
+
+    fn notify_chain_listeners(&self, notification: ChainNotification) {
+        self.chain_listeners.lock().retain(|tx| tx.unbounded_send(notification.clone()).is_ok());
     }
 }
 
+/// Returns the blocks after the given block number and their receipts, starting with the most
+/// recent block
+///
+/// Blocks whose transactions were already pruned from the storage are skipped.
+fn removed_blocks(storage: &BlockchainStorage, number: u64) -> Vec<RemovedBlock> {
+    ((number + 1)..=storage.best_number.as_u64())
+        .rev()
+        .filter_map(|n| {
+            let block = storage.blocks.get(storage.hashes.get(&n.into())?)?;
+            let receipts = block
+                .transactions
+                .iter()
+                .map(|tx| storage.transactions.get(&tx.hash()).map(|tx| tx.receipt.clone()))
+                .collect::<Option<Vec<_>>>()?;
+            Some(RemovedBlock { block: block.clone(), receipts })
+        })
+        .collect()
+}
+
+/// Returns true if neither the call nor any of its parent calls reverted
+fn is_committed(traces: &CallTraceArena, mut node: &CallTraceNode) -> bool {
+    loop {
+        if !node.trace.success {
+            return false
+        }
+        match node.parent {
+            Some(parent) => node = &traces.arena[parent],
+            None => return true,
+        }
+    }
+}
+
+/// Returns the output of an executed transaction, which is the revert data if it reverted
+fn execution_output(result: ExecutionResult) -> Bytes {
+    match result {
+        ExecutionResult::Success { output, .. } => match output {
+            Output::Call(data) | Output::Create(data, _) => data.into(),
+        },
+        ExecutionResult::Revert { output, .. } => output.into(),
+        ExecutionResult::Halt { .. } => Default::default(),
+    }
+}
+
+/// Returns the environment of the simulated block that follows `parent` with the overrides applied
+///
+/// See `xcb_simulateV1`
+fn simulated_block_env(
+    parent: &BlockEnv,
+    overrides: BlockOverrides,
+) -> Result<BlockEnv, BlockchainError> {
+    let BlockOverrides { number, time, energy_limit, coinbase } = overrides;
+
+    let number = number
+        .map(|number| number.to_ruint())
+        .unwrap_or_else(|| parent.number.saturating_add(rU256::from(1)));
+    if number <= parent.number {
+        return Err(RpcError::invalid_params(format!(
+            "simulated block number {number} must be greater than {}",
+            parent.number
+        ))
+        .into());
+    }
+    let timestamp = time
+        .map(|time| rU256::from(time.as_u64()))
+        .unwrap_or_else(|| parent.timestamp.saturating_add(rU256::from(1)));
+    if timestamp <= parent.timestamp {
+        return Err(RpcError::invalid_params(format!(
+            "simulated block timestamp {timestamp} must be greater than {}",
+            parent.timestamp
+        ))
+        .into());
+    }
+
+    Ok(BlockEnv {
+        number,
+        coinbase: coinbase.map(Into::into).unwrap_or(parent.coinbase),
+        timestamp,
+        difficulty: parent.difficulty,
+        energy_limit: energy_limit.map(|limit| limit.to_ruint()).unwrap_or(parent.energy_limit),
+    })
+}
+
 /// Get max nonce from transaction pool by address
 fn get_pool_transactions_nonce(
     pool_transactions: &[Arc<PoolTransaction>],
@@ -2066,6 +3203,16 @@ pub fn transaction_build(
     transaction
 }
 
+/// Creates a `Transaction` as it's expected for the `eth` RPC api from a pool transaction
+pub fn pending_transaction_build(pending: PendingTransaction) -> Transaction {
+    let from = *pending.sender();
+    let mut tx = transaction_build(Some(*pending.hash()), pending.transaction, None, None);
+    // we set the from field here explicitly to the set sender of the pending transaction,
+    // in case the transaction is impersonated.
+    tx.from = from;
+    tx
+}
+
 /// Prove a storage key's existence or nonexistence in the account's storage
 /// trie.
 /// `storage_key` is the hash of the desired storage key, meaning
@@ -0,0 +1,355 @@
+//! Otterscan's `ots_*` endpoints on top of the mined blocks and their transaction traces
+//!
+//! The searches only cover the blocks that were mined locally, in fork mode the blocks before
+//! the fork are not indexed.
+use crate::eth::{
+    backend::mem::{storage::MinedTransaction, Backend},
+    error::BlockchainError,
+};
+use corebc::types::{Address, BlockNumber, Bytes, H256, U256};
+use foxar_evm::{revm::interpreter::InstructionResult, trace::CallTraceArena, CallKind};
+use shuttle_core::eth::{
+    otterscan::{
+        OtsBlock, OtsBlockDetails, OtsBlockTransactions, OtsContractCreator, OtsInternalOperation,
+        OtsInternalOperationKind, OtsIssuance, OtsSearchTransactions, OtsTrace, OtsTraceKind,
+        OtsTransactionReceipt,
+    },
+    transaction::TransactionInfo,
+};
+
+impl Backend {
+    /// Returns the value transfers and contract creations of the internal calls of the
+    /// transaction
+    ///
+    /// Self destructs are not recorded by the call traces, so they are not included.
+    pub fn ots_internal_operations(
+        &self,
+        hash: H256,
+    ) -> Result<Vec<OtsInternalOperation>, BlockchainError> {
+        Ok(internal_operations(&self.ots_mined_transaction(hash)?.info.traces))
+    }
+
+    /// Returns all calls of the transaction
+    pub fn ots_trace_transaction(&self, hash: H256) -> Result<Vec<OtsTrace>, BlockchainError> {
+        Ok(trace_calls(&self.ots_mined_transaction(hash)?.info.traces))
+    }
+
+    /// Returns the revert data of the transaction, or empty bytes if it didn't revert
+    pub fn ots_transaction_error(&self, hash: H256) -> Result<Bytes, BlockchainError> {
+        let MinedTransaction { info, .. } = self.ots_mined_transaction(hash)?;
+        if info.exit == InstructionResult::Revert {
+            return Ok(info.out.unwrap_or_default())
+        }
+        Ok(Bytes::default())
+    }
+
+    /// Returns the block without its transactions together with its fees and rewards
+    pub async fn ots_block_details(
+        &self,
+        number: BlockNumber,
+    ) -> Result<OtsBlockDetails, BlockchainError> {
+        let mut block =
+            self.block_by_number(number).await?.ok_or(BlockchainError::BlockNotFound)?;
+
+        let mut total_fees = U256::zero();
+        for hash in block.transactions.iter() {
+            let energy_price = self
+                .transaction_by_hash(*hash)
+                .await?
+                .map(|tx| tx.energy_price)
+                .unwrap_or_default();
+            let energy_used = self
+                .transaction_receipt(*hash)
+                .await?
+                .and_then(|receipt| receipt.energy_used)
+                .unwrap_or_default();
+            total_fees += energy_used * energy_price;
+        }
+
+        let transaction_count = block.transactions.len();
+        block.transactions.clear();
+        block.logs_bloom = None;
+
+        Ok(OtsBlockDetails {
+            block: OtsBlock { block, transaction_count },
+            // there are no block rewards
+            issuance: OtsIssuance::default(),
+            total_fees,
+        })
+    }
+
+    /// Returns a page of the transactions of the block and their receipts
+    ///
+    /// The input of the transactions is truncated to the function selector and the logs of the
+    /// receipts are removed.
+    pub async fn ots_block_transactions(
+        &self,
+        number: u64,
+        page: usize,
+        page_size: usize,
+    ) -> Result<OtsBlockTransactions, BlockchainError> {
+        let mut block = self
+            .block_by_number_full(number.into())
+            .await?
+            .ok_or(BlockchainError::BlockNotFound)?;
+        let transaction_count = block.transactions.len();
+
+        block.transactions = block
+            .transactions
+            .into_iter()
+            .skip(page.saturating_mul(page_size))
+            .take(page_size)
+            .collect();
+
+        let mut receipts = Vec::with_capacity(block.transactions.len());
+        for tx in block.transactions.iter_mut() {
+            let mut receipt =
+                self.transaction_receipt(tx.hash).await?.ok_or(BlockchainError::DataUnavailable)?;
+            receipt.logs.clear();
+            receipts.push(receipt);
+            tx.input = tx.input[..tx.input.len().min(4)].to_vec().into();
+        }
+
+        Ok(OtsBlockTransactions { fullblock: OtsBlock { block, transaction_count }, receipts })
+    }
+
+    /// Returns the transactions of the address that were mined before the block, starting with the
+    /// most recent, or the most recent transactions if the block is `0`
+    ///
+    /// The page contains at least `page_size` transactions, unless there are no more, and always
+    /// all transactions of the oldest block.
+    pub fn ots_search_transactions_before(
+        &self,
+        address: Address,
+        block_number: u64,
+        page_size: usize,
+    ) -> Result<OtsSearchTransactions, BlockchainError> {
+        let best_number = self.best_number().as_u64();
+        let end =
+            if block_number == 0 { best_number + 1 } else { block_number.min(best_number + 1) };
+        let blocks = (self.ots_first_block()..end).rev();
+        let (hashes, exhausted) = self.ots_transactions_of(address, blocks, page_size, true);
+
+        let mut search = self.ots_search_result(hashes)?;
+        search.first_page = end > best_number;
+        search.last_page = exhausted;
+        Ok(search)
+    }
+
+    /// Returns the transactions of the address that were mined after the block, starting with the
+    /// oldest, or the oldest transactions if the block is `0`
+    ///
+    /// The transactions of the page are ordered from the most recent to the oldest, like the
+    /// results of [Backend::ots_search_transactions_before].
+    pub fn ots_search_transactions_after(
+        &self,
+        address: Address,
+        block_number: u64,
+        page_size: usize,
+    ) -> Result<OtsSearchTransactions, BlockchainError> {
+        let start = if block_number == 0 { 0 } else { block_number + 1 };
+        let blocks = start.max(self.ots_first_block())..=self.best_number().as_u64();
+        let (mut hashes, exhausted) = self.ots_transactions_of(address, blocks, page_size, false);
+        hashes.reverse();
+
+        let mut search = self.ots_search_result(hashes)?;
+        search.first_page = exhausted;
+        search.last_page = block_number == 0;
+        Ok(search)
+    }
+
+    /// Returns the hash of the mined transaction of the sender with the nonce
+    pub fn ots_transaction_by_sender_and_nonce(
+        &self,
+        sender: Address,
+        nonce: U256,
+    ) -> Option<H256> {
+        let storage = self.blockchain.storage.read();
+        storage
+            .transactions
+            .values()
+            .find(|tx| {
+                tx.info.from == sender &&
+                    storage
+                        .blocks
+                        .get(&tx.block_hash)
+                        .and_then(|block| {
+                            block.transactions.get(tx.info.transaction_index as usize)
+                        })
+                        .map_or(false, |tx| *tx.nonce() == nonce)
+            })
+            .map(|tx| tx.info.transaction_hash)
+    }
+
+    /// Returns the transaction that created the contract and the address that created it
+    pub fn ots_contract_creator(&self, address: Address) -> Option<OtsContractCreator> {
+        let storage = self.blockchain.storage.read();
+        for number in self.ots_first_block()..=storage.best_number.as_u64() {
+            let Some(block) =
+                storage.hashes.get(&number.into()).and_then(|hash| storage.blocks.get(hash))
+            else {
+                continue
+            };
+            for tx in block.transactions.iter() {
+                let Some(tx) = storage.transactions.get(&tx.hash()) else { continue };
+                let creation = tx.info.traces.arena.iter().find(|node| {
+                    node.trace.created() && node.trace.success && node.trace.address == address
+                });
+                if let Some(node) = creation {
+                    return Some(OtsContractCreator {
+                        hash: tx.info.transaction_hash,
+                        creator: node.trace.caller,
+                    })
+                }
+            }
+        }
+        None
+    }
+
+    fn ots_mined_transaction(&self, hash: H256) -> Result<MinedTransaction, BlockchainError> {
+        self.blockchain
+            .storage
+            .read()
+            .transactions
+            .get(&hash)
+            .cloned()
+            .ok_or(BlockchainError::DataUnavailable)
+    }
+
+    /// Returns the first block that is stored locally
+    fn ots_first_block(&self) -> u64 {
+        self.get_fork().map(|fork| fork.block_number()).unwrap_or_default()
+    }
+
+    /// Returns the hashes of the transactions of the address in the blocks, until the page is full
+    /// and whether all blocks were searched
+    ///
+    /// The transactions of a block are in reverse order if `newest_first` is set.
+    fn ots_transactions_of(
+        &self,
+        address: Address,
+        blocks: impl IntoIterator<Item = u64>,
+        page_size: usize,
+        newest_first: bool,
+    ) -> (Vec<H256>, bool) {
+        let storage = self.blockchain.storage.read();
+        let mut blocks = blocks.into_iter().peekable();
+        let mut hashes = Vec::new();
+        while let Some(number) = blocks.next() {
+            let Some(block) =
+                storage.hashes.get(&number.into()).and_then(|hash| storage.blocks.get(hash))
+            else {
+                continue
+            };
+            let mut found: Vec<_> = block
+                .transactions
+                .iter()
+                .map(|tx| tx.hash())
+                .filter(|hash| {
+                    storage.transactions.get(hash).map_or(false, |tx| touches(&tx.info, address))
+                })
+                .collect();
+            if newest_first {
+                found.reverse();
+            }
+            hashes.extend(found);
+            if hashes.len() >= page_size {
+                return (hashes, blocks.peek().is_none())
+            }
+        }
+        (hashes, true)
+    }
+
+    /// Returns the transactions and their receipts
+    fn ots_search_result(
+        &self,
+        hashes: Vec<H256>,
+    ) -> Result<OtsSearchTransactions, BlockchainError> {
+        let mut search = OtsSearchTransactions::default();
+        for hash in hashes {
+            let tx =
+                self.mined_transaction_by_hash(hash).ok_or(BlockchainError::DataUnavailable)?;
+            let receipt =
+                self.mined_transaction_receipt(hash).ok_or(BlockchainError::DataUnavailable)?.inner;
+            let timestamp = receipt
+                .block_hash
+                .and_then(|hash| self.get_block_by_hash(hash))
+                .map(|block| block.header.timestamp)
+                .unwrap_or_default();
+            search.txs.push(tx);
+            search.receipts.push(OtsTransactionReceipt { receipt, timestamp: timestamp.into() });
+        }
+        Ok(search)
+    }
+}
+
+/// Returns true if the address sent, received or created the transaction or any of its calls
+fn touches(info: &TransactionInfo, address: Address) -> bool {
+    info.from == address ||
+        info.to == Some(address) ||
+        info.contract_address == Some(address) ||
+        info.traces
+            .arena
+            .iter()
+            .any(|node| node.trace.caller == address || node.trace.address == address)
+}
+
+/// Returns the value transfers and contract creations of all calls but the transaction itself
+fn internal_operations(traces: &CallTraceArena) -> Vec<OtsInternalOperation> {
+    traces
+        .arena
+        .iter()
+        .skip(1)
+        .filter(|node| node.trace.success)
+        .filter_map(|node| {
+            let trace = &node.trace;
+            let kind = match trace.kind {
+                CallKind::Create => OtsInternalOperationKind::Create,
+                CallKind::Create2 => OtsInternalOperationKind::Create2,
+                CallKind::Call | CallKind::CallCode if !trace.value.is_zero() => {
+                    OtsInternalOperationKind::Transfer
+                }
+                _ => return None,
+            };
+            Some(OtsInternalOperation {
+                kind,
+                from: trace.caller,
+                to: trace.address,
+                value: trace.value,
+            })
+        })
+        .collect()
+}
+
+/// Returns all calls in the order they were executed
+fn trace_calls(traces: &CallTraceArena) -> Vec<OtsTrace> {
+    traces
+        .arena
+        .iter()
+        .map(|node| {
+            let trace = &node.trace;
+            let kind = match trace.kind {
+                CallKind::Call => OtsTraceKind::Call,
+                CallKind::StaticCall => OtsTraceKind::StaticCall,
+                CallKind::DelegateCall => OtsTraceKind::DelegateCall,
+                CallKind::CallCode => OtsTraceKind::CallCode,
+                CallKind::Create => OtsTraceKind::Create,
+                CallKind::Create2 => OtsTraceKind::Create2,
+            };
+            OtsTrace {
+                kind,
+                depth: trace.depth,
+                from: trace.caller,
+                to: trace.address,
+                // static and delegate calls don't transfer value
+                value: matches!(
+                    trace.kind,
+                    CallKind::Call | CallKind::CallCode | CallKind::Create | CallKind::Create2
+                )
+                .then_some(trace.value),
+                input: trace.data.to_raw().into(),
+                output: trace.output.to_raw().into(),
+            }
+        })
+        .collect()
+}
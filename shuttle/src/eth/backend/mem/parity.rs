@@ -0,0 +1,182 @@
+//! Helpers to build parity style traces of executed calls
+use crate::{
+    eth::backend::mem::State,
+    revm::{db::DatabaseRef, primitives::AccountInfo},
+};
+use corebc::types::{
+    AccountDiff, Action, Address, BlockTrace, Bytes, ChangedType, Diff, Res, StateDiff, Trace,
+    TraceType, TransactionTrace, U256,
+};
+use foxar_evm::{
+    executor::backend::{DatabaseError, DatabaseResult},
+    revm::primitives::SHA3_EMPTY,
+    trace::CallTraceArena,
+    utils::u256_to_h256_be,
+};
+use foxar_utils::types::ToEthersU256;
+use std::collections::BTreeMap;
+
+/// Returns the `BlockTrace` of an executed call, containing the requested `trace_types`
+///
+/// `db` is the state the call was executed on and `changes` are the state changes of the call.
+pub fn block_trace<D>(
+    db: &D,
+    changes: &State,
+    traces: &CallTraceArena,
+    output: Bytes,
+    trace_types: &[TraceType],
+) -> DatabaseResult<BlockTrace>
+where
+    D: DatabaseRef<Error = DatabaseError> + ?Sized,
+{
+    let trace = trace_types.contains(&TraceType::Trace).then(|| transaction_traces(traces));
+
+    let vm_trace = trace_types.contains(&TraceType::VmTrace).then(|| {
+        traces.parity_vm_trace(|node| {
+            if node.trace.created() {
+                // contract creations execute their init code
+                return node.trace.data.to_raw().into();
+            }
+            changes
+                .get(&node.trace.address)
+                .map(|account| account.info.clone())
+                .or_else(|| db.basic(node.trace.address.into()).ok().flatten())
+                .map(|info| account_code(db, &info))
+                .unwrap_or_default()
+        })
+    });
+
+    let state_diff = if trace_types.contains(&TraceType::StateDiff) {
+        Some(state_diff(db, changes)?)
+    } else {
+        None
+    };
+
+    Ok(BlockTrace { output, trace, vm_trace, state_diff, transaction_hash: None })
+}
+
+/// Converts all call traces into `TransactionTrace`s
+pub fn transaction_traces(traces: &CallTraceArena) -> Vec<TransactionTrace> {
+    traces
+        .arena
+        .iter()
+        .map(|node| TransactionTrace {
+            trace_address: traces.trace_address(node.idx),
+            subtraces: node.children.len(),
+            action: node.parity_action(),
+            action_type: node.parity_action_type(),
+            result: Some(node.parity_result()),
+            error: None,
+        })
+        .collect()
+}
+
+/// Returns the sender and the recipient of the trace, as used by `trace_filter`
+pub fn trace_addresses(trace: &Trace) -> (Option<Address>, Option<Address>) {
+    match &trace.action {
+        Action::Call(call) => (Some(call.from), Some(call.to)),
+        Action::Create(create) => {
+            // the recipient of a creation is the created contract
+            let created = match &trace.result {
+                Some(Res::Create(result)) => Some(result.address),
+                _ => None,
+            };
+            (Some(create.from), created)
+        }
+        Action::Suicide(suicide) => (Some(suicide.address), Some(suicide.refund_address)),
+        Action::Reward(reward) => (None, Some(reward.author)),
+    }
+}
+
+/// Returns the `StateDiff` of the given state changes compared to the state in `db`
+///
+/// Accounts that were loaded but not modified are not included.
+pub fn state_diff<D>(db: &D, changes: &State) -> DatabaseResult<StateDiff>
+where
+    D: DatabaseRef<Error = DatabaseError> + ?Sized,
+{
+    let mut diff = BTreeMap::new();
+    for (address, account) in changes {
+        let before = db.basic((*address).into())?.filter(|info| !info.is_empty());
+        let after = &account.info;
+
+        let account_diff = match before {
+            None if account.is_destroyed || after.is_empty() => continue,
+            None => AccountDiff {
+                balance: Diff::Born(after.balance.to_ethers_u256()),
+                nonce: Diff::Born(after.nonce.into()),
+                code: Diff::Born(account_code(db, after)),
+                storage: account
+                    .storage
+                    .iter()
+                    .filter(|(_, slot)| slot.present_value() != Default::default())
+                    .map(|(key, slot)| {
+                        let value = u256_to_h256_be(slot.present_value().to_ethers_u256());
+                        (u256_to_h256_be(key.to_ethers_u256()), Diff::Born(value))
+                    })
+                    .collect(),
+            },
+            Some(before) if account.is_destroyed => AccountDiff {
+                balance: Diff::Died(before.balance.to_ethers_u256()),
+                nonce: Diff::Died(before.nonce.into()),
+                code: Diff::Died(account_code(db, &before)),
+                storage: Default::default(),
+            },
+            Some(before) => AccountDiff {
+                balance: changed(before.balance.to_ethers_u256(), after.balance.to_ethers_u256()),
+                nonce: changed(U256::from(before.nonce), U256::from(after.nonce)),
+                code: if before.code_hash == after.code_hash {
+                    Diff::Same
+                } else {
+                    changed(account_code(db, &before), account_code(db, after))
+                },
+                storage: account
+                    .storage
+                    .iter()
+                    .filter(|(_, slot)| slot.original_value() != slot.present_value())
+                    .map(|(key, slot)| {
+                        let from = u256_to_h256_be(slot.original_value().to_ethers_u256());
+                        let to = u256_to_h256_be(slot.present_value().to_ethers_u256());
+                        (u256_to_h256_be(key.to_ethers_u256()), changed(from, to))
+                    })
+                    .collect(),
+            },
+        };
+
+        let unchanged = matches!(account_diff.balance, Diff::Same) &&
+            matches!(account_diff.nonce, Diff::Same) &&
+            matches!(account_diff.code, Diff::Same) &&
+            account_diff.storage.is_empty();
+        if !unchanged {
+            diff.insert(*address, account_diff);
+        }
+    }
+    Ok(StateDiff(diff))
+}
+
+/// Returns `Diff::Same` if both values are equal, otherwise `Diff::Changed`
+fn changed<T: PartialEq>(from: T, to: T) -> Diff<T> {
+    if from == to {
+        Diff::Same
+    } else {
+        Diff::Changed(ChangedType { from, to })
+    }
+}
+
+/// Returns the code of the account, loading it from the `db` if it's not part of the `info`
+fn account_code<D>(db: &D, info: &AccountInfo) -> Bytes
+where
+    D: DatabaseRef<Error = DatabaseError> + ?Sized,
+{
+    if info.code_hash == SHA3_EMPTY {
+        return Default::default();
+    }
+    let code = match &info.code {
+        Some(code) => code.clone(),
+        None => match db.code_by_hash(info.code_hash) {
+            Ok(code) => code,
+            Err(_) => return Default::default(),
+        },
+    };
+    code.bytes()[..code.len()].to_vec().into()
+}
@@ -5,7 +5,7 @@ use bytes::Bytes;
 use corebc::{
     abi::ethereum_types::BigEndianHash,
     types::H256,
-    utils::{rlp, rlp::RlpStream},
+    utils::{rlp, rlp::RlpStream, sha3},
 };
 use foxar_evm::{
     executor::backend::DatabaseError,
@@ -15,7 +15,10 @@ use foxar_evm::{
 use foxar_utils::types::{ToEthersU256, ToRuint};
 use memory_db::HashKey;
 use revm::primitives::B176;
-use shuttle_core::eth::{state::StateOverride, trie::RefSecTrieDBMut};
+use shuttle_core::{
+    eth::{state::StateOverride, trie::RefSecTrieDBMut},
+    types::{StorageEntry, StorageRange},
+};
 use spark::{
     executor::DatabaseRef,
     revm::{
@@ -68,6 +71,32 @@ pub fn storage_trie_db(storage: &Map<rU256, rU256>) -> (AsHashDB, H256) {
     (Box::new(db), H256::from(root))
 }
 
+/// Returns up to `max_result` non-empty slots of the storage, ordered by their hashed key and
+/// starting at `key_start`
+///
+/// See go-core's `debug_storageRangeAt`
+pub fn storage_range(
+    storage: &Map<rU256, rU256>,
+    key_start: H256,
+    max_result: usize,
+) -> StorageRange {
+    let mut slots = storage
+        .iter()
+        .filter(|(_k, v)| *v != &rU256::from(0))
+        .map(|(k, v)| {
+            let key = H256::from_uint(&k.to_ethers_u256());
+            let value = H256::from_uint(&v.to_ethers_u256());
+            (H256::from(sha3(key)), StorageEntry { key: Some(key), value })
+        })
+        .filter(|(hashed_key, _)| *hashed_key >= key_start)
+        .collect::<Vec<_>>();
+    slots.sort_by_key(|(hashed_key, _)| *hashed_key);
+
+    let next_key = slots.get(max_result).map(|(hashed_key, _)| *hashed_key);
+    slots.truncate(max_result);
+    StorageRange { storage: slots.into_iter().collect(), next_key }
+}
+
 /// Returns the account data as `HashDB`
 pub fn trie_hash_db(accounts: &Map<B176, DbAccount>) -> (AsHashDB, H256) {
     let accounts = trie_accounts(accounts);
@@ -122,6 +151,18 @@ where
     D: DatabaseRef<Error = DatabaseError>,
 {
     let mut cache_db = CacheDB::new(state);
+    apply_state_override_to(overrides, &mut cache_db)?;
+    Ok(cache_db)
+}
+
+/// Applies the given state overrides to an existing CacheDB state
+pub fn apply_state_override_to<D>(
+    overrides: StateOverride,
+    cache_db: &mut CacheDB<D>,
+) -> Result<(), BlockchainError>
+where
+    D: DatabaseRef<Error = DatabaseError>,
+{
     for (account, account_overrides) in overrides.iter() {
         let mut account_info = cache_db.basic((*account).into())?.unwrap_or_default();
 
@@ -169,5 +210,5 @@ where
             }
         };
     }
-    Ok(cache_db)
+    Ok(())
 }
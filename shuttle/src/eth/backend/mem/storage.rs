@@ -2,22 +2,24 @@
 use crate::eth::{
     backend::{
         db::{MaybeHashDatabase, StateDb},
-        mem::cache::DiskStateCache,
+        mem::{cache::DiskStateCache, chain_db::ChainDb},
     },
     pool::transactions::PoolTransaction,
 };
 use corebc::{
     prelude::{BlockId, BlockNumber, DefaultFrame, Trace, H256, H256 as TxHash, U64},
-    types::{ActionType, Bytes, GoCoreDebugTracingOptions, TransactionReceipt, U256},
+    types::{Bytes, GoCoreDebugTracingOptions, TransactionReceipt, U256},
 };
+use foxar_evm::executor::backend::{snapshot::StateSnapshot, MemDb};
 use foxar_utils::types::ToEthersU256;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use shuttle_core::eth::{
     block::{Block, PartialHeader},
     receipt::TypedReceipt,
-    transaction::{MaybeImpersonatedTransaction, TransactionInfo},
+    transaction::{MaybeImpersonatedTransaction, PendingTransaction, TransactionInfo},
 };
-use spark::revm::{interpreter::InstructionResult, primitives::Env};
+use spark::revm::primitives::Env;
 use std::{
     collections::{HashMap, VecDeque},
     fmt,
@@ -54,6 +56,9 @@ pub struct InMemoryBlockStates {
     present: VecDeque<H256>,
     /// Stores old states on disk
     disk_cache: DiskStateCache,
+    /// The persistent chain database, which holds the state after every block, including blocks
+    /// of previous runs and states that were evicted from the caches
+    chain_db: Option<ChainDb>,
 }
 
 // === impl InMemoryBlockStates ===
@@ -70,6 +75,7 @@ impl InMemoryBlockStates {
             oldest_on_disk: Default::default(),
             present: Default::default(),
             disk_cache: Default::default(),
+            chain_db: None,
         }
     }
 
@@ -79,6 +85,12 @@ impl InMemoryBlockStates {
         self
     }
 
+    /// Loads states that are neither in memory nor in the disk cache from the chain database
+    pub fn with_chain_db(mut self, chain_db: ChainDb) -> Self {
+        self.chain_db = Some(chain_db);
+        self
+    }
+
     /// This modifies the `limit` what to keep stored in memory.
     ///
     /// This will ensure the new limit adjusts based on the block time.
@@ -154,6 +166,14 @@ impl InMemoryBlockStates {
 
     /// Returns the state for the given `hash` if present
     pub fn get(&mut self, hash: &H256) -> Option<&StateDb> {
+        if !self.states.contains_key(hash) && !self.on_disk_states.contains_key(hash) {
+            // evicted states and states of previous runs are read from the chain database
+            let snapshot = self.chain_db.as_ref()?.read_state(*hash)?;
+            let mut state = StateDb::new(MemDb::default());
+            state.init_from_snapshot(snapshot);
+            self.insert(*hash, state);
+        }
+
         self.states.get(hash).or_else(|| {
             if let Some(state) = self.on_disk_states.get_mut(hash) {
                 if let Some(cached) = self.disk_cache.read(*hash) {
@@ -165,6 +185,28 @@ impl InMemoryBlockStates {
         })
     }
 
+    /// Returns a copy of the state for the given `hash` if present
+    pub fn snapshot(&mut self, hash: &H256) -> Option<StateSnapshot> {
+        self.get(hash)?;
+        let state = match self.states.get_mut(hash) {
+            Some(state) => state,
+            None => self.on_disk_states.get_mut(hash)?,
+        };
+        let snapshot = state.clear_into_snapshot();
+        state.init_from_snapshot(snapshot.clone());
+        Some(snapshot)
+    }
+
+    /// Removes the state for the given `hash`, if present
+    pub fn remove(&mut self, hash: &H256) {
+        self.states.remove(hash);
+        self.present.retain(|present| present != hash);
+        if self.on_disk_states.remove(hash).is_some() {
+            self.oldest_on_disk.retain(|on_disk| on_disk != hash);
+            self.disk_cache.remove(*hash);
+        }
+    }
+
     /// Sets the maximum number of stats we keep in memory
     pub fn set_cache_limit(&mut self, limit: usize) {
         self.in_memory_limit = limit;
@@ -259,7 +301,6 @@ impl BlockchainStorage {
         }
     }
 
-    #[allow(unused)]
     pub fn empty() -> Self {
         Self {
             blocks: Default::default(),
@@ -353,8 +394,17 @@ pub struct MinedBlockOutcome {
     pub invalid: Vec<Arc<PoolTransaction>>,
 }
 
+/// The outcome of replacing the most recent blocks with new blocks
+#[derive(Clone, Debug)]
+pub struct ReorgOutcome {
+    /// The transactions of the removed blocks that are not part of the new blocks
+    pub orphaned: Vec<PendingTransaction>,
+    /// The transactions of the new blocks that were invalid at the time of execution
+    pub invalid: Vec<TxHash>,
+}
+
 /// Container type for a mined transaction
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MinedTransaction {
     pub info: TransactionInfo,
     pub receipt: TypedReceipt,
@@ -369,25 +419,16 @@ impl MinedTransaction {
     pub fn parity_traces(&self) -> Vec<Trace> {
         let mut traces = Vec::with_capacity(self.info.traces.arena.len());
         for (idx, node) in self.info.traces.arena.iter().cloned().enumerate() {
-            let action = node.parity_action();
-            let result = node.parity_result();
-
-            let action_type = if node.status() == InstructionResult::SelfDestruct {
-                ActionType::Suicide
-            } else {
-                node.kind().into()
-            };
-
             let trace = Trace {
-                action,
-                result: Some(result),
+                action: node.parity_action(),
+                result: Some(node.parity_result()),
                 trace_address: self.info.trace_address(idx),
                 subtraces: node.children.len(),
                 transaction_position: Some(self.info.transaction_index as usize),
                 transaction_hash: Some(self.info.transaction_hash),
                 block_number: self.block_number,
                 block_hash: self.block_hash,
-                action_type,
+                action_type: node.parity_action_type(),
                 error: None,
             };
             traces.push(trace)
@@ -0,0 +1,368 @@
+//! go-core's built-in native tracers for `debug_traceTransaction` and `debug_traceCall`
+use crate::{
+    eth::{backend::mem::State, error::BlockchainError},
+    revm::db::DatabaseRef,
+};
+use corebc::{
+    types::{Address, Bytes, GoCoreDebugTracingOptions, H256, U256},
+    utils::hex,
+};
+use foxar_evm::{
+    decode::decode_revert,
+    executor::backend::{DatabaseError, DatabaseResult},
+    revm::{
+        interpreter::InstructionResult,
+        primitives::{AccountInfo, SHA3_EMPTY},
+    },
+    trace::{node::CallTraceNode, CallTraceArena, RawOrDecodedLog},
+    utils::u256_to_h256_be,
+    CallKind,
+};
+use foxar_utils::types::ToEthersU256;
+use serde::{Deserialize, Serialize};
+use shuttle_rpc::error::RpcError;
+use std::collections::BTreeMap;
+
+/// A built-in native tracer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NativeTracer {
+    /// `callTracer`, the tree of all calls
+    Call(CallConfig),
+    /// `prestateTracer`, the state of all touched accounts before, and optionally after, the
+    /// transaction
+    PreState(PreStateConfig),
+    /// `4byteTracer`, the number of calls per function selector and calldata size
+    FourByte,
+    /// `noopTracer`, returns an empty object
+    Noop,
+}
+
+/// The `tracerConfig` of the `callTracer`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CallConfig {
+    /// Only trace the top-level call
+    pub only_top_call: bool,
+    /// Include the logs emitted by the calls
+    pub with_log: bool,
+}
+
+/// The `tracerConfig` of the `prestateTracer`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PreStateConfig {
+    /// Return the modified fields before and after the transaction instead of all touched
+    /// accounts
+    pub diff_mode: bool,
+}
+
+// === impl NativeTracer ===
+
+impl NativeTracer {
+    /// Returns the native tracer that is requested by the options, or `None` if the default
+    /// struct logger is requested
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the tracer is not a built-in native tracer or its config is invalid
+    pub fn from_options(opts: &GoCoreDebugTracingOptions) -> Result<Option<Self>, BlockchainError> {
+        let Some(tracer) = &opts.tracer else { return Ok(None) };
+
+        // the tracer config is untagged, so it is parsed according to the tracer's name
+        let config = match serde_json::to_value(&opts.tracer_config) {
+            Ok(serde_json::Value::Null) | Err(_) => serde_json::Value::Object(Default::default()),
+            Ok(config) => config,
+        };
+        let name = serde_json::to_value(tracer).ok();
+
+        let tracer = match name.as_ref().and_then(|name| name.as_str()) {
+            Some("callTracer") => NativeTracer::Call(parse_config(config)?),
+            Some("prestateTracer") => NativeTracer::PreState(parse_config(config)?),
+            Some("4byteTracer") => NativeTracer::FourByte,
+            Some("noopTracer") => NativeTracer::Noop,
+            _ => {
+                return Err(RpcError::invalid_params(
+                    "only the built-in native tracers are supported: callTracer, prestateTracer, \
+                     4byteTracer and noopTracer",
+                )
+                .into())
+            }
+        };
+        Ok(Some(tracer))
+    }
+
+    /// Returns the json result of the tracer for an executed transaction
+    ///
+    /// `db` is the state before the transaction, `changes` are all accounts touched by the
+    /// transaction with their state after it.
+    pub fn trace<D>(
+        &self,
+        db: &D,
+        changes: &State,
+        traces: &CallTraceArena,
+    ) -> Result<serde_json::Value, BlockchainError>
+    where
+        D: DatabaseRef<Error = DatabaseError> + ?Sized,
+    {
+        let value = match self {
+            NativeTracer::Call(config) => {
+                let frame = traces.arena.first().map(|node| call_frame(traces, node, config));
+                serde_json::to_value(frame.unwrap_or_default())
+            }
+            NativeTracer::PreState(config) => {
+                if config.diff_mode {
+                    serde_json::to_value(prestate_diff(db, changes)?)
+                } else {
+                    serde_json::to_value(prestate(db, changes)?)
+                }
+            }
+            NativeTracer::FourByte => serde_json::to_value(four_byte(traces)),
+            NativeTracer::Noop => Ok(serde_json::Value::Object(Default::default())),
+        };
+        value.map_err(|err| BlockchainError::Internal(err.to_string()))
+    }
+}
+
+fn parse_config<T: serde::de::DeserializeOwned>(
+    config: serde_json::Value,
+) -> Result<T, BlockchainError> {
+    serde_json::from_value(config)
+        .map_err(|err| RpcError::invalid_params(format!("invalid tracer config: {err}")).into())
+}
+
+/// A call of the `callTracer`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub from: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+    pub energy: U256,
+    pub energy_used: U256,
+    pub input: Bytes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Bytes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<CallFrame>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub logs: Vec<CallLogFrame>,
+}
+
+/// A log of the `callTracer`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CallLogFrame {
+    pub address: Address,
+    pub topics: Vec<H256>,
+    pub data: Bytes,
+}
+
+/// An account of the `prestateTracer`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AccountState {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<H256, H256>,
+}
+
+/// The result of the `prestateTracer` in diff mode
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PreStateDiff {
+    pub pre: BTreeMap<Address, AccountState>,
+    pub post: BTreeMap<Address, AccountState>,
+}
+
+fn call_frame(traces: &CallTraceArena, node: &CallTraceNode, config: &CallConfig) -> CallFrame {
+    let trace = &node.trace;
+    let typ = match trace.kind {
+        CallKind::Call => "CALL",
+        CallKind::StaticCall => "STATICCALL",
+        CallKind::CallCode => "CALLCODE",
+        CallKind::DelegateCall => "DELEGATECALL",
+        CallKind::Create => "CREATE",
+        CallKind::Create2 => "CREATE2",
+    };
+    let output = trace.output.to_raw();
+
+    let (error, revert_reason) = if trace.success {
+        (None, None)
+    } else if trace.status == InstructionResult::Revert {
+        let reason = decode_revert(&output, None, Some(trace.status)).ok();
+        (Some("execution reverted".to_string()), reason)
+    } else {
+        (Some(format!("{:?}", trace.status)), None)
+    };
+
+    let calls = if config.only_top_call {
+        vec![]
+    } else {
+        node.children
+            .iter()
+            .map(|child| call_frame(traces, &traces.arena[*child], config))
+            .collect()
+    };
+
+    let logs = if config.with_log {
+        node.logs
+            .iter()
+            .filter_map(|log| match log {
+                RawOrDecodedLog::Raw(log) => Some(CallLogFrame {
+                    address: trace.address,
+                    topics: log.topics.clone(),
+                    data: log.data.clone().into(),
+                }),
+                RawOrDecodedLog::Decoded(..) => None,
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+
+    CallFrame {
+        typ: typ.to_string(),
+        from: trace.caller,
+        to: Some(trace.address),
+        // delegate calls don't transfer value
+        value: (trace.kind != CallKind::DelegateCall).then_some(trace.value),
+        energy: trace.energy_limit.into(),
+        energy_used: trace.energy_cost.into(),
+        input: trace.data.to_raw().into(),
+        output: (!output.is_empty()).then(|| output.into()),
+        error,
+        revert_reason,
+        calls,
+        logs,
+    }
+}
+
+/// Counts the calls per `<selector>-<calldata size>`
+fn four_byte(traces: &CallTraceArena) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    for node in traces.arena.iter() {
+        if matches!(node.trace.kind, CallKind::Create | CallKind::Create2) {
+            continue;
+        }
+        let input = node.trace.data.to_raw();
+        if input.len() < 4 {
+            continue;
+        }
+        let key = format!("0x{}-{}", hex::encode(&input[..4]), input.len() - 4);
+        *counts.entry(key).or_default() += 1;
+    }
+    counts
+}
+
+/// Returns the state of all touched accounts before the transaction
+fn prestate<D>(db: &D, changes: &State) -> DatabaseResult<BTreeMap<Address, AccountState>>
+where
+    D: DatabaseRef<Error = DatabaseError> + ?Sized,
+{
+    let mut accounts = BTreeMap::new();
+    for (address, account) in changes {
+        let info = db.basic((*address).into())?.unwrap_or_default();
+        let mut state = account_state(db, &info);
+        state.storage = account
+            .storage
+            .iter()
+            .map(|(key, slot)| {
+                (
+                    u256_to_h256_be(key.to_ethers_u256()),
+                    u256_to_h256_be(slot.original_value().to_ethers_u256()),
+                )
+            })
+            .collect();
+        accounts.insert(*address, state);
+    }
+    Ok(accounts)
+}
+
+/// Returns the modified fields of all accounts before and after the transaction
+fn prestate_diff<D>(db: &D, changes: &State) -> DatabaseResult<PreStateDiff>
+where
+    D: DatabaseRef<Error = DatabaseError> + ?Sized,
+{
+    let mut diff = PreStateDiff::default();
+    for (address, account) in changes {
+        let before_info = db.basic((*address).into())?.unwrap_or_default();
+        let mut before = account_state(db, &before_info);
+        let mut after = if account.is_destroyed {
+            AccountState::default()
+        } else {
+            account_state(db, &account.info)
+        };
+
+        // only keep the fields that were modified
+        let mut modified = account.is_destroyed;
+        if before.balance == after.balance {
+            after.balance = None;
+        } else {
+            modified = true;
+        }
+        if before.nonce == after.nonce {
+            after.nonce = None;
+        } else {
+            modified = true;
+        }
+        if before.code == after.code {
+            after.code = None;
+        } else {
+            modified = true;
+        }
+        for (key, slot) in account.storage.iter() {
+            if slot.original_value() == slot.present_value() {
+                continue;
+            }
+            modified = true;
+            let key = u256_to_h256_be(key.to_ethers_u256());
+            before.storage.insert(key, u256_to_h256_be(slot.original_value().to_ethers_u256()));
+            if !slot.present_value().is_zero() {
+                after.storage.insert(key, u256_to_h256_be(slot.present_value().to_ethers_u256()));
+            }
+        }
+
+        if !modified {
+            continue;
+        }
+        if !before_info.is_empty() {
+            diff.pre.insert(*address, before);
+        }
+        if !account.is_destroyed {
+            diff.post.insert(*address, after);
+        }
+    }
+    Ok(diff)
+}
+
+/// Returns the balance, nonce and code of the account
+fn account_state<D>(db: &D, info: &AccountInfo) -> AccountState
+where
+    D: DatabaseRef<Error = DatabaseError> + ?Sized,
+{
+    let code = if info.code_hash == SHA3_EMPTY {
+        None
+    } else {
+        match &info.code {
+            Some(code) => Some(code.clone()),
+            None => db.code_by_hash(info.code_hash).ok(),
+        }
+        .map(|code| code.bytes()[..code.len()].to_vec().into())
+    };
+    AccountState {
+        balance: Some(info.balance.to_ethers_u256()),
+        nonce: Some(info.nonce),
+        code,
+        storage: Default::default(),
+    }
+}
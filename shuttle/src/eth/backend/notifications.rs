@@ -2,7 +2,10 @@
 
 use corebc::types::H256;
 use futures::channel::mpsc::UnboundedReceiver;
-use shuttle_core::eth::block::Header;
+use shuttle_core::eth::{
+    block::{Block, Header},
+    receipt::TypedReceipt,
+};
 use std::sync::Arc;
 
 /// A notification that's emitted when a new block was imported
@@ -16,3 +19,24 @@ pub struct NewBlockNotification {
 
 /// Type alias for a receiver that receives [NewBlockNotification]
 pub type NewBlockNotifications = UnboundedReceiver<NewBlockNotification>;
+
+/// A block that was removed from the chain
+#[derive(Clone, Debug)]
+pub struct RemovedBlock {
+    pub block: Block,
+    /// The receipts of the transactions of the block
+    pub receipts: Vec<TypedReceipt>,
+}
+
+/// A notification that's emitted when the chain changed
+#[derive(Clone, Debug)]
+pub enum ChainNotification {
+    /// A new block was imported
+    NewBlock(NewBlockNotification),
+    /// Blocks were removed by reverting to a snapshot or resetting the fork, starting with the
+    /// most recent block
+    Removed(Arc<Vec<RemovedBlock>>),
+}
+
+/// Type alias for a receiver that receives [ChainNotification]
+pub type ChainNotifications = UnboundedReceiver<ChainNotification>;
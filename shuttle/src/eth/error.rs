@@ -5,7 +5,7 @@ use corebc::{
     abi::AbiDecode,
     providers::ProviderError,
     signers::WalletError,
-    types::{Bytes, SignatureError, U256},
+    types::{Bytes, SignatureError, H256, U256},
 };
 use foxar_common::SELECTOR_LEN;
 use foxar_evm::{executor::backend::DatabaseError, revm::interpreter::InstructionResult};
@@ -37,6 +37,8 @@ pub enum BlockchainError {
     FailedToDecodeTransaction,
     #[error("Failed to decode state")]
     FailedToDecodeStateDump,
+    #[error("Incompatible state dump: {0}")]
+    IncompatibleStateDump(String),
     #[error("Prevrandao not in th EVM's environment after merge")]
     PrevrandaoNotSet,
     #[error(transparent)]
@@ -61,6 +63,8 @@ pub enum BlockchainError {
     Internal(String),
     #[error("BlockOutOfRangeError: block height is {0} but requested was {1}")]
     BlockOutOfRange(u64, u64),
+    #[error("Imported block {0} diverged: expected hash {1:?} but executing it resulted in {2:?}")]
+    DivergedBlock(u64, H256, H256),
     #[error("Resource not found")]
     BlockNotFound,
     #[error("Required data unavailable")]
@@ -110,6 +114,13 @@ pub enum PoolError {
     ReplacementUnderpriced(Box<PoolTransaction>),
     #[error("Tx: [{0:?}] already Imported")]
     AlreadyImported(Box<PoolTransaction>),
+    /// Thrown if the pool is full and the transaction's gas price is not above the cheapest
+    /// transaction in the pool
+    #[error("Tx: [{0:?}] insufficient gas price for the full pool")]
+    Underpriced(Box<PoolTransaction>),
+    /// Thrown if the sender already has the maximum number of queued transactions
+    #[error("Tx: [{0:?}] exceeds the queued transactions limit of the sender")]
+    SenderLimitExceeded(Box<PoolTransaction>),
 }
 
 /// Errors that can occur with `eth_feeHistory`
@@ -254,6 +265,12 @@ impl<T: Serialize> ToRpcResponseResult for Result<T> {
                         PoolError::AlreadyImported(_) => {
                             RpcError::transaction_rejected("transaction already imported")
                         }
+                        PoolError::Underpriced(_) => {
+                            RpcError::transaction_rejected("transaction underpriced")
+                        }
+                        PoolError::SenderLimitExceeded(_) => {
+                            RpcError::transaction_rejected("account limit exceeded")
+                        }
                     }
                 }
                 BlockchainError::NoSignerAvailable => {
@@ -299,6 +316,9 @@ impl<T: Serialize> ToRpcResponseResult for Result<T> {
                 BlockchainError::FailedToDecodeStateDump => {
                     RpcError::invalid_params("Failed to decode state dump")
                 }
+                BlockchainError::IncompatibleStateDump(_) => {
+                    RpcError::invalid_params(err.to_string())
+                }
                 BlockchainError::SignatureError(err) => RpcError::invalid_params(err.to_string()),
                 BlockchainError::WalletError(err) => RpcError::invalid_params(err.to_string()),
                 BlockchainError::RpcUnimplemented => {
@@ -321,6 +341,9 @@ impl<T: Serialize> ToRpcResponseResult for Result<T> {
                 err @ BlockchainError::BlockOutOfRange(_, _) => {
                     RpcError::invalid_params(err.to_string())
                 }
+                err @ BlockchainError::DivergedBlock(..) => {
+                    RpcError::internal_error_with(err.to_string())
+                }
                 err @ BlockchainError::BlockNotFound => RpcError {
                     // <https://eips.ethereum.org/EIPS/eip-1898>
                     code: ErrorCode::ServerError(-32001),
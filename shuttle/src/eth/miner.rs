@@ -1,14 +1,20 @@
 //! Mines transactions
 
-use crate::eth::pool::{transactions::PoolTransaction, Pool};
-use corebc::prelude::TxHash;
+use crate::{
+    eth::pool::{transactions::PoolTransaction, Pool},
+    revm::primitives::Env,
+};
+use corebc::prelude::{TxHash, U256};
+use foxar_utils::types::ToEthersU256;
 use futures::{
     channel::mpsc::Receiver,
+    ready,
     stream::{Fuse, Stream, StreamExt},
     task::AtomicWaker,
 };
 use parking_lot::{lock_api::RwLockWriteGuard, RawRwLock, RwLock};
 use std::{
+    collections::HashSet,
     fmt,
     pin::Pin,
     sync::Arc,
@@ -111,6 +117,14 @@ pub enum MiningMode {
     Auto(ReadyTransactionMiner),
     /// A miner that constructs a new block every `interval` tick
     FixedBlockTime(FixedBlockTimeMiner),
+    /// A miner that constructs a new block once the ready transactions fill a fraction of the
+    /// block energy limit
+    EnergyFill(EnergyFillMiner),
+    /// A miner that constructs a new block for every `n` ready transactions
+    TransactionCount(TransactionCountMiner),
+    /// A miner that constructs a new block every `interval` tick, or earlier if the ready
+    /// transactions fill a fraction of the block energy limit
+    IntervalOrEnergyFill(IntervalOrEnergyFillMiner),
 }
 
 // === impl MiningMode ===
@@ -128,6 +142,35 @@ impl MiningMode {
         MiningMode::FixedBlockTime(FixedBlockTimeMiner::new(duration))
     }
 
+    /// Mines once the ready transactions use `fill` of the block energy limit of the `env`
+    pub fn energy_fill(fill: f64, env: Arc<RwLock<Env>>, listener: Receiver<TxHash>) -> Self {
+        MiningMode::EnergyFill(EnergyFillMiner::new(fill, env, listener))
+    }
+
+    /// Mines every `transactions` ready transactions
+    pub fn transaction_count(transactions: usize, listener: Receiver<TxHash>) -> Self {
+        MiningMode::TransactionCount(TransactionCountMiner {
+            transactions,
+            has_pending_txs: None,
+            in_flight: Default::default(),
+            rx: listener.fuse(),
+        })
+    }
+
+    /// Mines every `duration`, or earlier once the ready transactions use `fill` of the block
+    /// energy limit of the `env`
+    pub fn interval_or_energy_fill(
+        duration: Duration,
+        fill: f64,
+        env: Arc<RwLock<Env>>,
+        listener: Receiver<TxHash>,
+    ) -> Self {
+        MiningMode::IntervalOrEnergyFill(IntervalOrEnergyFillMiner {
+            interval: FixedBlockTimeMiner::new(duration),
+            fill: EnergyFillMiner::new(fill, env, listener),
+        })
+    }
+
     /// polls the [Pool] and returns those transactions that should be put in a block, if any.
     pub fn poll(
         &mut self,
@@ -138,6 +181,9 @@ impl MiningMode {
             MiningMode::None => Poll::Pending,
             MiningMode::Auto(miner) => miner.poll(pool, cx),
             MiningMode::FixedBlockTime(miner) => miner.poll(pool, cx),
+            MiningMode::EnergyFill(miner) => miner.poll(pool, cx),
+            MiningMode::TransactionCount(miner) => miner.poll(pool, cx),
+            MiningMode::IntervalOrEnergyFill(miner) => miner.poll(pool, cx),
         }
     }
 }
@@ -220,3 +266,166 @@ impl fmt::Debug for ReadyTransactionMiner {
             .finish_non_exhaustive()
     }
 }
+
+/// Tracks the ready transactions that were handed out for a new block, but are not mined yet
+#[derive(Debug, Default)]
+struct InFlightTransactions(HashSet<TxHash>);
+
+// === impl InFlightTransactions ===
+
+impl InFlightTransactions {
+    /// Returns all ready transactions of the pool that were not handed out yet
+    fn ready_transactions(&mut self, pool: &Pool) -> Vec<Arc<PoolTransaction>> {
+        // mined transactions are removed from the pool
+        self.0.retain(|hash| pool.contains(hash));
+        pool.ready_transactions().filter(|tx| !self.0.contains(tx.hash())).collect()
+    }
+
+    /// Marks the transactions as handed out
+    fn hand_out(&mut self, transactions: Vec<Arc<PoolTransaction>>) -> Vec<Arc<PoolTransaction>> {
+        self.0.extend(transactions.iter().map(|tx| *tx.hash()));
+        transactions
+    }
+}
+
+/// A miner that mines a block once the ready transactions would use a fraction of the block
+/// energy limit.
+///
+/// The block includes as many ready transactions as fit into the block energy limit.
+pub struct EnergyFillMiner {
+    /// the fraction of the block energy limit that must be used, in `(0, 1]`
+    fill: f64,
+    /// the environment of the chain, which holds the current block energy limit
+    env: Arc<RwLock<Env>>,
+    /// stores whether there are pending transacions (if known)
+    has_pending_txs: Option<bool>,
+    /// transactions that are already part of a block
+    in_flight: InFlightTransactions,
+    /// Receives hashes of transactions that are ready
+    rx: Fuse<Receiver<TxHash>>,
+}
+
+// === impl EnergyFillMiner ===
+
+impl EnergyFillMiner {
+    fn new(fill: f64, env: Arc<RwLock<Env>>, listener: Receiver<TxHash>) -> Self {
+        Self {
+            fill,
+            env,
+            has_pending_txs: None,
+            in_flight: Default::default(),
+            rx: listener.fuse(),
+        }
+    }
+
+    fn poll(&mut self, pool: &Arc<Pool>, cx: &mut Context<'_>) -> Poll<Vec<Arc<PoolTransaction>>> {
+        // drain the notification stream
+        while let Poll::Ready(Some(_hash)) = Pin::new(&mut self.rx).poll_next(cx) {
+            self.has_pending_txs = Some(true);
+        }
+
+        if self.has_pending_txs == Some(false) {
+            return Poll::Pending
+        }
+
+        let energy_limit = self.env.read().block.energy_limit.to_ethers_u256();
+        let target = U256::from((energy_limit.low_u128() as f64 * self.fill) as u128);
+
+        let mut energy = U256::zero();
+        let mut transactions = Vec::new();
+        for tx in self.in_flight.ready_transactions(pool) {
+            let tx_energy = energy.saturating_add(tx.energy_limit());
+            if tx_energy > energy_limit {
+                break
+            }
+            energy = tx_energy;
+            transactions.push(tx);
+        }
+
+        if transactions.is_empty() || energy < target {
+            self.has_pending_txs = Some(false);
+            return Poll::Pending
+        }
+
+        // the remaining transactions may fill another block
+        Poll::Ready(self.in_flight.hand_out(transactions))
+    }
+
+    /// Marks all ready transactions as handed out and returns them
+    fn drain(&mut self, pool: &Arc<Pool>) -> Vec<Arc<PoolTransaction>> {
+        let transactions = self.in_flight.ready_transactions(pool);
+        self.in_flight.hand_out(transactions)
+    }
+}
+
+impl fmt::Debug for EnergyFillMiner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnergyFillMiner").field("fill", &self.fill).finish_non_exhaustive()
+    }
+}
+
+/// A miner that mines a block for every `transactions` ready transactions
+pub struct TransactionCountMiner {
+    /// how many transactions to mine per block
+    transactions: usize,
+    /// stores whether there are pending transacions (if known)
+    has_pending_txs: Option<bool>,
+    /// transactions that are already part of a block
+    in_flight: InFlightTransactions,
+    /// Receives hashes of transactions that are ready
+    rx: Fuse<Receiver<TxHash>>,
+}
+
+// === impl TransactionCountMiner ===
+
+impl TransactionCountMiner {
+    fn poll(&mut self, pool: &Arc<Pool>, cx: &mut Context<'_>) -> Poll<Vec<Arc<PoolTransaction>>> {
+        // drain the notification stream
+        while let Poll::Ready(Some(_hash)) = Pin::new(&mut self.rx).poll_next(cx) {
+            self.has_pending_txs = Some(true);
+        }
+
+        if self.has_pending_txs == Some(false) {
+            return Poll::Pending
+        }
+
+        let mut transactions = self.in_flight.ready_transactions(pool);
+        if transactions.len() < self.transactions {
+            self.has_pending_txs = Some(false);
+            return Poll::Pending
+        }
+        transactions.truncate(self.transactions);
+
+        // the remaining transactions may complete another block
+        Poll::Ready(self.in_flight.hand_out(transactions))
+    }
+}
+
+impl fmt::Debug for TransactionCountMiner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransactionCountMiner")
+            .field("transactions", &self.transactions)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A miner that mines a block every `interval` tick, or earlier once the ready transactions would
+/// use a fraction of the block energy limit, in which case the interval restarts.
+#[derive(Debug)]
+pub struct IntervalOrEnergyFillMiner {
+    interval: FixedBlockTimeMiner,
+    fill: EnergyFillMiner,
+}
+
+// === impl IntervalOrEnergyFillMiner ===
+
+impl IntervalOrEnergyFillMiner {
+    fn poll(&mut self, pool: &Arc<Pool>, cx: &mut Context<'_>) -> Poll<Vec<Arc<PoolTransaction>>> {
+        if self.interval.interval.poll_tick(cx).is_ready() {
+            return Poll::Ready(self.fill.drain(pool))
+        }
+        let transactions = ready!(self.fill.poll(pool, cx));
+        self.interval.interval.reset();
+        Poll::Ready(transactions)
+    }
+}
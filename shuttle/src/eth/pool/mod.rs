@@ -38,7 +38,7 @@ use crate::{
 };
 use corebc::{
     prelude::TxpoolStatus,
-    types::{TxHash, U64},
+    types::{TxHash, U256, U64},
 };
 use futures::channel::mpsc::{channel, Receiver, Sender};
 use parking_lot::{Mutex, RwLock};
@@ -48,6 +48,32 @@ use tracing::{debug, trace, warn};
 
 pub mod transactions;
 
+/// The number of evicted transactions that are kept to report them
+const MAX_EVICTED_HISTORY: usize = 1_024;
+
+/// The policy that limits which transactions the pool accepts and keeps
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoolConfig {
+    /// The maximum number of ready and queued transactions in the pool
+    ///
+    /// Once exceeded, the lowest priced transactions are evicted.
+    pub max_transactions: Option<usize>,
+    /// The minimum energy price increase, in percent, of a transaction that replaces a
+    /// transaction with the same sender and nonce
+    pub price_bump: u64,
+    /// The maximum number of queued transactions, that wait for a lower nonce, per sender
+    pub max_queued_per_sender: Option<usize>,
+}
+
+// == impl PoolConfig ==
+
+impl PoolConfig {
+    /// Returns the minimum energy price of a transaction that replaces one with the given price
+    pub fn min_replacement_price(&self, energy_price: U256) -> U256 {
+        energy_price.saturating_add(energy_price * U256::from(self.price_bump) / U256::from(100))
+    }
+}
+
 /// Transaction pool that performs validation.
 #[derive(Default)]
 pub struct Pool {
@@ -60,6 +86,14 @@ pub struct Pool {
 // == impl Pool ==
 
 impl Pool {
+    /// Creates a new, empty pool with the given policy
+    pub fn new(config: PoolConfig) -> Self {
+        Self {
+            inner: RwLock::new(PoolInner { config, ..Default::default() }),
+            transaction_listener: Default::default(),
+        }
+    }
+
     /// Returns an iterator that yields all transactions that are currently ready
     pub fn ready_transactions(&self) -> TransactionsIterator {
         self.inner.read().ready_transactions()
@@ -70,6 +104,11 @@ impl Pool {
         self.inner.read().pending_transactions.transactions().collect()
     }
 
+    /// Returns the most recently evicted transactions, the oldest first
+    pub fn evicted_transactions(&self) -> Vec<Arc<PoolTransaction>> {
+        self.inner.read().evicted.iter().cloned().collect()
+    }
+
     /// Returns the _pending_ transaction for that `hash` if it exists in the mempool
     pub fn get_transaction(&self, hash: TxHash) -> Option<PendingTransaction> {
         self.inner.read().get_transaction(hash)
@@ -197,8 +236,11 @@ impl Pool {
 /// Contains all transactions that are ready to be executed
 #[derive(Debug, Default)]
 struct PoolInner {
+    config: PoolConfig,
     ready_transactions: ReadyTransactions,
     pending_transactions: PendingTransactions,
+    /// transactions that were evicted because the pool was full
+    evicted: VecDeque<Arc<PoolTransaction>>,
 }
 
 // == impl PoolInner ==
@@ -226,22 +268,100 @@ impl PoolInner {
         self.pending_transactions.contains(tx_hash) || self.ready_transactions.contains(tx_hash)
     }
 
+    /// Returns the number of ready and queued transactions
+    fn len(&self) -> usize {
+        self.ready_transactions.len() + self.pending_transactions.len()
+    }
+
     fn add_transaction(&mut self, tx: PoolTransaction) -> Result<AddedTransaction, PoolError> {
         if self.contains(tx.hash()) {
             warn!(target: "txpool", "[{:?}] Already imported", tx.hash());
             return Err(PoolError::AlreadyImported(Box::new(tx)))
         }
 
+        let replaced_price = self.replaced_energy_price(&tx);
+        if let Some(replaced_price) = replaced_price {
+            if tx.energy_price() < self.config.min_replacement_price(replaced_price) {
+                warn!(target: "txpool", "replacement transaction underpriced [{:?}]", tx.hash());
+                return Err(PoolError::ReplacementUnderpriced(Box::new(tx)))
+            }
+        } else if self.config.max_transactions.map_or(false, |max| self.len() >= max) {
+            // a full pool only accepts transactions that are priced higher than the cheapest one
+            if let Some(cheapest) = self.cheapest_transaction() {
+                if tx.energy_price() <= cheapest.energy_price() {
+                    warn!(target: "txpool", "transaction underpriced for full pool [{:?}]", tx.hash());
+                    return Err(PoolError::Underpriced(Box::new(tx)))
+                }
+            }
+        }
+
         let tx = PendingPoolTransaction::new(tx, self.ready_transactions.provided_markers());
         trace!(target: "txpool", "[{:?}] {:?}", tx.transaction.hash(), tx);
+        self.evicted.retain(|evicted| evicted.hash() != tx.transaction.hash());
 
         // If all markers are not satisfied import to future
         if !tx.is_ready() {
+            if let Some(max) = self.config.max_queued_per_sender {
+                let sender = tx.transaction.pending_transaction.sender();
+                if replaced_price.is_none() && self.pending_transactions.sender_count(sender) >= max
+                {
+                    warn!(target: "txpool", "queued transactions limit of sender exceeded [{:?}]", tx.transaction.hash());
+                    return Err(PoolError::SenderLimitExceeded(Box::new(
+                        tx.transaction.as_ref().clone(),
+                    )))
+                }
+            }
+
             let hash = *tx.transaction.hash();
             self.pending_transactions.add_transaction(tx)?;
+            self.evict();
             return Ok(AddedTransaction::Pending { hash })
         }
-        self.add_ready_transaction(tx)
+        let added = self.add_ready_transaction(tx)?;
+        self.evict();
+        Ok(added)
+    }
+
+    /// Returns the energy price of the transaction with the same sender and nonce as the `tx`,
+    /// which the `tx` would replace
+    fn replaced_energy_price(&self, tx: &PoolTransaction) -> Option<U256> {
+        if let Some(pending) = self.pending_transactions.get_by_provides(&tx.provides) {
+            return Some(pending.transaction.energy_price())
+        }
+        tx.provides
+            .iter()
+            .filter_map(|marker| self.ready_transactions.provided_markers().get(marker))
+            .find_map(|hash| self.ready_transactions.get(hash))
+            .map(|ready| ready.energy_price())
+    }
+
+    /// Returns the transaction with the lowest energy price
+    ///
+    /// Queued transactions are preferred over ready transactions of the same price.
+    fn cheapest_transaction(&self) -> Option<Arc<PoolTransaction>> {
+        let queued = self.pending_transactions.transactions().map(|tx| (tx, false));
+        let ready = self.ready_transactions.transactions().into_iter().map(|tx| (tx, true));
+        queued
+            .chain(ready)
+            .min_by_key(|(tx, is_ready)| (tx.energy_price(), *is_ready))
+            .map(|(tx, _)| tx)
+    }
+
+    /// Evicts the lowest priced transactions until the pool doesn't exceed its size limit
+    ///
+    /// Evicting a ready transaction also evicts the transactions that depend on it.
+    fn evict(&mut self) {
+        let Some(max) = self.config.max_transactions else { return };
+        while self.len() > max {
+            let Some(cheapest) = self.cheapest_transaction() else { break };
+            for tx in self.remove_invalid(vec![*cheapest.hash()]) {
+                debug!(target: "txpool", "evicted transaction [{:?}]", tx.hash());
+                if self.evicted.len() == MAX_EVICTED_HISTORY {
+                    self.evicted.pop_front();
+                }
+                self.evicted.push_back(tx);
+            }
+        }
     }
 
     /// Adds the transaction to the ready queue
@@ -96,6 +96,11 @@ impl PoolTransaction {
     pub fn energy_price(&self) -> U256 {
         self.pending_transaction.transaction.energy_price()
     }
+
+    /// Returns the energy limit of this transaction
+    pub fn energy_limit(&self) -> U256 {
+        self.pending_transaction.transaction.energy_limit()
+    }
 }
 
 impl fmt::Debug for PoolTransaction {
@@ -185,6 +190,20 @@ impl PendingTransactions {
         self.waiting_queue.get(hash)
     }
 
+    /// Returns the waiting transaction that provides the same markers, i.e. the transaction with
+    /// the same sender and nonce
+    pub fn get_by_provides(&self, provides: &[TxMarker]) -> Option<&PendingPoolTransaction> {
+        self.waiting_markers.get(provides).and_then(|hash| self.waiting_queue.get(hash))
+    }
+
+    /// Returns the number of waiting transactions of the sender
+    pub fn sender_count(&self, sender: &Address) -> usize {
+        self.waiting_queue
+            .values()
+            .filter(|tx| tx.transaction.pending_transaction.sender() == sender)
+            .count()
+    }
+
     /// This will check off the markers of pending transactions.
     ///
     /// Returns the those transactions that become unlocked (all markers checked) and can be moved
@@ -378,6 +397,20 @@ impl ReadyTransactions {
         }
     }
 
+    /// Returns the number of ready transactions
+    pub fn len(&self) -> usize {
+        self.ready_tx.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ready_tx.read().is_empty()
+    }
+
+    /// Returns all ready transactions, in no particular order
+    pub fn transactions(&self) -> Vec<Arc<PoolTransaction>> {
+        self.ready_tx.read().values().map(|tx| tx.transaction.transaction.clone()).collect()
+    }
+
     /// Returns true if the transaction is part of the queue.
     pub fn contains(&self, hash: &TxHash) -> bool {
         self.ready_tx.read().contains_key(hash)
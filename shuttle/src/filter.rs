@@ -1,7 +1,10 @@
 //! Support for polling based filters
 use crate::{
-    eth::{backend::notifications::NewBlockNotifications, error::ToRpcResponseResult},
-    pubsub::filter_logs,
+    eth::{
+        backend::notifications::{ChainNotification, ChainNotifications, NewBlockNotifications},
+        error::ToRpcResponseResult,
+    },
+    pubsub::{filter_logs, filter_removed_logs},
     StorageInfo,
 };
 use corebc::{
@@ -154,10 +157,12 @@ impl Stream for EthFilter {
 }
 
 /// Listens for new blocks and matching logs emitted in that block
+///
+/// The matching logs of removed blocks are returned again with `removed: true`.
 #[derive(Debug)]
 pub struct LogsFilter {
-    /// listener for new blocks
-    pub blocks: NewBlockNotifications,
+    /// listener for new and removed blocks
+    pub blocks: ChainNotifications,
     /// accessor for block storage
     pub storage: StorageInfo,
     /// matcher with all provided filter params
@@ -174,11 +179,18 @@ impl LogsFilter {
     /// Returns all the logs since the last time this filter was polled
     pub fn poll(&mut self, cx: &mut Context<'_>) -> Vec<corebcLog> {
         let mut logs = self.historic.take().unwrap_or_default();
-        while let Poll::Ready(Some(block)) = self.blocks.poll_next_unpin(cx) {
-            let b = self.storage.block(block.hash);
-            let receipts = self.storage.receipts(block.hash);
-            if let (Some(receipts), Some(block)) = (receipts, b) {
-                logs.extend(filter_logs(block, receipts, &self.filter))
+        while let Poll::Ready(Some(notification)) = self.blocks.poll_next_unpin(cx) {
+            match notification {
+                ChainNotification::NewBlock(block) => {
+                    let b = self.storage.block(block.hash);
+                    let receipts = self.storage.receipts(block.hash);
+                    if let (Some(receipts), Some(block)) = (receipts, b) {
+                        logs.extend(filter_logs(block, receipts, &self.filter))
+                    }
+                }
+                ChainNotification::Removed(blocks) => {
+                    logs.extend(filter_removed_logs(&blocks, &self.filter))
+                }
             }
         }
         logs
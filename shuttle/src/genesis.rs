@@ -1,12 +1,15 @@
 //! Bindings for geth's `genesis.json` format
-use crate::revm::primitives::AccountInfo;
+use crate::{
+    eth::backend::db::SerializableAccountRecord, revm::primitives::AccountInfo, ForkCondition,
+    Hardfork, HardforkSchedule,
+};
 use corebc::{
     signers::LocalWallet,
-    types::{serde_helpers::*, Address, Bytes, H256, U256},
+    types::{serde_helpers::*, Address, Bytes, H256, U256, U64},
 };
 use foxar_common::errors::FsPathError;
 use foxar_evm::utils::u256_to_ru256;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 use spark::{
     revm::primitives::{Bytecode, Env, SHA3_EMPTY, U256 as rU256},
     utils::h176_to_b176,
@@ -26,20 +29,28 @@ pub struct Genesis {
     #[serde(
         default,
         deserialize_with = "deserialize_stringified_u64_opt",
+        serialize_with = "serialize_quantity_opt",
         skip_serializing_if = "Option::is_none"
     )]
     pub nonce: Option<u64>,
     #[serde(
         default,
         deserialize_with = "deserialize_stringified_u64_opt",
+        serialize_with = "serialize_quantity_opt",
         skip_serializing_if = "Option::is_none"
     )]
     pub timestamp: Option<u64>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub extra_data: Option<Bytes>,
-    #[serde(deserialize_with = "deserialize_stringified_u64")]
+    #[serde(
+        deserialize_with = "deserialize_stringified_u64",
+        serialize_with = "serialize_quantity"
+    )]
     pub energy_limit: u64,
-    #[serde(deserialize_with = "deserialize_stringified_u64")]
+    #[serde(
+        deserialize_with = "deserialize_stringified_u64",
+        serialize_with = "serialize_quantity"
+    )]
     pub difficulty: u64,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub coinbase: Option<Address>,
@@ -48,12 +59,14 @@ pub struct Genesis {
     #[serde(
         default,
         deserialize_with = "deserialize_stringified_u64_opt",
+        serialize_with = "serialize_quantity_opt",
         skip_serializing_if = "Option::is_none"
     )]
     pub number: Option<u64>,
     #[serde(
         default,
         deserialize_with = "deserialize_stringified_u64_opt",
+        serialize_with = "serialize_quantity_opt",
         skip_serializing_if = "Option::is_none"
     )]
     pub energy_used: Option<u64>,
@@ -117,6 +130,7 @@ pub struct GenesisAccount {
     #[serde(
         default,
         deserialize_with = "deserialize_stringified_u64_opt",
+        serialize_with = "serialize_quantity_opt",
         skip_serializing_if = "Option::is_none"
     )]
     pub nonce: Option<u64>,
@@ -129,6 +143,23 @@ pub struct GenesisAccount {
     pub private_key: Option<LocalWallet>,
 }
 
+impl From<SerializableAccountRecord> for GenesisAccount {
+    fn from(record: SerializableAccountRecord) -> Self {
+        let SerializableAccountRecord { nonce, balance, code, storage } = record;
+        GenesisAccount {
+            code: (!code.is_empty()).then_some(code),
+            storage: storage
+                .into_iter()
+                .filter(|(_, value)| !value.is_zero())
+                .map(|(slot, value)| (H256::from_uint(&slot), H256::from_uint(&value)))
+                .collect(),
+            balance,
+            nonce: Some(nonce),
+            private_key: None,
+        }
+    }
+}
+
 impl From<GenesisAccount> for AccountInfo {
     fn from(acc: GenesisAccount) -> Self {
         let GenesisAccount { code, balance, nonce, .. } = acc;
@@ -148,7 +179,7 @@ impl From<GenesisAccount> for AccountInfo {
 /// that any network, identified by its genesis block, can have its own
 /// set of configuration options.
 /// <(https://github.com/ethereum/go-ethereum/blob/0ce494b60cd00d70f1f9f2dd0b9bfbd76204168a/params/config.go#L342-L387>
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -192,6 +223,20 @@ pub struct Config {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub cancun_block: Option<u64>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homestead_time: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eip150_time: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eip158_time: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub byzantium_time: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub constantinople_time: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub petersburg_time: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub istanbul_time: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub terminal_total_difficulty: Option<u64>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub terminal_total_difficulty_passed: Option<bool>,
@@ -201,6 +246,99 @@ pub struct Config {
     pub clique: Option<CliqueConfig>,
 }
 
+impl Config {
+    /// Returns the hardfork schedule defined by the `*Block` and `*Time` activations, or `None` if
+    /// no hardfork is configured
+    ///
+    /// A block activation takes precedence over a time activation of the same hardfork.
+    pub fn hardfork_schedule(&self) -> Option<HardforkSchedule> {
+        let forks = [
+            (Hardfork::Homestead, self.homestead_block, self.homestead_time),
+            (Hardfork::Dao, self.dao_fork_block, None),
+            (Hardfork::Tangerine, self.eip150_block, self.eip150_time),
+            (Hardfork::SpuriousDragon, self.eip158_block, self.eip158_time),
+            (Hardfork::Byzantium, self.byzantium_block, self.byzantium_time),
+            (Hardfork::Constantinople, self.constantinople_block, self.constantinople_time),
+            (Hardfork::Petersburg, self.petersburg_block, self.petersburg_time),
+            (Hardfork::Istanbul, self.istanbul_block, self.istanbul_time),
+        ];
+        let forks: Vec<_> = forks
+            .into_iter()
+            .filter_map(|(fork, block, time)| {
+                let condition =
+                    block.map(ForkCondition::Block).or(time.map(ForkCondition::Timestamp))?;
+                Some((fork, condition))
+            })
+            .collect();
+        (!forks.is_empty()).then(|| HardforkSchedule::new(forks))
+    }
+
+    /// Returns the config of the chain with the given id and hardforks
+    ///
+    /// Every hardfork includes the ones before it, so a hardfork that isn't in the schedule
+    /// activates with the next one that is, e.g. all hardforks activate at genesis if
+    /// [`Hardfork::Latest`] does. [`Hardfork::Frontier`] and [`Hardfork::Latest`] themselves have
+    /// no activation in the config.
+    pub fn new(network_id: u64, schedule: &HardforkSchedule) -> Self {
+        let configured = |hardfork: Hardfork| {
+            schedule.forks().iter().find(|(fork, _)| *fork == hardfork).map(|(_, c)| *c)
+        };
+        let mut next = None;
+        let mut forks: Vec<_> = [
+            Hardfork::Homestead,
+            Hardfork::Tangerine,
+            Hardfork::SpuriousDragon,
+            Hardfork::Byzantium,
+            Hardfork::Constantinople,
+            Hardfork::Petersburg,
+            Hardfork::Istanbul,
+            Hardfork::Latest,
+        ]
+        .into_iter()
+        .rev()
+        .filter_map(|fork| {
+            next = configured(fork).or(next);
+            Some((fork, next?))
+        })
+        .collect();
+        // the dao fork is optional, so it's only activated if it's configured
+        forks.extend(configured(Hardfork::Dao).map(|condition| (Hardfork::Dao, condition)));
+
+        let mut config = Config { network_id: Some(network_id), ..Default::default() };
+        for (fork, condition) in forks.iter() {
+            let (block, time) = match fork {
+                Hardfork::Homestead => (&mut config.homestead_block, &mut config.homestead_time),
+                Hardfork::Dao => {
+                    if let ForkCondition::Block(block) = condition {
+                        config.dao_fork_block = Some(*block);
+                    }
+                    continue
+                }
+                Hardfork::Tangerine => (&mut config.eip150_block, &mut config.eip150_time),
+                Hardfork::SpuriousDragon => {
+                    if let ForkCondition::Block(block) = condition {
+                        // replay protection activated with spurious dragon
+                        config.eip155_block = Some(*block);
+                    }
+                    (&mut config.eip158_block, &mut config.eip158_time)
+                }
+                Hardfork::Byzantium => (&mut config.byzantium_block, &mut config.byzantium_time),
+                Hardfork::Constantinople => {
+                    (&mut config.constantinople_block, &mut config.constantinople_time)
+                }
+                Hardfork::Petersburg => (&mut config.petersburg_block, &mut config.petersburg_time),
+                Hardfork::Istanbul => (&mut config.istanbul_block, &mut config.istanbul_time),
+                Hardfork::Frontier | Hardfork::Latest => continue,
+            };
+            match *condition {
+                ForkCondition::Block(number) => *block = Some(number),
+                ForkCondition::Timestamp(timestamp) => *time = Some(timestamp),
+            }
+        }
+        config
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EthashConfig {}
 
@@ -210,6 +348,21 @@ pub struct CliqueConfig {
     pub epoch: u64,
 }
 
+/// Serializes a number as a hex quantity, like go-core writes the genesis header fields
+fn serialize_quantity<S>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    U64::from(*value).serialize(serializer)
+}
+
+fn serialize_quantity_opt<S>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    value.map(U64::from).serialize(serializer)
+}
+
 /// serde support for `secretKey` in genesis
 
 pub mod secret_key {
@@ -284,5 +437,76 @@ mod tests {
         assert_eq!(gen.alloc.accounts.len(), 1);
         let config = gen.config.unwrap();
         assert_eq!(config.network_id, Some(19763));
+        assert_eq!(config.hardfork_schedule().unwrap().hardfork_at(0, 0), Hardfork::Byzantium);
+    }
+
+    #[test]
+    fn can_parse_hardfork_activations() {
+        let s = r#"{
+    "networkId": 1337,
+    "byzantiumBlock": 0,
+    "istanbulBlock": 10,
+    "istanbulTime": 100,
+    "petersburgTime": 50
+}
+"#;
+        let config: Config = serde_json::from_str(s).unwrap();
+        let schedule = config.hardfork_schedule().unwrap();
+        assert_eq!(
+            schedule.forks(),
+            &[
+                (Hardfork::Byzantium, ForkCondition::Block(0)),
+                (Hardfork::Petersburg, ForkCondition::Timestamp(50)),
+                (Hardfork::Istanbul, ForkCondition::Block(10)),
+            ]
+        );
+        assert_eq!(schedule.hardfork_at(9, 50), Hardfork::Petersburg);
+        assert_eq!(schedule.hardfork_at(10, 0), Hardfork::Istanbul);
+    }
+
+    #[test]
+    fn can_convert_hardfork_schedule_to_config() {
+        let schedule = HardforkSchedule::new([
+            (Hardfork::SpuriousDragon, ForkCondition::Block(0)),
+            (Hardfork::Byzantium, ForkCondition::Block(5)),
+            (Hardfork::Istanbul, ForkCondition::Timestamp(100)),
+            (Hardfork::Latest, ForkCondition::Block(10)),
+        ]);
+        let config = Config::new(1337, &schedule);
+        assert_eq!(config.network_id, Some(1337));
+        assert_eq!(config.eip155_block, Some(0));
+        assert_eq!(config.istanbul_time, Some(100));
+        assert_eq!(
+            config.hardfork_schedule().unwrap().forks(),
+            &[
+                (Hardfork::Homestead, ForkCondition::Block(0)),
+                (Hardfork::Tangerine, ForkCondition::Block(0)),
+                (Hardfork::SpuriousDragon, ForkCondition::Block(0)),
+                (Hardfork::Byzantium, ForkCondition::Block(5)),
+                (Hardfork::Constantinople, ForkCondition::Timestamp(100)),
+                (Hardfork::Petersburg, ForkCondition::Timestamp(100)),
+                (Hardfork::Istanbul, ForkCondition::Timestamp(100)),
+            ]
+        );
+    }
+
+    #[test]
+    fn activates_all_hardforks_for_latest() {
+        let config = Config::new(1337, &HardforkSchedule::single(Hardfork::Latest));
+        for block in [
+            config.homestead_block,
+            config.eip150_block,
+            config.eip155_block,
+            config.eip158_block,
+            config.byzantium_block,
+            config.constantinople_block,
+            config.petersburg_block,
+            config.istanbul_block,
+        ] {
+            assert_eq!(block, Some(0));
+        }
+        assert_eq!(config.dao_fork_block, None);
+        // the latest rules are the rules of istanbul
+        assert_eq!(config.hardfork_schedule().unwrap().hardfork_at(0, 0), Hardfork::Istanbul);
     }
 }
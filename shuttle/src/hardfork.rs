@@ -1,9 +1,11 @@
-use corebc::types::BlockNumber;
+use corebc::types::{BlockNumber, H256};
+use crc::{Crc, CRC_32_ISO_HDLC};
 use ethereum_forkid::{ForkHash, ForkId};
 use foxar_evm::revm::primitives::SpecId;
-use std::str::FromStr;
+use std::{collections::BTreeSet, str::FromStr};
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+/// The hardforks in the order they were activated
+#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash, Default)]
 pub enum Hardfork {
     Frontier,
     Homestead,
@@ -18,17 +20,173 @@ pub enum Hardfork {
     Latest,
 }
 
-impl Hardfork {
-    /// Get the first block number of the hardfork.
-    pub fn fork_block(&self) -> u64 {
-        // match *self {
-        //     _ => 0,
-        // }
-        0
+/// When a hardfork activates
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ForkCondition {
+    /// Activates at the block with this number
+    Block(u64),
+    /// Activates at the first block with a timestamp at or after this timestamp
+    Timestamp(u64),
+}
+
+// === impl ForkCondition ===
+
+impl ForkCondition {
+    /// Returns true if the condition is met by the block with the given number and timestamp
+    pub fn is_active_at(&self, number: u64, timestamp: u64) -> bool {
+        match *self {
+            ForkCondition::Block(block) => number >= block,
+            ForkCondition::Timestamp(time) => timestamp >= time,
+        }
     }
+}
+
+/// The hardforks of a chain and when they activate
+///
+/// Blocks before the first activation use the [`Hardfork::Frontier`] rules.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HardforkSchedule {
+    forks: Vec<(Hardfork, ForkCondition)>,
+}
 
-    pub fn fork_id(&self) -> ForkId {
-        ForkId { hash: ForkHash([0x87, 0x9d, 0x6e, 0x30]), next: 0 }
+// === impl HardforkSchedule ===
+
+impl HardforkSchedule {
+    /// Creates a new schedule from the hardforks and their activation conditions
+    pub fn new(forks: impl IntoIterator<Item = (Hardfork, ForkCondition)>) -> Self {
+        let mut forks: Vec<_> = forks.into_iter().collect();
+        forks.sort_by_key(|(fork, _)| *fork);
+        forks.dedup_by_key(|(fork, _)| *fork);
+        Self { forks }
+    }
+
+    /// Returns a schedule that activates the given hardfork at genesis
+    pub fn single(hardfork: Hardfork) -> Self {
+        Self::new([(hardfork, ForkCondition::Block(0))])
+    }
+
+    /// The schedule of Core mainnet, which runs the latest rules since genesis
+    pub fn mainnet() -> Self {
+        Self::single(Hardfork::Latest)
+    }
+
+    /// The schedule of the Core devin testnet, which runs the latest rules since genesis
+    pub fn devin() -> Self {
+        Self::single(Hardfork::Latest)
+    }
+
+    /// Returns all hardforks with their activation conditions, ordered by hardfork
+    pub fn forks(&self) -> &[(Hardfork, ForkCondition)] {
+        &self.forks
+    }
+
+    /// Returns the hardfork that is active at the block with the given number and timestamp
+    pub fn hardfork_at(&self, number: u64, timestamp: u64) -> Hardfork {
+        self.forks
+            .iter()
+            .filter(|(_, condition)| condition.is_active_at(number, timestamp))
+            .map(|(fork, _)| *fork)
+            .max()
+            .unwrap_or(Hardfork::Frontier)
+    }
+
+    /// Returns the spec of the hardfork that is active at the block with the given number and
+    /// timestamp
+    pub fn spec_id_at(&self, number: u64, timestamp: u64) -> SpecId {
+        self.hardfork_at(number, timestamp).into()
+    }
+
+    /// Returns the [EIP-2124](https://eips.ethereum.org/EIPS/eip-2124) fork id of a chain at the
+    /// given head
+    ///
+    /// Block based activations are followed by timestamp based activations as specified by
+    /// [EIP-6122](https://eips.ethereum.org/EIPS/eip-6122), activations at genesis are skipped.
+    pub fn fork_id(
+        &self,
+        genesis_hash: H256,
+        genesis_timestamp: u64,
+        number: u64,
+        timestamp: u64,
+    ) -> ForkId {
+        let mut blocks = BTreeSet::new();
+        let mut timestamps = BTreeSet::new();
+        for (_, condition) in self.forks.iter() {
+            match *condition {
+                ForkCondition::Block(block) if block > 0 => {
+                    blocks.insert(block);
+                }
+                ForkCondition::Timestamp(time) if time > genesis_timestamp => {
+                    timestamps.insert(time);
+                }
+                _ => {}
+            }
+        }
+
+        let crc = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+        let mut digest = crc.digest();
+        digest.update(genesis_hash.as_bytes());
+
+        let activations = blocks
+            .into_iter()
+            .map(|block| (block, block <= number))
+            .chain(timestamps.into_iter().map(|time| (time, time <= timestamp)));
+        let mut next = 0;
+        for (activation, passed) in activations {
+            if !passed {
+                next = activation;
+                break
+            }
+            digest.update(&activation.to_be_bytes());
+        }
+
+        ForkId { hash: ForkHash(digest.finalize().to_be_bytes()), next }
+    }
+}
+
+impl Default for HardforkSchedule {
+    fn default() -> Self {
+        Self::single(Hardfork::default())
+    }
+}
+
+impl FromStr for HardforkSchedule {
+    type Err = String;
+
+    /// Parses either the name of a network, `mainnet` or `devin`, or a comma separated list of
+    /// `<hardfork>=<block>` and `<hardfork>=@<timestamp>` activations, e.g.
+    /// `byzantium=0,istanbul=100,latest=@1700000000`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "mainnet" => return Ok(Self::mainnet()),
+            "devin" => return Ok(Self::devin()),
+            _ => {}
+        }
+
+        let mut forks = Vec::new();
+        for activation in s.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (name, condition) = activation
+                .split_once('=')
+                .ok_or_else(|| format!("Missing activation of hardfork {activation}"))?;
+            let hardfork = name.trim().parse::<Hardfork>()?;
+            let condition = condition.trim();
+            let condition = if let Some(timestamp) = condition.strip_prefix('@') {
+                ForkCondition::Timestamp(
+                    timestamp.parse().map_err(|_| format!("Invalid timestamp {timestamp}"))?,
+                )
+            } else {
+                ForkCondition::Block(
+                    condition.parse().map_err(|_| format!("Invalid block number {condition}"))?,
+                )
+            };
+            if forks.iter().any(|(fork, _)| *fork == hardfork) {
+                return Err(format!("Duplicate hardfork {name}"))
+            }
+            forks.push((hardfork, condition));
+        }
+        if forks.is_empty() {
+            return Err("Empty hardfork schedule".to_string())
+        }
+        Ok(Self::new(forks))
     }
 }
 
@@ -47,7 +205,8 @@ impl FromStr for Hardfork {
             "constantinople" | "7" => Hardfork::Constantinople,
             "petersburg" | "8" => Hardfork::Petersburg,
             "istanbul" | "9" => Hardfork::Istanbul,
-            "latest" => Hardfork::Latest,
+            // Core mainnet and devin run the latest rules
+            "latest" | "mainnet" | "devin" => Hardfork::Latest,
             _ => return Err(format!("Unknown hardfork {s}")),
         };
         Ok(hardfork)
@@ -84,47 +243,55 @@ impl<T: Into<BlockNumber>> From<T> for Hardfork {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
 
     #[test]
-    // this test checks that the fork hash assigned to forks accurately map to the fork_id method
+    // this test checks that the fork hash is the crc32 of the genesis hash and all passed fork
+    // blocks
     fn test_forkhash_from_fork_blocks() {
-        // // set the genesis hash
-        // let genesis =
-        //     hex::decode("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3")
-        //         .unwrap();
-        //
-        // // instantiate the crc "hasher"
-        // let crc_hasher = Crc::<u32>::new(&CRC_32_ISO_HDLC);
-        // let mut crc_digest = crc_hasher.digest();
-        //
-        // // check frontier forkhash
-        // crc_digest.update(&genesis);
-        //
-        // // now we go through enum members
-        // let frontier_forkid = Hardfork::Frontier.fork_id();
-        // let frontier_forkhash = u32::from_be_bytes(frontier_forkid.hash.0);
-        // // clone the digest for finalization so we can update it again
-        // assert_eq!(crc_digest.clone().finalize(), frontier_forkhash);
-        //
-        // // list of the above hardforks
-        // let hardforks = vec![
-        //     Hardfork::Homestead,
-        //     Hardfork::Dao,
-        //     Hardfork::Tangerine,
-        //     Hardfork::SpuriousDragon,
-        //     Hardfork::Byzantium,
-        //     Hardfork::Constantinople,
-        //     Hardfork::Istanbul,
-        // ];
-        //
-        // // now loop through each hardfork, conducting each forkhash test
-        // for hardfork in hardforks {
-        //     // this could also be done with frontier_forkhash.next, but fork_block is used for
-        // more     // coverage
-        //     let fork_block = hardfork.fork_block().to_be_bytes();
-        //     crc_digest.update(&fork_block);
-        //     let fork_hash = u32::from_be_bytes(hardfork.fork_id().hash.0);
-        //     assert_eq!(crc_digest.clone().finalize(), fork_hash);
-        // }
+        let genesis =
+            H256::from_str("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3")
+                .unwrap();
+        let schedule = HardforkSchedule::new([
+            (Hardfork::Frontier, ForkCondition::Block(0)),
+            (Hardfork::Homestead, ForkCondition::Block(1_150_000)),
+            (Hardfork::Dao, ForkCondition::Block(1_920_000)),
+            (Hardfork::Tangerine, ForkCondition::Block(2_463_000)),
+        ]);
+
+        // the well known mainnet fork ids of EIP-2124
+        let frontier = schedule.fork_id(genesis, 0, 0, 0);
+        assert_eq!(frontier.hash, ForkHash([0xfc, 0x64, 0xec, 0x04]));
+        assert_eq!(frontier.next, 1_150_000);
+
+        let homestead = schedule.fork_id(genesis, 0, 1_150_000, 0);
+        assert_eq!(homestead.hash, ForkHash([0x97, 0xc2, 0xc3, 0x4c]));
+        assert_eq!(homestead.next, 1_920_000);
+
+        let dao = schedule.fork_id(genesis, 0, 1_920_000, 0);
+        assert_eq!(dao.hash, ForkHash([0x91, 0xd1, 0xf9, 0x48]));
+        assert_eq!(dao.next, 2_463_000);
+
+        let tangerine = schedule.fork_id(genesis, 0, 2_463_000, 0);
+        assert_eq!(tangerine.hash, ForkHash([0x7a, 0x64, 0xda, 0x13]));
+        assert_eq!(tangerine.next, 0);
+    }
+
+    #[test]
+    fn test_hardfork_at() {
+        let schedule: HardforkSchedule = "byzantium=0,istanbul=10,latest=@1000".parse().unwrap();
+        assert_eq!(schedule.hardfork_at(0, 0), Hardfork::Byzantium);
+        assert_eq!(schedule.hardfork_at(9, 999), Hardfork::Byzantium);
+        assert_eq!(schedule.hardfork_at(10, 999), Hardfork::Istanbul);
+        assert_eq!(schedule.hardfork_at(11, 1000), Hardfork::Latest);
+
+        let schedule: HardforkSchedule = "istanbul=5".parse().unwrap();
+        assert_eq!(schedule.hardfork_at(4, 0), Hardfork::Frontier);
+        assert_eq!(schedule.spec_id_at(5, 0), SpecId::ISTANBUL);
+
+        assert_eq!("mainnet".parse::<HardforkSchedule>().unwrap(), HardforkSchedule::mainnet());
+        assert_eq!("devin".parse::<HardforkSchedule>().unwrap(), HardforkSchedule::devin());
+        assert!("istanbul".parse::<HardforkSchedule>().is_err());
+        assert!("istanbul=1,istanbul=2".parse::<HardforkSchedule>().is_err());
     }
 }
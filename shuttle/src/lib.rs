@@ -9,6 +9,7 @@ use crate::{
     },
     filter::Filters,
     logging::{LoggingManager, NodeLogLayer},
+    network::NetworkLink,
     service::NodeService,
     shutdown::Signal,
     tasks::TaskManager,
@@ -47,7 +48,7 @@ use crate::server::{
     error::{NodeError, NodeResult},
     spawn_ipc,
 };
-pub use hardfork::Hardfork;
+pub use hardfork::{ForkCondition, Hardfork, HardforkSchedule};
 
 /// ethereum related implementations
 pub mod eth;
@@ -57,6 +58,8 @@ pub mod filter;
 pub mod genesis;
 /// commandline output
 pub mod logging;
+/// support for running a local network of nodes
+pub mod network;
 /// types for subscriptions
 pub mod pubsub;
 /// axum RPC server implementations
@@ -89,11 +92,36 @@ pub mod cmd;
 /// handle.await.unwrap();
 /// # }
 /// ```
-pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
+///
+/// # Panics
+///
+/// Panics if the node can't be created, see [try_spawn()]
+pub async fn spawn(config: NodeConfig) -> (EthApi, NodeHandle) {
+    try_spawn(config).await.expect("failed to spawn node")
+}
+
+/// Creates the node and runs the server
+///
+/// Returns an error if the node can't be created, for example if the configured init state can't
+/// be loaded.
+///
+/// See also [spawn()]
+pub async fn try_spawn(config: NodeConfig) -> NodeResult<(EthApi, NodeHandle)> {
+    spawn_node(config, None).await
+}
+
+/// Creates the node and runs the server, if `network_link` is set the node follows the mining node
+/// of a local network
+///
+/// See also [network::spawn_network()]
+pub(crate) async fn spawn_node(
+    mut config: NodeConfig,
+    network_link: Option<NetworkLink>,
+) -> NodeResult<(EthApi, NodeHandle)> {
     let logger = if config.enable_tracing { init_tracing() } else { Default::default() };
     logger.set_enabled(!config.silent);
 
-    let backend = Arc::new(config.setup().await);
+    let backend = Arc::new(config.setup().await?);
 
     let fork = backend.get_fork().cloned();
 
@@ -104,14 +132,27 @@ pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
         max_transactions,
         server_config,
         no_mining,
+        mine_on_fill,
+        mine_every,
         transaction_order,
         genesis,
+        pool_config,
         ..
     } = config.clone();
 
-    let pool = Arc::new(Pool::default());
+    let pool = Arc::new(Pool::new(pool_config));
 
-    let mode = if let Some(block_time) = block_time {
+    let mode = if let Some(transactions) = mine_every {
+        MiningMode::transaction_count(transactions, pool.add_ready_listener())
+    } else if let Some(fill) = mine_on_fill {
+        let env = backend.env().clone();
+        let listener = pool.add_ready_listener();
+        if let Some(block_time) = block_time {
+            MiningMode::interval_or_energy_fill(block_time, fill, env, listener)
+        } else {
+            MiningMode::energy_fill(fill, env, listener)
+        }
+    } else if let Some(block_time) = block_time {
         MiningMode::interval(block_time)
     } else if no_mining {
         MiningMode::None
@@ -144,7 +185,8 @@ pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
         logger,
         filters.clone(),
         transaction_order,
-    );
+    )
+    .with_network_link(network_link);
 
     // spawn the node service
     let node_service = tokio::task::spawn(NodeService::new(pool, backend, miner, filters));
@@ -153,7 +195,7 @@ pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
     let mut addr = SocketAddr::new(host, port);
 
     // configure the rpc server and use its actual local address
-    let server = server::serve(addr, api.clone(), server_config);
+    let server = server::serve(addr, api.clone(), server_config.clone());
     addr = server.local_addr();
 
     // spawn the server on a new task
@@ -163,7 +205,7 @@ pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
     let (signal, on_shutdown) = shutdown::signal();
     let task_manager = TaskManager::new(tokio_handle, on_shutdown);
 
-    let ipc_task = config.get_ipc_path().map(|path| spawn_ipc(api.clone(), path));
+    let ipc_task = config.get_ipc_path().map(|path| spawn_ipc(api.clone(), path, &server_config));
 
     let handle = NodeHandle {
         config,
@@ -177,7 +219,7 @@ pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
 
     handle.print(fork.as_ref());
 
-    (api, handle)
+    Ok((api, handle))
 }
 
 type IpcTask = JoinHandle<io::Result<()>>;
@@ -0,0 +1,231 @@
+//! Support for running a local network of nodes that share a genesis
+//!
+//! The first node of the network mines blocks, all other nodes follow it by executing its blocks.
+//! A following node can be partitioned from the network, in which case it mines its own blocks
+//! until it's healed and replaces them with the chain of the mining node.
+
+use crate::{
+    eth::{error::BlockchainError, EthApi},
+    spawn_node,
+    tasks::block_listener::BlockListener,
+    NodeConfig, NodeHandle, NodeResult,
+};
+use corebc::{types::H256, utils::rlp};
+use futures::{
+    channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+    StreamExt,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::Mutex;
+use tracing::{trace, warn};
+
+/// The connection of a following node to the mining node of a local network
+#[derive(Clone, Debug)]
+pub struct NetworkLink {
+    inner: Arc<LinkInner>,
+}
+
+#[derive(Debug)]
+struct LinkInner {
+    /// Whether the node is currently partitioned from the network
+    partitioned: AtomicBool,
+    /// Wakes up the node when the link changes
+    on_change: UnboundedSender<()>,
+}
+
+// === impl NetworkLink ===
+
+impl NetworkLink {
+    fn new() -> (Self, UnboundedReceiver<()>) {
+        let (on_change, changes) = unbounded();
+        let link =
+            Self { inner: Arc::new(LinkInner { partitioned: AtomicBool::new(false), on_change }) };
+        (link, changes)
+    }
+
+    /// Returns true if the node is partitioned from the network
+    pub fn is_partitioned(&self) -> bool {
+        self.inner.partitioned.load(Ordering::SeqCst)
+    }
+
+    /// Partitions the node from the network
+    ///
+    /// Returns false if the node was already partitioned.
+    pub fn partition(&self) -> bool {
+        self.set_partitioned(true)
+    }
+
+    /// Reconnects the node to the network
+    ///
+    /// Returns false if the node wasn't partitioned.
+    pub fn heal(&self) -> bool {
+        self.set_partitioned(false)
+    }
+
+    fn set_partitioned(&self, partitioned: bool) -> bool {
+        let changed = self.inner.partitioned.swap(partitioned, Ordering::SeqCst) != partitioned;
+        if changed {
+            let _ = self.inner.on_change.unbounded_send(());
+        }
+        changed
+    }
+}
+
+/// Spawns a local network of `nodes` nodes from the given config
+///
+/// All nodes share the genesis of the config and node `i` listens on `port + i`, unless the port
+/// is `0`. The first node mines blocks according to the config, the other nodes don't mine and
+/// follow the first node instead. Transactions that are submitted to a following node are
+/// forwarded to the first node.
+///
+/// Returns the [EthApi] and [NodeHandle] of every node, starting with the mining node, or an error
+/// if a node can't be created.
+///
+/// # Example
+///
+/// ```rust
+/// # use shuttle::NodeConfig;
+/// # async fn spawn() {
+/// let nodes = shuttle::network::spawn_network(NodeConfig::default(), 3).await.unwrap();
+/// let (miner, _) = &nodes[0];
+/// # }
+/// ```
+pub async fn spawn_network(
+    config: NodeConfig,
+    nodes: usize,
+) -> NodeResult<Vec<(EthApi, NodeHandle)>> {
+    // all nodes must start with the same genesis block
+    let timestamp = config.get_genesis_timestamp();
+    let config = config.with_genesis_timestamp(Some(timestamp));
+
+    let (leader, leader_handle) = spawn_node(config.clone(), None).await?;
+    let fork_block_number = leader.get_fork().map(|fork| fork.block_number());
+
+    let mut network = Vec::with_capacity(nodes.max(1));
+    for index in 1..nodes {
+        let port = if config.port == 0 { 0 } else { config.port.saturating_add(index as u16) };
+        let mut follower_config = config
+            .clone()
+            .with_port(port)
+            .with_blocktime::<Duration>(None)
+            .with_no_mining(true)
+            .with_mine_on_fill(None)
+            .with_mine_every(None)
+            .with_ipc(None)
+            .with_data_dir(None);
+        if fork_block_number.is_some() {
+            follower_config = follower_config.with_fork_block_number(fork_block_number);
+        }
+        // the global logger is set up by the mining node, which also prints the shared config
+        follower_config.config_out = None;
+        follower_config.enable_tracing = false;
+        follower_config.silent = true;
+
+        let (link, changes) = NetworkLink::new();
+        let (follower, handle) = spawn_node(follower_config, Some(link.clone())).await?;
+        if !config.silent {
+            println!("Node {index} listening on {}", handle.socket_address());
+        }
+        spawn_follower(leader.clone(), follower.clone(), link.clone(), changes, &handle);
+        spawn_forwarder(leader.clone(), follower.clone(), link, &handle);
+        network.push((follower, handle));
+    }
+    network.insert(0, (leader, leader_handle));
+    Ok(network)
+}
+
+/// Spawns the task that keeps the chain of `follower` in sync with the chain of `leader`
+fn spawn_follower(
+    leader: EthApi,
+    follower: EthApi,
+    link: NetworkLink,
+    changes: UnboundedReceiver<()>,
+    handle: &NodeHandle,
+) {
+    // catch up with the blocks the leader mined before the follower was spawned
+    let _ = link.inner.on_change.unbounded_send(());
+
+    let task_manager = handle.task_manager().clone();
+    let notifications = futures::stream::select(leader.chain_notifications().map(|_| ()), changes);
+    let lock = Arc::new(Mutex::new(()));
+    let listener = BlockListener::new(task_manager.on_shutdown(), notifications, move |_| {
+        let leader = leader.clone();
+        let follower = follower.clone();
+        let link = link.clone();
+        let lock = Arc::clone(&lock);
+        // run on a separate task so a sync isn't cancelled by the next notification
+        let sync = task_manager.spawn(async move {
+            let _guard = lock.lock().await;
+            if link.is_partitioned() {
+                return
+            }
+            if let Err(err) = sync(&leader, &follower).await {
+                warn!(target: "network", ?err, "failed to sync with the mining node");
+            }
+        });
+        async move {
+            let _ = sync.await;
+        }
+    });
+    handle.task_manager().spawn(listener);
+}
+
+/// Spawns the task that forwards the transactions that are submitted to `follower` to `leader`
+fn spawn_forwarder(leader: EthApi, follower: EthApi, link: NetworkLink, handle: &NodeHandle) {
+    let task_manager = handle.task_manager();
+    let mut ready = follower.new_ready_transactions().take_until(task_manager.on_shutdown());
+    task_manager.spawn(async move {
+        while let Some(hash) = ready.next().await {
+            if link.is_partitioned() {
+                continue
+            }
+            let Some(tx) = follower.pool().get_transaction(hash) else { continue };
+            if tx.pending_transaction.transaction.impersonated_sender.is_some() {
+                trace!(target: "network", ?hash, "not forwarding impersonated transaction");
+                continue
+            }
+            let raw = rlp::encode(&tx.pending_transaction.transaction.transaction);
+            if let Err(err) = leader.send_raw_transaction(raw.to_vec().into()).await {
+                warn!(target: "network", ?hash, ?err, "failed to forward transaction");
+            }
+        }
+    });
+}
+
+/// Replaces the blocks of `follower` that aren't part of the chain of `leader` and imports the
+/// missing blocks of `leader`
+async fn sync(leader: &EthApi, follower: &EthApi) -> Result<(), BlockchainError> {
+    let leader_best = leader.backend().best_number().as_u64();
+    let follower_best = follower.backend().best_number().as_u64();
+
+    // find the most recent block both chains have in common
+    let mut common = leader_best.min(follower_best);
+    while block_hash(follower, common) != block_hash(leader, common) {
+        if common == 0 {
+            return Err(BlockchainError::Internal("nodes don't share a genesis".to_string()))
+        }
+        common -= 1;
+    }
+
+    if follower_best > common {
+        trace!(target: "network", "removing {} diverged blocks", follower_best - common);
+        follower.backend().rollback(follower_best - common).await?;
+    }
+    for number in (common + 1)..=leader_best {
+        let block = leader.backend().get_block(number).ok_or(BlockchainError::BlockNotFound)?;
+        let transactions = leader.backend().mined_pending_transactions(&block)?;
+        follower.import_block(&block, transactions).await?;
+    }
+    Ok(())
+}
+
+/// Returns the hash of the block with the given number
+fn block_hash(api: &EthApi, number: u64) -> Option<H256> {
+    api.backend().get_block(number).map(|block| block.header.hash())
+}
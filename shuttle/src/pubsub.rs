@@ -1,5 +1,12 @@
 use crate::{
-    eth::{backend::notifications::NewBlockNotifications, error::to_rpc_result},
+    eth::{
+        backend::notifications::{
+            ChainNotification, ChainNotifications, NewBlockNotifications, RemovedBlock,
+        },
+        error::to_rpc_result,
+        pool::Pool,
+    },
+    mem::pending_transaction_build,
     StorageInfo, U256,
 };
 use corebc::{
@@ -11,19 +18,24 @@ use serde::Serialize;
 use shuttle_core::eth::{
     block::Block,
     receipt::{EIP658Receipt, Log, TypedReceipt},
-    subscription::{SubscriptionId, SubscriptionResult},
+    subscription::{SubscriptionId, SubscriptionResult, ValueTransfer},
 };
 use shuttle_rpc::{request::Version, response::ResponseResult};
 use std::{
     collections::VecDeque,
+    fmt,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
 /// Listens for new blocks and matching logs emitted in that block
+///
+/// If blocks are removed, by reverting to a snapshot or resetting the fork, their matching logs
+/// are emitted again with `removed: true`.
 #[derive(Debug)]
 pub struct LogsSubscription {
-    pub blocks: NewBlockNotifications,
+    pub blocks: ChainNotifications,
     pub storage: StorageInfo,
     pub filter: FilteredParams,
     pub queued: VecDeque<EthersLog>,
@@ -43,25 +55,102 @@ impl LogsSubscription {
                 return Poll::Ready(Some(EthSubscriptionResponse::new(params)))
             }
 
-            if let Some(block) = ready!(self.blocks.poll_next_unpin(cx)) {
-                let b = self.storage.block(block.hash);
-                let receipts = self.storage.receipts(block.hash);
-                if let (Some(receipts), Some(block)) = (receipts, b) {
-                    let logs = filter_logs(block, receipts, &self.filter);
-                    if logs.is_empty() {
-                        // this ensures we poll the receiver until it is pending, in which case the
-                        // underlying `UnboundedReceiver` will register the new waker, see
-                        // [`futures::channel::mpsc::UnboundedReceiver::poll_next()`]
-                        continue
+            if let Some(notification) = ready!(self.blocks.poll_next_unpin(cx)) {
+                match notification {
+                    ChainNotification::NewBlock(block) => {
+                        let b = self.storage.block(block.hash);
+                        let receipts = self.storage.receipts(block.hash);
+                        if let (Some(receipts), Some(block)) = (receipts, b) {
+                            self.queued.extend(filter_logs(block, receipts, &self.filter))
+                        }
+                    }
+                    ChainNotification::Removed(blocks) => {
+                        self.queued.extend(filter_removed_logs(&blocks, &self.filter))
                     }
-                    self.queued.extend(logs)
+                }
+                if self.queued.is_empty() {
+                    // this ensures we poll the receiver until it is pending, in which case the
+                    // underlying `UnboundedReceiver` will register the new waker, see
+                    // [`futures::channel::mpsc::UnboundedReceiver::poll_next()`]
+                    continue
                 }
             } else {
                 return Poll::Ready(None)
             }
+        }
+    }
+}
+
+/// Listens for new ready transactions and yields the full transactions
+pub struct FullPendingTransactionsSubscription {
+    pub transactions: Receiver<TxHash>,
+    pub pool: Arc<Pool>,
+    pub storage: StorageInfo,
+    pub id: SubscriptionId,
+}
+
+// === impl FullPendingTransactionsSubscription ===
 
-            if self.queued.is_empty() {
-                return Poll::Pending
+impl fmt::Debug for FullPendingTransactionsSubscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FullPendingTransactionsSubscription")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FullPendingTransactionsSubscription {
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<Option<EthSubscriptionResponse>> {
+        loop {
+            let Some(hash) = ready!(self.transactions.poll_next_unpin(cx)) else {
+                return Poll::Ready(None)
+            };
+            // the transaction may have been mined already
+            let tx = self
+                .pool
+                .get_transaction(hash)
+                .map(pending_transaction_build)
+                .or_else(|| self.storage.transaction(hash));
+            if let Some(tx) = tx {
+                let params = EthSubscriptionParams {
+                    subscription: self.id.clone(),
+                    result: to_rpc_result(SubscriptionResult::Transaction(Box::new(tx))),
+                };
+                return Poll::Ready(Some(EthSubscriptionResponse::new(params)))
+            }
+        }
+    }
+}
+
+/// Listens for new blocks and the value transfers of their transactions
+#[derive(Debug)]
+pub struct TransfersSubscription {
+    pub blocks: NewBlockNotifications,
+    pub storage: StorageInfo,
+    pub queued: VecDeque<ValueTransfer>,
+    pub id: SubscriptionId,
+}
+
+// === impl TransfersSubscription ===
+
+impl TransfersSubscription {
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<Option<EthSubscriptionResponse>> {
+        loop {
+            if let Some(transfer) = self.queued.pop_front() {
+                let params = EthSubscriptionParams {
+                    subscription: self.id.clone(),
+                    result: to_rpc_result(SubscriptionResult::Transfer(Box::new(transfer))),
+                };
+                return Poll::Ready(Some(EthSubscriptionResponse::new(params)))
+            }
+
+            // this loop ensures we poll the receiver until it is pending, in which case the
+            // underlying `UnboundedReceiver` will register the new waker
+            let Some(block) = ready!(self.blocks.poll_next_unpin(cx)) else {
+                return Poll::Ready(None)
+            };
+            if let Some(transfers) = self.storage.value_transfers(block.hash) {
+                self.queued.extend(transfers);
             }
         }
     }
@@ -96,6 +185,8 @@ pub enum EthSubscription {
     Logs(Box<LogsSubscription>),
     Header(NewBlockNotifications, StorageInfo, SubscriptionId),
     PendingTransactions(Receiver<TxHash>, SubscriptionId),
+    FullPendingTransactions(Box<FullPendingTransactionsSubscription>),
+    Transfers(Box<TransfersSubscription>),
 }
 
 // === impl EthSubscription ===
@@ -132,6 +223,8 @@ impl EthSubscription {
                     });
                 Poll::Ready(res)
             }
+            EthSubscription::FullPendingTransactions(listener) => listener.poll(cx),
+            EthSubscription::Transfers(listener) => listener.poll(cx),
         }
     }
 }
@@ -214,3 +307,15 @@ pub fn filter_logs(
     }
     logs
 }
+
+/// Returns all the logs of the removed blocks that match the given filter, with `removed: true`
+pub fn filter_removed_logs(blocks: &[RemovedBlock], filter: &FilteredParams) -> Vec<EthersLog> {
+    blocks
+        .iter()
+        .flat_map(|removed| filter_logs(removed.block.clone(), removed.receipts.clone(), filter))
+        .map(|mut log| {
+            log.removed = Some(true);
+            log
+        })
+        .collect()
+}
@@ -1,3 +1,5 @@
+use crate::eth::error::BlockchainError;
+
 /// Result alias
 pub type NodeResult<T> = Result<T, NodeError>;
 
@@ -8,4 +10,6 @@ pub enum NodeError {
     Hyper(#[from] hyper::Error),
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Blockchain(#[from] BlockchainError),
 }
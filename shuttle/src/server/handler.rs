@@ -1,7 +1,10 @@
 //! Contains RPC handlers
 use crate::{
     eth::error::to_rpc_result,
-    pubsub::{EthSubscription, LogsSubscription},
+    pubsub::{
+        EthSubscription, FullPendingTransactionsSubscription, LogsSubscription,
+        TransfersSubscription,
+    },
     EthApi,
 };
 use corebc::types::FilteredParams;
@@ -62,12 +65,13 @@ impl PubSubEthRpcHandler {
                 ResponseResult::Success(canceled.into())
             }
             EthPubSub::EthSubscribe(kind, params) => {
+                let full_transactions = params.full_transactions;
                 let params = FilteredParams::new(params.filter);
 
                 let subscription = match kind {
                     SubscriptionKind::Logs => {
                         trace!(target: "rpc::ws", "received logs subscription {:?}", params);
-                        let blocks = self.api.new_block_notifications();
+                        let blocks = self.api.chain_notifications();
                         let storage = self.api.storage_info();
                         EthSubscription::Logs(Box::new(LogsSubscription {
                             blocks,
@@ -83,6 +87,17 @@ impl PubSubEthRpcHandler {
                         let storage = self.api.storage_info();
                         EthSubscription::Header(blocks, storage, id.clone())
                     }
+                    SubscriptionKind::NewPendingTransactions if full_transactions => {
+                        trace!(target: "rpc::ws", "received full pending transactions subscription");
+                        EthSubscription::FullPendingTransactions(Box::new(
+                            FullPendingTransactionsSubscription {
+                                transactions: self.api.new_ready_transactions(),
+                                pool: self.api.pool().clone(),
+                                storage: self.api.storage_info(),
+                                id: id.clone(),
+                            },
+                        ))
+                    }
                     SubscriptionKind::NewPendingTransactions => {
                         trace!(target: "rpc::ws", "received pending transactions subscription");
                         EthSubscription::PendingTransactions(
@@ -90,6 +105,15 @@ impl PubSubEthRpcHandler {
                             id.clone(),
                         )
                     }
+                    SubscriptionKind::ShuttleTransfers => {
+                        trace!(target: "rpc::ws", "received transfers subscription");
+                        EthSubscription::Transfers(Box::new(TransfersSubscription {
+                            blocks: self.api.new_block_notifications(),
+                            storage: self.api.storage_info(),
+                            queued: Default::default(),
+                            id: id.clone(),
+                        }))
+                    }
                     SubscriptionKind::Syncing => {
                         return RpcError::internal_error_with("Not implemented").into()
                     }
@@ -2,7 +2,7 @@
 use crate::EthApi;
 use futures::StreamExt;
 use handler::{HttpEthRpcHandler, PubSubEthRpcHandler};
-use shuttle_server::{ipc::IpcEndpoint, ServerConfig, ShuttleServer};
+use shuttle_server::{ipc::IpcEndpoint, AccessControl, ServerConfig, ShuttleServer};
 use std::net::SocketAddr;
 use tokio::{io, task::JoinHandle};
 use tracing::trace;
@@ -23,18 +23,23 @@ pub fn serve(addr: SocketAddr, api: EthApi, config: ServerConfig) -> ShuttleServ
 /// # Panics
 ///
 /// if setting up the ipc connection was unsuccessful
-pub fn spawn_ipc(api: EthApi, path: impl Into<String>) -> JoinHandle<io::Result<()>> {
-    try_spawn_ipc(api, path).expect("failed to establish ipc connection")
+pub fn spawn_ipc(
+    api: EthApi,
+    path: impl Into<String>,
+    config: &ServerConfig,
+) -> JoinHandle<io::Result<()>> {
+    try_spawn_ipc(api, path, config).expect("failed to establish ipc connection")
 }
 
 /// Launches an ipc server at the given path in a new task
 pub fn try_spawn_ipc(
     api: EthApi,
     path: impl Into<String>,
+    config: &ServerConfig,
 ) -> io::Result<JoinHandle<io::Result<()>>> {
     let path = path.into();
     let handler = PubSubEthRpcHandler::new(api);
-    let ipc = IpcEndpoint::new(handler, path);
+    let ipc = IpcEndpoint::new(handler, path).with_access(AccessControl::new(config));
     let incoming = ipc.incoming()?;
 
     let task = tokio::task::spawn(async move {
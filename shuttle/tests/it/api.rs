@@ -11,7 +11,11 @@ use shuttle::{
     eth::{api::CLIENT_VERSION, EthApi},
     spawn, NodeConfig, CHAIN_ID,
 };
-use shuttle_core::eth::{state::AccountOverride, transaction::EthTransactionRequest};
+use shuttle_core::eth::{
+    simulate::{BlockOverrides, BlockStateCall, SimulatePayload},
+    state::AccountOverride,
+    transaction::EthTransactionRequest,
+};
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use crate::abi::{MulticallContract, SimpleStorage};
@@ -336,3 +340,85 @@ async fn can_call_with_state_override() {
     // `value` *is* changed with state
     assert_eq!(value, "");
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_simulate_blocks() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+
+    let sender = Address::random();
+    let recipient = Address::random();
+    let coinbase = Address::random();
+    let value = U256::from(1337u64);
+
+    let payload = SimulatePayload {
+        block_state_calls: vec![
+            BlockStateCall {
+                state_overrides: Some(HashMap::from([(
+                    sender,
+                    AccountOverride { balance: Some(value), ..Default::default() },
+                )])),
+                calls: vec![EthTransactionRequest {
+                    from: Some(sender),
+                    to: Some(recipient),
+                    value: Some(value),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            BlockStateCall {
+                block_overrides: Some(BlockOverrides {
+                    number: Some(10u64.into()),
+                    time: Some(1_000_000u64.into()),
+                    coinbase: Some(coinbase),
+                    ..Default::default()
+                }),
+                calls: vec![
+                    // only succeeds if the transfer of the first block carried over
+                    EthTransactionRequest {
+                        from: Some(recipient),
+                        to: Some(sender),
+                        value: Some(value),
+                        ..Default::default()
+                    },
+                    // init code that reverts: PUSH1 0 PUSH1 0 REVERT
+                    EthTransactionRequest {
+                        from: Some(sender),
+                        data: Some(vec![0x60, 0x00, 0x60, 0x00, 0xfd].into()),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+        ],
+    };
+
+    let blocks = api.simulate_v1(payload, None).await.unwrap();
+    assert_eq!(blocks.len(), 2);
+
+    assert_eq!(blocks[0].number, U256::from(1u64));
+    assert_eq!(blocks[0].calls.len(), 1);
+    assert_eq!(blocks[0].calls[0].status, 1u64.into());
+    assert_eq!(blocks[0].energy_used, blocks[0].calls[0].energy_used);
+
+    assert_eq!(blocks[1].number, U256::from(10u64));
+    assert_eq!(blocks[1].timestamp, 1_000_000u64.into());
+    assert_eq!(blocks[1].coinbase, coinbase);
+    assert_eq!(blocks[1].calls[0].status, 1u64.into());
+    assert_eq!(blocks[1].calls[1].status, 0u64.into());
+    assert_eq!(blocks[1].calls[1].error.as_deref(), Some("execution reverted"));
+
+    // block numbers must increase
+    let payload = SimulatePayload {
+        block_state_calls: vec![BlockStateCall {
+            block_overrides: Some(BlockOverrides {
+                number: Some(0u64.into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }],
+    };
+    assert!(api.simulate_v1(payload, None).await.is_err());
+
+    // nothing was mined
+    assert_eq!(api.block_number().unwrap(), U256::zero());
+}
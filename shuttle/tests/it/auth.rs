@@ -0,0 +1,124 @@
+//! tests for the authentication and access control of the rpc server
+
+use corebc::{
+    prelude::{Middleware, Provider, Ws},
+    providers::Authorization,
+};
+use shuttle::{spawn, NodeConfig};
+use shuttle_server::{JwtSecret, ServerConfig};
+use std::num::NonZeroU32;
+
+fn secret() -> JwtSecret {
+    JwtSecret::from_hex("0x7365637265747365637265747365637265747365637265747365637265747365")
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_reject_unauthenticated_requests() {
+    let server_config = ServerConfig::default().with_jwt_secret(Some(secret()));
+    let (_api, handle) = spawn(NodeConfig::test().with_server_config(server_config)).await;
+
+    let err = handle.http_provider().get_block_number().await.unwrap_err();
+    assert!(err.to_string().contains("401"), "{err}");
+
+    assert!(Provider::<Ws>::connect(handle.ws_endpoint()).await.is_err());
+
+    let other_secret = JwtSecret::from_hex(&"11".repeat(32)).unwrap();
+    let auth = Authorization::Bearer(other_secret.encode());
+    assert!(Provider::<Ws>::connect_with_auth(handle.ws_endpoint(), auth).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_authenticate_with_jwt() {
+    let server_config = ServerConfig::default().with_jwt_secret(Some(secret()));
+    let (_api, handle) = spawn(NodeConfig::test().with_server_config(server_config)).await;
+
+    let auth = Authorization::Bearer(secret().encode());
+    let provider = Provider::<Ws>::connect_with_auth(handle.ws_endpoint(), auth).await.unwrap();
+    assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 0);
+}
+
+#[test]
+fn rejects_invalid_tokens() {
+    let secret = secret();
+    let token = secret.encode();
+    secret.validate(&token).unwrap();
+
+    // tampered signature
+    let (message, _) = token.rsplit_once('.').unwrap();
+    assert!(secret.validate(&format!("{message}.AAAA")).is_err());
+    assert!(secret.validate("not-a-token").is_err());
+
+    // signed with a different secret
+    let other = JwtSecret::from_hex(&"11".repeat(32)).unwrap();
+    assert!(secret.validate(&other.encode()).is_err());
+
+    // secrets must be 32 bytes
+    assert!(JwtSecret::from_hex("0x1234").is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_deny_namespaces() {
+    let server_config = ServerConfig::default().with_rpc_deny(["shuttle", "evm_mine"]);
+    let (_api, handle) = spawn(NodeConfig::test().with_server_config(server_config)).await;
+    let provider = handle.http_provider();
+
+    assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 0);
+
+    for method in ["shuttle_getAutomine", "hardhat_getAutomine", "evm_mine"] {
+        let err = provider.request::<_, serde_json::Value>(method, ()).await.unwrap_err();
+        assert!(err.to_string().contains("not available"), "{method}: {err}");
+    }
+
+    // other methods of the namespace are still available
+    provider.request::<_, serde_json::Value>("evm_snapshot", ()).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_allow_namespaces() {
+    let server_config = ServerConfig::default().with_rpc_allow(["eth", "web3"]);
+    let (_api, handle) = spawn(NodeConfig::test().with_server_config(server_config)).await;
+    let provider = handle.http_provider();
+
+    // `xcb_` methods belong to the `eth` namespace
+    assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 0);
+    provider.request::<_, String>("web3_clientVersion", ()).await.unwrap();
+
+    let err = provider.request::<_, String>("net_version", ()).await.unwrap_err();
+    assert!(err.to_string().contains("not available"), "{err}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_rate_limit_calls() {
+    let server_config = ServerConfig::default().with_rpc_rate_limit(NonZeroU32::new(2));
+    let (_api, handle) = spawn(NodeConfig::test().with_server_config(server_config)).await;
+    let provider = handle.http_provider();
+
+    provider.get_block_number().await.unwrap();
+    provider.get_block_number().await.unwrap();
+    let err = provider.get_block_number().await.unwrap_err();
+    assert!(err.to_string().contains("rate limit exceeded"), "{err}");
+
+    // the bucket refills over time
+    tokio::time::sleep(std::time::Duration::from_millis(600)).await;
+    provider.get_block_number().await.unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn restricts_ipc_socket_to_owner_with_jwt() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let server_config = ServerConfig::default().with_jwt_secret(Some(secret()));
+    let config = NodeConfig::test()
+        .with_server_config(server_config)
+        .with_ipc(Some(Some(crate::ipc::rand_ipc_endpoint())));
+    let (_api, handle) = spawn(config).await;
+
+    // IPC requests carry no JWT, the socket is protected by its permissions instead
+    let mode = std::fs::metadata(handle.ipc_path().unwrap()).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    let provider = handle.ipc_provider().await.unwrap();
+    assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 0);
+}
@@ -1,6 +1,10 @@
 //! genesis.json tests
 
-use corebc::{abi::Address, prelude::Middleware, types::U256};
+use corebc::{
+    abi::Address,
+    prelude::Middleware,
+    types::{Bytes, H256, U256},
+};
 use shuttle::{genesis::Genesis, spawn, NodeConfig};
 
 #[tokio::test(flavor = "multi_thread")]
@@ -44,3 +48,37 @@ async fn can_apply_genesis() {
     let expected: U256 = "ffffffffffffffffffffffffff".parse().unwrap();
     assert_eq!(balance, expected);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_dump_genesis() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let addr: Address = "cb6671562b71999873db5b286df957af199ec94617f7".parse().unwrap();
+    let code = Bytes::from(vec![0x60, 0x01, 0x60, 0x00, 0x55]);
+    let slot = U256::from(1u64);
+    let value = H256::from_low_u64_be(42);
+    api.shuttle_set_balance(addr, 1337u64.into()).await.unwrap();
+    api.shuttle_set_code(addr, code.clone()).await.unwrap();
+    api.shuttle_set_storage_at(addr, slot, value).await.unwrap();
+    api.shuttle_set_coinbase(addr).await.unwrap();
+
+    let genesis: serde_json::Value = provider.request("shuttle_dumpGenesis", ()).await.unwrap();
+    assert_eq!(genesis["energyLimit"], serde_json::json!("0x1c9c380"));
+    // the chain runs the latest rules since genesis
+    assert_eq!(genesis["config"]["homesteadBlock"], serde_json::json!(0));
+    assert_eq!(genesis["config"]["istanbulBlock"], serde_json::json!(0));
+    let genesis: Genesis = serde_json::from_value(genesis).unwrap();
+    assert_eq!(genesis.chain_id(), Some(api.chain_id()));
+    assert_eq!(genesis.coinbase, Some(addr));
+    let account = &genesis.alloc.accounts[&addr];
+    assert_eq!(account.code, Some(code.clone()));
+    assert_eq!(account.storage[&H256::from_uint(&slot)], value);
+
+    // start a new chain from the dumped genesis
+    let (_api, handle) = spawn(NodeConfig::test().with_genesis(Some(genesis))).await;
+    let provider = handle.http_provider();
+    assert_eq!(provider.get_balance(addr, None).await.unwrap(), 1337u64.into());
+    assert_eq!(provider.get_code(addr, None).await.unwrap(), code);
+    assert_eq!(provider.get_storage_at(addr, H256::from_uint(&slot), None).await.unwrap(), value);
+}
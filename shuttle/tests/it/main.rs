@@ -1,10 +1,13 @@
 mod abi;
 mod api;
+mod auth;
 mod fork;
 mod genesis;
 mod geth;
 mod ipc;
 mod logs;
+mod network;
+mod otterscan;
 mod proof;
 mod pubsub;
 mod shuttle;
@@ -0,0 +1,100 @@
+//! tests for local networks of nodes
+
+use corebc::{
+    prelude::{Middleware, TransactionRequest},
+    types::{Address, BlockNumber, H256},
+};
+use shuttle::{eth::EthApi, network::spawn_network, NodeConfig};
+use std::time::Duration;
+
+/// Waits until the given node reached the given block number
+async fn wait_for_block(api: &EthApi, number: u64) {
+    for _ in 0..50 {
+        if api.block_number().unwrap().as_u64() == number {
+            return
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("node didn't reach block {number}");
+}
+
+async fn block_hash(api: &EthApi, number: u64) -> H256 {
+    api.block_by_number(BlockNumber::Number(number.into())).await.unwrap().unwrap().hash.unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn followers_import_mined_blocks() {
+    let nodes = spawn_network(NodeConfig::test(), 3).await.unwrap();
+    let (leader, _) = &nodes[0];
+
+    leader.mine_one().await;
+    leader.mine_one().await;
+
+    for (follower, handle) in &nodes[1..] {
+        assert_ne!(handle.socket_address(), nodes[0].1.socket_address());
+        wait_for_block(follower, 2).await;
+        for number in 0..=2 {
+            assert_eq!(block_hash(follower, number).await, block_hash(leader, number).await);
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn forwards_transactions_to_mining_node() {
+    let nodes = spawn_network(NodeConfig::test(), 2).await.unwrap();
+    let (leader, _) = &nodes[0];
+    let (_, handle) = &nodes[1];
+    let provider = handle.http_provider();
+
+    let from = handle.dev_accounts().next().unwrap();
+    let tx = TransactionRequest::new().from(from).to(Address::random()).value(1u64);
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+
+    assert_eq!(receipt.block_number, Some(1u64.into()));
+    assert_eq!(receipt.block_hash.unwrap(), block_hash(leader, 1).await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_partition_and_heal_follower() {
+    let nodes = spawn_network(NodeConfig::test(), 2).await.unwrap();
+    let (leader, _) = &nodes[0];
+    let (follower, _) = &nodes[1];
+
+    // the mining node can't be partitioned
+    assert!(leader.shuttle_partition().await.is_err());
+
+    follower.shuttle_partition().await.unwrap();
+    leader.mine_one().await;
+    leader.mine_one().await;
+    follower.evm_increase_time(100u64.into()).await.unwrap();
+    follower.mine_one().await;
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(follower.block_number().unwrap().as_u64(), 1);
+    assert_ne!(block_hash(follower, 1).await, block_hash(leader, 1).await);
+
+    // the diverged block is replaced with the chain of the mining node
+    follower.shuttle_heal().await.unwrap();
+    wait_for_block(follower, 2).await;
+    for number in 1..=2 {
+        assert_eq!(block_hash(follower, number).await, block_hash(leader, number).await);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn followers_keep_their_own_block_env() {
+    let nodes = spawn_network(NodeConfig::test(), 2).await.unwrap();
+    let (leader, _) = &nodes[0];
+    let (follower, _) = &nodes[1];
+    let coinbase = follower.author().unwrap();
+
+    let leader_coinbase = Address::random();
+    leader.shuttle_set_coinbase(leader_coinbase).await.unwrap();
+    leader.mine_one().await;
+    wait_for_block(follower, 1).await;
+
+    let block = follower.block_by_number(BlockNumber::Number(1u64.into())).await.unwrap().unwrap();
+    assert_eq!(block.author, Some(leader_coinbase));
+    // the header values of the imported block don't replace the follower's own
+    assert_eq!(follower.author().unwrap(), coinbase);
+}
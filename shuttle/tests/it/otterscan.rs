@@ -0,0 +1,172 @@
+//! tests for the otterscan `ots_*` endpoints
+
+use corebc::{
+    contract::ContractInstance,
+    prelude::{ContractFactory, Middleware, Signer, SignerMiddleware, TransactionRequest},
+    types::{BlockNumber, U256},
+};
+use corebc_ylem::{project_util::TempProject, Artifact};
+use shuttle::{spawn, NodeConfig};
+use shuttle_core::eth::otterscan::{OtsInternalOperationKind, OtsTraceKind, OTS_API_LEVEL};
+use std::sync::Arc;
+
+const FACTORY_CONTRACT: &str = r#"
+pragma solidity >=1.1.0;
+contract Child {
+    constructor() payable {}
+}
+contract Factory {
+    function deploy() public payable returns (address) {
+        Child child = new Child{value: msg.value}();
+        return address(child);
+    }
+    function fail() public pure {
+        revert("failed");
+    }
+}
+"#;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_internal_operations_and_creator() {
+    let prj = TempProject::dapptools().unwrap();
+    prj.add_source("Factory", FACTORY_CONTRACT).unwrap();
+    let mut compiled = prj.compile().unwrap();
+    assert!(!compiled.has_compiler_errors());
+    let contract = compiled.remove_first("Factory").unwrap();
+    let (abi, bytecode, _) = contract.into_contract_bytecode().into_parts();
+
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let wallets = handle.dev_wallets().collect::<Vec<_>>();
+    let sender = wallets[0].address();
+    let client = Arc::new(SignerMiddleware::new(handle.http_provider(), wallets[0].clone()));
+
+    let factory = ContractFactory::new(abi.clone().unwrap(), bytecode.unwrap(), client.clone());
+    let (contract, deploy_receipt) = factory.deploy(()).unwrap().send_with_receipt().await.unwrap();
+    let contract = ContractInstance::new(contract.address(), abi.unwrap(), client);
+
+    let call = contract.method::<_, ()>("deploy", ()).unwrap().value(1u64);
+    let receipt = call.send().await.unwrap().await.unwrap().unwrap();
+    let hash = receipt.transaction_hash;
+
+    let ops = api.ots_get_internal_operations(hash).await.unwrap();
+    assert_eq!(ops.len(), 1);
+    assert_eq!(ops[0].kind, OtsInternalOperationKind::Create);
+    assert_eq!(ops[0].from, contract.address());
+    assert_eq!(ops[0].value, U256::from(1u64));
+    let child = ops[0].to;
+
+    let traces = api.ots_trace_transaction(hash).await.unwrap();
+    assert_eq!(traces.len(), 2);
+    assert_eq!(traces[0].kind, OtsTraceKind::Call);
+    assert_eq!(traces[0].from, sender);
+    assert_eq!(traces[1].kind, OtsTraceKind::Create);
+    assert_eq!(traces[1].to, child);
+    assert_eq!(traces[1].depth, traces[0].depth + 1);
+
+    let creator = api.ots_get_contract_creator(child).await.unwrap().unwrap();
+    assert_eq!(creator.hash, hash);
+    assert_eq!(creator.creator, contract.address());
+    let creator = api.ots_get_contract_creator(contract.address()).await.unwrap().unwrap();
+    assert_eq!(creator.hash, deploy_receipt.transaction_hash);
+    assert_eq!(creator.creator, sender);
+    assert!(api.ots_get_contract_creator(sender).await.unwrap().is_none());
+
+    assert!(api.ots_has_code(child, BlockNumber::Latest).await.unwrap());
+    assert!(!api.ots_has_code(sender, BlockNumber::Latest).await.unwrap());
+    assert!(!api.ots_has_code(child, BlockNumber::Number(1u64.into())).await.unwrap());
+
+    assert!(api.ots_get_transaction_error(hash).await.unwrap().is_empty());
+
+    // send the reverting call with a fixed energy limit, so it's mined
+    let calldata = contract.method::<_, ()>("fail", ()).unwrap().calldata().unwrap();
+    let tx = TransactionRequest::new()
+        .from(sender)
+        .to(contract.address())
+        .data(calldata)
+        .energy(100_000u64);
+    let provider = handle.http_provider();
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    assert_eq!(receipt.status, Some(0u64.into()));
+
+    let error = api.ots_get_transaction_error(receipt.transaction_hash).await.unwrap();
+    // `Error(string)`
+    assert_eq!(error[..4], [0x08, 0xc3, 0x79, 0xa0]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_search_transactions() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let level: u64 = provider.request("ots_getApiLevel", ()).await.unwrap();
+    assert_eq!(level, OTS_API_LEVEL);
+
+    let accounts = handle.dev_accounts().collect::<Vec<_>>();
+    let (from, to) = (accounts[0], accounts[1]);
+
+    // every transaction is mined in its own block
+    let mut hashes = vec![];
+    for _ in 0..3 {
+        let tx = TransactionRequest::new().from(from).to(to).value(1u64);
+        let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+        hashes.push(receipt.transaction_hash);
+    }
+    let hashes_of =
+        |txs: &[corebc::types::Transaction]| -> Vec<_> { txs.iter().map(|tx| tx.hash).collect() };
+
+    let page = api.ots_search_transactions_before(to, 0, 2).await.unwrap();
+    assert_eq!(hashes_of(&page.txs), vec![hashes[2], hashes[1]]);
+    assert_eq!(page.receipts[0].receipt.transaction_hash, hashes[2]);
+    assert!(page.first_page);
+    assert!(!page.last_page);
+
+    let page = api.ots_search_transactions_before(to, 2, 2).await.unwrap();
+    assert_eq!(hashes_of(&page.txs), vec![hashes[0]]);
+    assert!(!page.first_page);
+    assert!(page.last_page);
+
+    let page = api.ots_search_transactions_after(to, 0, 2).await.unwrap();
+    assert_eq!(hashes_of(&page.txs), vec![hashes[1], hashes[0]]);
+    assert!(!page.first_page);
+    assert!(page.last_page);
+
+    let page = api.ots_search_transactions_after(to, 1, 2).await.unwrap();
+    assert_eq!(hashes_of(&page.txs), vec![hashes[2], hashes[1]]);
+    assert!(page.first_page);
+    assert!(!page.last_page);
+
+    let page = api.ots_search_transactions_before(accounts[2], 0, 2).await.unwrap();
+    assert!(page.txs.is_empty());
+    assert!(page.first_page && page.last_page);
+
+    let hash = api.ots_get_transaction_by_sender_and_nonce(from, 1u64.into()).await.unwrap();
+    assert_eq!(hash, Some(hashes[1]));
+    let hash = api.ots_get_transaction_by_sender_and_nonce(from, 5u64.into()).await.unwrap();
+    assert!(hash.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_block_details_and_transactions() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let accounts = handle.dev_accounts().collect::<Vec<_>>();
+    let tx = TransactionRequest::new().from(accounts[0]).to(accounts[1]).value(1u64);
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    let tx = provider.get_transaction(receipt.transaction_hash).await.unwrap().unwrap();
+
+    let details = api.ots_get_block_details(BlockNumber::Number(1u64.into())).await.unwrap();
+    assert_eq!(details.block.transaction_count, 1);
+    assert!(details.block.block.transactions.is_empty());
+    assert_eq!(details.total_fees, receipt.energy_used.unwrap() * tx.energy_price);
+
+    let block = api.ots_get_block_transactions(1, 0, 10).await.unwrap();
+    assert_eq!(block.fullblock.transaction_count, 1);
+    assert_eq!(block.fullblock.block.transactions[0].hash, receipt.transaction_hash);
+    assert_eq!(block.receipts.len(), 1);
+
+    let block = api.ots_get_block_transactions(1, 1, 10).await.unwrap();
+    assert_eq!(block.fullblock.transaction_count, 1);
+    assert!(block.fullblock.block.transactions.is_empty());
+    assert!(block.receipts.is_empty());
+}
@@ -6,10 +6,11 @@ use corebc::{
     prelude::{Middleware, Ws},
     providers::{JsonRpcClient, PubsubClient},
     signers::Signer,
-    types::{Address, Block, Filter, TransactionRequest, TxHash, ValueOrArray, U256},
+    types::{Address, Block, Filter, Transaction, TransactionRequest, TxHash, ValueOrArray, U256},
 };
 use futures::StreamExt;
 use shuttle::{spawn, NodeConfig};
+use shuttle_core::eth::subscription::ValueTransfer;
 use std::sync::Arc;
 
 #[tokio::test(flavor = "multi_thread")]
@@ -146,6 +147,99 @@ async fn test_sub_logs_impersonated() {
     assert_eq!(receipt.logs[0], log);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sub_removed_logs_on_revert() {
+    abigen!(EmitLogs, "test-data/emit_logs.json");
+
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.ws_provider().await;
+
+    let wallet = handle.dev_wallets().next().unwrap();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+
+    let contract = EmitLogs::deploy(Arc::clone(&client), "First Message".to_string())
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+
+    let filter = Filter::new().address(ValueOrArray::Value(contract.address()));
+    let mut logs_sub = client.subscribe_logs(&filter).await.unwrap();
+
+    let snapshot = api.evm_snapshot().await.unwrap();
+    let receipt = contract
+        .set_value("Next Message".to_string())
+        .send()
+        .await
+        .unwrap()
+        .await
+        .unwrap()
+        .unwrap();
+
+    let log = logs_sub.next().await.unwrap();
+    assert_eq!(log.removed, Some(false));
+
+    assert!(api.evm_revert(snapshot).await.unwrap());
+
+    // the log of the reverted block is sent again
+    let removed = logs_sub.next().await.unwrap();
+    assert_eq!(removed.removed, Some(true));
+    assert_eq!(removed.transaction_hash, Some(receipt.transaction_hash));
+    assert_eq!(removed.block_hash, log.block_hash);
+    assert_eq!(removed.data, log.data);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sub_full_pending_transactions() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    api.shuttle_set_auto_mine(false).await.unwrap();
+    let ws = Ws::connect(handle.ws_endpoint()).await.unwrap();
+
+    let sub_id: U256 = ws.request("xcb_subscribe", ("newPendingTransactions", true)).await.unwrap();
+    let mut stream = ws.subscribe(sub_id).unwrap();
+
+    let accounts = handle.dev_accounts().collect::<Vec<_>>();
+    let tx = TransactionRequest::new().from(accounts[0]).to(accounts[1]).value(1337u64);
+    let provider = handle.http_provider();
+    let pending = provider.send_transaction(tx, None).await.unwrap();
+
+    let item = stream.next().await.unwrap();
+    let tx: Transaction = serde_json::from_str(item.get()).unwrap();
+    assert_eq!(tx.hash, pending.tx_hash());
+    assert_eq!(tx.from, accounts[0]);
+    assert_eq!(tx.to, Some(accounts[1]));
+    assert_eq!(tx.value, 1337u64.into());
+    assert!(tx.block_number.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sub_transfers() {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let ws = Ws::connect(handle.ws_endpoint()).await.unwrap();
+
+    let sub_id: U256 = ws.request("xcb_subscribe", ["shuttleTransfers"]).await.unwrap();
+    let mut stream = ws.subscribe(sub_id).unwrap();
+
+    let accounts = handle.dev_accounts().collect::<Vec<_>>();
+    let provider = handle.http_provider();
+
+    // transactions without value are skipped
+    let tx = TransactionRequest::new().from(accounts[0]).to(accounts[1]);
+    provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+
+    let tx = TransactionRequest::new().from(accounts[0]).to(accounts[1]).value(1337u64);
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+
+    let item = stream.next().await.unwrap();
+    let transfer: ValueTransfer = serde_json::from_str(item.get()).unwrap();
+    assert_eq!(transfer.transaction_hash, receipt.transaction_hash);
+    assert_eq!(transfer.block_hash, receipt.block_hash.unwrap());
+    assert_eq!(transfer.from, accounts[0]);
+    assert_eq!(transfer.to, accounts[1]);
+    assert_eq!(transfer.value, 1337u64.into());
+    assert_eq!(transfer.depth, 0);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_filters_legacy() {
     abigen!(EmitLogs, "test-data/emit_logs.json");
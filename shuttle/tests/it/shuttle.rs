@@ -1,7 +1,11 @@
 //! tests for shuttle specific logic
 
-use corebc::{prelude::Middleware, types::Address};
+use corebc::{
+    prelude::{Middleware, TransactionRequest},
+    types::{Address, BlockNumber},
+};
 use shuttle::{spawn, NodeConfig};
+use std::path::Path;
 
 #[tokio::test(flavor = "multi_thread")]
 async fn test_can_change_mining_mode() {
@@ -68,3 +72,51 @@ async fn test_can_use_default_genesis_timestamp() {
 
     assert_ne!(0u64, provider.get_block(0).await.unwrap().unwrap().timestamp.as_u64());
 }
+
+/// Copies the files of a directory recursively
+fn copy_dir(from: &Path, to: &Path) {
+    std::fs::create_dir_all(to).unwrap();
+    for entry in std::fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let target = to.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else {
+            std::fs::copy(entry.path(), target).unwrap();
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_restart_from_data_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let data_dir = dir.path().join("data");
+    let (api, handle) = spawn(NodeConfig::test().with_data_dir(Some(data_dir.clone()))).await;
+    let provider = handle.http_provider();
+
+    let from = handle.dev_accounts().next().unwrap();
+    let to = Address::random();
+    let tx = TransactionRequest::new().from(from).to(to).value(1337u64);
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    api.mine_one().await;
+    let best = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+
+    // the chain database is flushed with every block, so a copy of it can be used without
+    // shutting down the node
+    let restarted_dir = dir.path().join("restarted");
+    copy_dir(&data_dir, &restarted_dir);
+    let (_api, handle) = spawn(NodeConfig::test().with_data_dir(Some(restarted_dir))).await;
+    let provider = handle.http_provider();
+
+    let restored = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(restored.number, best.number);
+    assert_eq!(restored.hash, best.hash);
+
+    let restored_receipt =
+        provider.get_transaction_receipt(receipt.transaction_hash).await.unwrap().unwrap();
+    assert_eq!(restored_receipt.block_hash, receipt.block_hash);
+    assert_eq!(restored_receipt.energy_used, receipt.energy_used);
+
+    assert_eq!(provider.get_balance(to, None).await.unwrap(), 1337u64.into());
+    assert_eq!(provider.get_transaction_count(from, None).await.unwrap(), 1u64.into());
+}
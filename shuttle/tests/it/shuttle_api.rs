@@ -5,10 +5,19 @@ use corebc::{
     prelude::{Middleware, SignerMiddleware},
     types::{Address, BlockNumber, TransactionRequest, H256, U256, U64},
 };
-use shuttle::{spawn, Hardfork, NodeConfig};
+use shuttle::{
+    eth::{
+        backend::db::{SerializableState, STATE_DUMP_VERSION},
+        EthApi,
+    },
+    spawn, try_spawn, ForkCondition, Hardfork, HardforkSchedule, NodeConfig, NodeHandle,
+};
 use shuttle_core::{
-    eth::EthRequest,
-    types::{NodeEnvironment, NodeForkConfig, NodeInfo},
+    eth::{transaction::EthTransactionRequest, EthRequest},
+    types::{
+        DumpStateOptions, NodeEnvironment, NodeForkConfig, NodeForkId, NodeInfo, ReorgOptions,
+        TransactionData,
+    },
 };
 use spark::revm::primitives::SpecId;
 use std::{
@@ -367,12 +376,15 @@ async fn can_get_node_info() {
 
     let block_number = provider.get_block_number().await.unwrap();
     let block = provider.get_block(block_number).await.unwrap().unwrap();
+    // no hardfork activates after genesis
+    let fork_id = HardforkSchedule::default().fork_id(block.hash.unwrap(), 1, 0, 1);
 
     let expected_node_info = NodeInfo {
         current_block_number: U64([0]),
         current_block_timestamp: 1,
         current_block_hash: block.hash.unwrap(),
         hard_fork: SpecId::ISTANBUL,
+        fork_id: NodeForkId { hash: fork_id.hash.0.to_vec().into(), next: 0 },
         transaction_order: "fees".to_owned(),
         environment: NodeEnvironment {
             chain_id: U256::from_str("0x1").unwrap(),
@@ -388,3 +400,227 @@ async fn can_get_node_info() {
 
     assert_eq!(node_info, expected_node_info);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_mine_across_hardfork_activation() {
+    let schedule = HardforkSchedule::new([
+        (Hardfork::Byzantium, ForkCondition::Block(0)),
+        (Hardfork::Istanbul, ForkCondition::Block(2)),
+    ]);
+    let (api, _handle) = spawn(NodeConfig::test().with_hardfork_schedule(Some(schedule))).await;
+
+    let node_info = api.shuttle_node_info().await.unwrap();
+    assert_eq!(node_info.hard_fork, SpecId::BYZANTIUM);
+    assert_eq!(node_info.fork_id.next, 2);
+
+    api.mine_one().await;
+    assert_eq!(api.shuttle_node_info().await.unwrap().hard_fork, SpecId::BYZANTIUM);
+
+    api.mine_one().await;
+    let activated = api.shuttle_node_info().await.unwrap();
+    assert_eq!(activated.hard_fork, SpecId::ISTANBUL);
+    assert_eq!(activated.fork_id.next, 0);
+    assert_ne!(activated.fork_id.hash, node_info.fork_id.hash);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_rollback_blocks() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    rollback_blocks(&api, &handle).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_rollback_blocks_on_fork() {
+    let (_origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let (api, handle) =
+        spawn(NodeConfig::test().with_eth_rpc_url(Some(origin_handle.http_endpoint()))).await;
+    rollback_blocks(&api, &handle).await;
+}
+
+async fn rollback_blocks(api: &EthApi, handle: &NodeHandle) {
+    let provider = handle.http_provider();
+
+    let from = handle.dev_accounts().next().unwrap();
+    let to = Address::random();
+    let mut hashes = vec![];
+    for _ in 0..3 {
+        let tx = TransactionRequest::new().from(from).to(to).value(100u64);
+        let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+        hashes.push(receipt.transaction_hash);
+    }
+    assert_eq!(provider.get_block_number().await.unwrap(), 3.into());
+
+    api.shuttle_rollback(Some(2)).await.unwrap();
+    assert_eq!(provider.get_block_number().await.unwrap(), 1.into());
+    assert_eq!(provider.get_balance(to, None).await.unwrap(), 100u64.into());
+    assert_eq!(provider.get_transaction_count(from, None).await.unwrap(), 1u64.into());
+    assert!(provider.get_transaction_receipt(hashes[1]).await.unwrap().is_none());
+    assert!(provider.get_block(3u64).await.unwrap().is_none());
+
+    // the chain continues from the new best block
+    let tx = TransactionRequest::new().from(from).to(to).value(100u64);
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    assert_eq!(receipt.block_number, Some(2u64.into()));
+    assert_eq!(provider.get_balance(to, None).await.unwrap(), 200u64.into());
+
+    // only mined blocks can be removed
+    assert!(api.shuttle_rollback(Some(3)).await.is_err());
+    assert!(api.shuttle_rollback(Some(0)).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_reorg_blocks() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    reorg_blocks(&api, &handle).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_reorg_blocks_on_fork() {
+    let (_origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let (api, handle) =
+        spawn(NodeConfig::test().with_eth_rpc_url(Some(origin_handle.http_endpoint()))).await;
+    reorg_blocks(&api, &handle).await;
+}
+
+async fn reorg_blocks(api: &EthApi, handle: &NodeHandle) {
+    let provider = handle.http_provider();
+
+    let from = handle.dev_accounts().next().unwrap();
+    let to = Address::random();
+    let mut hashes = vec![];
+    for _ in 0..3 {
+        let tx = TransactionRequest::new().from(from).to(to).value(100u64);
+        let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+        hashes.push(receipt.transaction_hash);
+    }
+    let replaced = provider.get_block(3u64).await.unwrap().unwrap();
+
+    let options = ReorgOptions {
+        depth: 2,
+        tx_block_pairs: vec![
+            (TransactionData::Json(transfer_request(api, from, to, 1)), 1),
+            (TransactionData::Json(transfer_request(api, from, to, 2)), 1),
+        ],
+    };
+    api.shuttle_reorg(options).await.unwrap();
+
+    // the chain has the same height but the last two blocks were replaced
+    assert_eq!(provider.get_block_number().await.unwrap(), 3.into());
+    let block = provider.get_block(2u64).await.unwrap().unwrap();
+    assert!(block.transactions.is_empty());
+    let block = provider.get_block(3u64).await.unwrap().unwrap();
+    assert_ne!(block.hash, replaced.hash);
+    assert_eq!(block.transactions.len(), 2);
+
+    assert!(provider.get_transaction_receipt(hashes[2]).await.unwrap().is_none());
+    assert!(provider.get_transaction_receipt(hashes[0]).await.unwrap().is_some());
+    assert_eq!(provider.get_balance(to, None).await.unwrap(), 103u64.into());
+    assert_eq!(provider.get_transaction_count(from, None).await.unwrap(), 3u64.into());
+
+    let options = ReorgOptions {
+        depth: 1,
+        tx_block_pairs: vec![(TransactionData::Json(transfer_request(api, from, to, 1)), 1)],
+    };
+    assert!(api.shuttle_reorg(options).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reorg_readds_removed_and_rejects_invalid_transactions() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let from = handle.dev_accounts().next().unwrap();
+    let to = Address::random();
+    let mut hashes = vec![];
+    for _ in 0..2 {
+        let tx = TransactionRequest::new().from(from).to(to).value(100u64);
+        let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+        hashes.push(receipt.transaction_hash);
+    }
+    api.shuttle_set_auto_mine(false).await.unwrap();
+
+    // the nonce was used by the first block, which is kept
+    let invalid =
+        EthTransactionRequest { nonce: Some(U256::zero()), ..transfer_request(&api, from, to, 1) };
+    let options =
+        ReorgOptions { depth: 1, tx_block_pairs: vec![(TransactionData::Json(invalid), 0)] };
+    let err = api.shuttle_reorg(options).await.unwrap_err();
+    assert!(err.to_string().contains("invalid"), "{err}");
+    assert_eq!(provider.get_block_number().await.unwrap(), 2.into());
+    assert!(provider.get_block(2u64).await.unwrap().unwrap().transactions.is_empty());
+
+    // the transaction of the removed block is back in the pool
+    assert!(provider.get_transaction_receipt(hashes[1]).await.unwrap().is_none());
+    assert_eq!(api.txpool_status().await.unwrap().pending, 1u64.into());
+    api.mine_one().await;
+    let receipt = provider.get_transaction_receipt(hashes[1]).await.unwrap().unwrap();
+    assert_eq!(receipt.block_number, Some(3u64.into()));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_dump_and_load_selected_accounts() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let (kept, dropped) = (Address::random(), Address::random());
+    api.shuttle_set_balance(kept, 1337u64.into()).await.unwrap();
+    api.shuttle_set_balance(dropped, 42u64.into()).await.unwrap();
+
+    let options = DumpStateOptions { include_history: false, addresses: Some(vec![kept]) };
+    let state = api.shuttle_dump_state(Some(options)).await.unwrap();
+
+    let (other, _handle) = spawn(NodeConfig::test()).await;
+    assert!(other.shuttle_state_conflicts(state.clone()).await.unwrap().is_empty());
+    other.shuttle_set_balance(kept, 1u64.into()).await.unwrap();
+    assert_eq!(other.shuttle_state_conflicts(state.clone()).await.unwrap(), vec![kept]);
+
+    assert_eq!(other.shuttle_load_state(state).await.unwrap(), vec![kept]);
+    assert_eq!(other.balance(kept, None).await.unwrap(), 1337u64.into());
+    assert_eq!(other.balance(dropped, None).await.unwrap(), U256::zero());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_dump_and_load_history() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_accounts().next().unwrap();
+    let to = Address::random();
+
+    let tx = TransactionRequest::new().from(from).to(to).value(100u64);
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    api.mine_one().await;
+
+    let options = DumpStateOptions { include_history: true, addresses: None };
+    let state = api.shuttle_dump_state(Some(options)).await.unwrap();
+
+    let (other, _handle) = spawn(NodeConfig::test()).await;
+    let conflicts = other.shuttle_load_state(state.clone()).await.unwrap();
+    assert!(conflicts.contains(&from));
+    assert_eq!(other.block_number().unwrap(), 2u64.into());
+    let loaded = other.transaction_receipt(receipt.transaction_hash).await.unwrap().unwrap();
+    assert_eq!(loaded.block_hash, receipt.block_hash);
+    assert_eq!(other.balance(to, None).await.unwrap(), 100u64.into());
+
+    // the history can't replace blocks that were already mined
+    assert!(other.shuttle_load_state(state).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn fails_to_spawn_with_incompatible_init_state() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let buf = api.shuttle_dump_state(None).await.unwrap();
+    let mut state = SerializableState::decode(&buf.0).unwrap();
+    state.version = STATE_DUMP_VERSION + 1;
+
+    let err = try_spawn(NodeConfig::test().with_init_state(Some(state))).await.unwrap_err();
+    assert!(err.to_string().contains("Incompatible state dump"), "{err}");
+}
+
+fn transfer_request(api: &EthApi, from: Address, to: Address, value: u64) -> EthTransactionRequest {
+    EthTransactionRequest {
+        from: Some(from),
+        to: Some(to),
+        value: Some(value.into()),
+        energy: Some(21_000u64.into()),
+        network_id: api.chain_id().into(),
+        ..Default::default()
+    }
+}
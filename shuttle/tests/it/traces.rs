@@ -5,11 +5,18 @@ use corebc::{
         Action, ContractFactory, GoCoreTrace, GoCoreTraceFrame, Middleware, Signer,
         SignerMiddleware, TransactionRequest,
     },
-    types::{ActionType, Address, GoCoreDebugTracingCallOptions, Trace},
-    utils::hex,
+    types::{
+        ActionType, Address, ChangedType, Diff, GoCoreDebugTracingCallOptions,
+        GoCoreDebugTracingOptions, Trace, TraceType, H256, U256,
+    },
+    utils::{hex, rlp},
 };
 use corebc_ylem::{project_util::TempProject, Artifact};
 use shuttle::{spawn, NodeConfig};
+use shuttle_core::{
+    eth::transaction::{EthTransactionRequest, TypedTransaction},
+    types::TraceFilter,
+};
 use std::sync::Arc;
 
 #[tokio::test(flavor = "multi_thread")]
@@ -46,6 +53,60 @@ async fn test_get_transfer_parity_traces() {
     assert_eq!(traces, block_traces);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_filter_and_replay_parity_traces() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let from = accounts[0].address();
+    let to = accounts[1].address();
+    let amount = handle.genesis_balance().checked_div(2u64.into()).unwrap();
+    let tx = TransactionRequest::new().to(to).value(amount).from(from);
+    let tx = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+
+    let traces = api
+        .trace_filter(TraceFilter { from_address: Some(vec![from]), ..Default::default() })
+        .await
+        .unwrap();
+    assert_eq!(traces.len(), 1);
+    assert_eq!(traces[0].transaction_hash, Some(tx.transaction_hash));
+
+    let traces = api
+        .trace_filter(TraceFilter { to_address: Some(vec![from]), ..Default::default() })
+        .await
+        .unwrap();
+    assert!(traces.is_empty());
+
+    let replayed = api
+        .trace_replay_transaction(tx.transaction_hash, vec![TraceType::Trace, TraceType::StateDiff])
+        .await
+        .unwrap();
+    assert_eq!(replayed.trace.unwrap().len(), 1);
+    assert!(replayed.vm_trace.is_none());
+    match &replayed.state_diff.unwrap().0[&to].balance {
+        Diff::Changed(ChangedType { from, to }) => assert_eq!(*to, *from + amount),
+        diff => unreachable!("unexpected diff {diff:?}"),
+    }
+
+    let block_traces = api
+        .trace_replay_block_transactions(tx.block_number.unwrap().into(), vec![TraceType::Trace])
+        .await
+        .unwrap();
+    assert_eq!(block_traces.len(), 1);
+    assert_eq!(block_traces[0].transaction_hash, Some(tx.transaction_hash));
+
+    let call = EthTransactionRequest {
+        from: Some(from),
+        to: Some(to),
+        value: Some(1u64.into()),
+        ..Default::default()
+    };
+    let traced = api.trace_call(call, vec![TraceType::VmTrace], None).await.unwrap();
+    assert!(traced.trace.is_none());
+    assert!(traced.vm_trace.is_some());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_parity_suicide_trace() {
     let prj = TempProject::dapptools().unwrap();
@@ -154,6 +215,146 @@ contract Contract {
     }
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_native_tracers() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let from = accounts[0].address();
+    let to = accounts[1].address();
+    let tx = TransactionRequest::new().to(to).value(1337u64).from(from);
+    let tx = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+
+    let tracer_opts = |opts: serde_json::Value| -> GoCoreDebugTracingOptions {
+        serde_json::from_value(opts).unwrap()
+    };
+    let to_key = serde_json::to_value(to).unwrap().as_str().unwrap().to_string();
+
+    let opts = tracer_opts(serde_json::json!({ "tracer": "callTracer" }));
+    match api.debug_trace_transaction(tx.transaction_hash, opts).await.unwrap() {
+        GoCoreTrace::Unknown(frame) => {
+            assert_eq!(frame["type"], "CALL");
+            assert_eq!(frame["to"], to_key.as_str());
+            assert_eq!(frame["value"], serde_json::to_value(U256::from(1337u64)).unwrap());
+            // the energy limit of the call, not the energy it used
+            assert_ne!(frame["energy"], frame["energyUsed"]);
+        }
+        trace => unreachable!("unexpected trace {trace:?}"),
+    }
+
+    let opts = tracer_opts(serde_json::json!({ "tracer": "prestateTracer" }));
+    match api.debug_trace_transaction(tx.transaction_hash, opts).await.unwrap() {
+        GoCoreTrace::Unknown(frame) => {
+            // the recipient had its genesis balance before the transfer
+            let balance = serde_json::to_value(handle.genesis_balance()).unwrap();
+            assert_eq!(frame[&to_key]["balance"], balance);
+        }
+        trace => unreachable!("unexpected trace {trace:?}"),
+    }
+
+    let opts = tracer_opts(serde_json::json!({ "tracer": "4byteTracer" }));
+    match api.debug_trace_transaction(tx.transaction_hash, opts).await.unwrap() {
+        GoCoreTrace::Unknown(frame) => assert_eq!(frame, serde_json::json!({})),
+        trace => unreachable!("unexpected trace {trace:?}"),
+    }
+
+    let call = EthTransactionRequest {
+        from: Some(from),
+        to: Some(to),
+        value: Some(1u64.into()),
+        ..Default::default()
+    };
+    let opts = tracer_opts(serde_json::json!({ "tracer": "noopTracer" }));
+    match api.debug_trace_call(call, None, opts).await.unwrap() {
+        GoCoreTrace::Unknown(frame) => assert_eq!(frame, serde_json::json!({})),
+        trace => unreachable!("unexpected trace {trace:?}"),
+    }
+
+    let opts = tracer_opts(serde_json::json!({ "tracer": "{ result: function() {} }" }));
+    assert!(api.debug_trace_transaction(tx.transaction_hash, opts).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_debug_block_traces_and_storage_range() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let from = accounts[0].address();
+    let to = accounts[1].address();
+    let tx = TransactionRequest::new().to(to).value(1337u64).from(from);
+    let tx = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    let block_number = tx.block_number.unwrap();
+    let block_hash = tx.block_hash.unwrap();
+
+    let traces = api
+        .debug_trace_block_by_number(block_number.as_u64().into(), Default::default())
+        .await
+        .unwrap();
+    assert_eq!(traces.len(), 1);
+    assert_eq!(traces[0].tx_hash, tx.transaction_hash);
+    assert!(matches!(traces[0].result, GoCoreTrace::Known(GoCoreTraceFrame::Default(_))));
+
+    let opts: GoCoreDebugTracingOptions =
+        serde_json::from_value(serde_json::json!({ "tracer": "callTracer" })).unwrap();
+    let traces = api.debug_trace_block_by_hash(block_hash, opts).await.unwrap();
+    assert_eq!(traces.len(), 1);
+    match &traces[0].result {
+        GoCoreTrace::Unknown(frame) => assert_eq!(frame["type"], "CALL"),
+        trace => unreachable!("unexpected trace {trace:?}"),
+    }
+
+    let raw = api.debug_get_raw_transaction(tx.transaction_hash).await.unwrap().unwrap();
+    let decoded: TypedTransaction = rlp::decode(&raw).unwrap();
+    assert_eq!(decoded.hash(), tx.transaction_hash);
+    let receipts = api.debug_get_raw_receipts(block_hash.into()).await.unwrap();
+    assert_eq!(receipts.len(), 1);
+
+    let account = Address::random();
+    api.shuttle_set_storage_at(account, 1u64.into(), H256::from_low_u64_be(1)).await.unwrap();
+    api.shuttle_set_storage_at(account, 2u64.into(), H256::from_low_u64_be(2)).await.unwrap();
+
+    // the state after the block, paginated by hashed key
+    let range = api.debug_storage_range_at(block_hash, 1, account, H256::zero(), 1).await.unwrap();
+    assert_eq!(range.storage.len(), 1);
+    let next_key = range.next_key.unwrap();
+    let rest = api.debug_storage_range_at(block_hash, 1, account, next_key, 10).await.unwrap();
+    assert_eq!(rest.storage.len(), 1);
+    assert!(rest.next_key.is_none());
+    let slots: Vec<_> = range
+        .storage
+        .values()
+        .chain(rest.storage.values())
+        .map(|entry| (entry.key.unwrap(), entry.value))
+        .collect();
+    assert!(slots.contains(&(H256::from_low_u64_be(1), H256::from_low_u64_be(1))));
+    assert!(slots.contains(&(H256::from_low_u64_be(2), H256::from_low_u64_be(2))));
+
+    // the state before the transaction did not have the storage yet
+    let range = api.debug_storage_range_at(block_hash, 0, account, H256::zero(), 10).await.unwrap();
+    assert!(range.storage.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_storage_range_unsupported_on_fork() {
+    let (_origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let (api, handle) =
+        spawn(NodeConfig::test().with_eth_rpc_url(Some(origin_handle.http_endpoint()))).await;
+    let provider = handle.http_provider();
+
+    let from = handle.dev_accounts().next().unwrap();
+    let tx = TransactionRequest::new().to(Address::random()).value(1337u64).from(from);
+    let tx = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+
+    // the fork only holds the storage it fetched or modified, so the range would be incomplete
+    let err = api
+        .debug_storage_range_at(tx.block_hash.unwrap(), 0, from, H256::zero(), 10)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not supported"), "{err}");
+}
+
 // <https://github.com/foxar-rs/foxar/issues/2656>
 #[tokio::test(flavor = "multi_thread")]
 #[ignore]
@@ -8,6 +8,7 @@ use corebc::{
 };
 use futures::{future::join_all, FutureExt, StreamExt};
 use shuttle::{spawn, NodeConfig};
+use shuttle_core::types::MiningModeOptions;
 use std::{collections::HashSet, sync::Arc, time::Duration};
 use tokio::time::timeout;
 
@@ -318,6 +319,59 @@ async fn can_mine_automatically() {
     assert_eq!(receipt.status.unwrap().as_u64(), 1u64);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_mine_every_n_transactions() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    api.shuttle_set_mining_mode(MiningModeOptions::TransactionCount { transactions: 3 }).unwrap();
+
+    let from = handle.dev_accounts().next().unwrap();
+    let to = Address::random();
+    let mut pending = Vec::new();
+    for nonce in 0..3u64 {
+        if nonce == 2 {
+            // nothing is mined until the third transaction is ready
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            assert_eq!(provider.get_block_number().await.unwrap(), 0u64.into());
+        }
+        let tx =
+            TransactionRequest::new().from(from).to(to).value(1u64).nonce(nonce).energy(21_000);
+        pending.push(provider.send_transaction(tx, None).await.unwrap());
+    }
+
+    for tx in pending {
+        let receipt = tx.await.unwrap().unwrap();
+        assert_eq!(receipt.block_number, Some(1u64.into()));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_mine_on_energy_fill() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    assert!(api.evm_set_block_energy_limit(84_000u64.into()).unwrap());
+    api.shuttle_set_mining_mode(MiningModeOptions::EnergyFill { fill: 0.5 }).unwrap();
+
+    let from = handle.dev_accounts().next().unwrap();
+    let to = Address::random();
+    let tx = TransactionRequest::new().from(from).to(to).value(1u64).nonce(0u64).energy(21_000);
+    let first = provider.send_transaction(tx, None).await.unwrap();
+
+    // a single transfer only fills a quarter of the block
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(provider.get_block_number().await.unwrap(), 0u64.into());
+
+    let tx = TransactionRequest::new().from(from).to(to).value(1u64).nonce(1u64).energy(21_000);
+    let second = provider.send_transaction(tx, None).await.unwrap();
+
+    let first = first.await.unwrap().unwrap();
+    let second = second.await.unwrap().unwrap();
+    assert_eq!(first.block_number, Some(1u64.into()));
+    assert_eq!(second.block_number, Some(1u64.into()));
+
+    assert!(api.shuttle_set_mining_mode(MiningModeOptions::EnergyFill { fill: 1.5 }).is_err());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_call_greeter_historic() {
     let (_api, handle) = spawn(NodeConfig::test()).await;
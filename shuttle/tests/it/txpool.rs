@@ -2,9 +2,9 @@
 
 use corebc::{
     prelude::Middleware,
-    types::{TransactionRequest, U256},
+    types::{Address, TransactionRequest, U256},
 };
-use shuttle::{spawn, NodeConfig};
+use shuttle::{eth::pool::PoolConfig, spawn, NodeConfig};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn geth_txpool() {
@@ -49,3 +49,100 @@ async fn geth_txpool() {
         assert!(content.contains_key(&nonce.to_string()));
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn txpool_requires_price_bump_for_replacement() {
+    let config =
+        NodeConfig::test().with_pool_config(PoolConfig { price_bump: 10, ..Default::default() });
+    let (api, handle) = spawn(config).await;
+    let provider = handle.http_provider();
+    api.shuttle_set_auto_mine(false).await.unwrap();
+
+    let account = provider.get_accounts().await.unwrap()[0];
+    let energy_price: U256 = 221435145689u64.into();
+    let tx = TransactionRequest::new().to(account).from(account).value(1u64).nonce(0u64);
+
+    provider.send_transaction(tx.clone().energy_price(energy_price), None).await.unwrap();
+
+    // a bump of 5% is not enough
+    let underpriced = energy_price * 105u64 / 100u64;
+    let err =
+        provider.send_transaction(tx.clone().energy_price(underpriced), None).await.unwrap_err();
+    assert!(err.to_string().contains("replacement transaction underpriced"));
+
+    let bumped = energy_price * 110u64 / 100u64;
+    let tx_hash = provider.send_transaction(tx.energy_price(bumped), None).await.unwrap();
+
+    let content = provider.txpool_content().await.unwrap();
+    let pending = content.pending.get(&account).unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending.get("0").unwrap().hash, *tx_hash);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn txpool_evicts_lowest_priced_transactions() {
+    let config = NodeConfig::test()
+        .with_pool_config(PoolConfig { max_transactions: Some(2), ..Default::default() });
+    let (api, handle) = spawn(config).await;
+    let provider = handle.http_provider();
+    api.shuttle_set_auto_mine(false).await.unwrap();
+
+    let accounts = provider.get_accounts().await.unwrap();
+    let energy_price: U256 = 221435145689u64.into();
+    let tx = |from: Address, multiplier: u64| {
+        TransactionRequest::new()
+            .to(from)
+            .from(from)
+            .value(1u64)
+            .energy_price(energy_price * multiplier)
+    };
+
+    let cheapest = provider.send_transaction(tx(accounts[0], 1), None).await.unwrap();
+    provider.send_transaction(tx(accounts[1], 2), None).await.unwrap();
+
+    // the pool is full and the transaction doesn't pay more than the cheapest one
+    let err = provider.send_transaction(tx(accounts[2], 1), None).await.unwrap_err();
+    assert!(err.to_string().contains("transaction underpriced"));
+
+    provider.send_transaction(tx(accounts[2], 3), None).await.unwrap();
+
+    let status = provider.txpool_status().await.unwrap();
+    assert_eq!(status.pending.as_u64(), 2);
+
+    // evictions are only part of shuttle's `txpool_content` and `txpool_inspect`
+    let content = api.txpool_content().await.unwrap();
+    assert!(!content.pending.contains_key(&accounts[0]));
+    assert_eq!(content.evicted.get(&accounts[0]).unwrap().get("0").unwrap().hash, *cheapest);
+
+    let inspect = api.txpool_inspect().await.unwrap();
+    assert!(inspect.evicted.contains_key(&accounts[0]));
+    assert!(inspect.pending.contains_key(&accounts[1]));
+    assert!(inspect.pending.contains_key(&accounts[2]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn txpool_limits_queued_transactions_per_sender() {
+    let config = NodeConfig::test()
+        .with_pool_config(PoolConfig { max_queued_per_sender: Some(1), ..Default::default() });
+    let (api, handle) = spawn(config).await;
+    let provider = handle.http_provider();
+    api.shuttle_set_auto_mine(false).await.unwrap();
+
+    let account = provider.get_accounts().await.unwrap()[0];
+    let energy_price: U256 = 221435145689u64.into();
+    let tx =
+        TransactionRequest::new().to(account).from(account).value(1u64).energy_price(energy_price);
+
+    // nonce 0 is missing, so both transactions are queued
+    provider.send_transaction(tx.clone().nonce(1u64), None).await.unwrap();
+    let err = provider.send_transaction(tx.clone().nonce(2u64), None).await.unwrap_err();
+    assert!(err.to_string().contains("account limit exceeded"));
+
+    // ready transactions are not limited
+    provider.send_transaction(tx.clone().nonce(0u64), None).await.unwrap();
+    provider.send_transaction(tx.nonce(2u64), None).await.unwrap();
+
+    let status = provider.txpool_status().await.unwrap();
+    assert_eq!(status.pending.as_u64(), 3);
+    assert_eq!(status.queued.as_u64(), 0);
+}
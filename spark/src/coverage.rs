@@ -1,6 +1,14 @@
 use comfy_table::{presets::ASCII_MARKDOWN, Attribute, Cell, Color, Row, Table};
+use foxar_common::fs;
 pub use foxar_evm::coverage::*;
-use std::io::Write;
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    io::Write,
+    ops::AddAssign,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 /// A coverage reporter.
 pub trait CoverageReporter {
@@ -81,10 +89,7 @@ impl<'a> LcovReporter<'a> {
 impl<'a> CoverageReporter for LcovReporter<'a> {
     fn report(self, report: &CoverageReport) -> eyre::Result<()> {
         for (file, items) in report.items_by_source() {
-            let summary = items.iter().fold(CoverageSummary::default(), |mut summary, item| {
-                summary += item;
-                summary
-            });
+            let summary = summarize(&items);
 
             writeln!(self.destination, "TN:")?;
             writeln!(self.destination, "SF:{file}")?;
@@ -168,3 +173,569 @@ impl CoverageReporter for DebugReporter {
         Ok(())
     }
 }
+
+/// Coverage data for a single source line, aggregated from the coverage items on that line.
+#[derive(Debug, Default)]
+struct LineCoverage {
+    /// The hit count of the line item, if the line is executable.
+    hits: Option<u64>,
+    /// The branch arms starting on this line, as `(branch_id, path_id, hits)`.
+    branches: Vec<(usize, usize, u64)>,
+}
+
+impl LineCoverage {
+    /// The number of branch arms on this line that were hit.
+    fn branches_hit(&self) -> usize {
+        self.branches.iter().filter(|(_, _, hits)| *hits > 0).count()
+    }
+
+    /// The hit count of the line, falling back to the branch hits for non-executable lines.
+    fn line_hits(&self) -> u64 {
+        self.hits.unwrap_or_else(|| self.branches.iter().map(|(_, _, hits)| *hits).sum())
+    }
+}
+
+/// Sums up the coverage of the items, or of other summaries.
+fn summarize<'a, T: 'a>(items: impl IntoIterator<Item = &'a T>) -> CoverageSummary
+where
+    CoverageSummary: AddAssign<&'a T>,
+{
+    items.into_iter().fold(CoverageSummary::default(), |mut summary, item| {
+        summary += item;
+        summary
+    })
+}
+
+/// Groups the line and branch items of a source file by line number.
+fn lines_by_number(items: &[CoverageItem]) -> BTreeMap<usize, LineCoverage> {
+    let mut lines: BTreeMap<usize, LineCoverage> = BTreeMap::new();
+    for item in items {
+        match item.kind {
+            CoverageItemKind::Line => {
+                let line = lines.entry(item.loc.line).or_default();
+                line.hits = Some(line.hits.unwrap_or_default().max(item.hits));
+            }
+            CoverageItemKind::Branch { branch_id, path_id } => {
+                lines
+                    .entry(item.loc.line)
+                    .or_default()
+                    .branches
+                    .push((branch_id, path_id, item.hits));
+            }
+            CoverageItemKind::Statement | CoverageItemKind::Function { .. } => (),
+        }
+    }
+    lines
+}
+
+/// Returns the ratio of `hits` to `total`, treating an empty set as fully covered.
+fn rate(hits: usize, total: usize) -> f64 {
+    if total == 0 {
+        1.
+    } else {
+        hits as f64 / total as f64
+    }
+}
+
+/// Escapes the characters that are not allowed verbatim in HTML and XML text and attributes.
+pub(crate) fn escape_markup(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A reporter that writes a self-contained HTML report.
+///
+/// The report consists of an `index.html` page with a per-file summary, and one page per source
+/// file with the source annotated with line hit counts and branch arms. Sources that can't be read
+/// are left out of the report with a warning.
+pub struct HtmlReporter {
+    /// The project root, used to read the sources in the report.
+    root: PathBuf,
+    /// The directory the report is written to.
+    destination: PathBuf,
+}
+
+impl HtmlReporter {
+    pub fn new(root: impl Into<PathBuf>, destination: impl Into<PathBuf>) -> Self {
+        Self { root: root.into(), destination: destination.into() }
+    }
+
+    /// Returns the name of the page for the given source file.
+    ///
+    /// Path separators are replaced with `__` and underscores with `_u`, so that the pages of
+    /// different sources never collide.
+    fn page_name(path: &str) -> String {
+        let mut name = String::with_capacity(path.len() + 5);
+        for c in path.chars() {
+            match c {
+                '/' | '\\' => name.push_str("__"),
+                '_' => name.push_str("_u"),
+                c => name.push(c),
+            }
+        }
+        name.push_str(".html");
+        name
+    }
+
+    /// Renders the annotated source page for a single file.
+    fn render_file(path: &str, source: &str, items: &[CoverageItem]) -> eyre::Result<String> {
+        let summary = summarize(items);
+        let lines = lines_by_number(items);
+
+        // Mark the source ranges of every branch, missed arms take precedence over hit ones.
+        let mut branch_ranges: BTreeMap<usize, (usize, usize, bool)> = BTreeMap::new();
+        for item in items {
+            if let CoverageItemKind::Branch { branch_id, .. } = item.kind {
+                let end = item.loc.start + item.loc.length.unwrap_or_default();
+                let range = branch_ranges.entry(branch_id).or_insert((item.loc.start, end, true));
+                range.2 &= item.hits > 0;
+            }
+        }
+        let mut markers = vec![HtmlMarker::None; source.len()];
+        for (start, end, covered) in branch_ranges.into_values() {
+            let marker = if covered { HtmlMarker::BranchHit } else { HtmlMarker::BranchMissed };
+            for slot in markers.iter_mut().take(end.min(source.len())).skip(start) {
+                if *slot != HtmlMarker::BranchMissed {
+                    *slot = marker;
+                }
+            }
+        }
+
+        let mut page = String::new();
+        writeln!(page, "{}", html_header(path))?;
+        writeln!(page, "<h1>{}</h1>", escape_markup(path))?;
+        writeln!(page, "<p><a href=\"index.html\">Back to index</a></p>")?;
+        writeln!(page, "{}", html_summary_table(&[(path.to_string(), summary)], false))?;
+        writeln!(page, "<table class=\"source\">")?;
+        writeln!(page, "<tr><th>Line</th><th>Hits</th><th>Branches</th><th>Source</th></tr>")?;
+
+        let mut offset = 0;
+        for (idx, text) in source.split('\n').enumerate() {
+            let number = idx + 1;
+            let coverage = lines.get(&number);
+            let class = match coverage {
+                Some(line) if line.branches_hit() < line.branches.len() && line.line_hits() > 0 => {
+                    "partial"
+                }
+                Some(line) if line.hits.is_some() || !line.branches.is_empty() => {
+                    if line.line_hits() > 0 {
+                        "covered"
+                    } else {
+                        "uncovered"
+                    }
+                }
+                _ => "",
+            };
+            let hits = coverage
+                .filter(|line| line.hits.is_some() || !line.branches.is_empty())
+                .map(|line| line.line_hits().to_string())
+                .unwrap_or_default();
+            let branches = coverage
+                .map(|line| {
+                    line.branches
+                        .iter()
+                        .map(|(branch_id, path_id, hits)| {
+                            format!(
+                                "<span class=\"{}\" title=\"branch {branch_id}, path {path_id}\">{}</span>",
+                                if *hits > 0 { "arm-hit" } else { "arm-missed" },
+                                if *hits > 0 { hits.to_string() } else { "-".to_string() }
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .unwrap_or_default();
+
+            writeln!(
+                page,
+                "<tr class=\"{class}\"><td class=\"line\">{number}</td><td class=\"hits\">{hits}</td><td class=\"branches\">{branches}</td><td class=\"code\"><pre>{}</pre></td></tr>",
+                render_source_line(text, offset, &markers)
+            )?;
+            offset += text.len() + 1;
+        }
+
+        writeln!(page, "</table>")?;
+        writeln!(page, "</body>\n</html>")?;
+        Ok(page)
+    }
+}
+
+impl CoverageReporter for HtmlReporter {
+    fn report(self, report: &CoverageReport) -> eyre::Result<()> {
+        fs::create_dir_all(&self.destination)?;
+
+        let mut summaries = Vec::new();
+        for (path, items) in report.items_by_source() {
+            let source = match fs::read_to_string(self.root.join(&path)) {
+                Ok(source) => source,
+                Err(err) => {
+                    eprintln!(
+                        "{}: {path} is left out of the HTML report: {err}",
+                        yansi::Paint::yellow("warning").bold(),
+                    );
+                    continue
+                }
+            };
+            let page = Self::render_file(&path, &source, &items)?;
+            fs::write(self.destination.join(Self::page_name(&path)), page)?;
+            summaries.push((path, summarize(&items)));
+        }
+
+        let total = summarize(summaries.iter().map(|(_, summary)| summary));
+
+        let mut index = String::new();
+        writeln!(index, "{}", html_header("Coverage report"))?;
+        writeln!(index, "<h1>Coverage report</h1>")?;
+        writeln!(index, "{}", html_summary_table(&summaries, true))?;
+        writeln!(index, "{}", html_summary_table(&[("Total".to_string(), total)], false))?;
+        writeln!(index, "</body>\n</html>")?;
+        fs::write(self.destination.join("index.html"), index)?;
+
+        println!("Wrote HTML report to {}", self.destination.display());
+
+        Ok(())
+    }
+}
+
+/// How a source byte is highlighted in the HTML report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum HtmlMarker {
+    None,
+    BranchHit,
+    BranchMissed,
+}
+
+impl HtmlMarker {
+    fn class(&self) -> Option<&'static str> {
+        match self {
+            HtmlMarker::None => None,
+            HtmlMarker::BranchHit => Some("branch-hit"),
+            HtmlMarker::BranchMissed => Some("branch-missed"),
+        }
+    }
+}
+
+/// Renders a single line of source code, wrapping highlighted branch ranges in spans.
+///
+/// `offset` is the byte offset of the start of the line in the source file.
+fn render_source_line(text: &str, offset: usize, markers: &[HtmlMarker]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut current = HtmlMarker::None;
+    for (idx, c) in text.char_indices() {
+        let marker = markers.get(offset + idx).copied().unwrap_or(HtmlMarker::None);
+        if marker != current {
+            if current.class().is_some() {
+                out.push_str("</span>");
+            }
+            if let Some(class) = marker.class() {
+                let _ = write!(out, "<span class=\"{class}\">");
+            }
+            current = marker;
+        }
+        out.push_str(&escape_markup(c.encode_utf8(&mut [0; 4])));
+    }
+    if current.class().is_some() {
+        out.push_str("</span>");
+    }
+    out
+}
+
+/// Renders the opening of an HTML page, including the inline stylesheet.
+fn html_header(title: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; margin-bottom: 1em; }}
+th, td {{ border: 1px solid #ddd; padding: 2px 8px; text-align: left; }}
+table.source td {{ border: none; vertical-align: top; }}
+td.line, td.hits {{ color: #888; text-align: right; }}
+pre {{ margin: 0; }}
+tr.covered td.code {{ background: #e6ffed; }}
+tr.uncovered td.code {{ background: #ffeef0; }}
+tr.partial td.code {{ background: #fff5b1; }}
+span.branch-hit {{ background: #acf2bd; }}
+span.branch-missed {{ background: #fdb8c0; }}
+span.arm-hit {{ color: #22863a; }}
+span.arm-missed {{ color: #cb2431; font-weight: bold; }}
+td.low {{ color: #cb2431; }}
+td.medium {{ color: #b08800; }}
+td.high {{ color: #22863a; }}
+</style>
+</head>
+<body>"#,
+        escape_markup(title)
+    )
+}
+
+/// Renders a summary table, optionally linking every row to the page of its source file.
+fn html_summary_table(rows: &[(String, CoverageSummary)], link: bool) -> String {
+    let mut table = String::from(
+        "<table>\n<tr><th>File</th><th>% Lines</th><th>% Statements</th><th>% Branches</th><th>% Funcs</th></tr>\n",
+    );
+    for (path, summary) in rows {
+        let name = if link {
+            format!(
+                "<a href=\"{}\">{}</a>",
+                escape_markup(&HtmlReporter::page_name(path)),
+                escape_markup(path)
+            )
+        } else {
+            escape_markup(path)
+        };
+        let _ = writeln!(
+            table,
+            "<tr><td>{name}</td>{}{}{}{}</tr>",
+            html_cell(summary.line_hits, summary.line_count),
+            html_cell(summary.statement_hits, summary.statement_count),
+            html_cell(summary.branch_hits, summary.branch_count),
+            html_cell(summary.function_hits, summary.function_count),
+        );
+    }
+    table.push_str("</table>");
+    table
+}
+
+fn html_cell(hits: usize, total: usize) -> String {
+    let percentage = rate(hits, total);
+    let class = match percentage {
+        _ if total == 0 => "",
+        _ if percentage < 0.5 => "low",
+        _ if percentage < 0.75 => "medium",
+        _ => "high",
+    };
+    format!("<td class=\"{class}\">{:.2}% ({hits}/{total})</td>", percentage * 100.)
+}
+
+/// A reporter that writes a Cobertura XML report.
+///
+/// Source files are grouped into packages by their parent directory, and every source file is
+/// reported as a class.
+pub struct CoberturaReporter<'a> {
+    /// Destination buffer
+    destination: &'a mut (dyn Write + 'a),
+    /// The root the source paths in the report are relative to.
+    source_root: &'a Path,
+}
+
+impl<'a> CoberturaReporter<'a> {
+    pub fn new(destination: &'a mut (dyn Write + 'a), source_root: &'a Path) -> Self {
+        Self { destination, source_root }
+    }
+}
+
+impl<'a> CoverageReporter for CoberturaReporter<'a> {
+    fn report(self, report: &CoverageReport) -> eyre::Result<()> {
+        let mut packages: BTreeMap<String, Vec<(String, Vec<CoverageItem>, CoverageSummary)>> =
+            BTreeMap::new();
+        let mut total = CoverageSummary::default();
+        for (path, items) in report.items_by_source() {
+            let summary = summarize(&items);
+            total += &summary;
+            let package = Path::new(&path)
+                .parent()
+                .map(|parent| parent.to_string_lossy().replace(['/', '\\'], "."))
+                .unwrap_or_default();
+            packages.entry(package).or_default().push((path, items, summary));
+        }
+
+        let timestamp =
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+
+        writeln!(self.destination, r#"<?xml version="1.0" ?>"#)?;
+        writeln!(
+            self.destination,
+            r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+        )?;
+        writeln!(
+            self.destination,
+            r#"<coverage line-rate="{:.4}" branch-rate="{:.4}" lines-covered="{}" lines-valid="{}" branches-covered="{}" branches-valid="{}" complexity="0" version="1.9" timestamp="{timestamp}">"#,
+            rate(total.line_hits, total.line_count),
+            rate(total.branch_hits, total.branch_count),
+            total.line_hits,
+            total.line_count,
+            total.branch_hits,
+            total.branch_count,
+        )?;
+        writeln!(self.destination, "  <sources>")?;
+        writeln!(
+            self.destination,
+            "    <source>{}</source>",
+            escape_markup(&self.source_root.to_string_lossy())
+        )?;
+        writeln!(self.destination, "  </sources>")?;
+        writeln!(self.destination, "  <packages>")?;
+
+        for (package, files) in packages {
+            let package_summary = summarize(files.iter().map(|(_, _, summary)| summary));
+            writeln!(
+                self.destination,
+                r#"    <package name="{}" line-rate="{:.4}" branch-rate="{:.4}" complexity="0">"#,
+                escape_markup(&package),
+                rate(package_summary.line_hits, package_summary.line_count),
+                rate(package_summary.branch_hits, package_summary.branch_count),
+            )?;
+            writeln!(self.destination, "      <classes>")?;
+
+            for (path, items, summary) in files {
+                writeln!(
+                    self.destination,
+                    r#"        <class name="{}" filename="{}" line-rate="{:.4}" branch-rate="{:.4}" complexity="0">"#,
+                    escape_markup(
+                        &Path::new(&path)
+                            .file_stem()
+                            .map(|stem| stem.to_string_lossy().to_string())
+                            .unwrap_or_else(|| path.clone())
+                    ),
+                    escape_markup(&path),
+                    rate(summary.line_hits, summary.line_count),
+                    rate(summary.branch_hits, summary.branch_count),
+                )?;
+
+                // Functions
+                writeln!(self.destination, "          <methods>")?;
+                for item in &items {
+                    if let CoverageItemKind::Function { name } = &item.kind {
+                        writeln!(
+                            self.destination,
+                            r#"            <method name="{}" signature="" line-rate="{}" branch-rate="1">"#,
+                            escape_markup(&format!("{}.{name}", item.loc.contract_name)),
+                            u8::from(item.hits > 0),
+                        )?;
+                        writeln!(self.destination, "              <lines>")?;
+                        writeln!(
+                            self.destination,
+                            r#"                <line number="{}" hits="{}"/>"#,
+                            item.loc.line, item.hits
+                        )?;
+                        writeln!(self.destination, "              </lines>")?;
+                        writeln!(self.destination, "            </method>")?;
+                    }
+                }
+                writeln!(self.destination, "          </methods>")?;
+
+                // Lines and branches
+                writeln!(self.destination, "          <lines>")?;
+                for (number, line) in lines_by_number(&items) {
+                    if line.branches.is_empty() {
+                        writeln!(
+                            self.destination,
+                            r#"            <line number="{number}" hits="{}" branch="false"/>"#,
+                            line.line_hits()
+                        )?;
+                    } else {
+                        let (hit, total) = (line.branches_hit(), line.branches.len());
+                        writeln!(
+                            self.destination,
+                            r#"            <line number="{number}" hits="{}" branch="true" condition-coverage="{}% ({hit}/{total})"/>"#,
+                            line.line_hits(),
+                            (rate(hit, total) * 100.).round() as usize,
+                        )?;
+                    }
+                }
+                writeln!(self.destination, "          </lines>")?;
+                writeln!(self.destination, "        </class>")?;
+            }
+
+            writeln!(self.destination, "      </classes>")?;
+            writeln!(self.destination, "    </package>")?;
+        }
+
+        writeln!(self.destination, "  </packages>")?;
+        writeln!(self.destination, "</coverage>")?;
+
+        println!("Wrote Cobertura report.");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semver::Version;
+
+    const SOURCE: &str =
+        "contract Counter {\n    function inc() public {\n        if (x) { x += 1; }\n    }\n}\n";
+
+    fn item(
+        kind: CoverageItemKind,
+        line: usize,
+        start: usize,
+        length: usize,
+        hits: u64,
+    ) -> CoverageItem {
+        CoverageItem {
+            kind,
+            loc: SourceLocation {
+                source_id: 0,
+                contract_name: "Counter".to_string(),
+                start,
+                length: Some(length),
+                line,
+            },
+            hits,
+        }
+    }
+
+    /// A function with a line that is hit, and an `if` without an `else` that is only taken
+    fn items() -> Vec<CoverageItem> {
+        vec![
+            item(CoverageItemKind::Function { name: "inc".to_string() }, 2, 23, 55, 1),
+            item(CoverageItemKind::Line, 3, 55, 18, 1),
+            item(CoverageItemKind::Branch { branch_id: 0, path_id: 0 }, 3, 55, 18, 1),
+            item(CoverageItemKind::Branch { branch_id: 0, path_id: 1 }, 3, 55, 18, 0),
+        ]
+    }
+
+    #[test]
+    fn can_render_html_page() {
+        let page = HtmlReporter::render_file("src/Counter.sol", SOURCE, &items()).unwrap();
+        assert!(page.contains("<h1>src/Counter.sol</h1>"));
+        assert!(
+            page.contains(r#"<tr class="partial"><td class="line">3</td><td class="hits">1</td>"#)
+        );
+        assert!(page.contains(r#"<span class="arm-missed" title="branch 0, path 1">-</span>"#));
+        assert!(page.contains(r#"<td class="line">5</td><td class="hits"></td>"#));
+        assert_eq!(HtmlReporter::page_name("src/Counter.sol"), "src__Counter.sol.html");
+        assert_ne!(HtmlReporter::page_name("src/a_b.sol"), HtmlReporter::page_name("src/a/b.sol"));
+        assert_ne!(HtmlReporter::page_name("a/_b.sol"), HtmlReporter::page_name("a_/b.sol"));
+    }
+
+    #[test]
+    fn can_write_cobertura_report() {
+        let version = Version::new(1, 1, 0);
+        let mut report = CoverageReport::default();
+        report.add_source(version.clone(), 0, "src/Counter.sol".to_string());
+        report.add_items(version, items());
+
+        let mut out = Vec::new();
+        CoberturaReporter::new(&mut out, Path::new("/project")).report(&report).unwrap();
+        let xml = String::from_utf8(out).unwrap();
+
+        assert!(xml.contains(
+            r#"lines-covered="1" lines-valid="1" branches-covered="1" branches-valid="2""#
+        ));
+        assert!(xml.contains("<source>/project</source>"));
+        assert!(xml.contains(r#"<package name="src" line-rate="1.0000" branch-rate="0.5000""#));
+        assert!(xml.contains(r#"<class name="Counter" filename="src/Counter.sol""#));
+        assert!(xml.contains(r#"<method name="Counter.inc" signature="" line-rate="1""#));
+        assert!(xml.contains(
+            r#"<line number="3" hits="1" branch="true" condition-coverage="50% (1/2)"/>"#
+        ));
+    }
+}
@@ -0,0 +1,146 @@
+//! Persistence of fuzz and invariant test failures
+
+use corebc::types::{Address, Bytes};
+use foxar_common::fs;
+use foxar_evm::fuzz::{invariant::BasicTxDetails, CounterExample};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A single call of a persisted invariant failure sequence.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PersistedCall {
+    /// The account that made the call
+    pub sender: Address,
+    /// The contract that was called
+    pub target: Address,
+    /// The calldata of the call
+    pub calldata: Bytes,
+}
+
+/// A counterexample that is persisted to disk, so that it can be replayed in later runs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum PersistedFailure {
+    /// The calldata of a failing fuzz case.
+    Fuzz { calldata: Bytes },
+    /// The call sequence that broke an invariant.
+    Invariant { calls: Vec<PersistedCall> },
+}
+
+impl PersistedFailure {
+    /// Creates a persisted failure from the counterexample of a test result.
+    ///
+    /// Returns `None` for invariant sequences that contain calls without a known sender or
+    /// target.
+    pub fn from_counterexample(counterexample: &CounterExample) -> Option<Self> {
+        match counterexample {
+            CounterExample::Single(example) => {
+                Some(Self::Fuzz { calldata: example.calldata.clone() })
+            }
+            CounterExample::Sequence(sequence) => {
+                let calls = sequence
+                    .iter()
+                    .map(|example| {
+                        Some(PersistedCall {
+                            sender: example.sender?,
+                            target: example.addr?,
+                            calldata: example.calldata.clone(),
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(Self::Invariant { calls })
+            }
+        }
+    }
+
+    /// Returns the calldata of a persisted fuzz failure.
+    pub fn fuzz_calldata(self) -> Option<Bytes> {
+        match self {
+            Self::Fuzz { calldata } => Some(calldata),
+            Self::Invariant { .. } => None,
+        }
+    }
+
+    /// Returns the call sequence of a persisted invariant failure.
+    pub fn invariant_calls(self) -> Option<Vec<BasicTxDetails>> {
+        match self {
+            Self::Fuzz { .. } => None,
+            Self::Invariant { calls } => Some(
+                calls.into_iter().map(|call| (call.sender, (call.target, call.calldata))).collect(),
+            ),
+        }
+    }
+}
+
+/// The on-disk store of fuzz and invariant failures.
+///
+/// Every failure is stored as a JSON file at `<root>/<contract id>/<test signature>.json`, where
+/// the contract id is the `<path>:<name>` identifier of the test contract, so that contracts with
+/// the same name in different files don't share failures.
+#[derive(Clone, Debug)]
+pub struct FailureCorpus {
+    root: PathBuf,
+}
+
+impl FailureCorpus {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The directory the failures are stored in.
+    pub fn root(&self) -> &PathBuf {
+        &self.root
+    }
+
+    /// Returns the file the failure of the given test is stored at.
+    fn path(&self, contract_id: &str, test: &str) -> PathBuf {
+        self.root.join(sanitize(contract_id)).join(format!("{}.json", sanitize(test)))
+    }
+
+    /// Whether a failure is stored for the given test.
+    pub fn contains(&self, contract_id: &str, test: &str) -> bool {
+        self.path(contract_id, test).is_file()
+    }
+
+    /// Loads the failure stored for the given test, if any.
+    pub fn load(&self, contract_id: &str, test: &str) -> Option<PersistedFailure> {
+        let path = self.path(contract_id, test);
+        if !path.is_file() {
+            return None
+        }
+        match fs::read_json_file(&path) {
+            Ok(failure) => Some(failure),
+            Err(err) => {
+                warn!(?err, "failed to read persisted failure");
+                None
+            }
+        }
+    }
+
+    /// Stores the failure of the given test, replacing any previous one.
+    pub fn persist(&self, contract_id: &str, test: &str, failure: &PersistedFailure) {
+        let path = self.path(contract_id, test);
+        let res = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write_json_file(&path, failure));
+        if let Err(err) = res {
+            warn!(?err, "failed to persist failure");
+        }
+    }
+
+    /// Removes the failure stored for the given test.
+    pub fn remove(&self, contract_id: &str, test: &str) {
+        let path = self.path(contract_id, test);
+        if path.is_file() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Replaces all characters that may not be valid in a file name.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect()
+}
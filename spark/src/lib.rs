@@ -4,7 +4,7 @@ use foxar_config::{
     InvariantConfig, NatSpec,
 };
 use proptest::test_runner::{RngAlgorithm, TestRng, TestRunner};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[macro_use]
 extern crate tracing;
@@ -15,6 +15,13 @@ pub mod gas_report;
 /// Coverage reports
 pub mod coverage;
 
+/// JUnit and TAP test reports
+pub mod test_report;
+
+/// Persistence of fuzz and invariant failures
+pub mod failures;
+use failures::FailureCorpus;
+
 /// The Spark test runner
 mod runner;
 pub use runner::ContractRunner;
@@ -44,6 +51,11 @@ pub struct TestOptions {
     pub inline_fuzz: InlineConfig<FuzzConfig>,
     /// Contains per-test specific "invariant" configurations.
     pub inline_invariant: InlineConfig<InvariantConfig>,
+    /// Where fuzz and invariant failures are persisted, so they are replayed first on later
+    /// runs.
+    pub failure_corpus: Option<FailureCorpus>,
+    /// Only run the tests that have a persisted failure.
+    pub rerun_failures: bool,
 }
 
 impl TestOptions {
@@ -105,6 +117,15 @@ impl TestOptions {
         self.inline_invariant.get(contract_id, test_fn).unwrap_or(&self.invariant)
     }
 
+    /// Returns whether the given test should be run, this is always the case unless only tests
+    /// with persisted failures should be rerun.
+    pub fn should_run(&self, contract_id: &str, test_signature: &str) -> bool {
+        !self.rerun_failures ||
+            self.failure_corpus
+                .as_ref()
+                .is_some_and(|corpus| corpus.contains(contract_id, test_signature))
+    }
+
     pub fn fuzzer_with_cases(&self, cases: u32) -> TestRunner {
         // TODO: Add Options to modify the persistence
         let cfg = proptest::test_runner::Config {
@@ -181,7 +202,14 @@ where
             }
         }
 
-        Ok(Self { fuzz: base_fuzz, invariant: base_invariant, inline_fuzz, inline_invariant })
+        Ok(Self {
+            fuzz: base_fuzz,
+            invariant: base_invariant,
+            inline_fuzz,
+            inline_invariant,
+            failure_corpus: None,
+            rerun_failures: false,
+        })
     }
 }
 
@@ -192,6 +220,8 @@ pub struct TestOptionsBuilder {
     invariant: Option<InvariantConfig>,
     profiles: Option<Vec<String>>,
     output: Option<ProjectCompileOutput>,
+    failure_corpus: Option<FailureCorpus>,
+    rerun_failures: bool,
 }
 
 impl TestOptionsBuilder {
@@ -224,6 +254,18 @@ impl TestOptionsBuilder {
         self
     }
 
+    /// Sets the directory fuzz and invariant failures are persisted in.
+    pub fn failure_persist_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.failure_corpus = Some(FailureCorpus::new(dir));
+        self
+    }
+
+    /// Only run the tests that have a persisted failure.
+    pub fn rerun_failures(mut self, rerun_failures: bool) -> Self {
+        self.rerun_failures = rerun_failures;
+        self
+    }
+
     /// Creates an instance of [`TestOptions`]. This takes care of creating "fuzz" and
     /// "invariant" fallbacks, and extracting all inline test configs, if available.
     ///
@@ -236,20 +278,25 @@ impl TestOptionsBuilder {
         let base_fuzz = self.fuzz.unwrap_or_default();
         let base_invariant = self.invariant.unwrap_or_default();
 
-        match self.output {
-            Some(compile_output) => Ok(TestOptions::try_from((
+        let mut options = match self.output {
+            Some(compile_output) => TestOptions::try_from((
                 &compile_output,
                 &root,
                 profiles,
                 base_fuzz,
                 base_invariant,
-            ))?),
-            None => Ok(TestOptions {
+            ))?,
+            None => TestOptions {
                 fuzz: base_fuzz,
                 invariant: base_invariant,
                 inline_fuzz: InlineConfig::default(),
                 inline_invariant: InlineConfig::default(),
-            }),
-        }
+                failure_corpus: None,
+                rerun_failures: false,
+            },
+        };
+        options.failure_corpus = self.failure_corpus;
+        options.rerun_failures = self.rerun_failures;
+        Ok(options)
     }
 }
@@ -103,6 +103,9 @@ pub struct TestResult {
 
     /// pc breakpoint char map
     pub breakpoints: Breakpoints,
+
+    /// How long the test took to run
+    pub duration: Duration,
 }
 
 impl TestResult {
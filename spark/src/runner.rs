@@ -1,4 +1,5 @@
 use crate::{
+    failures::{FailureCorpus, PersistedFailure},
     result::{SuiteResult, TestKind, TestResult, TestSetup, TestStatus},
     TestFilter, TestOptions,
 };
@@ -17,7 +18,8 @@ use foxar_evm::{
     executor::{CallResult, EvmError, ExecutionErr, Executor},
     fuzz::{
         invariant::{
-            InvariantContract, InvariantExecutor, InvariantFuzzError, InvariantFuzzTestResult,
+            replay_sequence, InvariantContract, InvariantExecutor, InvariantFuzzError,
+            InvariantFuzzTestResult,
         },
         FuzzedExecutor,
     },
@@ -33,6 +35,7 @@ use std::{
 /// A type that executes all tests of a contract
 #[derive(Debug, Clone)]
 pub struct ContractRunner<'a> {
+    /// The identifier of the test contract, `<path>:<name>`
     pub name: &'a str,
     /// The executor used by the runner.
     pub executor: Executor,
@@ -205,7 +208,10 @@ impl<'a> ContractRunner<'a> {
                 [(
                     "setUp()".to_string(),
                     // TODO-f: get the breakpoints here
-                    TestResult::fail("Multiple setUp functions".to_string()),
+                    TestResult {
+                        duration: start.elapsed(),
+                        ..TestResult::fail("Multiple setUp functions".to_string())
+                    },
                 )]
                 .into(),
                 warnings,
@@ -240,6 +246,7 @@ impl<'a> ContractRunner<'a> {
                         coverage: None,
                         labeled_addresses: setup.labeled_addresses,
                         breakpoints: Default::default(),
+                        duration: start.elapsed(),
                     },
                 )]
                 .into(),
@@ -252,13 +259,24 @@ impl<'a> ContractRunner<'a> {
             .functions
             .par_iter()
             .flat_map(|(_, f)| f)
-            .filter(|&func| func.is_test() && filter.matches_test(func.signature()))
+            .filter(|&func| {
+                func.is_test() &&
+                    filter.matches_test(func.signature()) &&
+                    test_options.should_run(self.name, &func.signature())
+            })
             .map(|func| {
                 let should_fail = func.is_test_fail();
                 let res = if func.is_fuzz_test() {
                     let runner = test_options.fuzz_runner(self.name, &func.name);
                     let fuzz_config = test_options.fuzz_config(self.name, &func.name);
-                    self.run_fuzz_test(func, should_fail, runner, setup.clone(), *fuzz_config)
+                    self.run_fuzz_test(
+                        func,
+                        should_fail,
+                        runner,
+                        setup.clone(),
+                        *fuzz_config,
+                        test_options.failure_corpus.as_ref(),
+                    )
                 } else {
                     self.clone().run_test(func, should_fail, setup.clone())
                 };
@@ -274,10 +292,11 @@ impl<'a> ContractRunner<'a> {
             );
 
             // TODO: par_iter ?
-            let functions = self
-                .contract
-                .functions()
-                .filter(|&func| func.is_invariant_test() && filter.matches_test(func.signature()));
+            let functions = self.contract.functions().filter(|&func| {
+                func.is_invariant_test() &&
+                    filter.matches_test(func.signature()) &&
+                    test_options.should_run(self.name, &func.signature())
+            });
             for func in functions {
                 let runner = test_options.invariant_runner(self.name, &func.name);
                 let invariant_config = test_options.invariant_config(self.name, &func.name);
@@ -288,6 +307,7 @@ impl<'a> ContractRunner<'a> {
                     vec![func],
                     known_contracts,
                     identified_contracts.clone(),
+                    test_options.failure_corpus.as_ref(),
                 );
                 for result in results {
                     test_results.insert(func.signature(), result);
@@ -365,6 +385,7 @@ impl<'a> ContractRunner<'a> {
                     traces,
                     labeled_addresses,
                     kind: TestKind::Standard(0),
+                    duration: start.elapsed(),
                     ..Default::default()
                 }
             }
@@ -376,6 +397,7 @@ impl<'a> ContractRunner<'a> {
                     traces,
                     labeled_addresses,
                     kind: TestKind::Standard(0),
+                    duration: start.elapsed(),
                     ..Default::default()
                 }
             }
@@ -408,10 +430,12 @@ impl<'a> ContractRunner<'a> {
             coverage,
             labeled_addresses,
             breakpoints,
+            duration: start.elapsed(),
         }
     }
 
     #[instrument(name = "invariant-test", skip_all)]
+    #[allow(clippy::too_many_arguments)]
     pub fn run_invariant_test(
         &mut self,
         runner: TestRunner,
@@ -420,11 +444,13 @@ impl<'a> ContractRunner<'a> {
         functions: Vec<&Function>,
         known_contracts: Option<&ContractsByArtifact>,
         identified_contracts: ContractsByAddress,
+        failure_corpus: Option<&FailureCorpus>,
     ) -> Vec<TestResult> {
         trace!(target: "spark::test::fuzz", "executing invariant test with invariant functions {:?}",  functions.iter().map(|f|&f.name).collect::<Vec<_>>());
         let empty = ContractsByArtifact::default();
         let project_contracts = known_contracts.unwrap_or(&empty);
         let TestSetup { address, logs, traces, labeled_addresses, .. } = setup;
+        let start = Instant::now();
 
         // First, run the test normally to see if it needs to be skipped.
         if let Err(EvmError::SkipError) = self.executor.execute_test::<(), _, _>(
@@ -442,23 +468,54 @@ impl<'a> ContractRunner<'a> {
                 traces,
                 labeled_addresses,
                 kind: TestKind::Standard(0),
+                duration: start.elapsed(),
                 ..Default::default()
             }];
         };
 
-        let mut evm = InvariantExecutor::new(
-            &mut self.executor,
-            runner,
-            invariant_config,
-            &identified_contracts,
-            project_contracts,
-        );
-
         let invariant_contract =
             InvariantContract { address, invariant_functions: functions, abi: self.contract };
 
+        // Replay the persisted failure first, and only fuzz if it doesn't break the invariant
+        // anymore.
+        let replayed = invariant_contract.invariant_functions.first().and_then(|func| {
+            let corpus = failure_corpus?;
+            let calls = corpus.load(self.name, &func.signature())?.invariant_calls()?;
+            match replay_sequence(&invariant_contract, func, &self.executor, &calls) {
+                Ok(Some(error)) => {
+                    info!(name = %func.name, "persisted failure still breaks the invariant");
+                    Some(InvariantFuzzTestResult {
+                        invariants: BTreeMap::from([(func.name.clone(), Some(error))]),
+                        cases: vec![],
+                        reverts: 0,
+                        last_call_results: None,
+                    })
+                }
+                Ok(None) => {
+                    corpus.remove(self.name, &func.signature());
+                    None
+                }
+                Err(err) => {
+                    error!(?err, "Failed to replay persisted invariant failure");
+                    None
+                }
+            }
+        });
+
+        let fuzz_result = match replayed {
+            Some(result) => Ok(result),
+            None => InvariantExecutor::new(
+                &mut self.executor,
+                runner,
+                invariant_config,
+                &identified_contracts,
+                project_contracts,
+            )
+            .invariant_fuzz(invariant_contract.clone()),
+        };
+
         let Ok(InvariantFuzzTestResult { invariants, cases, reverts, mut last_call_results }) =
-            evm.invariant_fuzz(invariant_contract)
+            fuzz_result
         else {
             return vec![];
         };
@@ -485,7 +542,20 @@ impl<'a> ContractRunner<'a> {
                             &mut logs,
                             &mut traces,
                         ) {
-                            Ok(c) => counterexample = c,
+                            Ok(c) => {
+                                if let Some((corpus, failure)) = failure_corpus.zip(
+                                    c.as_ref().and_then(PersistedFailure::from_counterexample),
+                                ) {
+                                    if let Some(func) = invariant_contract
+                                        .invariant_functions
+                                        .iter()
+                                        .find(|func| func.name == func_name)
+                                    {
+                                        corpus.persist(self.name, &func.signature(), &failure);
+                                    }
+                                }
+                                counterexample = c
+                            }
                             Err(err) => {
                                 error!(?err, "Failed to replay invariant error")
                             }
@@ -532,6 +602,7 @@ impl<'a> ContractRunner<'a> {
                     traces,
                     labeled_addresses: labeled_addresses.clone(),
                     breakpoints: Default::default(),
+                    duration: start.elapsed(),
                 }
             })
             .collect()
@@ -545,13 +616,31 @@ impl<'a> ContractRunner<'a> {
         runner: TestRunner,
         setup: TestSetup,
         fuzz_config: FuzzConfig,
+        failure_corpus: Option<&FailureCorpus>,
     ) -> TestResult {
         let TestSetup { address, mut logs, mut traces, mut labeled_addresses, .. } = setup;
 
         // Run fuzz test
         let start = Instant::now();
-        let mut result = FuzzedExecutor::new(&self.executor, runner, self.sender, fuzz_config)
-            .fuzz(func, address, should_fail, self.errors);
+        let fuzzer = FuzzedExecutor::new(&self.executor, runner, self.sender, fuzz_config);
+        let signature = func.signature();
+
+        // Replay the persisted failure first, and only fuzz if it doesn't fail anymore.
+        let replayed = failure_corpus.and_then(|corpus| {
+            let calldata = corpus.load(self.name, &signature)?.fuzz_calldata()?;
+            let result = fuzzer.replay(func, address, should_fail, self.errors, calldata);
+            if result.is_none() {
+                corpus.remove(self.name, &signature);
+            }
+            result
+        });
+        let mut result = match replayed {
+            Some(result) => {
+                info!(name = %func.name, "persisted failure still fails");
+                result
+            }
+            None => fuzzer.fuzz(func, address, should_fail, self.errors),
+        };
 
         // Check the last test result and skip the test
         // if it's marked as so.
@@ -563,10 +652,20 @@ impl<'a> ContractRunner<'a> {
                 traces,
                 labeled_addresses,
                 kind: TestKind::Standard(0),
+                duration: start.elapsed(),
                 ..Default::default()
             };
         }
 
+        // Persist the counterexample, so it is replayed first on the next run
+        if !result.success {
+            if let Some((corpus, failure)) = failure_corpus.zip(
+                result.counterexample.as_ref().and_then(PersistedFailure::from_counterexample),
+            ) {
+                corpus.persist(self.name, &signature, &failure);
+            }
+        }
+
         let kind = TestKind::Fuzz {
             median_gas: result.median_energy(false),
             mean_gas: result.mean_energy(false),
@@ -599,6 +698,7 @@ impl<'a> ContractRunner<'a> {
             coverage: result.coverage,
             labeled_addresses,
             breakpoints: Default::default(),
+            duration: start.elapsed(),
         }
     }
 }
@@ -0,0 +1,232 @@
+use crate::{
+    coverage::escape_markup,
+    result::{SuiteResult, TestKind, TestResult, TestStatus},
+};
+use foxar_evm::fuzz::CounterExample;
+use std::{collections::BTreeMap, io::Write, time::Duration};
+
+/// Writes test results as a JUnit XML report.
+///
+/// Every test contract is reported as a `testsuite`, and every test function as a `testcase`
+/// with its energy usage attached as properties.
+pub struct JunitReporter<'a> {
+    /// Destination buffer
+    destination: &'a mut (dyn Write + 'a),
+}
+
+impl<'a> JunitReporter<'a> {
+    pub fn new(destination: &'a mut (dyn Write + 'a)) -> Self {
+        Self { destination }
+    }
+
+    /// Writes the report for all the given suites, keyed by their contract identifier.
+    pub fn report(self, results: &BTreeMap<String, SuiteResult>) -> eyre::Result<()> {
+        let count = |status: TestStatus| {
+            results
+                .values()
+                .flat_map(|suite| suite.tests())
+                .filter(|(_, t)| t.status == status)
+                .count()
+        };
+        let tests: usize = results.values().map(SuiteResult::len).sum();
+        let duration = results.values().fold(Duration::ZERO, |acc, suite| acc + suite.duration);
+
+        writeln!(self.destination, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            self.destination,
+            r#"<testsuites name="spark" tests="{tests}" failures="{}" skipped="{}" time="{:.6}">"#,
+            count(TestStatus::Failure),
+            count(TestStatus::Skipped),
+            duration.as_secs_f64(),
+        )?;
+
+        for (suite_name, suite) in results {
+            let suite_name = escape_markup(suite_name);
+            writeln!(
+                self.destination,
+                r#"  <testsuite name="{suite_name}" tests="{}" failures="{}" skipped="{}" time="{:.6}">"#,
+                suite.len(),
+                suite.failures().count(),
+                suite.tests().filter(|(_, t)| t.status == TestStatus::Skipped).count(),
+                suite.duration.as_secs_f64(),
+            )?;
+
+            for warning in &suite.warnings {
+                writeln!(
+                    self.destination,
+                    "    <system-err>{}</system-err>",
+                    escape_markup(warning)
+                )?;
+            }
+
+            for (name, result) in suite.tests() {
+                writeln!(
+                    self.destination,
+                    r#"    <testcase name="{}" classname="{suite_name}" time="{:.6}">"#,
+                    escape_markup(name),
+                    result.duration.as_secs_f64(),
+                )?;
+
+                writeln!(self.destination, "      <properties>")?;
+                for (key, value) in test_properties(result) {
+                    writeln!(
+                        self.destination,
+                        r#"        <property name="{key}" value="{}"/>"#,
+                        escape_markup(&value)
+                    )?;
+                }
+                writeln!(self.destination, "      </properties>")?;
+
+                match result.status {
+                    TestStatus::Success => (),
+                    TestStatus::Skipped => writeln!(self.destination, "      <skipped/>")?,
+                    TestStatus::Failure => {
+                        writeln!(
+                            self.destination,
+                            r#"      <failure message="{}">{}</failure>"#,
+                            escape_markup(&failure_reason(result)),
+                            escape_markup(&failure_details(result)),
+                        )?;
+                    }
+                }
+
+                if !result.decoded_logs.is_empty() {
+                    writeln!(
+                        self.destination,
+                        "      <system-out>{}</system-out>",
+                        escape_markup(&result.decoded_logs.join("\n"))
+                    )?;
+                }
+
+                writeln!(self.destination, "    </testcase>")?;
+            }
+
+            writeln!(self.destination, "  </testsuite>")?;
+        }
+
+        writeln!(self.destination, "</testsuites>")?;
+
+        Ok(())
+    }
+}
+
+/// Streams test results in the Test Anything Protocol (TAP) version 13 format.
+///
+/// Test points are emitted as soon as a suite finishes, and the plan is written at the end by
+/// [`TapReporter::finish`].
+pub struct TapReporter<'a> {
+    /// Destination buffer
+    destination: &'a mut (dyn Write + 'a),
+    /// The number of test points written so far.
+    count: usize,
+}
+
+impl<'a> TapReporter<'a> {
+    /// Creates a new reporter and writes the TAP version header.
+    pub fn new(destination: &'a mut (dyn Write + 'a)) -> eyre::Result<Self> {
+        writeln!(destination, "TAP version 13")?;
+        Ok(Self { destination, count: 0 })
+    }
+
+    /// Writes a test point for every test in the given suite.
+    pub fn suite(&mut self, suite_name: &str, suite: &SuiteResult) -> eyre::Result<()> {
+        for warning in &suite.warnings {
+            writeln!(self.destination, "# {suite_name}: {warning}")?;
+        }
+
+        for (name, result) in suite.tests() {
+            self.count += 1;
+            let description = format!("{suite_name} {name}");
+            match result.status {
+                TestStatus::Success => writeln!(
+                    self.destination,
+                    "ok {} - {description} {}",
+                    self.count,
+                    result.kind.report()
+                )?,
+                TestStatus::Skipped => {
+                    writeln!(self.destination, "ok {} - {description} # SKIP", self.count)?
+                }
+                TestStatus::Failure => {
+                    writeln!(self.destination, "not ok {} - {description}", self.count)?;
+                    writeln!(self.destination, "  ---")?;
+                    writeln!(
+                        self.destination,
+                        "  message: {}",
+                        yaml_string(&failure_reason(result))
+                    )?;
+                    writeln!(
+                        self.destination,
+                        "  duration_ms: {:.3}",
+                        result.duration.as_secs_f64() * 1000.
+                    )?;
+                    for (key, value) in test_properties(result) {
+                        writeln!(self.destination, "  {key}: {}", yaml_string(&value))?;
+                    }
+                    let details = failure_details(result);
+                    if !details.is_empty() {
+                        writeln!(self.destination, "  counterexample: |")?;
+                        for line in details.lines() {
+                            writeln!(self.destination, "    {line}")?;
+                        }
+                    }
+                    writeln!(self.destination, "  ...")?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the test plan, this must be called after all the suites were reported.
+    pub fn finish(self) -> eyre::Result<()> {
+        writeln!(self.destination, "1..{}", self.count)?;
+        Ok(())
+    }
+}
+
+/// Returns the energy usage and run counts of a test as `(name, value)` pairs.
+fn test_properties(result: &TestResult) -> Vec<(&'static str, String)> {
+    match &result.kind {
+        TestKind::Standard(energy) => {
+            vec![("kind", "standard".to_string()), ("energy", energy.to_string())]
+        }
+        TestKind::Fuzz { runs, mean_gas, median_gas, .. } => vec![
+            ("kind", "fuzz".to_string()),
+            ("runs", runs.to_string()),
+            ("mean_energy", mean_gas.to_string()),
+            ("median_energy", median_gas.to_string()),
+        ],
+        TestKind::Invariant { runs, calls, reverts } => vec![
+            ("kind", "invariant".to_string()),
+            ("runs", runs.to_string()),
+            ("calls", calls.to_string()),
+            ("reverts", reverts.to_string()),
+        ],
+    }
+}
+
+/// Returns the decoded revert reason of a failed test.
+fn failure_reason(result: &TestResult) -> String {
+    result.reason.clone().unwrap_or_else(|| "Assertion failed.".to_string())
+}
+
+/// Returns the counterexample of a failed fuzz or invariant test, one call per line.
+fn failure_details(result: &TestResult) -> String {
+    match &result.counterexample {
+        Some(CounterExample::Single(example)) => {
+            format!("calldata=0x{}\n{example}", hex::encode(&example.calldata))
+        }
+        Some(CounterExample::Sequence(sequence)) => sequence
+            .iter()
+            .map(|example| format!("calldata=0x{} {example}", hex::encode(&example.calldata)))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => String::new(),
+    }
+}
+
+/// Quotes a string as a single-quoted YAML scalar.
+fn yaml_string(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''").replace('\n', " "))
+}
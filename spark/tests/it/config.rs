@@ -112,6 +112,7 @@ pub fn test_opts() -> TestOptions {
                 max_fuzz_dictionary_addresses: 10_000,
                 max_fuzz_dictionary_values: 10_000,
             },
+            coverage_guided: false,
         },
         invariant: InvariantConfig {
             runs: 512,
@@ -126,9 +127,12 @@ pub fn test_opts() -> TestOptions {
                 max_fuzz_dictionary_values: 10_000,
             },
             shrink_sequence: true,
+            coverage_guided: false,
         },
         inline_fuzz: Default::default(),
         inline_invariant: Default::default(),
+        failure_corpus: None,
+        rerun_failures: false,
     }
 }
 
@@ -83,6 +83,16 @@ async fn test_core() {
     );
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_failed_setup_duration() {
+    let mut runner = runner().await;
+    let results =
+        runner.test(&Filter::new(".*", ".*", ".*core/FailingSetup"), None, test_opts()).await;
+
+    let result = &results["core/FailingSetup.t.sol:FailingSetupTest"].test_results["setUp()"];
+    assert!(!result.duration.is_zero());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_logs() {
     let mut runner = runner().await;
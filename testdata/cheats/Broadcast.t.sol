@@ -233,6 +233,14 @@ contract BroadcastTestNoLinking is DSTest {
         cheats.stopBroadcast();
     }
 
+    function deployCreate2AndTransfer() public {
+        cheats.startBroadcast();
+        // skipped once the contract is deployed
+        new NoLink{salt: bytes32(uint256(1338))}();
+        payable(ACCOUNT_B).transfer(1);
+        cheats.stopBroadcast();
+    }
+
     function errorStaticCall() public {
         cheats.broadcast();
         NoLink test11 = new NoLink();